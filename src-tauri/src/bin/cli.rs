@@ -0,0 +1,8 @@
+// The console build of the app for processing files from the command line, e.g. of scripted
+// batch jobs, which prints its progress to the terminal it's started from on every platform
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    add_logo_processor_lib::run_headless(args)
+}
@@ -0,0 +1,128 @@
+use log::{info, warn};
+use rayon::prelude::*;
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use crate::shared::{
+    external_command::ffmpeg_command, ffmpeg_logger::ffmpeg_logger, natural_sort::natural_path_cmp,
+    process_manager::check_process_cancelled, run_report::RunReportManager,
+};
+
+/// File name of the contact sheet written to every output folder, without extension
+const CONTACT_SHEET_NAME: &str = "contact_sheet";
+
+/// Width and height of the square cell of each thumbnail on a contact sheet
+const THUMBNAIL_SIZE: u32 = 320;
+
+const COLUMNS: usize = 6;
+
+/// Folders with more images get multiple numbered contact sheets, e.g. `contact_sheet_2.jpg`
+const MAX_THUMBNAILS_PER_SHEET: usize = 60;
+
+/// Write a contact sheet of the output images of every folder, for a quick review of the run
+///
+/// Only the images of the current run are included. Sheets which fail are reported as warnings,
+/// since the watermarked images themselves were written. Returns the paths of the sheets.
+pub fn write_contact_sheets(
+    output_paths: &[PathBuf],
+) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+    let mut folders: BTreeMap<&Path, Vec<&Path>> = BTreeMap::new();
+    for output_path in output_paths {
+        if let Some(folder) = output_path.parent() {
+            folders.entry(folder).or_default().push(output_path);
+        }
+    }
+
+    let mut sheets: Vec<(PathBuf, Vec<&Path>)> = Vec::new();
+    for (folder, mut image_paths) in folders {
+        image_paths.sort_by(|a, b| natural_path_cmp(a, b));
+        for (sheet_index, chunk) in image_paths.chunks(MAX_THUMBNAILS_PER_SHEET).enumerate() {
+            let file_name = match sheet_index {
+                0 => format!("{}.jpg", CONTACT_SHEET_NAME),
+                _ => format!("{}_{}.jpg", CONTACT_SHEET_NAME, sheet_index + 1),
+            };
+            sheets.push((folder.join(file_name), chunk.to_vec()));
+        }
+    }
+
+    let sheet_paths = sheets
+        .par_iter()
+        .filter_map(|(sheet_path, image_paths)| {
+            if let Err(e) = check_process_cancelled() {
+                return Some(Err(e));
+            }
+
+            match write_contact_sheet(image_paths, sheet_path) {
+                Ok(()) => Some(Ok(sheet_path.clone())),
+                Err(e) => {
+                    let message = format!(
+                        "Failed to write the contact sheet {}: {}",
+                        sheet_path.display(),
+                        e
+                    );
+                    warn!("{}", message);
+                    RunReportManager::add_warning(message);
+                    None
+                }
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    info!("Wrote {} contact sheets", sheet_paths.len());
+
+    Ok(sheet_paths)
+}
+
+/// Tile the thumbnails of the images into a single contact sheet
+fn write_contact_sheet(
+    image_paths: &[&Path],
+    sheet_path: &Path,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut cmd = ffmpeg_command()?;
+
+    #[cfg(target_os = "windows")]
+    cmd.hide_banner();
+
+    cmd.arg("-y");
+
+    for image_path in image_paths {
+        cmd.input(image_path.to_str().ok_or("Invalid image file path")?);
+    }
+
+    // Every image becomes a single frame of the same size, so the frames can be tiled
+    let mut filter_parts: Vec<String> = (0..image_paths.len())
+        .map(|i| {
+            format!(
+                "[{i}:v]trim=end_frame=1,scale={size}:{size}:force_original_aspect_ratio=decrease,\
+                 pad={size}:{size}:(ow-iw)/2:(oh-ih)/2:color=white,setsar=1,format=yuv420p[thumb{i}]",
+                i = i,
+                size = THUMBNAIL_SIZE,
+            )
+        })
+        .collect();
+
+    let columns = COLUMNS.min(image_paths.len());
+    let rows = image_paths.len().div_ceil(columns);
+    let thumbnail_labels: String = (0..image_paths.len())
+        .map(|i| format!("[thumb{}]", i))
+        .collect();
+    filter_parts.push(format!(
+        "{}concat=n={}:v=1:a=0,tile={}x{}:padding=8:margin=8:color=white[sheet]",
+        thumbnail_labels,
+        image_paths.len(),
+        columns,
+        rows
+    ));
+
+    cmd.args(["-filter_complex", &filter_parts.join(";")]);
+    cmd.args(["-map", "[sheet]", "-frames:v", "1", "-q:v", "3"]);
+
+    let ffmpeg_child = cmd
+        .output(sheet_path.to_str().ok_or("Invalid contact sheet path")?)
+        .spawn()?;
+
+    ffmpeg_logger(ffmpeg_child, None, None)
+}
@@ -0,0 +1,153 @@
+use ffmpeg_sidecar::command::FfmpegCommand;
+use std::{fs, path::Path};
+
+use crate::image::image_formats::image_format;
+
+/// Check if an image file contains multiple frames, e.g. an animated GIF, WebP or PNG
+///
+/// Only the container structure is parsed, the frames aren't decoded. Files which can't be read
+/// are treated as still images.
+pub fn is_animated_image(path: &Path) -> bool {
+    let Ok(data) = fs::read(path) else {
+        return false;
+    };
+
+    if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        count_gif_frames(&data, 2) >= 2
+    } else if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+        is_animated_webp(&data)
+    } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        is_animated_png(&data)
+    } else {
+        false
+    }
+}
+
+/// Count the frames of a GIF, stopping once `limit` frames are found
+fn count_gif_frames(data: &[u8], limit: usize) -> usize {
+    // Skip the header and the global color table which follows the logical screen descriptor
+    let Some(&screen_flags) = data.get(10) else {
+        return 0;
+    };
+    let mut position = 13 + color_table_size(screen_flags);
+    let mut frame_count = 0;
+
+    while frame_count < limit {
+        match data.get(position) {
+            // Extension: label followed by data sub-blocks
+            Some(0x21) => position = skip_sub_blocks(data, position + 2),
+            // Image descriptor: local color table and LZW code size followed by data sub-blocks
+            Some(0x2C) => {
+                frame_count += 1;
+                let Some(&image_flags) = data.get(position + 9) else {
+                    break;
+                };
+                position = skip_sub_blocks(data, position + 10 + color_table_size(image_flags) + 1);
+            }
+            // Trailer, or a truncated file
+            _ => break,
+        }
+    }
+
+    frame_count
+}
+
+fn color_table_size(flags: u8) -> usize {
+    if flags & 0x80 == 0 {
+        0
+    } else {
+        3 * (1 << ((flags & 0x07) + 1))
+    }
+}
+
+/// Skip a sequence of GIF data sub-blocks, which ends with an empty block
+fn skip_sub_blocks(data: &[u8], mut position: usize) -> usize {
+    while let Some(&block_size) = data.get(position) {
+        position += 1 + block_size as usize;
+        if block_size == 0 {
+            break;
+        }
+    }
+    position
+}
+
+/// Check the animation flag of the extended WebP header
+fn is_animated_webp(data: &[u8]) -> bool {
+    const ANIMATION_FLAG: u8 = 0x02;
+    data.get(12..16) == Some(b"VP8X")
+        && data
+            .get(20)
+            .is_some_and(|flags| flags & ANIMATION_FLAG != 0)
+}
+
+/// Check for an animation control chunk with multiple frames before the image data
+fn is_animated_png(data: &[u8]) -> bool {
+    let mut position = 8;
+    while let Some(chunk_header) = data.get(position..position + 8) {
+        let chunk_length = u32::from_be_bytes([
+            chunk_header[0],
+            chunk_header[1],
+            chunk_header[2],
+            chunk_header[3],
+        ]) as usize;
+        match &chunk_header[4..8] {
+            b"acTL" => {
+                return data
+                    .get(position + 8..position + 12)
+                    .is_some_and(|frame_count| {
+                        u32::from_be_bytes([
+                            frame_count[0],
+                            frame_count[1],
+                            frame_count[2],
+                            frame_count[3],
+                        ]) > 1
+                    })
+            }
+            // The animation control chunk must come before the image data
+            b"IDAT" => return false,
+            _ => position += 12 + chunk_length,
+        }
+    }
+    false
+}
+
+/// Build the filter which converts the frames of an animated GIF output to an optimized palette
+///
+/// Returns the filter and the label of the stream to map, or `None` for other formats.
+pub fn build_gif_palette_filter(
+    image_format: &str,
+    output_label: &str,
+) -> Option<(String, String)> {
+    if !image_format::GIF.extensions.contains(&image_format) {
+        return None;
+    }
+
+    let filter = format!(
+        "[{label}]split[{label}_frames][{label}_palettesource];\
+         [{label}_palettesource]palettegen=reserve_transparent=1:stats_mode=diff[{label}_palette];\
+         [{label}_frames][{label}_palette]paletteuse=dither=bayer:diff_mode=rectangle[{label}_gif]",
+        label = output_label
+    );
+    Some((filter, format!("{}_gif", output_label)))
+}
+
+/// Apply the arguments which keep an animated output looping, after the format specific arguments
+///
+/// Formats which can't store an animation only get the first frame.
+pub fn apply_animation_args(image_format: &str, cmd: &mut FfmpegCommand) {
+    match image_format {
+        name if image_format::GIF.extensions.contains(&name) => {
+            // The palette filter already reduced the frames to 256 colors
+            cmd.args(["-pix_fmt", "pal8", "-loop", "0"]);
+        }
+        name if image_format::WEBP.extensions.contains(&name) => {
+            cmd.args(["-loop", "0"]);
+        }
+        name if image_format::PNG.extensions.contains(&name) => {
+            cmd.args(["-f", "apng", "-plays", "0"]);
+        }
+        _ => {
+            cmd.args(["-frames:v", "1"]);
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use log::{info, warn};
+use rayon::prelude::*;
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use crate::shared::{
+    external_command::{external_command, ExternalBinary},
+    file_utils::FileInfo,
+    natural_sort::natural_path_cmp,
+    process_manager::check_process_cancelled,
+    run_report::RunReportManager,
+};
+
+/// Width and height of the grayscale thumbnail the hash is calculated from
+const HASH_WIDTH: usize = 9;
+const HASH_HEIGHT: usize = 8;
+
+/// Calculate the difference hash of an image, which is equal for images that look the same
+///
+/// The image is reduced to a tiny grayscale thumbnail, each bit tells if a pixel is brighter than
+/// its right neighbour. Resizing, recompressing or small edits only flip a few bits.
+pub fn image_difference_hash(path: &Path) -> Result<u64, Box<dyn Error + Send + Sync>> {
+    let output = external_command(ExternalBinary::Ffmpeg)?
+        .args(["-v", "error", "-i"])
+        .arg(path)
+        .args([
+            "-vf",
+            &format!(
+                "scale={}:{}:flags=area,format=gray",
+                HASH_WIDTH, HASH_HEIGHT
+            ),
+            "-frames:v",
+            "1",
+            "-f",
+            "rawvideo",
+            "-",
+        ])
+        .output()?;
+
+    let pixels = output.stdout;
+    if pixels.len() != HASH_WIDTH * HASH_HEIGHT {
+        return Err(format!(
+            "Failed to decode the thumbnail: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    let mut hash = 0u64;
+    for row in pixels.chunks(HASH_WIDTH) {
+        for pair in row.windows(2) {
+            hash = (hash << 1) | u64::from(pair[0] > pair[1]);
+        }
+    }
+    Ok(hash)
+}
+
+/// Skip images which look the same as another image, only the best copy is processed
+///
+/// Images are duplicates when their hashes differ in at most `max_distance` bits. Of each group of
+/// duplicates the largest file is kept, since it's usually the highest quality copy. Images which
+/// can't be hashed are always kept.
+pub fn skip_duplicate_images(
+    paths: Vec<PathBuf>,
+    max_distance: u32,
+) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+    let hash_start = std::time::Instant::now();
+    let mut hashed_paths = paths
+        .into_par_iter()
+        .map(|path| -> Result<_, Box<dyn Error + Send + Sync>> {
+            check_process_cancelled()?;
+
+            let hash = image_difference_hash(&path)
+                .map_err(|e| warn!("Failed to hash {}: {}", path.display(), e))
+                .ok();
+            let file_size = FileInfo::read(&path).map(|info| info.size).unwrap_or(0);
+            Ok((path, hash, file_size))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    info!("Hashing images took: {:?}", hash_start.elapsed());
+
+    hashed_paths.sort_by(|(a_path, _, a_size), (b_path, _, b_size)| {
+        b_size
+            .cmp(a_size)
+            .then_with(|| natural_path_cmp(a_path, b_path))
+    });
+
+    let mut kept_hashes: Vec<(u64, PathBuf)> = Vec::new();
+    let mut kept_paths = Vec::with_capacity(hashed_paths.len());
+    let mut duplicate_count = 0;
+    for (path, hash, _) in hashed_paths {
+        let Some(hash) = hash else {
+            kept_paths.push(path);
+            continue;
+        };
+
+        match kept_hashes
+            .iter()
+            .find(|(kept_hash, _)| (kept_hash ^ hash).count_ones() <= max_distance)
+        {
+            Some((_, original_path)) => {
+                duplicate_count += 1;
+                RunReportManager::record_skipped_file(
+                    path,
+                    format!("Duplicate of {}", original_path.display()),
+                );
+            }
+            None => {
+                kept_hashes.push((hash, path.clone()));
+                kept_paths.push(path);
+            }
+        }
+    }
+
+    if duplicate_count > 0 {
+        info!("Skipped {} duplicate images", duplicate_count);
+    }
+
+    Ok(kept_paths)
+}
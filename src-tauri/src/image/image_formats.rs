@@ -1,9 +1,119 @@
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use ts_rs::TS;
 
 use crate::shared::ffmpeg_structs::{Format, FormatSupport};
 
 pub type ImageFormat = Format;
 
+/// Quality and compression of the output formats which support them
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")] // Settings added later fall back to their default
+pub struct FormatQualitySettings {
+    /// Bits per channel of AVIF outputs: 8, 10 or 12
+    pub avif_bit_depth: u32,
+    /// AVIF encoding effort from 0 (fastest) to 8 (smallest file)
+    pub avif_effort: u32,
+    /// AVIF quality from 0 (smallest file) to 100 (best quality)
+    pub avif_quality: u32,
+    /// JPEG quality from 1 (smallest file) to 100 (best quality)
+    pub jpeg_quality: u32,
+    /// Bits per channel of JPEG XL outputs: 8 or 16
+    pub jxl_bit_depth: u32,
+    /// JPEG XL encoding effort from 1 (fastest) to 9 (smallest file)
+    pub jxl_effort: u32,
+    /// JPEG XL quality from 1 (smallest file) to 100 (lossless)
+    pub jxl_quality: u32,
+    /// Keep the transparency of the source in AVIF and JPEG XL outputs, off by default so the
+    /// outputs of existing settings don't change
+    pub keep_alpha: bool,
+    /// PNG compression level from 0 (fastest) to 9 (smallest file)
+    pub png_compression_level: u32,
+    /// WebP quality from 0 (smallest file) to 100 (best quality)
+    pub webp_quality: u32,
+}
+
+impl FormatQualitySettings {
+    /// Map the AVIF bit depth to the pixel formats of the color and the alpha stream
+    pub fn avif_pixel_formats(&self) -> (&'static str, &'static str) {
+        match self.avif_bit_depth {
+            depth if depth >= 12 => ("yuv420p12le", "gray12le"),
+            10..=11 => ("yuv420p10le", "gray10le"),
+            _ => ("yuv420p", "gray"),
+        }
+    }
+
+    /// Map the AVIF effort to the `-cpu-used` speed of libaom, which runs from 0 (slowest) to 8
+    pub fn avif_cpu_used(&self) -> u32 {
+        8 - self.avif_effort.min(8)
+    }
+
+    /// Map the AVIF quality to the CRF of libaom, which runs from 0 (best) to 63 (worst)
+    pub fn avif_crf(&self) -> u32 {
+        63 - (self.avif_quality.min(100) * 63 + 50) / 100
+    }
+
+    /// Map the JPEG XL bit depth to a pixel format, with or without alpha
+    pub fn jxl_pixel_format(&self) -> &'static str {
+        match (self.jxl_bit_depth > 8, self.keep_alpha) {
+            (false, false) => "rgb24",
+            (false, true) => "rgba",
+            (true, false) => "rgb48le",
+            (true, true) => "rgba64le",
+        }
+    }
+
+    pub fn jxl_effort(&self) -> u32 {
+        self.jxl_effort.clamp(1, 9)
+    }
+
+    /// Map the JPEG XL quality to the butteraugli distance of libjxl, where 0 is lossless
+    ///
+    /// Uses the same mapping as the quality option of `cjxl`.
+    pub fn jxl_distance(&self) -> f32 {
+        let quality = self.jxl_quality.clamp(1, 100) as f32;
+        if quality >= 100.0 {
+            0.0
+        } else if quality >= 30.0 {
+            0.1 + (100.0 - quality) * 0.09
+        } else {
+            53.0 / 3000.0 * quality * quality - 23.0 / 20.0 * quality + 25.0
+        }
+    }
+
+    /// Map the JPEG quality to FFmpeg's `-q:v` scale, which runs from 2 (best) to 31 (worst)
+    pub fn jpeg_qscale(&self) -> u32 {
+        let quality = self.jpeg_quality.clamp(1, 100);
+        31 - ((quality - 1) * 29 + 49) / 99
+    }
+
+    pub fn png_compression_level(&self) -> u32 {
+        self.png_compression_level.min(9)
+    }
+
+    pub fn webp_quality(&self) -> u32 {
+        self.webp_quality.min(100)
+    }
+}
+
+impl Default for FormatQualitySettings {
+    fn default() -> Self {
+        Self {
+            avif_bit_depth: 8,
+            avif_effort: 4,
+            avif_quality: 75,
+            jpeg_quality: 95,
+            jxl_bit_depth: 8,
+            jxl_effort: 7,
+            jxl_quality: 90,
+            keep_alpha: false,
+            png_compression_level: 1,
+            webp_quality: 75,
+        }
+    }
+}
+
 pub mod image_format {
     use super::{FormatSupport, ImageFormat};
 
@@ -1,26 +1,104 @@
-use ffmpeg_sidecar::command::FfmpegCommand;
-use log::info;
+use log::{info, warn};
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::{error::Error, fs::read_dir, path::Path};
 
-use crate::image::image_struct::{apply_image_format_specific_args, Image};
+use crate::image::contact_sheet::write_contact_sheets;
+use crate::image::image_animation::{apply_animation_args, build_gif_palette_filter};
+use crate::image::image_dedup::skip_duplicate_images;
+use crate::image::image_hdr::{hdr_fallback_reason, hdr_format_quality, read_image_hdr_transfer};
+use crate::image::image_metadata::{
+    copy_exif_metadata, embed_exif_thumbnail, read_image_comment, write_image_comment, MetadataMode,
+};
+use crate::image::image_struct::{
+    apply_image_format_specific_args, build_alpha_stream_filter, build_background_filter, Image,
+};
 use crate::image::image_validator::ImageSettingsValidator;
-use crate::shared::ffmpeg_processor::spawn_ffmpeg_process;
+use crate::shared::background_mode::run_in_background_mode;
+use crate::shared::brand_kit::resolve_image_brand_kit;
+use crate::shared::disk_readers::DiskReaderManager;
+use crate::shared::disk_space::{check_free_space, estimate_image_output_size};
+use crate::shared::external_command::ffmpeg_command;
+use crate::shared::ffmpeg_command_history::FfmpegCommandHistoryManager;
+use crate::shared::ffmpeg_file_log::FfmpegLogDirectory;
+use crate::shared::ffmpeg_logger::{ffmpeg_logger, FfmpegProcessError};
+use crate::shared::ffmpeg_processor::{finish_partial_outputs, spawn_ffmpeg_process};
+use crate::shared::ffmpeg_script::{script_logo_directory, write_ffmpeg_script};
 use crate::shared::ffmpeg_structs::FfmpegBatchCommand;
-use crate::shared::file_utils::{clear_and_create_folder, get_relative_path};
-use crate::shared::logo_handler::handle_logos;
+use crate::shared::file_utils::{
+    clear_and_create_folder, find_missing_output_files, get_relative_path, partial_output_path,
+    trash_and_create_folder, validate_directory_overlap,
+};
+use crate::shared::filename_template::FilenameTemplate;
+use crate::shared::filter_builder::{
+    build_media_filter, collect_overlay_layers, parse_ffmpeg_color, OverlayLayer, ResizeFilter,
+};
+use crate::shared::hdr::{apply_hdr_color_args, tone_map_filter};
+use crate::shared::job_manifest::{skip_finished_inputs, JobManifestManager};
+use crate::shared::job_queue::{try_lock_run, Job};
+use crate::shared::logo_handler::{find_logo, handle_logos, temp_logo_directory};
+use crate::shared::logo_overrides::{LogoOverride, LogoOverrides};
 use crate::shared::logo_structs::Logo;
-use crate::shared::media_structs::{Media, Resolution};
+use crate::shared::media_structs::{validate_export_sizes, Media, Resolution};
 use crate::shared::media_validator::{
-    filter_valid_media_paths, read_media_paths_recursive, sort_by_file_size,
+    filter_valid_media_paths, handle_cloud_placeholders, handle_tiny_files,
+    partition_growing_files, read_media_paths_recursive, skip_media_outside_input_limits,
+    sort_by_file_size, wait_for_growing_files,
 };
+use crate::shared::natural_sort::natural_path_cmp;
+use crate::shared::output_collision::{CollisionStrategy, OutputPathClaims};
+use crate::shared::process_limiter::ProcessLimiter;
 use crate::shared::process_manager::{check_process_cancelled, ProcessManager};
-use crate::shared::progress_handler::{ProgressManager, ProgressMode};
+use crate::shared::processing_marker::{skip_marked_files, ProcessingMarker};
+use crate::shared::progress_handler::{
+    ProgressMode, ProgressSink, ProgressStage, SilentProgressSink,
+};
+use crate::shared::project_config::resolve_image_project_config;
+use crate::shared::review_queue::review_flagged_media;
+use crate::shared::run_history::RunHistoryManager;
+use crate::shared::run_log::{is_run_log_path, RunLogManager};
+use crate::shared::run_plan::{PlannedFile, RunPlan};
+use crate::shared::run_report::{FailedFile, RunId, RunPhase, RunReport, RunReportManager};
+use crate::shared::source_disposition::dispose_source_file;
 use crate::ImageSettings;
 
-pub fn handle_images(image_settings: &ImageSettings) -> Result<(), Box<dyn Error + Send + Sync>> {
+pub fn handle_images(
+    image_settings: &ImageSettings,
+    progress: &dyn ProgressSink,
+) -> Result<RunReport, Box<dyn Error + Send + Sync>> {
+    let image_settings = &resolve_image_project_config(image_settings)?;
+    let run_id = RunId::next();
+    let started_at = chrono::Local::now();
+    let result = run_in_background_mode(image_settings.background_mode, || {
+        run_image_processing(image_settings, run_id, progress)
+    });
+    RunHistoryManager::record_run(
+        Job::Images(image_settings.clone()),
+        run_id,
+        started_at,
+        &result,
+    );
+
+    result
+}
+
+fn run_image_processing(
+    image_settings: &ImageSettings,
+    run_id: RunId,
+    progress: &dyn ProgressSink,
+) -> Result<RunReport, Box<dyn Error + Send + Sync>> {
+    let image_settings = &resolve_image_brand_kit(image_settings)?;
+    validate_export_sizes(&image_settings.export_sizes)?;
+    image_settings.layers.validate()?;
+    image_settings.input_filter()?;
+    image_settings.output_mapping()?;
+    validate_directory_overlap(
+        &image_settings.input_directory,
+        &image_settings.output_directory,
+        image_settings.clear_files_output_directory,
+    )?;
+
     info!(
         "Starting image processing with settings: {:?}",
         image_settings,
@@ -32,66 +110,453 @@ pub fn handle_images(image_settings: &ImageSettings) -> Result<(), Box<dyn Error
     let input_directory = &image_settings.input_directory;
     let output_directory = &image_settings.output_directory;
 
-    let mut image_list;
+    DiskReaderManager::configure(input_directory, image_settings.max_disk_readers);
+    ProcessLimiter::configure(image_settings.max_concurrent_processes);
 
     let start_time = std::time::Instant::now();
 
-    ProgressManager::start_progress_with_terminal(
-        "Clearing and creating output folder... (Step 1/7)".to_string(),
-        None,
-        Some("images".to_string()),
+    RunReportManager::start_report(run_id, "images");
+
+    progress.start(
+        "Clearing and creating output folder...".to_string(),
         None,
+        Some("bytes".to_string()),
         None,
+        Some("images".to_string()),
     );
+    progress.set_stage(ProgressStage::Scanning);
 
     check_process_cancelled()?;
 
     if image_settings.clear_files_output_directory || !output_directory.exists() {
         let clear_folder_time = std::time::Instant::now();
-        clear_and_create_folder(output_directory).unwrap();
+        trash_and_create_folder(output_directory, is_run_log_path)?;
         info!(
             "Clearing and creating output directory took: {:?}",
             clear_folder_time.elapsed()
         );
     }
 
-    ProgressManager::set_status(
-        "Reading image paths from input directory... (Step 2/7)".to_string(),
+    // Numbered outputs depend on all inputs, so a run of them can't be resumed
+    JobManifestManager::start(
+        output_directory,
+        &ProcessingMarker::for_settings(image_settings)?,
+        image_settings.sequence_naming.is_none(),
     );
+    let _run_log_guard = RunLogManager::start(output_directory, image_settings.write_run_log);
+    FfmpegCommandHistoryManager::start(image_settings.record_ffmpeg_commands);
+
+    progress.set_status("Reading image paths from input directory...".to_string());
     check_process_cancelled()?;
 
     let read_paths_time = std::time::Instant::now();
-    let valid_image_paths =
-        read_image_paths_from_input_directory(image_settings, input_directory, output_directory)?;
+    let valid_image_paths = discover_image_paths(
+        image_settings,
+        &JobManifestManager::finished_inputs(),
+        false,
+    )?;
     info!("Reading image paths took: {:?}", read_paths_time.elapsed());
+    RunReportManager::record_phase(RunPhase::Discovery, read_paths_time.elapsed());
 
     if valid_image_paths.is_empty() {
-        ProgressManager::set_status("No images found in the input directory".to_string());
+        progress.set_status("No images found in the input directory".to_string());
         info!("No images found in the input directory, returning early.");
         info!("Total time: {:?}", start_time.elapsed());
-        return Ok(());
+        JobManifestManager::finish();
+        return RunReportManager::finish_completed_report();
     }
 
     check_process_cancelled()?;
 
-    ProgressManager::set_status("Creating image structs... (Step 3/7)".to_string());
+    let (mut valid_image_paths, mut growing_image_paths) =
+        partition_growing_files(valid_image_paths)?;
+
+    // A run which stops on a collision waits for the files which are still being written first,
+    // so every collision is found before anything is processed
+    if image_settings.collision_strategy == CollisionStrategy::Error
+        && !growing_image_paths.is_empty()
+    {
+        progress.set_status(format!(
+            "Waiting for {} images which are still being written...",
+            growing_image_paths.len()
+        ));
+        valid_image_paths.extend(wait_for_growing_files(std::mem::take(
+            &mut growing_image_paths,
+        ))?);
+    }
+
+    let mut output_path_claims = OutputPathClaims::new();
+    // Numbered outputs of the images processed last continue the sequence of the images before
+    let mut sequence_position = 0;
+    let mut output_paths = if valid_image_paths.is_empty() {
+        Vec::new()
+    } else {
+        process_image_paths(
+            image_settings,
+            &valid_image_paths,
+            &mut sequence_position,
+            &mut output_path_claims,
+            progress,
+        )?
+    };
+
+    // Files which were still being written are processed last, once they're complete. The
+    // deferred pass runs the stages from scanning again, see `ProgressStage`.
+    if !growing_image_paths.is_empty() {
+        progress.set_stage(ProgressStage::Scanning);
+        progress.set_status(format!(
+            "Waiting for {} images which are still being written...",
+            growing_image_paths.len()
+        ));
+        let deferred_image_paths = wait_for_growing_files(growing_image_paths)?;
+        if !deferred_image_paths.is_empty() {
+            output_paths.extend(process_image_paths(
+                image_settings,
+                &deferred_image_paths,
+                &mut sequence_position,
+                &mut output_path_claims,
+                progress,
+            )?);
+        }
+    }
+
+    progress.set_stage(ProgressStage::Verifying);
+    let verification_start = std::time::Instant::now();
+    let missing_output_paths = find_missing_output_files(&output_paths);
+    for missing_output_path in &missing_output_paths {
+        log::warn!("Output image is missing: {}", missing_output_path.display());
+    }
+    info!(
+        "Verifying {} output images took: {:?}",
+        output_paths.len(),
+        verification_start.elapsed()
+    );
+    RunReportManager::record_phase(RunPhase::Verification, verification_start.elapsed());
+    progress.finish();
+    let output_paths: Vec<PathBuf> = output_paths
+        .into_iter()
+        .filter(|output_path| !missing_output_paths.contains(output_path))
+        .collect();
+
+    if image_settings.create_contact_sheets && !output_paths.is_empty() {
+        let contact_sheets_start = std::time::Instant::now();
+        write_contact_sheets(&output_paths)?;
+        info!(
+            "Writing contact sheets took: {:?}",
+            contact_sheets_start.elapsed()
+        );
+    }
+
+    RunReportManager::record_output_paths(output_paths);
+
+    info!("Total time: {:?}", start_time.elapsed());
+    JobManifestManager::finish();
+    RunReportManager::finish_completed_report()
+}
+
+/// Plan a run with the settings, returning the images it would process and the outputs it would
+/// write without running FFmpeg
+///
+/// Only the steps which don't change any file are taken, so cloud placeholders aren't downloaded,
+/// tiny files aren't quarantined and flagged images aren't reviewed.
+pub fn plan_images(
+    image_settings: &ImageSettings,
+) -> Result<RunPlan, Box<dyn Error + Send + Sync>> {
+    let image_settings = &resolve_image_brand_kit(&resolve_image_project_config(image_settings)?)?;
+    validate_export_sizes(&image_settings.export_sizes)?;
+    image_settings.layers.validate()?;
+    image_settings.input_filter()?;
+    image_settings.output_mapping()?;
+    validate_directory_overlap(
+        &image_settings.input_directory,
+        &image_settings.output_directory,
+        image_settings.clear_files_output_directory,
+    )?;
+
+    // Holding the run lock keeps a run from starting while the plan reads the input directory
+    let _run_guard = try_lock_run()?;
+    ProcessManager::reset_cancel();
+
+    let (image_batches, report) =
+        RunReportManager::collect_report("images", || plan_image_run(image_settings))?;
+    let image_batches = image_batches?;
+
+    Ok(RunPlan {
+        media_type: report.media_type,
+        planned_files: image_batches
+            .iter()
+            .flat_map(|image_batch| &image_batch.batch_data)
+            .map(|(image, output_paths)| PlannedFile {
+                input_path: image.file_path.clone(),
+                output_paths: output_paths.clone(),
+            })
+            .collect(),
+        estimated_batch_count: image_batches
+            .iter()
+            .map(|image_batch| {
+                image_batch
+                    .batch_data
+                    .len()
+                    .div_ceil(IMAGE_BATCH_CHUNK_SIZE)
+            })
+            .sum(),
+        skipped_files: report.skipped_files,
+        warnings: report.warnings,
+    })
+}
+
+/// Write the FFmpeg commands a run with the settings would execute to a `.sh` or `.bat` script,
+/// so the images can be processed on another machine, returning the number of commands
+///
+/// The logos are prepared into a folder next to the script. The steps which run outside FFmpeg,
+/// e.g. embedding EXIF thumbnails and writing contact sheets, aren't part of the script.
+pub fn export_images_script(
+    image_settings: &ImageSettings,
+    script_path: &Path,
+) -> Result<usize, Box<dyn Error + Send + Sync>> {
+    let image_settings = &resolve_image_brand_kit(&resolve_image_project_config(image_settings)?)?;
+    validate_export_sizes(&image_settings.export_sizes)?;
+    image_settings.layers.validate()?;
+    image_settings.input_filter()?;
+    image_settings.output_mapping()?;
+    validate_directory_overlap(
+        &image_settings.input_directory,
+        &image_settings.output_directory,
+        image_settings.clear_files_output_directory,
+    )?;
+
+    // Holding the run lock keeps a run from starting while the export reads the input directory
+    let _run_guard = try_lock_run()?;
+    ProcessManager::reset_cancel();
+
+    let (ffmpeg_batch_commands, _) = RunReportManager::collect_report("images", || {
+        create_image_script_commands(image_settings, script_path)
+    })?;
+    let ffmpeg_batch_commands = ffmpeg_batch_commands?;
+
+    write_ffmpeg_script(
+        script_path,
+        &image_settings.input_directory,
+        &image_settings.output_directory,
+        &ffmpeg_batch_commands,
+    )?;
+    info!(
+        "Exported {} FFmpeg commands to {}",
+        ffmpeg_batch_commands.len(),
+        script_path.display()
+    );
+
+    Ok(ffmpeg_batch_commands.len())
+}
+
+/// Create the FFmpeg commands of a planned run, with the logos prepared next to the script
+fn create_image_script_commands(
+    image_settings: &ImageSettings,
+    script_path: &Path,
+) -> Result<Vec<FfmpegBatchCommand>, Box<dyn Error + Send + Sync>> {
+    let image_batches = plan_image_run(image_settings)?;
+
+    let image_list: Vec<Image> = image_batches
+        .iter()
+        .flat_map(|image_batch| &image_batch.batch_data)
+        .map(|(image, _)| image.clone())
+        .collect();
+    let logo_list = process_logos_for_image_resolutions(
+        image_settings,
+        &image_list,
+        &script_logo_directory(script_path),
+        &SilentProgressSink,
+    )?;
+
+    let resize_filter = create_resize_filter(image_settings)?;
+    let mut ffmpeg_command_list: Vec<ImageBatchCommand> = Vec::new();
+    for ImageBatch {
+        key: batch_key,
+        batch_data,
+    } in image_batches
+    {
+        let logos: Option<Vec<&Logo>> = match logo_list {
+            Some(ref logo_list) => Some(
+                batch_key
+                    .resolutions
+                    .iter()
+                    .map(|resolution| {
+                        find_logo(logo_list, resolution, &batch_key.logo_override).ok_or_else(
+                            || {
+                                format!(
+                                    "No logo found for the given image resolution: {}",
+                                    resolution
+                                )
+                            },
+                        )
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            None => None,
+        };
+
+        create_image_ffmpeg_command_list(
+            &batch_data,
+            logos.as_deref(),
+            image_settings,
+            &resize_filter,
+            &mut ffmpeg_command_list,
+        )?;
+    }
+
+    Ok(ffmpeg_command_list
+        .into_iter()
+        .map(|image_batch_command| image_batch_command.ffmpeg_batch_command)
+        .collect())
+}
+
+fn plan_image_run(
+    image_settings: &ImageSettings,
+) -> Result<Vec<ImageBatch>, Box<dyn Error + Send + Sync>> {
+    let input_directory = &image_settings.input_directory;
+    let output_directory = &image_settings.output_directory;
+
+    DiskReaderManager::configure(input_directory, image_settings.max_disk_readers);
+
+    // A run which clears the output directory removes the manifest of an interrupted run as well
+    let finished_inputs = if image_settings.clear_files_output_directory {
+        HashSet::new()
+    } else {
+        JobManifestManager::read_finished_inputs(
+            output_directory,
+            &ProcessingMarker::for_settings(image_settings)?,
+            image_settings.sequence_naming.is_none(),
+        )
+    };
+
+    // The outputs in an output directory which the run clears don't prevent processing an image
+    let clearing_image_settings;
+    let image_settings = if image_settings.clear_files_output_directory {
+        clearing_image_settings = ImageSettings {
+            collision_strategy: CollisionStrategy::Overwrite,
+            ..image_settings.clone()
+        };
+        &clearing_image_settings
+    } else {
+        image_settings
+    };
+
+    let image_paths = discover_image_paths(image_settings, &finished_inputs, true)?;
+    let (image_paths, growing_image_paths) = partition_growing_files(image_paths)?;
+
+    let image_list = prepare_image_list(image_settings, &image_paths, true, &SilentProgressSink)?;
+    let mut output_path_claims = OutputPathClaims::new();
+    let mut sequence_position = 0;
+    let mut image_batches = plan_image_batches(
+        output_directory,
+        image_list,
+        image_settings,
+        input_directory,
+        &mut sequence_position,
+        &mut output_path_claims,
+    )?;
+
+    // A run processes the files which are still being written last, once they're complete
+    if !growing_image_paths.is_empty() {
+        RunReportManager::add_warning(format!(
+            "{} images are still being written, a run processes them last",
+            growing_image_paths.len()
+        ));
+        let deferred_image_list = prepare_image_list(
+            image_settings,
+            &growing_image_paths,
+            true,
+            &SilentProgressSink,
+        )?;
+        image_batches.extend(plan_image_batches(
+            output_directory,
+            deferred_image_list,
+            image_settings,
+            input_directory,
+            &mut sequence_position,
+            &mut output_path_claims,
+        )?);
+    }
+
+    Ok(image_batches)
+}
+
+/// Read the image paths from the input directory and skip the files a run doesn't process
+///
+/// A dry run, e.g. of a plan, doesn't download cloud placeholders or quarantine tiny files.
+fn discover_image_paths(
+    image_settings: &ImageSettings,
+    finished_inputs: &HashSet<PathBuf>,
+    dry_run: bool,
+) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+    let input_directory = &image_settings.input_directory;
+    let output_directory = &image_settings.output_directory;
+
+    let image_paths =
+        read_image_paths_from_input_directory(image_settings, input_directory, output_directory)?;
+    let image_paths = skip_finished_inputs(image_paths, finished_inputs);
+    let image_paths = handle_cloud_placeholders(
+        image_paths,
+        image_settings.hydrate_cloud_placeholders && !dry_run,
+    )?;
+    let image_paths = handle_tiny_files(
+        image_paths,
+        image_settings.min_file_size,
+        image_settings.quarantine_tiny_files && !dry_run,
+        input_directory,
+        output_directory,
+    )?;
+    let image_paths = if image_settings.skip_marked_files {
+        let processing_marker = ProcessingMarker::for_settings(image_settings)?;
+        skip_marked_files(image_paths, &processing_marker, read_image_comment)?
+    } else {
+        image_paths
+    };
+    let image_paths = if image_settings.skip_duplicate_images {
+        skip_duplicate_images(image_paths, image_settings.duplicate_max_distance)?
+    } else {
+        image_paths
+    };
+
+    Ok(image_paths)
+}
+
+/// Create the image structs for the paths and apply the settings to them, sorted in the order
+/// they're processed
+///
+/// A dry run, e.g. of a plan, doesn't hold flagged images for review.
+fn prepare_image_list(
+    image_settings: &ImageSettings,
+    image_paths: &[PathBuf],
+    dry_run: bool,
+    progress: &dyn ProgressSink,
+) -> Result<Vec<Image>, Box<dyn Error + Send + Sync>> {
+    progress.set_stage(ProgressStage::Probing);
+    progress.set_status("Creating image structs...".to_string());
     let image_creation_time = std::time::Instant::now();
-    image_list = create_images_from_paths_parallel(&valid_image_paths)?;
+    let mut image_list = create_images_from_paths_parallel(image_paths)?;
     info!(
         "Creating image structs took: {:?}",
         image_creation_time.elapsed()
     );
+    RunReportManager::record_phase(RunPhase::StructCreation, image_creation_time.elapsed());
 
     if image_list.is_empty() {
-        ProgressManager::set_status("No valid images could be loaded".to_string());
-        info!("No valid images could be loaded, returning early.");
-        info!("Total time: {:?}", start_time.elapsed());
-        return Ok(());
+        return Ok(image_list);
+    }
+
+    check_process_cancelled()?;
+
+    image_list =
+        skip_media_outside_input_limits(image_list, &ImageSettingsValidator::new(image_settings));
+
+    if image_settings.review_flagged_files && !dry_run {
+        image_list = review_flagged_media(image_list, image_settings.min_pixel_count, progress)?;
     }
 
     check_process_cancelled()?;
 
-    ProgressManager::set_status("Sorting images by file size... (Step 4/7)".to_string());
+    progress.set_status("Sorting images by file size...".to_string());
     let sort_start = std::time::Instant::now();
     sort_by_file_size(&mut image_list);
     info!(
@@ -101,61 +566,213 @@ pub fn handle_images(image_settings: &ImageSettings) -> Result<(), Box<dyn Error
 
     check_process_cancelled()?;
 
-    ProgressManager::set_status("Applying image settings... (Step 5/7)".to_string());
+    progress.set_status("Applying image settings...".to_string());
     let apply_settings_start = std::time::Instant::now();
-    apply_image_settings_per_image(image_settings, &mut image_list)?;
+    let logo_overrides = if image_settings.add_logo && image_settings.search_child_folders {
+        LogoOverrides::load(
+            &image_settings.input_directory,
+            image_list.iter().map(|image| image.file_path.as_path()),
+        )?
+    } else {
+        LogoOverrides::default()
+    };
+    apply_image_settings_per_image(image_settings, &logo_overrides, &mut image_list)?;
     info!(
         "Applying image settings took: {:?}",
         apply_settings_start.elapsed()
     );
 
-    ProgressManager::set_status("Processing logos... (Step 6/7)".to_string());
+    Ok(image_list)
+}
+
+/// Process a single image with the settings into the preview folder, so the logo size and
+/// placement can be checked before a run
+///
+/// Only the first output size is written and the input directory isn't touched. Returns the path
+/// of the preview image.
+pub fn preview_image(
+    image_settings: &ImageSettings,
+    image_path: &Path,
+) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+    let image_settings = &resolve_image_brand_kit(image_settings)?;
+    validate_export_sizes(&image_settings.export_sizes)?;
+    image_settings.layers.validate()?;
+
+    // A cancelled run keeps the cancel flag set until the next run, which would cancel the preview
+    if !RunReportManager::is_run_active() {
+        ProcessManager::clear();
+    }
+
+    let preview_directory = temp_preview_directory()?;
+    clear_and_create_folder(&preview_directory)?;
+
+    let mut image_list = vec![Image::new(image_path.to_path_buf())?];
+    apply_image_settings_per_image(image_settings, &LogoOverrides::default(), &mut image_list)?;
+    for image in &mut image_list {
+        image.export_resolutions.truncate(1);
+    }
+
+    let logo_list = process_logos_for_image_resolutions(
+        image_settings,
+        &image_list,
+        &preview_directory.join("logos"),
+        &SilentProgressSink,
+    )?;
+    let image = image_list.remove(0);
+    let target_resolutions = image.output_resolutions();
+    let logos: Option<Vec<&Logo>> = logo_list
+        .as_ref()
+        .map(|logo_list| logo_list.iter().collect());
+
+    // Every preview gets its own name, so the UI doesn't show a cached earlier preview
+    let preview_path = preview_directory.join(format!(
+        "preview_{}.{}",
+        chrono::Local::now().format("%H%M%S%3f"),
+        image.file_type
+    ));
+    let target_file_type = image.file_type.clone();
+    let mut ffmpeg_batch_command = create_image_ffmpeg_command(
+        &[(image, vec![preview_path.clone()])],
+        logos.as_deref(),
+        image_settings,
+        &create_resize_filter(image_settings)?,
+        &target_resolutions,
+        &target_file_type,
+    )?;
+    ffmpeg_logger(ffmpeg_batch_command.command.spawn()?, None, None)?;
+    finish_partial_outputs(&ffmpeg_batch_command.output_paths)?;
+
+    Ok(preview_path)
+}
+
+/// Folder of the preview images, in the application root
+pub fn temp_preview_directory() -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+    let app_root = std::env::current_exe()?
+        .parent()
+        .ok_or("Failed to get application directory")?
+        .to_path_buf();
+
+    Ok(app_root.join("temp_preview_images"))
+}
+
+/// Create the resize filter of the images, which applies every setting besides the overlays
+fn create_resize_filter(
+    image_settings: &ImageSettings,
+) -> Result<ResizeFilter, Box<dyn Error + Send + Sync>> {
+    ResizeFilter::new(
+        image_settings.resize_mode,
+        Some(image_settings.scale_algorithm.ffmpeg_flags()),
+        &image_settings.resize_pad_color,
+    )?
+    .with_sharpening(image_settings.sharpen_amount)
+    .with_color_adjustments(&image_settings.color_adjustments)
+    .with_border(image_settings.border.as_ref())
+}
+
+/// Create the image structs for the paths and process them, returning the output paths
+fn process_image_paths(
+    image_settings: &ImageSettings,
+    image_paths: &[PathBuf],
+    sequence_position: &mut usize,
+    output_path_claims: &mut OutputPathClaims,
+    progress: &dyn ProgressSink,
+) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+    let output_directory = &image_settings.output_directory;
+
+    let image_list = prepare_image_list(image_settings, image_paths, false, progress)?;
+    if image_list.is_empty() {
+        progress.set_status("No valid images could be loaded".to_string());
+        info!("No valid images could be loaded, skipping processing.");
+        return Ok(Vec::new());
+    }
+
+    check_free_space(
+        output_directory,
+        image_list
+            .iter()
+            .flat_map(|image| {
+                image
+                    .output_resolutions()
+                    .into_iter()
+                    .map(|resolution| estimate_image_output_size(&resolution, &image.file_type))
+            })
+            .sum(),
+        progress,
+    )?;
+
+    progress.set_stage(ProgressStage::LogoPrep);
+    progress.set_status("Processing logos...".to_string());
     let logo_processing_start = std::time::Instant::now();
-    let logo_list = process_logos_for_image_resolutions(image_settings, &image_list)?;
+    let logo_list = process_logos_for_image_resolutions(
+        image_settings,
+        &image_list,
+        &temp_logo_directory()?,
+        progress,
+    )?;
     info!(
         "Processing logos took: {:?}",
         logo_processing_start.elapsed()
     );
+    RunReportManager::record_phase(RunPhase::LogoPreparation, logo_processing_start.elapsed());
 
     check_process_cancelled()?;
 
-    ProgressManager::set_status("Processing images... (Step 7/7)".to_string());
-    ProgressManager::set_total(image_list.len());
+    progress.set_stage(ProgressStage::Encoding);
+    progress.set_status("Processing images...".to_string());
+    progress.add_total(
+        image_list
+            .iter()
+            .map(|image| image.progress_size() as usize)
+            .sum(),
+    );
+    progress.add_alternative_total(image_list.len());
     let image_processing_start = std::time::Instant::now();
-    process_images_from_image_list(
-        output_directory,
+    let output_paths = process_images_from_image_list(
         image_list,
         logo_list,
         image_settings,
-        input_directory,
+        sequence_position,
+        output_path_claims,
+        progress,
     )?;
 
-    ProgressManager::finish_progress();
-
     info!(
         "Processing images took: {:?}",
         image_processing_start.elapsed()
     );
+    RunReportManager::record_phase(RunPhase::Processing, image_processing_start.elapsed());
 
-    info!("Total time: {:?}", start_time.elapsed());
-
-    Ok(())
+    Ok(output_paths)
 }
 
 /// Apply the image settings per image in parallel
 fn apply_image_settings_per_image(
     image_settings: &ImageSettings,
+    logo_overrides: &LogoOverrides,
     image_list: &mut Vec<Image>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     check_process_cancelled()?;
 
+    let resize_target = image_settings.resize_target();
+    let export_resize_targets = image_settings.export_resize_targets();
+
     // Use try_for_each to allow early termination on cancellation
     image_list.par_iter_mut().try_for_each(
         |image| -> Result<(), Box<dyn Error + Send + Sync>> {
             check_process_cancelled()?;
 
-            image.resize_dimensions(&image_settings.min_pixel_count);
+            image.export_resolutions = export_resize_targets
+                .iter()
+                .map(|export_resize_target| {
+                    export_resize_target.calculate_resolution(&image.resolution)
+                })
+                .collect();
+            image.resize_to_target(&resize_target);
+            if image_settings.preserve_hdr && !image.is_animated {
+                image.hdr_transfer = read_image_hdr_transfer(&image.file_path, &image.file_type);
+            }
             image.file_type = image_settings.format.clone();
+            image.logo_override = logo_overrides.for_media(&image.file_path);
             Ok(())
         },
     )?;
@@ -163,29 +780,235 @@ fn apply_image_settings_per_image(
     Ok(())
 }
 
+/// Largest number of images per FFmpeg command, batches are split into chunks for better load
+/// balancing and more frequent progress bar progression
+const IMAGE_BATCH_CHUNK_SIZE: usize = 10;
+
+/// A batch command with the images and logos it was created from, so a failed batch can be split
+pub struct ImageBatchCommand<'a> {
+    pub ffmpeg_batch_command: FfmpegBatchCommand,
+    pub batch_data: Vec<(Image, Vec<PathBuf>)>,
+    pub logos: Option<Vec<&'a Logo>>,
+}
+
+/// Images with the same outputs, with an output path per output resolution of each image
+struct ImageBatch {
+    key: BatchKey,
+    batch_data: Vec<(Image, Vec<PathBuf>)>,
+}
+
 #[derive(Hash, Eq, PartialEq, Clone)]
 struct BatchKey {
-    resolution: Resolution,
+    /// The resolution of every output, so all images of a batch share their logos
+    resolutions: Vec<Resolution>,
     file_type: String,
+    logo_override: Option<LogoOverride>,
+    /// Animations are processed in their own commands, since their outputs need other arguments
+    is_animated: bool,
+    /// HDR images are processed in their own commands, since their outputs need other arguments
+    hdr_transfer: Option<String>,
 }
 
 /// Process the images from the image list in batches sequentially by size
+///
+/// Numbered outputs start at `sequence_position` in the sequence. Returns the paths of all output
+/// files that were written
 fn process_images_from_image_list(
-    output_directory: &Path,
     image_list: Vec<Image>,
     logo_list: Option<Vec<Logo>>,
     image_settings: &ImageSettings,
+    sequence_position: &mut usize,
+    output_path_claims: &mut OutputPathClaims,
+    progress: &dyn ProgressSink,
+) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+    let input_directory = &image_settings.input_directory;
+    let output_directory = &image_settings.output_directory;
+
+    let batches = plan_image_batches(
+        output_directory,
+        image_list,
+        image_settings,
+        input_directory,
+        sequence_position,
+        output_path_claims,
+    )?;
+
+    check_process_cancelled()?;
+
+    let resize_filter = create_resize_filter(image_settings)?;
+
+    let processing_marker = ProcessingMarker::for_settings(image_settings)?;
+    let ffmpeg_log_directory =
+        FfmpegLogDirectory::new(output_directory, image_settings.ffmpeg_log_level)?;
+
+    let mut ffmpeg_command_list: Vec<ImageBatchCommand> = Vec::new();
+
+    for ImageBatch {
+        key: batch_key,
+        batch_data,
+    } in batches
+    {
+        // Check cancellation at the start of each work unit
+        check_process_cancelled()?;
+
+        // A logo per output resolution, in the same order as the resolutions
+        let logos: Option<Vec<&Logo>> = match logo_list {
+            Some(ref logo_list) => Some(
+                batch_key
+                    .resolutions
+                    .iter()
+                    .map(|resolution| {
+                        find_logo(logo_list, resolution, &batch_key.logo_override).ok_or_else(
+                            || {
+                                format!(
+                                    "No logo found for the given image resolution: {}",
+                                    resolution
+                                )
+                            },
+                        )
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            None => None,
+        };
+
+        info!(
+            "Processing work unit with {} images ({}, {})",
+            batch_data.len(),
+            format_resolutions(&batch_key.resolutions),
+            batch_key.file_type
+        );
+        progress.redraw();
+
+        create_image_ffmpeg_command_list(
+            &batch_data,
+            logos.as_deref(),
+            image_settings,
+            &resize_filter,
+            &mut ffmpeg_command_list,
+        )
+        .map_err(|e| -> Box<dyn Error + Send + Sync> {
+            format!("Failed to process image batch: {}", e).into()
+        })?;
+    }
+
+    // Sort the commands by batch size
+    ffmpeg_command_list.sort_by(|a, b| {
+        b.ffmpeg_batch_command
+            .batch_size
+            .cmp(&a.ffmpeg_batch_command.batch_size)
+    });
+
+    let output_paths: Vec<PathBuf> = ffmpeg_command_list
+        .iter()
+        .flat_map(|command| command.ffmpeg_batch_command.output_paths.iter().cloned())
+        .collect();
+    RunReportManager::record_planned_files(
+        ffmpeg_command_list
+            .iter()
+            .map(|command| command.ffmpeg_batch_command.batch_size)
+            .sum(),
+    );
+    JobManifestManager::record_planned_inputs(ffmpeg_command_list.iter().flat_map(|command| {
+        command
+            .batch_data
+            .iter()
+            .map(|(image, output_paths)| (image.file_path.as_path(), output_paths.as_slice()))
+    }));
+
+    // Execute FFmpeg commands in parallel
+    let failed_image_commands: Vec<ImageBatchCommand> = ffmpeg_command_list
+        .into_iter()
+        .par_bridge()
+        .map(|image_batch_command| {
+            run_image_batch_command(
+                image_batch_command,
+                image_settings,
+                &resize_filter,
+                &processing_marker,
+                ffmpeg_log_directory.as_ref(),
+                progress,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+
+    // Failed images are retried one at a time, since some only fail next to the other processes,
+    // e.g. when the memory runs out
+    let mut failed_output_paths = Vec::new();
+    for image_batch_command in failed_image_commands {
+        check_process_cancelled()?;
+        failed_output_paths.extend(retry_failed_image(
+            image_batch_command,
+            image_settings,
+            &resize_filter,
+            &processing_marker,
+            ffmpeg_log_directory.as_ref(),
+            progress,
+        )?);
+    }
+
+    if !failed_output_paths.is_empty() {
+        warn!(
+            "Skipped {} outputs of images which failed to process",
+            failed_output_paths.len()
+        );
+    }
+
+    Ok(output_paths
+        .into_iter()
+        .filter(|output_path| !failed_output_paths.contains(output_path))
+        .collect())
+}
+
+/// Group the images into batches of images with the same outputs, with an output path per output
+/// resolution of each image
+///
+/// Numbered outputs start at `sequence_position`, which is advanced past the images.
+fn plan_image_batches(
+    output_directory: &Path,
+    image_list: Vec<Image>,
+    image_settings: &ImageSettings,
     input_directory: &Path,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
+    sequence_position: &mut usize,
+    output_path_claims: &mut OutputPathClaims,
+) -> Result<Vec<ImageBatch>, Box<dyn Error + Send + Sync>> {
     check_process_cancelled()?;
 
+    // Numbers follow the natural path order, so they don't depend on how the batches are scheduled
+    let sequence_stems: HashMap<PathBuf, String> = match &image_settings.sequence_naming {
+        Some(sequence_naming) => {
+            let mut image_paths: Vec<&PathBuf> =
+                image_list.iter().map(|image| &image.file_path).collect();
+            image_paths.sort_by(|a, b| natural_path_cmp(a, b));
+            image_paths
+                .into_iter()
+                .enumerate()
+                .map(|(position, image_path)| {
+                    (
+                        image_path.clone(),
+                        sequence_naming.file_stem(*sequence_position + position),
+                    )
+                })
+                .collect()
+        }
+        None => HashMap::new(),
+    };
+    // Every input takes a number, however many outputs it has
+    *sequence_position += image_list.len();
+
     // Group images by resolution and file type to create initial batches
     let mut batches: HashMap<BatchKey, Vec<Image>> = HashMap::new();
 
     for image in image_list {
         let key = BatchKey {
-            resolution: image.resolution.clone(),
+            resolutions: image.output_resolutions(),
             file_type: image.file_type.clone(),
+            logo_override: image.logo_override.clone(),
+            is_animated: image.is_animated,
+            hdr_transfer: image.hdr_transfer.clone(),
         };
         batches.entry(key).or_default().push(image);
     }
@@ -194,92 +1017,392 @@ fn process_images_from_image_list(
 
     check_process_cancelled()?;
 
-    let mut ffmpeg_command_list: Vec<FfmpegBatchCommand> = Vec::new();
+    let filename_template = FilenameTemplate::new(&image_settings.filename_template)?;
+    let output_mapping = image_settings.output_mapping()?;
 
+    let mut image_batches = Vec::with_capacity(batches.len());
     for (batch_key, images) in batches {
-        // Check cancellation at the start of each work unit
         check_process_cancelled()?;
 
-        let logo: Option<&Logo> = if let Some(ref logo_list) = logo_list {
-            logo_list
+        // Prepare batch data with an output path per output resolution, without the images whose
+        // outputs already exist when those are skipped
+        let mut batch_data: Vec<(Image, Vec<PathBuf>)> = Vec::with_capacity(images.len());
+        for image in &images {
+            let mapped_output = output_mapping.find(&image.file_path, input_directory);
+            let final_output_directory = if let Some(mapped_output) = mapped_output {
+                output_directory.join(&mapped_output.subfolder)
+            } else if image_settings.keep_child_folders_structure_in_output_directory {
+                let relative_image_path = get_relative_path(input_directory, &image.file_path)
+                    .unwrap_or_else(|_| PathBuf::from(""));
+                let relative_dir_path = relative_image_path.parent().unwrap_or(Path::new(""));
+                output_directory.join(relative_dir_path)
+            } else {
+                output_directory.to_path_buf()
+            };
+
+            let output_paths = batch_key
+                .resolutions
                 .iter()
-                .find(|logo| logo.compatible_image_resolution == batch_key.resolution)
-        } else {
-            None
-        };
+                .enumerate()
+                .map(|(size_index, resolution)| {
+                    // A mapped name replaces the number of the sequence
+                    let file_stem = mapped_output
+                        .and_then(|output| output.file_stem.as_ref())
+                        .or_else(|| sequence_stems.get(&image.file_path));
+                    let relative_output_path = match file_stem {
+                        Some(sequence_stem) => filename_template.render_with_stem(
+                            sequence_stem,
+                            Some(resolution),
+                            &image.file_type,
+                        )?,
+                        None => filename_template.render(
+                            &image.file_path,
+                            Some(resolution),
+                            &image.file_type,
+                        )?,
+                    };
+                    // Every export size is written to its own subfolder
+                    let output_path = match image_settings.export_sizes.get(size_index) {
+                        Some(export_size) => final_output_directory
+                            .join(&export_size.name)
+                            .join(relative_output_path),
+                        None => final_output_directory.join(relative_output_path),
+                    };
+                    Ok(output_path)
+                })
+                .collect::<Result<_, Box<dyn Error + Send + Sync>>>()?;
+            let Some(output_paths) = output_path_claims
+                .resolve_output_collisions(output_paths, image_settings.collision_strategy)?
+            else {
+                RunReportManager::record_skipped_file(
+                    image.file_path.clone(),
+                    "The outputs already exist".to_string(),
+                );
+                continue;
+            };
+            batch_data.push((image.clone(), output_paths));
+        }
+        if batch_data.is_empty() {
+            continue;
+        }
 
-        if logo.is_none() && logo_list.is_some() {
-            return Err(format!(
-                "No logo found for the given image resolution: {}",
-                batch_key.resolution
-            )
-            .into());
+        image_batches.push(ImageBatch {
+            key: batch_key,
+            batch_data,
+        });
+    }
+
+    Ok(image_batches)
+}
+
+/// Run a batch command, returning the commands of the images which failed to retry them once the
+/// other images are done
+fn run_image_batch_command<'a>(
+    mut image_batch_command: ImageBatchCommand<'a>,
+    image_settings: &ImageSettings,
+    resize_filter: &ResizeFilter,
+    processing_marker: &ProcessingMarker,
+    ffmpeg_log_directory: Option<&FfmpegLogDirectory>,
+    progress: &dyn ProgressSink,
+) -> Result<Vec<ImageBatchCommand<'a>>, Box<dyn Error + Send + Sync>> {
+    match spawn_ffmpeg_process(
+        &mut image_batch_command.ffmpeg_batch_command,
+        ProgressMode::Batch,
+        progress,
+        ffmpeg_log_directory,
+    ) {
+        Ok(()) => {
+            finish_image_batch(&image_batch_command, image_settings, processing_marker);
+            Ok(Vec::new())
         }
+        Err(error) => handle_failed_image_batch(
+            image_batch_command,
+            error,
+            image_settings,
+            resize_filter,
+            processing_marker,
+            ffmpeg_log_directory,
+            progress,
+        ),
+    }
+}
 
-        // Prepare batch data with output directories
-        let batch_data: Vec<(Image, PathBuf)> = images
-            .iter()
-            .map(|image| {
-                let final_output_directory = if image_settings
-                    .keep_child_folders_structure_in_output_directory
-                {
-                    let relative_image_path = get_relative_path(input_directory, &image.file_path)
-                        .unwrap_or_else(|_| PathBuf::from(""));
-                    let relative_dir_path = relative_image_path.parent().unwrap_or(Path::new(""));
-                    output_directory.join(relative_dir_path)
-                } else {
-                    output_directory.to_path_buf()
-                };
-                (image.clone(), final_output_directory)
-            })
-            .collect();
+/// Find the images of a failed batch which fail on their own
+///
+/// A corrupt image fails the whole command, so a batch which failed on the data of an input is
+/// split in halves until the failing images are found, and the other images are processed
+/// without them. Other failures, e.g. when the memory runs out, don't depend on a single image,
+/// so the images of the batch are retried one at a time instead. Returns the commands of the
+/// images to retry.
+fn handle_failed_image_batch<'a>(
+    image_batch_command: ImageBatchCommand<'a>,
+    error: Box<dyn Error + Send + Sync>,
+    image_settings: &ImageSettings,
+    resize_filter: &ResizeFilter,
+    processing_marker: &ProcessingMarker,
+    ffmpeg_log_directory: Option<&FfmpegLogDirectory>,
+    progress: &dyn ProgressSink,
+) -> Result<Vec<ImageBatchCommand<'a>>, Box<dyn Error + Send + Sync>> {
+    // A cancelled process fails as well, which must not be retried
+    check_process_cancelled()?;
 
-        info!(
-            "Processing work unit with {} images ({}x{}, {})",
+    if let [(image, _)] = image_batch_command.batch_data.as_slice() {
+        warn!(
+            "Processing {} failed, retrying it once the other images are done: {}",
+            image.file_path.display(),
+            error
+        );
+        return Ok(vec![image_batch_command]);
+    }
+
+    let batch_data = image_batch_command.batch_data;
+    let first_image = &batch_data[0].0;
+    let target_resolutions = &first_image.output_resolutions();
+    let target_file_type = &first_image.file_type;
+    let create_command = |images: &[(Image, Vec<PathBuf>)]| {
+        create_image_ffmpeg_command(
+            images,
+            image_batch_command.logos.as_deref(),
+            image_settings,
+            resize_filter,
+            target_resolutions,
+            target_file_type,
+        )
+        .map(|ffmpeg_batch_command| ImageBatchCommand {
+            ffmpeg_batch_command,
+            batch_data: images.to_vec(),
+            logos: image_batch_command.logos.clone(),
+        })
+    };
+
+    let is_input_error = error
+        .downcast_ref::<FfmpegProcessError>()
+        .is_some_and(FfmpegProcessError::is_input_error);
+    if !is_input_error {
+        warn!(
+            "Batch of {} images failed, retrying the images one at a time: {}",
             batch_data.len(),
-            batch_key.resolution.width,
-            batch_key.resolution.height,
-            batch_key.file_type
+            error
         );
-        ProgressManager::redraw_progress();
+        return batch_data
+            .chunks(1)
+            .map(create_command)
+            .collect::<Result<_, _>>();
+    }
 
-        create_image_ffmpeg_command_list(&batch_data, logo, &mut ffmpeg_command_list).map_err(
-            |e| -> Box<dyn Error + Send + Sync> {
-                format!("Failed to process image batch: {}", e).into()
-            },
-        )?;
+    warn!(
+        "Batch of {} images failed, retrying it in halves to find the failing images: {}",
+        batch_data.len(),
+        error
+    );
+
+    let mut failed_halves = Vec::new();
+    for half in batch_data.chunks(batch_data.len().div_ceil(2)) {
+        let mut half_command = create_command(half)?;
+        match spawn_ffmpeg_process(
+            &mut half_command.ffmpeg_batch_command,
+            ProgressMode::Batch,
+            progress,
+            ffmpeg_log_directory,
+        ) {
+            Ok(()) => finish_image_batch(&half_command, image_settings, processing_marker),
+            Err(half_error) => failed_halves.push((half_command, half_error)),
+        }
     }
 
-    // Sort the commands by batch size
-    ffmpeg_command_list.sort_by(|a, b| b.batch_size.cmp(&a.batch_size));
+    // A corrupt image is in one of the halves, an error of both halves comes from the command
+    if let [(_, first_error), (_, second_error)] = failed_halves.as_slice() {
+        if let (Some(first_error), Some(second_error)) = (
+            first_error.downcast_ref::<FfmpegProcessError>(),
+            second_error.downcast_ref::<FfmpegProcessError>(),
+        ) {
+            if first_error.error_messages == second_error.error_messages {
+                return Err(format!(
+                    "Processing the images failed with the same error in both halves of a batch: {}",
+                    first_error.error_messages.join("; ")
+                )
+                .into());
+            }
+        }
+    }
 
-    // Execute FFmpeg commands in parallel
-    ffmpeg_command_list.into_iter().par_bridge().try_for_each(
-        |mut ffmpeg_batch_command| -> Result<(), Box<dyn Error + Send + Sync>> {
-            spawn_ffmpeg_process(&mut ffmpeg_batch_command, ProgressMode::Batch)?;
-            Ok(())
-        },
+    let mut failed_image_commands = Vec::new();
+    for (half_command, half_error) in failed_halves {
+        failed_image_commands.extend(handle_failed_image_batch(
+            half_command,
+            half_error,
+            image_settings,
+            resize_filter,
+            processing_marker,
+            ffmpeg_log_directory,
+            progress,
+        )?);
+    }
+
+    Ok(failed_image_commands)
+}
+
+/// Retry the command of an image which failed on its own, an image which fails again is skipped
+/// and reported as a failed file
+///
+/// Returns the output paths of the image when it failed again.
+fn retry_failed_image(
+    image_batch_command: ImageBatchCommand,
+    image_settings: &ImageSettings,
+    resize_filter: &ResizeFilter,
+    processing_marker: &ProcessingMarker,
+    ffmpeg_log_directory: Option<&FfmpegLogDirectory>,
+    progress: &dyn ProgressSink,
+) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+    let [(image, output_paths)] = image_batch_command.batch_data.as_slice() else {
+        return Err("Only the commands of single images are retried".into());
+    };
+
+    // The command is rebuilt, since running it changed its arguments
+    let mut ffmpeg_batch_command = create_image_ffmpeg_command(
+        &image_batch_command.batch_data,
+        image_batch_command.logos.as_deref(),
+        image_settings,
+        resize_filter,
+        &image.output_resolutions(),
+        &image.file_type,
     )?;
+    let error = match spawn_ffmpeg_process(
+        &mut ffmpeg_batch_command,
+        ProgressMode::Batch,
+        progress,
+        ffmpeg_log_directory,
+    ) {
+        Ok(()) => {
+            let image_batch_command = ImageBatchCommand {
+                ffmpeg_batch_command,
+                ..image_batch_command
+            };
+            finish_image_batch(&image_batch_command, image_settings, processing_marker);
+            return Ok(Vec::new());
+        }
+        Err(e) => e,
+    };
 
-    Ok(())
+    check_process_cancelled()?;
+
+    warn!(
+        "Skipping {}, it failed to process again: {}",
+        image.file_path.display(),
+        error
+    );
+    RunReportManager::record_failed_file(FailedFile {
+        path: image.file_path.clone(),
+        error: error.to_string(),
+        artifact_directory: None,
+    });
+    progress.increment(image.progress_size() as usize);
+    progress.increment_alternative(1);
+    Ok(output_paths.clone())
+}
+
+/// Apply the steps after FFmpeg to the outputs of a finished batch
+fn finish_image_batch(
+    image_batch_command: &ImageBatchCommand,
+    image_settings: &ImageSettings,
+    processing_marker: &ProcessingMarker,
+) {
+    let ffmpeg_batch_command = &image_batch_command.ffmpeg_batch_command;
+    if image_settings.metadata_mode == MetadataMode::Preserve {
+        copy_exif_metadata_for_batch(ffmpeg_batch_command);
+    }
+    if image_settings.embed_exif_thumbnail {
+        embed_exif_thumbnails_for_batch(ffmpeg_batch_command);
+    }
+    write_processing_marker_for_batch(ffmpeg_batch_command, processing_marker);
+    JobManifestManager::record_finished_inputs(
+        image_batch_command
+            .batch_data
+            .iter()
+            .map(|(image, _)| image.file_path.as_path()),
+    );
+    for (image, output_paths) in &image_batch_command.batch_data {
+        dispose_source_file(
+            &image.file_path,
+            output_paths,
+            image_settings.source_disposition,
+            &image_settings.input_directory,
+        );
+    }
+}
+
+/// Copy the EXIF data of the source images into the output images of a finished batch
+fn copy_exif_metadata_for_batch(ffmpeg_batch_command: &FfmpegBatchCommand) {
+    for (input_path, output_path) in ffmpeg_batch_command
+        .input_paths
+        .iter()
+        .zip(&ffmpeg_batch_command.output_paths)
+    {
+        if let Err(e) = copy_exif_metadata(input_path, output_path) {
+            log::warn!(
+                "Failed to copy the metadata of {} to {}: {}",
+                input_path.display(),
+                output_path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// Embed a thumbnail in the EXIF data of the output images of a finished batch
+fn embed_exif_thumbnails_for_batch(ffmpeg_batch_command: &FfmpegBatchCommand) {
+    for output_path in &ffmpeg_batch_command.output_paths {
+        // Every thumbnail runs FFmpeg, a cancelled run doesn't wait for the rest of the batch
+        if ProcessManager::is_cancelled() {
+            return;
+        }
+        if let Err(e) = embed_exif_thumbnail(output_path) {
+            log::warn!(
+                "Failed to embed the EXIF thumbnail in {}: {}",
+                output_path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// Mark the output images of a finished batch as processed with the current settings
+fn write_processing_marker_for_batch(
+    ffmpeg_batch_command: &FfmpegBatchCommand,
+    processing_marker: &ProcessingMarker,
+) {
+    for output_path in &ffmpeg_batch_command.output_paths {
+        if let Err(e) = write_image_comment(output_path, processing_marker.as_str()) {
+            log::warn!(
+                "Failed to write the processing marker to {}: {}",
+                output_path.display(),
+                e
+            );
+        }
+    }
 }
 
 fn process_logos_for_image_resolutions(
     image_settings: &ImageSettings,
     image_list: &Vec<Image>,
+    logo_directory: &Path,
+    progress: &dyn ProgressSink,
 ) -> Result<Option<Vec<Logo>>, Box<dyn Error + Send + Sync>> {
     check_process_cancelled()?;
 
     let logo_list: Option<Vec<Logo>> = if image_settings.add_logo {
-        // Make a hashset of all the unique resolutions of the Images
-        let mut unique_resolutions = std::collections::HashSet::new();
+        // Make a hashset of all the unique resolutions and logo overrides of the Images
+        let mut logo_targets = std::collections::HashSet::new();
         for image in image_list {
-            unique_resolutions.insert(image.resolution.clone());
+            for resolution in image.output_resolutions() {
+                logo_targets.insert((resolution, image.logo_override.clone()));
+            }
         }
-        let unique_resolutions: Vec<Resolution> = unique_resolutions.into_iter().collect();
+        let logo_targets: Vec<(Resolution, Option<LogoOverride>)> =
+            logo_targets.into_iter().collect();
 
-        // Create a vector to store Logo structs for each unique resolution
-        let logos = handle_logos(image_settings, unique_resolutions)?;
+        // Create a vector to store Logo structs for each unique resolution and override
+        let logos = handle_logos(image_settings, logo_targets, logo_directory, progress)?;
         Some(logos)
     } else {
         None
@@ -336,70 +1459,102 @@ fn create_images_from_paths_parallel(
         })
         .collect()
 }
-pub fn create_image_ffmpeg_command_list(
-    batch_data: &[(Image, PathBuf)],
-    logo: Option<&Logo>,
-    ffmpeg_command_list: &mut Vec<FfmpegBatchCommand>,
+/// Format the output resolutions of a batch for logging, e.g. `2048x1365, 400x267`
+fn format_resolutions(resolutions: &[Resolution]) -> String {
+    resolutions
+        .iter()
+        .map(|resolution| format!("{}x{}", resolution.width, resolution.height))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn create_image_ffmpeg_command_list<'a>(
+    batch_data: &[(Image, Vec<PathBuf>)],
+    logos: Option<&[&'a Logo]>,
+    image_settings: &ImageSettings,
+    resize_filter: &ResizeFilter,
+    ffmpeg_command_list: &mut Vec<ImageBatchCommand<'a>>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     if batch_data.is_empty() {
         return Ok(());
     }
 
     let first_image = &batch_data[0].0;
-    let target_resolution = &first_image.resolution;
+    let target_resolutions = &first_image.output_resolutions();
     let target_file_type = &first_image.file_type;
 
     info!(
-        "Processing batch of {} images with resolution {}x{} and format {}",
+        "Processing batch of {} images with resolutions {} and format {}",
         batch_data.len(),
-        target_resolution.width,
-        target_resolution.height,
+        format_resolutions(target_resolutions),
         target_file_type,
     );
 
-    // Process in chunks for better load balancing and more frequent progress bar progression
-    const CHUNK_SIZE: usize = 10;
-
-    if batch_data.len() <= CHUNK_SIZE {
-        let batch_command =
-            create_image_ffmpeg_command(batch_data, logo, target_resolution, target_file_type)?;
+    if batch_data.len() <= IMAGE_BATCH_CHUNK_SIZE {
+        let batch_command = create_image_ffmpeg_command(
+            batch_data,
+            logos,
+            image_settings,
+            resize_filter,
+            target_resolutions,
+            target_file_type,
+        )?;
         info!(
             "Created command for batch of {} images",
             batch_command.batch_size
         );
-        ffmpeg_command_list.push(batch_command);
+        ffmpeg_command_list.push(ImageBatchCommand {
+            ffmpeg_batch_command: batch_command,
+            batch_data: batch_data.to_vec(),
+            logos: logos.map(<[&Logo]>::to_vec),
+        });
     } else {
-        let num_chunks = batch_data.len().div_ceil(CHUNK_SIZE);
+        let num_chunks = batch_data.len().div_ceil(IMAGE_BATCH_CHUNK_SIZE);
         let optimal_chunk_size = batch_data.len().div_ceil(num_chunks);
 
         for chunk in batch_data.chunks(optimal_chunk_size) {
-            let batch_command =
-                create_image_ffmpeg_command(chunk, logo, target_resolution, target_file_type)?;
+            let batch_command = create_image_ffmpeg_command(
+                chunk,
+                logos,
+                image_settings,
+                resize_filter,
+                target_resolutions,
+                target_file_type,
+            )?;
             info!(
                 "Created command for batch of {} images",
                 batch_command.batch_size
             );
-            ffmpeg_command_list.push(batch_command);
+            ffmpeg_command_list.push(ImageBatchCommand {
+                ffmpeg_batch_command: batch_command,
+                batch_data: chunk.to_vec(),
+                logos: logos.map(<[&Logo]>::to_vec),
+            });
         }
     }
 
     Ok(())
 }
 
+/// Create a single FFmpeg command which writes every output size of the images in the batch
 fn create_image_ffmpeg_command(
-    batch_data: &[(Image, PathBuf)],
-    logo: Option<&Logo>,
-    target_resolution: &Resolution,
+    batch_data: &[(Image, Vec<PathBuf>)],
+    logos: Option<&[&Logo]>,
+    image_settings: &ImageSettings,
+    resize_filter: &ResizeFilter,
+    target_resolutions: &[Resolution],
     target_file_type: &str,
 ) -> Result<FfmpegBatchCommand, Box<dyn Error + Send + Sync>> {
     check_process_cancelled()?;
 
-    // Create output directories
-    for (_, output_directory) in batch_data {
-        std::fs::create_dir_all(output_directory)?;
+    // Create output directories, templates and export sizes can place files in subfolders
+    for output_path in batch_data.iter().flat_map(|(_, output_paths)| output_paths) {
+        if let Some(output_directory) = output_path.parent() {
+            std::fs::create_dir_all(output_directory)?;
+        }
     }
 
-    let mut cmd = FfmpegCommand::new();
+    let mut cmd = ffmpeg_command()?;
 
     #[cfg(target_os = "windows")]
     cmd.hide_banner();
@@ -411,34 +1566,139 @@ fn create_image_ffmpeg_command(
         cmd.input(image.file_path.to_str().ok_or("Invalid image file path")?);
     }
 
-    // Add logo input if present
-    if let Some(logo_ref) = logo {
-        cmd.input(
-            logo_ref
-                .file_path
-                .to_str()
-                .ok_or("Invalid logo file path")?,
-        );
+    // Add a logo input per output size if present
+    for logo in logos.unwrap_or_default() {
+        cmd.input(logo.file_path.to_str().ok_or("Invalid logo file path")?);
     }
 
     // Build complex filter for this chunk
     let mut filter_parts = Vec::new();
+    // The streams which are mapped to each output, in the order of the outputs
+    let mut output_stream_labels: Vec<Vec<String>> = Vec::new();
+    // All images of a batch are either animated or still
+    let is_animated = batch_data[0].0.is_animated;
+    let background_color = parse_ffmpeg_color(&image_settings.background_color)?;
+
+    // HDR images keep their colors when the output can store them, otherwise they're tone mapped
+    let hdr_transfer = batch_data[0].0.hdr_transfer.as_deref();
+    let hdr_fallback_reason = hdr_transfer.and_then(|_| hdr_fallback_reason(target_file_type));
+    let tone_map_filter = hdr_fallback_reason.and_then(|_| tone_map_filter());
+    if let Some(reason) = hdr_fallback_reason {
+        for (image, _) in batch_data {
+            let message = format!(
+                "Converted the HDR image {} to SDR{}, since {}",
+                image.file_path.display(),
+                if tone_map_filter.is_some() {
+                    ""
+                } else {
+                    " without tone mapping"
+                },
+                reason
+            );
+            warn!("{}", message);
+            RunReportManager::add_warning(message);
+        }
+    }
+    let format_quality = match (hdr_transfer, hdr_fallback_reason) {
+        (Some(_), None) => hdr_format_quality(&image_settings.format_quality),
+        _ => image_settings.format_quality.clone(),
+    };
 
-    for (i, _) in batch_data.iter().enumerate() {
-        if let Some(logo_ref) = logo {
-            // Scale and overlay logo for each image
-            let logo_idx = batch_data.len(); // Logo is the last input
-            filter_parts.push(format!(
-                "[{}:v]scale={}:{}:flags=fast_bilinear[scaled{}];[scaled{}][{}:v]overlay={}:{}[out{}]",
-                i, target_resolution.width, target_resolution.height, i,
-                i, logo_idx, logo_ref.position.x, logo_ref.position.y, i
-            ));
+    // The logos are the last inputs, one per output size
+    let overlay_layers: Vec<Vec<OverlayLayer>> = (0..target_resolutions.len())
+        .map(|size_index| {
+            collect_overlay_layers(
+                &image_settings.layers,
+                logos.map(|logos| (logos[size_index], batch_data.len() + size_index)),
+            )
+        })
+        .collect();
+
+    for (i, (image, _)) in batch_data.iter().enumerate() {
+        let image_input_filter: Option<String> =
+            [tone_map_filter.map(str::to_string), image.input_filter()]
+                .into_iter()
+                .flatten()
+                .reduce(|filters, filter| format!("{},{}", filters, filter));
+        let (input_labels, input_filter) = if target_resolutions.len() == 1 {
+            (vec![format!("{}:v", i)], image_input_filter)
         } else {
-            // Scale each image without overlaying logo
-            filter_parts.push(format!(
-                "[{}:v]scale={}:{}:flags=fast_bilinear[out{}]",
-                i, target_resolution.width, target_resolution.height, i
+            // The image is decoded once and split into a stream per output size
+            let input_labels: Vec<String> = (0..target_resolutions.len())
+                .map(|size_index| format!("split{}_{}", i, size_index))
+                .collect();
+            let split_filter = format!(
+                "split={}{}",
+                input_labels.len(),
+                input_labels
+                    .iter()
+                    .map(|label| format!("[{}]", label))
+                    .collect::<String>()
+            );
+            filter_parts.push(match image_input_filter {
+                Some(input_filter) => format!("[{}:v]{},{}", i, input_filter, split_filter),
+                None => format!("[{}:v]{}", i, split_filter),
+            });
+            (input_labels, None)
+        };
+
+        // Scale and overlay the layers for each output size
+        for (size_index, (input_label, target_resolution)) in
+            input_labels.iter().zip(target_resolutions).enumerate()
+        {
+            if resize_filter.is_border_skipped(target_resolution) {
+                let message = format!(
+                    "Drew no border around {} at {}, since the image is too small to fit it",
+                    image.file_path.display(),
+                    target_resolution
+                );
+                warn!("{}", message);
+                RunReportManager::add_warning(message);
+            }
+
+            let output_label = format!("out{}_{}", i, size_index);
+            filter_parts.push(build_media_filter(
+                input_label,
+                input_filter.as_deref(),
+                target_resolution,
+                resize_filter,
+                &overlay_layers[size_index],
+                &output_label,
             ));
+
+            let output_label = match build_background_filter(
+                target_file_type,
+                &image_settings.format_quality,
+                &background_color,
+                target_resolution,
+                &output_label,
+            ) {
+                Some((background_filter, flat_label)) => {
+                    filter_parts.push(background_filter);
+                    flat_label
+                }
+                None => output_label,
+            };
+
+            let stream_filter = if is_animated {
+                build_gif_palette_filter(target_file_type, &output_label)
+                    .map(|(palette_filter, stream_label)| (palette_filter, vec![stream_label]))
+            } else {
+                None
+            };
+            match stream_filter.or_else(|| {
+                build_alpha_stream_filter(
+                    target_file_type,
+                    &image_settings.format_quality,
+                    &output_label,
+                )
+            }) {
+                Some((alpha_filter, stream_labels)) => {
+                    filter_parts.push(alpha_filter);
+                    output_stream_labels.push(stream_labels);
+                }
+                None => output_stream_labels.push(vec![output_label]),
+            }
         }
     }
 
@@ -446,24 +1706,41 @@ fn create_image_ffmpeg_command(
     cmd.args(["-filter_complex", &filter_complex]);
 
     // Add output mappings and files
-    for (i, (image, output_directory)) in batch_data.iter().enumerate() {
-        let file_stem = image
-            .file_path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .ok_or("Invalid file name")?;
-
-        let new_filename = format!("{}.{}", file_stem, target_file_type);
-        let output_file = output_directory.join(new_filename);
-
-        cmd.args(["-map", &format!("[out{}]", i)]);
-        apply_image_format_specific_args(target_file_type, &mut cmd);
-        cmd.output(output_file.to_str().ok_or("Invalid output file path")?);
+    let mut input_paths = Vec::with_capacity(batch_data.len());
+    let mut output_paths = Vec::with_capacity(batch_data.len());
+    let mut output_stream_labels = output_stream_labels.into_iter();
+    for (i, (image, output_files)) in batch_data.iter().enumerate() {
+        for output_file in output_files {
+            for stream_label in output_stream_labels.next().unwrap_or_default() {
+                cmd.args(["-map", &format!("[{}]", stream_label)]);
+            }
+            apply_image_format_specific_args(target_file_type, &format_quality, &mut cmd);
+            if let (Some(hdr_transfer), None) = (hdr_transfer, hdr_fallback_reason) {
+                apply_hdr_color_args(hdr_transfer, &mut cmd);
+            }
+            if is_animated {
+                apply_animation_args(target_file_type, &mut cmd);
+            }
+            image_settings.metadata_mode.apply_ffmpeg_args(i, &mut cmd);
+            cmd.output(
+                partial_output_path(output_file)
+                    .to_str()
+                    .ok_or("Invalid output file path")?,
+            );
+            input_paths.push(image.file_path.clone());
+            output_paths.push(output_file.clone());
+        }
     }
 
     // Return the command wrapped in ImageBatchCommand struct
     Ok(FfmpegBatchCommand {
         command: cmd,
         batch_size: batch_data.len(),
+        input_size: batch_data
+            .iter()
+            .map(|(image, _)| image.progress_size())
+            .sum(),
+        input_paths,
+        output_paths,
     })
 }
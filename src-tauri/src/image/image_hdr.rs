@@ -0,0 +1,49 @@
+use std::path::Path;
+
+use crate::{
+    image::image_formats::{image_format, FormatQualitySettings},
+    shared::{ffmpeg_capabilities::FFMPEG_CAPABILITIES, ffprobe::read_hdr_transfer},
+};
+
+/// Encoder which writes the AVIF outputs, it supports 10 and 12 bits per channel
+const AVIF_ENCODER: &str = "libaom-av1";
+
+/// Read the HDR transfer of an image, only formats which can store HDR colors are probed
+pub fn read_image_hdr_transfer(path: &Path, file_type: &str) -> Option<String> {
+    let can_store_hdr = [
+        image_format::AVIF,
+        image_format::JPEGXL,
+        image_format::PNG,
+        image_format::TIFF,
+    ]
+    .iter()
+    .any(|format| format.extensions.contains(&file_type));
+
+    if can_store_hdr {
+        read_hdr_transfer(path)
+    } else {
+        None
+    }
+}
+
+/// Get the reason HDR colors can't be written to the output format, `None` when they can
+///
+/// HDR colors are written to AVIF outputs, other formats are SDR only. Gain maps aren't
+/// supported by FFmpeg, so sources with a gain map are written from their SDR base image.
+pub fn hdr_fallback_reason(image_format: &str) -> Option<&'static str> {
+    if !image_format::AVIF.extensions.contains(&image_format) {
+        Some("the output format can't store HDR colors")
+    } else if !FFMPEG_CAPABILITIES.has_encoder(AVIF_ENCODER) {
+        Some("FFmpeg lacks the libaom-av1 encoder")
+    } else {
+        None
+    }
+}
+
+/// Get the quality settings of an HDR output, which needs at least 10 bits per channel
+pub fn hdr_format_quality(format_quality: &FormatQualitySettings) -> FormatQualitySettings {
+    FormatQualitySettings {
+        avif_bit_depth: format_quality.avif_bit_depth.max(10),
+        ..format_quality.clone()
+    }
+}
@@ -0,0 +1,433 @@
+use ffmpeg_sidecar::command::FfmpegCommand;
+use img_parts::{
+    jpeg::{markers as jpeg_markers, JpegSegment},
+    png::PngChunk,
+    Bytes, DynImage, ImageEXIF,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::BufReader,
+    path::Path,
+};
+use ts_rs::TS;
+
+use crate::shared::external_command::{external_command, ExternalBinary};
+use crate::shared::media_structs::Resolution;
+
+/// EXIF tag which stores the orientation of an image
+const EXIF_ORIENTATION_TAG: u16 = 0x0112;
+/// EXIF tags of the first IFD which store the thumbnail
+const EXIF_COMPRESSION_TAG: u16 = 0x0103;
+const EXIF_THUMBNAIL_OFFSET_TAG: u16 = 0x0201;
+const EXIF_THUMBNAIL_LENGTH_TAG: u16 = 0x0202;
+/// Longest side of the EXIF thumbnail, the size viewers like Explorer and Finder expect
+const EXIF_THUMBNAIL_SIZE: u32 = 160;
+/// EXIF data is stored in a single JPEG segment, which holds at most 64 KiB
+const MAX_EXIF_SIZE: usize = 65_000;
+/// Type of the PNG chunk which stores uncompressed text
+const PNG_TEXT_CHUNK: [u8; 4] = *b"tEXt";
+/// Keyword and separator which start the PNG text chunk of a comment
+const PNG_COMMENT_KEYWORD: &[u8] = b"Comment\0";
+
+/// How metadata like the capture date, GPS location and orientation is handled for output images
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum MetadataMode {
+    /// Copy the metadata and EXIF data of the source image to the output image
+    Preserve,
+    /// Remove all metadata from the output image, e.g. for privacy-focused exports
+    Strip,
+}
+
+impl MetadataMode {
+    /// Add the FFmpeg metadata mapping for the output of the input at `input_index`
+    ///
+    /// Without an explicit mapping FFmpeg copies the metadata of the first input to every output
+    /// of a batch.
+    pub fn apply_ffmpeg_args(&self, input_index: usize, cmd: &mut FfmpegCommand) {
+        match self {
+            MetadataMode::Preserve => cmd.args(["-map_metadata", &input_index.to_string()]),
+            MetadataMode::Strip => cmd.args(["-map_metadata", "-1"]),
+        };
+    }
+}
+
+/// Orientation of an image from its EXIF data, which describes how the stored pixels are
+/// transformed to display the image upright
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExifOrientation {
+    #[default]
+    Normal,
+    FlipHorizontal,
+    Rotate180,
+    FlipVertical,
+    Transpose,
+    Rotate90,
+    Transverse,
+    Rotate270,
+}
+
+impl ExifOrientation {
+    /// Read the orientation of an image, `Normal` when the image has no EXIF orientation
+    pub fn read(path: &Path) -> Self {
+        let Ok(file) = File::open(path) else {
+            return Self::Normal;
+        };
+
+        exif::Reader::new()
+            .read_from_container(&mut BufReader::new(file))
+            .ok()
+            .and_then(|exif| {
+                exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+                    .and_then(|field| field.value.get_uint(0))
+            })
+            .map(Self::from_exif_value)
+            .unwrap_or_default()
+    }
+
+    fn from_exif_value(value: u32) -> Self {
+        match value {
+            2 => Self::FlipHorizontal,
+            3 => Self::Rotate180,
+            4 => Self::FlipVertical,
+            5 => Self::Transpose,
+            6 => Self::Rotate90,
+            7 => Self::Transverse,
+            8 => Self::Rotate270,
+            _ => Self::Normal,
+        }
+    }
+
+    /// Get the FFmpeg filter which turns the stored pixels upright
+    pub fn to_filter(self) -> Option<&'static str> {
+        match self {
+            Self::Normal => None,
+            Self::FlipHorizontal => Some("hflip"),
+            Self::Rotate180 => Some("hflip,vflip"),
+            Self::FlipVertical => Some("vflip"),
+            Self::Transpose => Some("transpose=cclock_flip"),
+            Self::Rotate90 => Some("transpose=clock"),
+            Self::Transverse => Some("transpose=clock_flip"),
+            Self::Rotate270 => Some("transpose=cclock"),
+        }
+    }
+
+    /// Get the upright resolution of an image from the resolution of its stored pixels
+    pub fn apply_to_resolution(self, resolution: &Resolution) -> Resolution {
+        match self {
+            Self::Transpose | Self::Rotate90 | Self::Transverse | Self::Rotate270 => Resolution {
+                width: resolution.height,
+                height: resolution.width,
+            },
+            _ => resolution.clone(),
+        }
+    }
+}
+
+/// Copy the EXIF data of the source image into the output image
+///
+/// FFmpeg doesn't write EXIF data when encoding still images, so it's copied into the encoded
+/// file afterwards. Formats without EXIF support are left untouched. The orientation is reset,
+/// as the output pixels are already upright.
+pub fn copy_exif_metadata(
+    source_path: &Path,
+    output_path: &Path,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let Some(source_image) = DynImage::from_bytes(fs::read(source_path)?.into())? else {
+        return Ok(());
+    };
+    let Some(exif) = source_image.exif() else {
+        return Ok(());
+    };
+
+    let Some(mut output_image) = DynImage::from_bytes(fs::read(output_path)?.into())? else {
+        return Ok(());
+    };
+    output_image.set_exif(Some(reset_exif_orientation(exif)));
+
+    fs::write(output_path, output_image.encoder().bytes())?;
+
+    Ok(())
+}
+
+/// Embed a small thumbnail in the EXIF data of a JPEG image, so file browsers preview it quickly
+///
+/// A thumbnail copied from the source image is replaced, since it doesn't show the logo. Other
+/// formats are left untouched.
+pub fn embed_exif_thumbnail(path: &Path) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let Some(DynImage::Jpeg(mut jpeg)) = DynImage::from_bytes(fs::read(path)?.into())? else {
+        return Ok(());
+    };
+
+    let thumbnail = create_thumbnail_jpeg(path)?;
+    let exif = add_exif_thumbnail(jpeg.exif(), &thumbnail).ok_or("Invalid EXIF data")?;
+    if exif.len() > MAX_EXIF_SIZE {
+        return Err("The EXIF data with the thumbnail is too large".into());
+    }
+    jpeg.set_exif(Some(exif));
+
+    fs::write(path, jpeg.encoder().bytes())?;
+
+    Ok(())
+}
+
+/// Encode a thumbnail of an image as JPEG
+fn create_thumbnail_jpeg(path: &Path) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let output = external_command(ExternalBinary::Ffmpeg)?
+        .args(["-v", "error", "-i"])
+        .arg(path)
+        .args([
+            "-vf",
+            &format!(
+                "scale={size}:{size}:force_original_aspect_ratio=decrease,format=yuvj420p",
+                size = EXIF_THUMBNAIL_SIZE
+            ),
+            "-frames:v",
+            "1",
+            "-q:v",
+            "5",
+            "-f",
+            "mjpeg",
+            "-",
+        ])
+        .output()?;
+
+    if output.stdout.is_empty() {
+        return Err(format!(
+            "Failed to create the thumbnail: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(output.stdout)
+}
+
+/// Link a thumbnail IFD to raw EXIF data, or to new EXIF data when the image has none
+///
+/// The thumbnail IFD and the thumbnail are appended, so the offsets of the existing data stay
+/// valid. Returns `None` when the EXIF data can't be parsed.
+fn add_exif_thumbnail(exif: Option<Bytes>, thumbnail: &[u8]) -> Option<Bytes> {
+    let mut data = match exif {
+        Some(exif) => exif.to_vec(),
+        // Little endian TIFF header and a first IFD with a normal orientation
+        None => {
+            let mut data = b"II*\0".to_vec();
+            data.extend_from_slice(&8u32.to_le_bytes());
+            data.extend_from_slice(&1u16.to_le_bytes());
+            data.extend_from_slice(&ifd_entry(true, EXIF_ORIENTATION_TAG, 3, 1));
+            data.extend_from_slice(&0u32.to_le_bytes());
+            data
+        }
+    };
+
+    let is_little_endian = match data.get(0..2) {
+        Some(b"II") => true,
+        Some(b"MM") => false,
+        _ => return None,
+    };
+    let ifd_offset = u32_from_bytes(is_little_endian, data.get(4..8)?) as usize;
+    let entry_count = u16_from_bytes(is_little_endian, data.get(ifd_offset..ifd_offset + 2)?);
+    let next_ifd_pointer = ifd_offset + 2 + entry_count as usize * 12;
+    data.get(next_ifd_pointer..next_ifd_pointer + 4)?;
+
+    // IFDs start on a word boundary
+    if data.len() % 2 == 1 {
+        data.push(0);
+    }
+    let thumbnail_ifd_offset = data.len() as u32;
+    let thumbnail_offset = thumbnail_ifd_offset + 2 + 3 * 12 + 4;
+
+    let pointer_bytes = u32_to_bytes(is_little_endian, thumbnail_ifd_offset);
+    data[next_ifd_pointer..next_ifd_pointer + 4].copy_from_slice(&pointer_bytes);
+
+    data.extend_from_slice(&u16_to_bytes(is_little_endian, 3));
+    // JPEG compression
+    data.extend_from_slice(&ifd_entry(is_little_endian, EXIF_COMPRESSION_TAG, 3, 6));
+    data.extend_from_slice(&ifd_entry(
+        is_little_endian,
+        EXIF_THUMBNAIL_OFFSET_TAG,
+        4,
+        thumbnail_offset,
+    ));
+    data.extend_from_slice(&ifd_entry(
+        is_little_endian,
+        EXIF_THUMBNAIL_LENGTH_TAG,
+        4,
+        thumbnail.len() as u32,
+    ));
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(thumbnail);
+
+    Some(Bytes::from(data))
+}
+
+/// Encode an IFD entry with a single SHORT (type 3) or LONG (type 4) value
+fn ifd_entry(is_little_endian: bool, tag: u16, value_type: u16, value: u32) -> [u8; 12] {
+    let mut entry = [0u8; 12];
+    entry[0..2].copy_from_slice(&u16_to_bytes(is_little_endian, tag));
+    entry[2..4].copy_from_slice(&u16_to_bytes(is_little_endian, value_type));
+    entry[4..8].copy_from_slice(&u32_to_bytes(is_little_endian, 1));
+    // Values are left-aligned in the 4 value bytes
+    if value_type == 3 {
+        entry[8..10].copy_from_slice(&u16_to_bytes(is_little_endian, value as u16));
+    } else {
+        entry[8..12].copy_from_slice(&u32_to_bytes(is_little_endian, value));
+    }
+    entry
+}
+
+fn u16_from_bytes(is_little_endian: bool, bytes: &[u8]) -> u16 {
+    let bytes = [bytes[0], bytes[1]];
+    if is_little_endian {
+        u16::from_le_bytes(bytes)
+    } else {
+        u16::from_be_bytes(bytes)
+    }
+}
+
+fn u32_from_bytes(is_little_endian: bool, bytes: &[u8]) -> u32 {
+    let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+    if is_little_endian {
+        u32::from_le_bytes(bytes)
+    } else {
+        u32::from_be_bytes(bytes)
+    }
+}
+
+fn u16_to_bytes(is_little_endian: bool, value: u16) -> [u8; 2] {
+    if is_little_endian {
+        value.to_le_bytes()
+    } else {
+        value.to_be_bytes()
+    }
+}
+
+fn u32_to_bytes(is_little_endian: bool, value: u32) -> [u8; 4] {
+    if is_little_endian {
+        value.to_le_bytes()
+    } else {
+        value.to_be_bytes()
+    }
+}
+
+/// Read the comment of a JPEG or PNG image
+pub fn read_image_comment(path: &Path) -> Option<String> {
+    match DynImage::from_bytes(fs::read(path).ok()?.into()).ok()?? {
+        DynImage::Jpeg(jpeg) => jpeg
+            .segment_by_marker(jpeg_markers::COM)
+            .map(|segment| String::from_utf8_lossy(segment.contents()).into_owned()),
+        DynImage::Png(png) => png.chunks_by_type(PNG_TEXT_CHUNK).find_map(|chunk| {
+            chunk
+                .contents()
+                .strip_prefix(PNG_COMMENT_KEYWORD)
+                .map(|comment| String::from_utf8_lossy(comment).into_owned())
+        }),
+        DynImage::WebP(_) => None,
+    }
+}
+
+/// Replace the comment of a JPEG or PNG image, WebP images have no comment and are left untouched
+pub fn write_image_comment(path: &Path, comment: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let Some(image) = DynImage::from_bytes(fs::read(path)?.into())? else {
+        return Ok(());
+    };
+
+    let image_bytes = match image {
+        DynImage::Jpeg(mut jpeg) => {
+            jpeg.remove_segments_by_marker(jpeg_markers::COM);
+            // The comment goes after the application segments, as JFIF requires APP0 to be first
+            let comment_index = jpeg
+                .segments()
+                .iter()
+                .position(|segment| {
+                    !(jpeg_markers::APP0..=jpeg_markers::APP15).contains(&segment.marker())
+                })
+                .unwrap_or(jpeg.segments().len());
+            jpeg.segments_mut().insert(
+                comment_index,
+                JpegSegment::new_with_contents(
+                    jpeg_markers::COM,
+                    Bytes::copy_from_slice(comment.as_bytes()),
+                ),
+            );
+            jpeg.encoder().bytes()
+        }
+        DynImage::Png(mut png) => {
+            png.chunks_mut().retain(|chunk| {
+                chunk.kind() != PNG_TEXT_CHUNK || !chunk.contents().starts_with(PNG_COMMENT_KEYWORD)
+            });
+            // The last chunk is the image trailer, which has to stay last
+            let comment_index = png.chunks().len().saturating_sub(1);
+            png.chunks_mut().insert(
+                comment_index,
+                PngChunk::new(
+                    PNG_TEXT_CHUNK,
+                    Bytes::from([PNG_COMMENT_KEYWORD, comment.as_bytes()].concat()),
+                ),
+            );
+            png.encoder().bytes()
+        }
+        DynImage::WebP(_) => return Ok(()),
+    };
+
+    fs::write(path, image_bytes)?;
+
+    Ok(())
+}
+
+/// Set the orientation tag of raw EXIF data to normal, leaving the other data untouched
+fn reset_exif_orientation(exif: Bytes) -> Bytes {
+    let mut data = exif.to_vec();
+
+    let is_little_endian = match data.get(0..2) {
+        Some(b"II") => true,
+        Some(b"MM") => false,
+        _ => return exif,
+    };
+    let read_u16 = |data: &[u8], offset: usize| -> Option<u16> {
+        let bytes = data.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if is_little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    };
+    let read_u32 = |data: &[u8], offset: usize| -> Option<u32> {
+        let bytes = data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if is_little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    };
+
+    // The orientation is stored inline in an entry of the first IFD of the TIFF structure
+    let Some(ifd_offset) = read_u32(&data, 4).map(|offset| offset as usize) else {
+        return exif;
+    };
+    let Some(entry_count) = read_u16(&data, ifd_offset) else {
+        return exif;
+    };
+
+    for entry_index in 0..entry_count as usize {
+        let entry_offset = ifd_offset + 2 + entry_index * 12;
+        if read_u16(&data, entry_offset) != Some(EXIF_ORIENTATION_TAG) {
+            continue;
+        }
+
+        let normal_orientation = if is_little_endian {
+            1u16.to_le_bytes()
+        } else {
+            1u16.to_be_bytes()
+        };
+        if let Some(value) = data.get_mut(entry_offset + 8..entry_offset + 10) {
+            value.copy_from_slice(&normal_orientation);
+        }
+        return Bytes::from(data);
+    }
+
+    exif
+}
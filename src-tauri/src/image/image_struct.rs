@@ -7,10 +7,15 @@ use std::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    image::image_formats::{image_format, IMAGE_FORMAT_REGISTRY},
+    image::{
+        image_animation::is_animated_image,
+        image_formats::{image_format, FormatQualitySettings, ImageFormat, IMAGE_FORMAT_REGISTRY},
+        image_metadata::ExifOrientation,
+    },
     shared::{
-        file_utils::{read_file_size, read_file_type},
-        media_structs::{Media, Resolution},
+        file_utils::{read_file_type, FileInfo},
+        logo_overrides::LogoOverride,
+        media_structs::{Media, MediaAdjustment, Resolution},
     },
 };
 
@@ -20,31 +25,94 @@ pub struct Image {
     pub resolution: Resolution,
     pub file_size: u64,
     pub file_type: String,
+    pub logo_override: Option<LogoOverride>,
+    pub adjustment: Option<MediaAdjustment>,
+    pub orientation: ExifOrientation,
+    /// Resolution of every export size, empty when the image has a single output
+    pub export_resolutions: Vec<Resolution>,
+    /// The image has multiple frames, which are processed as an animation
+    pub is_animated: bool,
+    /// Transfer of the HDR colors of the image, only read when HDR is preserved
+    pub hdr_transfer: Option<String>,
 }
 
 impl Image {
     pub fn new(file_path: PathBuf) -> Result<Self, Box<dyn Error + Send + Sync>> {
         // Get file size
-        let file_size = read_file_size(&file_path)?;
+        let file_size = FileInfo::read(&file_path)?.scheduling_size();
 
         // Get file type from extension and validate it's supported by FFmpeg
         let file_type = read_image_file_type(&file_path)?;
 
-        // Read image dimensions
-        let resolution = read_image_resolution(&file_path)?;
+        // Read image dimensions, swapped when the EXIF orientation rotates the image
+        let orientation = ExifOrientation::read(&file_path);
+        let resolution = orientation.apply_to_resolution(&read_image_resolution(&file_path)?);
+
+        // Only formats which can store an animation are inspected
+        let is_animated = [
+            image_format::GIF,
+            image_format::WEBP,
+            image_format::PNG,
+            image_format::APNG,
+        ]
+        .iter()
+        .any(|format| format.extensions.contains(&file_type.as_str()))
+            && is_animated_image(&file_path);
 
         Ok(Self {
             file_path,
             resolution,
             file_size,
             file_type,
+            logo_override: None,
+            adjustment: None,
+            orientation,
+            export_resolutions: Vec::new(),
+            is_animated,
+            hdr_transfer: None,
         })
     }
+
+    /// Get the filters which prepare the image before it's resized
+    ///
+    /// The EXIF orientation is applied first, so adjustments apply to the upright image.
+    pub fn input_filter(&self) -> Option<String> {
+        let filters: Vec<String> = [
+            self.orientation.to_filter().map(str::to_string),
+            self.adjustment
+                .as_ref()
+                .and_then(|adjustment| adjustment.to_filter()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        (!filters.is_empty()).then(|| filters.join(","))
+    }
+
+    /// Get the size the progress is weighed by, at least a byte so images without local data, e.g.
+    /// cloud placeholders, still count
+    pub fn progress_size(&self) -> u64 {
+        self.file_size.max(1)
+    }
+
+    /// Get the resolution of every output of the image
+    pub fn output_resolutions(&self) -> Vec<Resolution> {
+        if self.export_resolutions.is_empty() {
+            vec![self.resolution.clone()]
+        } else {
+            self.export_resolutions.clone()
+        }
+    }
 }
 
 impl Media for Image {
     type FileType = String;
 
+    fn get_file_path(&self) -> &Path {
+        &self.file_path
+    }
+
     fn get_resolution(&self) -> &Resolution {
         &self.resolution
     }
@@ -60,6 +128,10 @@ impl Media for Image {
     fn set_resolution(&mut self, resolution: Resolution) {
         self.resolution = resolution;
     }
+
+    fn set_adjustment(&mut self, adjustment: MediaAdjustment) {
+        self.adjustment = Some(adjustment);
+    }
 }
 
 /// Read the image file type and validate it's supported by FFmpeg
@@ -82,12 +154,7 @@ pub fn read_image_resolution(path: &Path) -> Result<Resolution, Box<dyn Error +
         .to_lowercase();
 
     if image_format::SVG.extensions.contains(&extension.as_str()) {
-        // SVG files are vector format - use a default resolution
-        // FFmpeg will handle the actual rendering at the target size
-        return Ok(Resolution {
-            width: 1920,
-            height: 1080,
-        });
+        return read_svg_resolution(path);
     }
 
     // For non-SVG images, use imagesize
@@ -100,8 +167,25 @@ pub fn read_image_resolution(path: &Path) -> Result<Resolution, Box<dyn Error +
     })
 }
 
+/// Read the intrinsic size of an SVG file from its `width`, `height` and `viewBox` attributes
+fn read_svg_resolution(path: &Path) -> Result<Resolution, Box<dyn Error + Send + Sync>> {
+    let svg_data = std::fs::read(path)?;
+    let tree = resvg::usvg::Tree::from_data(&svg_data, &resvg::usvg::Options::default())
+        .map_err(|e| format!("Failed to read SVG dimensions: {}", e))?;
+
+    let size = tree.size();
+    Ok(Resolution {
+        width: (size.width().round() as u32).max(1),
+        height: (size.height().round() as u32).max(1),
+    })
+}
+
 /// Apply image format specific arguments to the FFmpeg command
-pub fn apply_image_format_specific_args(image_format: &str, cmd: &mut FfmpegCommand) {
+pub fn apply_image_format_specific_args(
+    image_format: &str,
+    format_quality: &FormatQualitySettings,
+    cmd: &mut FfmpegCommand,
+) {
     // Add general performance improvements
     cmd.args([
         "-preset", "fast", // Faster encoding preset
@@ -113,17 +197,31 @@ pub fn apply_image_format_specific_args(image_format: &str, cmd: &mut FfmpegComm
                 "-pix_fmt",
                 "rgba",
                 "-compression_level",
-                "1",
+                &format_quality.png_compression_level().to_string(),
                 "-pred",
                 "sub",
             ]);
         }
         name if image_format::JPEG.extensions.contains(&name) => {
-            cmd.args(["-pix_fmt", "yuv420p", "-q:v", "3", "-huffman", "0"]);
+            cmd.args([
+                "-pix_fmt",
+                "yuv420p",
+                "-q:v",
+                &format_quality.jpeg_qscale().to_string(),
+                "-huffman",
+                "0",
+            ]);
         }
         name if image_format::WEBP.extensions.contains(&name) => {
             cmd.args([
-                "-quality", "75", "-pix_fmt", "yuva420p", "-preset", "default", "-method", "2",
+                "-quality",
+                &format_quality.webp_quality().to_string(),
+                "-pix_fmt",
+                "yuva420p",
+                "-preset",
+                "default",
+                "-method",
+                "2",
             ]);
         }
         name if image_format::BMP.extensions.contains(&name) => {
@@ -142,10 +240,120 @@ pub fn apply_image_format_specific_args(image_format: &str, cmd: &mut FfmpegComm
                 "0",
             ]);
         }
+        name if image_format::AVIF.extensions.contains(&name) => {
+            let (color_pixel_format, alpha_pixel_format) = format_quality.avif_pixel_formats();
+            cmd.args([
+                "-c:v",
+                "libaom-av1",
+                "-still-picture",
+                "1",
+                "-crf",
+                &format_quality.avif_crf().to_string(),
+                "-cpu-used",
+                &format_quality.avif_cpu_used().to_string(),
+                "-pix_fmt:v:0",
+                color_pixel_format,
+            ]);
+            if format_quality.keep_alpha {
+                cmd.args(["-pix_fmt:v:1", alpha_pixel_format]);
+            }
+        }
+        name if image_format::JPEGXL.extensions.contains(&name) => {
+            cmd.args([
+                "-c:v",
+                "libjxl",
+                "-distance",
+                &format!("{:.2}", format_quality.jxl_distance()),
+                "-effort",
+                &format_quality.jxl_effort().to_string(),
+                "-pix_fmt",
+                format_quality.jxl_pixel_format(),
+            ]);
+        }
         _ => {}
     }
 }
 
+/// Build the filter which splits the transparency of an output into a separate stream
+///
+/// AVIF stores transparency as a second image, which FFmpeg muxes from a second video stream.
+/// Returns the filter and the labels of the streams to map, or `None` when the output is mapped
+/// as a single stream.
+pub fn build_alpha_stream_filter(
+    image_format: &str,
+    format_quality: &FormatQualitySettings,
+    output_label: &str,
+) -> Option<(String, Vec<String>)> {
+    if !format_quality.keep_alpha || !image_format::AVIF.extensions.contains(&image_format) {
+        return None;
+    }
+
+    // Sources without transparency get an opaque alpha plane
+    let filter = format!(
+        "[{label}]format=yuva444p,split[{label}_color][{label}_alphasource];\
+         [{label}_alphasource]alphaextract[{label}_alpha]",
+        label = output_label
+    );
+    Some((
+        filter,
+        vec![
+            format!("{}_color", output_label),
+            format!("{}_alpha", output_label),
+        ],
+    ))
+}
+
+/// Check if the output format keeps the transparency of the source
+fn keeps_transparency(image_format: &str, format_quality: &FormatQualitySettings) -> bool {
+    let is_format = |format: &ImageFormat| format.extensions.contains(&image_format);
+    if is_format(&image_format::AVIF) || is_format(&image_format::JPEGXL) {
+        return format_quality.keep_alpha;
+    }
+    [
+        image_format::PNG,
+        image_format::APNG,
+        image_format::WEBP,
+        image_format::GIF,
+        image_format::TIFF,
+        image_format::ICO,
+        image_format::TGA,
+        image_format::EXR,
+        image_format::QOI,
+        image_format::PAM,
+        image_format::PSD,
+        image_format::SGI,
+        image_format::DDS,
+    ]
+    .iter()
+    .any(is_format)
+}
+
+/// Build the filter which flattens the `[output_label]` pad onto a background color
+///
+/// Formats without transparency would otherwise turn the transparent areas black. Returns the
+/// filter and the label of the flattened stream, or `None` when the output keeps transparency.
+pub fn build_background_filter(
+    image_format: &str,
+    format_quality: &FormatQualitySettings,
+    background_color: &str,
+    resolution: &Resolution,
+    output_label: &str,
+) -> Option<(String, String)> {
+    if keeps_transparency(image_format, format_quality) {
+        return None;
+    }
+
+    let filter = format!(
+        "color=c={color}:s={width}x{height}[{label}_background];\
+         [{label}_background][{label}]overlay=format=auto:shortest=1[{label}_flat]",
+        color = background_color,
+        width = resolution.width,
+        height = resolution.height,
+        label = output_label
+    );
+    Some((filter, format!("{}_flat", output_label)))
+}
+
 // /// Handle resizing an image to ICO format with FFmpeg
 // fn handle_resize_to_ico_format(
 //     input_path: &Path,
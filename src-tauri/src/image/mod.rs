@@ -1,4 +1,9 @@
+pub mod contact_sheet;
+pub mod image_animation;
+pub mod image_dedup;
 pub mod image_formats;
 pub mod image_handler;
+pub mod image_hdr;
+pub mod image_metadata;
 pub mod image_struct;
 pub mod image_validator;
@@ -1,13 +1,31 @@
-use ffmpeg_sidecar::download::auto_download;
 use tauri::{AppHandle, Manager, RunEvent};
 use tauri_plugin_log::{Target, TargetKind};
 // Re-export types for ts-rs
+pub use shared::brand_kit::{BrandKit, SettingDiff};
 pub use shared::commands;
 pub use shared::config::{AppConfig, ImageSettings, VideoSettings};
+pub use shared::job_queue::QueuedJob;
+pub use shared::layer_structs::LayersSettings;
+pub use shared::logo_handler::LogoPlacement;
 pub use shared::media_structs::Corner;
 pub use shared::progress_handler::ProgressInfo;
+pub use shared::review_queue::ReviewItem;
+pub use shared::run_report::RunReport;
+pub use shared::settings_payload::SettingsPayloadError;
+pub use shared::update_check::UpdateInfo;
+pub use video::video_compatibility::VideoSettingsConflict;
+pub use video::video_streams::StreamInfo;
+// Re-export the processing API for library consumers
+pub use image::image_handler::handle_images;
+pub use shared::headless::run_headless;
+pub use shared::progress_handler::{GlobalProgressSink, ProgressSink};
+pub use video::video_handler::handle_videos;
 
-use crate::shared::process_manager::ProcessManager;
+use crate::shared::{
+    external_command::install_ffmpeg, job_queue::JobQueueManager,
+    progress_handler::ProgressManager, run_history::RunHistoryManager,
+    settings_overrides::SettingsOverrides, shutdown::shutdown, update_check::UpdateCheckManager,
+};
 
 mod image;
 mod shared;
@@ -40,37 +58,113 @@ pub fn run() {
             // Initialize the global configuration
             AppConfig::init(app.handle())?;
 
+            // Apply the settings overridden by environment variables and command-line arguments,
+            // e.g. of automated runs
+            let settings_overrides =
+                SettingsOverrides::from_env_and_args(std::env::args().skip(1))?;
+            AppConfig::set_session_overrides(settings_overrides)?;
+
+            // Load the jobs which were pending when the app last quit, the user decides to restore them
+            JobQueueManager::init(app.handle())?;
+
+            // Load the history of the runs of previous sessions
+            RunHistoryManager::init(app.handle())?;
+
+            // Emit the progress of runs as events
+            ProgressManager::init(app.handle());
+
             // Store the app handle in state
             app.manage(AppState {
                 app_handle: app.handle().clone(),
             });
 
+            if AppConfig::global().check_for_updates_on_startup {
+                UpdateCheckManager::check_on_startup(app.package_info().version.to_string());
+            }
+
             // Download FFmpeg if not already downloaded
-            auto_download()?;
+            install_ffmpeg().map_err(|e| e.to_string())?;
+
+            // Accept jobs from other tools on the local REST API
+            #[cfg(feature = "http-api")]
+            shared::http_api::start_http_api(app.handle())?;
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::load_config,
+            commands::validate_settings_payload,
             commands::get_progress_info,
+            commands::get_last_run_report,
+            commands::get_last_output_file_list,
+            commands::get_last_commands,
+            commands::get_run_history,
+            commands::get_run_history_stats,
+            commands::get_review_items,
+            commands::set_review_decision,
+            commands::finish_review,
+            commands::get_brand_kits,
+            commands::save_brand_kit,
+            commands::delete_brand_kit,
+            commands::diff_image_brand_kit_settings,
+            commands::diff_video_brand_kit_settings,
+            commands::get_pending_jobs,
+            commands::remove_pending_job,
+            commands::get_job_queue_state,
+            commands::move_pending_job,
+            commands::cancel_job,
+            commands::get_watched_job,
+            commands::stop_watching,
+            commands::get_restorable_jobs,
+            commands::restore_pending_jobs,
+            commands::discard_restorable_jobs,
             commands::cancel_process,
+            commands::pause_process,
+            commands::resume_process,
+            commands::export_settings,
+            commands::import_settings,
             commands::show_config_in_folder,
             commands::show_log_in_folder,
+            commands::check_for_updates,
+            commands::get_startup_update,
+            commands::set_check_for_updates_on_startup,
             commands::process_images,
+            commands::queue_images,
+            commands::watch_images,
+            commands::plan_images,
+            commands::export_image_script,
+            commands::preview_image,
+            commands::get_image_logo_placements,
             commands::get_supported_image_formats,
+            commands::get_image_presets,
+            commands::save_image_preset,
+            commands::apply_image_preset,
+            commands::diff_image_preset_settings,
+            commands::delete_image_preset,
             commands::process_videos,
+            commands::queue_videos,
+            commands::watch_videos,
+            commands::plan_videos,
+            commands::export_video_script,
+            commands::preview_video,
+            commands::get_video_logo_placements,
+            commands::probe_file,
             commands::get_supported_video_formats,
-            commands::get_supported_video_codecs
+            commands::get_supported_video_codecs,
+            commands::validate_video_settings,
+            commands::get_available_hardware_accelerations,
+            commands::get_video_presets,
+            commands::save_video_preset,
+            commands::apply_video_preset,
+            commands::diff_video_preset_settings,
+            commands::delete_video_preset
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
-        .run(|_app_handle, event| {
+        .run(|app_handle, event| {
             // Handle app exit events
             if let RunEvent::Exit = event {
-                log::info!("Application is exiting, cleaning up FFmpeg processes...");
-                if let Err(e) = ProcessManager::kill_all_processes() {
-                    log::error!("Failed to kill FFmpeg processes on exit: {}", e);
-                }
+                shutdown(app_handle);
             }
         });
 }
@@ -1,7 +1,11 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use add_logo_processor_lib::{AppConfig, Corner, ImageSettings, ProgressInfo, VideoSettings};
+use add_logo_processor_lib::{
+    AppConfig, BrandKit, Corner, ImageSettings, LayersSettings, LogoPlacement, ProgressInfo,
+    QueuedJob, ReviewItem, RunReport, SettingDiff, SettingsPayloadError, StreamInfo, UpdateInfo,
+    VideoSettings, VideoSettingsConflict,
+};
 use ts_rs::TS;
 
 fn main() {
@@ -9,11 +13,22 @@ fn main() {
     #[cfg(debug_assertions)]
     {
         AppConfig::export().expect("Failed to export AppConfig types");
+        BrandKit::export().expect("Failed to export BrandKit types");
         ImageSettings::export().expect("Failed to export ImageSettings types");
         VideoSettings::export().expect("Failed to export VideoSettings types");
         Corner::export().expect("Failed to export Corner types");
+        LayersSettings::export_all().expect("Failed to export LayersSettings types");
+        LogoPlacement::export_all().expect("Failed to export LogoPlacement types");
         ProgressInfo::export().expect("Failed to export ProgressInfo types");
+        QueuedJob::export_all().expect("Failed to export QueuedJob types");
+        ReviewItem::export_all().expect("Failed to export ReviewItem types");
+        RunReport::export_all().expect("Failed to export RunReport types");
+        SettingDiff::export().expect("Failed to export SettingDiff types");
+        SettingsPayloadError::export().expect("Failed to export SettingsPayloadError types");
+        StreamInfo::export_all().expect("Failed to export StreamInfo types");
+        UpdateInfo::export().expect("Failed to export UpdateInfo types");
+        VideoSettingsConflict::export().expect("Failed to export VideoSettingsConflict types");
     }
 
-    add_logo_processor_lib::run()
+    add_logo_processor_lib::run();
 }
@@ -0,0 +1,98 @@
+use ffmpeg_sidecar::{child::FfmpegChild, command::FfmpegCommand};
+use log::{info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Windows priority class of the FFmpeg processes in background mode
+#[cfg(target_os = "windows")]
+const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x00004000;
+
+/// Windows flag which keeps FFmpeg from opening a console window, which ffmpeg-sidecar sets as
+/// well and which setting the creation flags would otherwise replace
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+/// Nice value of the FFmpeg processes in background mode
+#[cfg(not(target_os = "windows"))]
+const BACKGROUND_NICE_VALUE: &str = "10";
+
+/// Whether the running run is in background mode
+static BACKGROUND_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Run the processing of a run, in background mode when enabled
+///
+/// Background mode keeps the machine usable while a large batch runs, by running the work on
+/// half of the CPU cores and starting FFmpeg with a lower priority.
+pub fn run_in_background_mode<T: Send>(background_mode: bool, run: impl FnOnce() -> T + Send) -> T {
+    if !background_mode {
+        return run();
+    }
+
+    let thread_count = (num_cpus::get() / 2).max(1);
+    let thread_pool = match rayon::ThreadPoolBuilder::new()
+        .num_threads(thread_count)
+        .build()
+    {
+        Ok(thread_pool) => thread_pool,
+        Err(e) => {
+            warn!(
+                "Failed to create the thread pool of the background mode: {}",
+                e
+            );
+            return run();
+        }
+    };
+
+    info!("Running in background mode on {} threads", thread_count);
+    BACKGROUND_MODE.store(true, Ordering::SeqCst);
+    let result = thread_pool.install(run);
+    BACKGROUND_MODE.store(false, Ordering::SeqCst);
+    result
+}
+
+/// Start an FFmpeg process with a lower priority in background mode
+///
+/// Windows sets the priority class when the process is created, other systems lower the priority
+/// of the started process with `renice`.
+pub fn spawn_ffmpeg(cmd: &mut FfmpegCommand) -> std::io::Result<FfmpegChild> {
+    if !BACKGROUND_MODE.load(Ordering::SeqCst) {
+        return cmd.spawn();
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.as_inner_mut()
+            .creation_flags(CREATE_NO_WINDOW | BELOW_NORMAL_PRIORITY_CLASS);
+    }
+
+    #[cfg_attr(target_os = "windows", allow(unused_mut))]
+    let mut ffmpeg_child = cmd.spawn()?;
+
+    #[cfg(not(target_os = "windows"))]
+    lower_process_priority(ffmpeg_child.as_inner().id());
+
+    Ok(ffmpeg_child)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn lower_process_priority(pid: u32) {
+    use crate::shared::external_command::{external_command, ExternalBinary};
+
+    let result = external_command(ExternalBinary::Renice).and_then(|mut command| {
+        Ok(command
+            .args(["-n", BACKGROUND_NICE_VALUE, "-p", &pid.to_string()])
+            .output()?)
+    });
+    match result {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => warn!(
+            "Failed to lower the priority of FFmpeg process {}: {}",
+            pid,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => warn!(
+            "Failed to lower the priority of FFmpeg process {}: {}",
+            pid, e
+        ),
+    }
+}
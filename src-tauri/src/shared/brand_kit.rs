@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+use std::path::PathBuf;
+use ts_rs::TS;
+
+use crate::image::image_metadata::MetadataMode;
+use crate::shared::media_structs::ColorAdjustments;
+use crate::{AppConfig, Corner, ImageSettings, VideoSettings};
+
+/// A named bundle of branding settings, e.g. per client, applied on top of the run's settings
+///
+/// Unset fields keep the value of the regular settings.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct BrandKit {
+    pub name: String,
+    pub color_adjustments: Option<ColorAdjustments>,
+    pub logo_corner: Option<Corner>,
+    pub logo_key_color: Option<String>,
+    pub logo_key_tolerance: Option<u32>,
+    #[ts(type = "string | null")]
+    pub logo_path: Option<PathBuf>,
+    pub logo_scale: Option<u32>,
+    pub metadata_mode: Option<MetadataMode>,
+    pub pad_color: Option<String>,
+}
+
+/// A setting whose stored value, of a preset or a brand kit, differs from the current settings
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct SettingDiff {
+    /// Name of the setting, e.g. `logoScale`
+    pub field: String,
+    #[ts(type = "unknown")]
+    pub current_value: Value,
+    /// Value of the preset, or of the settings after applying the brand kit
+    #[ts(type = "unknown")]
+    pub stored_value: Value,
+}
+
+impl BrandKit {
+    fn apply_to_image_settings(&self, image_settings: &mut ImageSettings) {
+        if let Some(color_adjustments) = &self.color_adjustments {
+            image_settings.color_adjustments = color_adjustments.clone();
+        }
+        if let Some(logo_corner) = self.logo_corner {
+            image_settings.logo_corner = logo_corner;
+        }
+        if let Some(logo_key_color) = &self.logo_key_color {
+            image_settings.logo_key_color = Some(logo_key_color.clone());
+        }
+        if let Some(logo_key_tolerance) = self.logo_key_tolerance {
+            image_settings.logo_key_tolerance = logo_key_tolerance;
+        }
+        if let Some(logo_path) = &self.logo_path {
+            image_settings.logo_path = Some(logo_path.clone());
+        }
+        if let Some(logo_scale) = self.logo_scale {
+            image_settings.logo_scale = logo_scale;
+        }
+        if let Some(metadata_mode) = self.metadata_mode {
+            image_settings.metadata_mode = metadata_mode;
+        }
+        if let Some(pad_color) = &self.pad_color {
+            image_settings.resize_pad_color = pad_color.clone();
+        }
+    }
+
+    fn apply_to_video_settings(&self, video_settings: &mut VideoSettings) {
+        if let Some(color_adjustments) = &self.color_adjustments {
+            video_settings.color_adjustments = color_adjustments.clone();
+        }
+        if let Some(logo_corner) = self.logo_corner {
+            video_settings.logo_corner = logo_corner;
+        }
+        if let Some(logo_key_color) = &self.logo_key_color {
+            video_settings.logo_key_color = Some(logo_key_color.clone());
+        }
+        if let Some(logo_key_tolerance) = self.logo_key_tolerance {
+            video_settings.logo_key_tolerance = logo_key_tolerance;
+        }
+        if let Some(logo_path) = &self.logo_path {
+            video_settings.logo_path = Some(logo_path.clone());
+        }
+        if let Some(logo_scale) = self.logo_scale {
+            video_settings.logo_scale = logo_scale;
+        }
+        if let Some(pad_color) = &self.pad_color {
+            video_settings.resize_pad_color = pad_color.clone();
+        }
+    }
+}
+
+/// Find a brand kit of the global config by its name
+fn find_brand_kit(name: &str) -> Result<BrandKit, Box<dyn Error + Send + Sync>> {
+    AppConfig::global()
+        .brand_kits
+        .into_iter()
+        .find(|brand_kit| brand_kit.name == name)
+        .ok_or_else(|| format!("Brand kit not found: {}", name).into())
+}
+
+/// Resolve the selected brand kit into the image settings of a run
+pub fn resolve_image_brand_kit(
+    image_settings: &ImageSettings,
+) -> Result<ImageSettings, Box<dyn Error + Send + Sync>> {
+    let mut resolved_settings = image_settings.clone();
+    if let Some(brand_kit_name) = &image_settings.brand_kit {
+        find_brand_kit(brand_kit_name)?.apply_to_image_settings(&mut resolved_settings);
+        log::info!("Using brand kit: {}", brand_kit_name);
+    }
+    Ok(resolved_settings)
+}
+
+/// Resolve the selected brand kit into the video settings of a run
+pub fn resolve_video_brand_kit(
+    video_settings: &VideoSettings,
+) -> Result<VideoSettings, Box<dyn Error + Send + Sync>> {
+    let mut resolved_settings = video_settings.clone();
+    if let Some(brand_kit_name) = &video_settings.brand_kit {
+        find_brand_kit(brand_kit_name)?.apply_to_video_settings(&mut resolved_settings);
+        log::info!("Using brand kit: {}", brand_kit_name);
+    }
+    Ok(resolved_settings)
+}
+
+/// Compare the image settings with the same settings after applying the brand kit
+pub fn diff_image_brand_kit(
+    name: &str,
+    image_settings: &ImageSettings,
+) -> Result<Vec<SettingDiff>, Box<dyn Error + Send + Sync>> {
+    let mut brand_kit_settings = image_settings.clone();
+    find_brand_kit(name)?.apply_to_image_settings(&mut brand_kit_settings);
+    diff_settings(image_settings, &brand_kit_settings)
+}
+
+/// Compare the video settings with the same settings after applying the brand kit
+pub fn diff_video_brand_kit(
+    name: &str,
+    video_settings: &VideoSettings,
+) -> Result<Vec<SettingDiff>, Box<dyn Error + Send + Sync>> {
+    let mut brand_kit_settings = video_settings.clone();
+    find_brand_kit(name)?.apply_to_video_settings(&mut brand_kit_settings);
+    diff_settings(video_settings, &brand_kit_settings)
+}
+
+/// List the fields which differ between the current and the stored settings, sorted by their name
+pub fn diff_settings<T: Serialize>(
+    current_settings: &T,
+    stored_settings: &T,
+) -> Result<Vec<SettingDiff>, Box<dyn Error + Send + Sync>> {
+    let (Value::Object(current_values), Value::Object(stored_values)) = (
+        serde_json::to_value(current_settings)?,
+        serde_json::to_value(stored_settings)?,
+    ) else {
+        return Err("Settings must serialize to an object".into());
+    };
+
+    Ok(stored_values
+        .into_iter()
+        .filter_map(|(field, stored_value)| {
+            let current_value = current_values.get(&field).cloned().unwrap_or(Value::Null);
+            (current_value != stored_value).then_some(SettingDiff {
+                field,
+                current_value,
+                stored_value,
+            })
+        })
+        .collect())
+}
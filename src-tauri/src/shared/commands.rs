@@ -1,16 +1,48 @@
-use tauri::{AppHandle, Manager, State};
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Emitter, Manager, State};
 
 use crate::{
-    image::{image_formats::IMAGE_FORMAT_REGISTRY, image_handler::handle_images},
+    image::{
+        image_formats::IMAGE_FORMAT_REGISTRY,
+        image_handler::{
+            export_images_script, plan_images as plan_image_run,
+            preview_image as process_preview_image,
+        },
+    },
     shared::{
-        file_utils::show_in_file_explorer, process_manager::ProcessManager,
+        brand_kit::{
+            diff_image_brand_kit, diff_settings, diff_video_brand_kit, resolve_image_brand_kit,
+            resolve_video_brand_kit, BrandKit, SettingDiff,
+        },
+        config::{ImagePreset, ImportedSettings, VideoPreset},
+        ffmpeg_command_history::{FfmpegCommandHistoryManager, FfmpegCommandRecord},
+        file_utils::{path_to_file_url, show_in_file_explorer},
+        job_queue::{Job, JobQueueManager, JobQueueState, QueuedJob},
+        logo_handler::{calculate_logo_placements, LogoPlacement},
+        media_structs::Resolution,
+        process_manager::ProcessManager,
         progress_handler::ProgressManager,
+        review_queue::{ReviewDecision, ReviewItem, ReviewQueueManager},
+        run_history::{RunHistoryEntry, RunHistoryManager, RunHistoryStats},
+        run_plan::RunPlan,
+        run_report::RunReportManager,
+        settings_payload::{find_settings_payload_errors, SettingsPayloadError},
+        update_check::{check_for_updates as check_release_feed, UpdateCheckManager, UpdateInfo},
+        watch_folder::WatchFolderManager,
     },
     video::{
-        video_codecs::VIDEO_CODEC_REGISTRY, video_formats::VIDEO_FORMAT_REGISTRY,
-        video_handler::handle_videos,
+        hardware_encoders::{HardwareAcceleration, HardwareEncoderManager},
+        video_codecs::VIDEO_CODEC_REGISTRY,
+        video_compatibility::{find_video_settings_conflicts, VideoSettingsConflict},
+        video_formats::VIDEO_FORMAT_REGISTRY,
+        video_handler::{
+            export_videos_script, plan_videos as plan_video_run,
+            preview_video as process_preview_video,
+        },
+        video_streams::{probe_streams, StreamInfo},
     },
-    AppConfig, AppState, ImageSettings, ProgressInfo, VideoSettings,
+    AppConfig, AppState, ImageSettings, ProgressInfo, RunReport, VideoSettings,
 };
 
 /* -------------------------------------------------------------------------- */
@@ -21,23 +53,247 @@ pub fn load_config() -> Result<AppConfig, String> {
     Ok(AppConfig::global())
 }
 
+/// Check if settings of the UI deserialize, listing the fields which don't match the config types
+#[tauri::command]
+pub fn validate_settings_payload(json: String) -> Result<Vec<SettingsPayloadError>, String> {
+    find_settings_payload_errors(&json).map_err(|e| e.to_string())
+}
+
+/// Get the progress of the run
+///
+/// Deprecated: listen to the `progress://update` event, which is emitted whenever the progress
+/// changes, instead of polling this command.
 #[tauri::command]
 pub fn get_progress_info() -> Result<Option<ProgressInfo>, String> {
     Ok(ProgressManager::get_progress())
 }
 
 #[tauri::command]
-pub fn cancel_process() -> Result<(), String> {
+pub fn get_last_run_report() -> Result<Option<RunReport>, String> {
+    Ok(RunReportManager::get_last_report())
+}
+
+/// Get the most recent runs of the run history, newest first
+#[tauri::command]
+pub fn get_run_history(limit: usize) -> Result<Vec<RunHistoryEntry>, String> {
+    Ok(RunHistoryManager::get_recent_runs(limit))
+}
+
+/// Get the totals over the runs of the run history
+#[tauri::command]
+pub fn get_run_history_stats() -> Result<RunHistoryStats, String> {
+    Ok(RunHistoryManager::get_stats())
+}
+
+/// Get the FFmpeg commands of the work units of the last run, when it recorded them
+#[tauri::command]
+pub fn get_last_commands() -> Result<Vec<FfmpegCommandRecord>, String> {
+    Ok(FfmpegCommandHistoryManager::get_last_commands())
+}
+
+/// Get the output files of the last run as newline-separated text, ready to paste elsewhere
+#[tauri::command]
+pub fn get_last_output_file_list(as_file_urls: bool) -> Result<String, String> {
+    let report = RunReportManager::get_last_report().ok_or("No processing run has finished yet")?;
+
+    let lines: Vec<String> = report
+        .output_paths
+        .iter()
+        .map(|output_path| {
+            if as_file_urls {
+                path_to_file_url(output_path)
+            } else {
+                output_path.display().to_string()
+            }
+        })
+        .collect();
+
+    Ok(lines.join("\n"))
+}
+
+#[tauri::command]
+pub fn get_review_items() -> Result<Vec<ReviewItem>, String> {
+    Ok(ReviewQueueManager::get_items())
+}
+
+#[tauri::command]
+pub fn set_review_decision(path: PathBuf, decision: ReviewDecision) -> Result<(), String> {
+    ReviewQueueManager::set_decision(&path, decision)
+}
+
+#[tauri::command]
+pub fn finish_review() -> Result<(), String> {
+    ReviewQueueManager::finish_review();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_brand_kits() -> Result<Vec<BrandKit>, String> {
+    Ok(AppConfig::global().brand_kits)
+}
+
+/// Add a brand kit, or replace the brand kit with the same name
+#[tauri::command]
+pub fn save_brand_kit(app_state: State<AppState>, brand_kit: BrandKit) -> Result<(), String> {
+    if brand_kit.name.trim().is_empty() {
+        return Err("A brand kit requires a name".to_string());
+    }
+
+    let mut brand_kits = AppConfig::global().brand_kits;
+    match brand_kits.iter_mut().find(|kit| kit.name == brand_kit.name) {
+        Some(existing_brand_kit) => *existing_brand_kit = brand_kit,
+        None => brand_kits.push(brand_kit),
+    }
+
+    AppConfig::update_global_brand_kits(brand_kits, &app_state.app_handle)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_brand_kit(app_state: State<AppState>, name: String) -> Result<(), String> {
+    let mut brand_kits = AppConfig::global().brand_kits;
+    brand_kits.retain(|brand_kit| brand_kit.name != name);
+
+    AppConfig::update_global_brand_kits(brand_kits, &app_state.app_handle)
+        .map_err(|e| e.to_string())
+}
+
+/// List the image settings which the brand kit changes, e.g. to mark them as modified
+#[tauri::command]
+pub fn diff_image_brand_kit_settings(
+    name: String,
+    image_settings: ImageSettings,
+) -> Result<Vec<SettingDiff>, String> {
+    diff_image_brand_kit(&name, &image_settings).map_err(|e| e.to_string())
+}
+
+/// List the video settings which the brand kit changes, e.g. to mark them as modified
+#[tauri::command]
+pub fn diff_video_brand_kit_settings(
+    name: String,
+    video_settings: VideoSettings,
+) -> Result<Vec<SettingDiff>, String> {
+    diff_video_brand_kit(&name, &video_settings).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_pending_jobs() -> Result<Vec<QueuedJob>, String> {
+    Ok(JobQueueManager::get_pending_jobs())
+}
+
+#[tauri::command]
+pub fn remove_pending_job(id: u64) -> Result<(), String> {
+    JobQueueManager::remove_pending_job(id)
+}
+
+/// Get the running job and the jobs waiting after it
+#[tauri::command]
+pub fn get_job_queue_state() -> Result<JobQueueState, String> {
+    Ok(JobQueueManager::get_state())
+}
+
+/// Move a pending job to another position in the queue, 0 runs it next
+#[tauri::command]
+pub fn move_pending_job(id: u64, position: usize) -> Result<(), String> {
+    JobQueueManager::move_pending_job(id, position)
+}
+
+/// Cancel a job of the queue, a running job is cancelled like a run and the queue continues with
+/// the next job
+#[tauri::command]
+pub fn cancel_job(app_handle: AppHandle, id: u64) -> Result<(), String> {
+    if JobQueueManager::is_running_job(id) {
+        return cancel_process(app_handle);
+    }
+    JobQueueManager::remove_pending_job(id)
+}
+
+/// Get the job of the watched folder, `None` when no folder is watched
+#[tauri::command]
+pub fn get_watched_job() -> Result<Option<Job>, String> {
+    Ok(WatchFolderManager::get_watched_job())
+}
+
+#[tauri::command]
+pub fn stop_watching() -> Result<(), String> {
+    WatchFolderManager::stop().map_err(|e| e.to_string())
+}
+
+/// Get the jobs which were still pending when the app last quit
+#[tauri::command]
+pub fn get_restorable_jobs() -> Result<Vec<QueuedJob>, String> {
+    Ok(JobQueueManager::get_restorable_jobs())
+}
+
+#[tauri::command]
+pub fn restore_pending_jobs() -> Result<(), String> {
+    JobQueueManager::restore_jobs();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn discard_restorable_jobs() -> Result<(), String> {
+    JobQueueManager::discard_restorable_jobs();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn cancel_process(app_handle: AppHandle) -> Result<(), String> {
     ProcessManager::request_cancel();
 
     // Wait a moment to make sure no new processes are created
     std::thread::sleep(std::time::Duration::from_secs(1));
 
+    // The partial report is taken before the kill, which would make the running files look failed
+    let report = RunReportManager::cancel_report("Cancelled by the user");
+
     ProcessManager::kill_all_processes().map_err(|e| e.to_string())?;
 
+    if let Some(report) = report {
+        app_handle
+            .emit("run://cancelled", &report)
+            .map_err(|e| format!("Failed to emit the cancelled run report: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Suspend the FFmpeg processes of the running run until it's resumed
+#[tauri::command]
+pub fn pause_process() -> Result<(), String> {
+    if !RunReportManager::is_run_active() {
+        return Err("No run is active".to_string());
+    }
+
+    ProcessManager::pause_all_processes().map_err(|e| e.to_string())?;
+    ProgressManager::set_paused(true);
+    Ok(())
+}
+
+/// Continue the FFmpeg processes of a paused run
+#[tauri::command]
+pub fn resume_process() -> Result<(), String> {
+    ProcessManager::resume_all_processes().map_err(|e| e.to_string())?;
+    ProgressManager::set_paused(false);
     Ok(())
 }
 
+/// Write the brand kits, presets and settings to a file, e.g. to share a studio's standard setup
+#[tauri::command]
+pub fn export_settings(path: PathBuf) -> Result<(), String> {
+    AppConfig::export_to_file(&path).map_err(|e| e.to_string())
+}
+
+/// Import the brand kits, presets and settings of an exported file, returning the new config with
+/// the files it refers to which don't exist on this machine
+#[tauri::command]
+pub fn import_settings(
+    app_state: State<AppState>,
+    path: PathBuf,
+) -> Result<ImportedSettings, String> {
+    AppConfig::import_from_file(&path, &app_state.app_handle).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn show_config_in_folder(app_handle: AppHandle) -> Result<(), String> {
     let config_dir = app_handle
@@ -62,6 +318,28 @@ pub fn show_log_in_folder(app_handle: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// Check whether a newer release of the app is available
+#[tauri::command(async)]
+pub fn check_for_updates(app_handle: AppHandle) -> Result<UpdateInfo, String> {
+    let current_version = app_handle.package_info().version.to_string();
+    check_release_feed(&current_version).map_err(|e| e.to_string())
+}
+
+/// Get the update which was found by the automatic check on startup
+#[tauri::command]
+pub fn get_startup_update() -> Result<Option<UpdateInfo>, String> {
+    Ok(UpdateCheckManager::get_startup_update())
+}
+
+#[tauri::command]
+pub fn set_check_for_updates_on_startup(
+    app_state: State<AppState>,
+    enabled: bool,
+) -> Result<(), String> {
+    AppConfig::update_global_check_for_updates_on_startup(enabled, &app_state.app_handle)
+        .map_err(|e| e.to_string())
+}
+
 /* -------------------------------------------------------------------------- */
 /*                                   IMAGES                                   */
 /* -------------------------------------------------------------------------- */
@@ -69,13 +347,74 @@ pub fn show_log_in_folder(app_handle: AppHandle) -> Result<(), String> {
 pub fn process_images(
     app_state: State<AppState>,
     image_settings: ImageSettings,
+) -> Result<RunReport, String> {
+    AppConfig::update_global_image_settings(image_settings.clone(), &app_state.app_handle)
+        .map_err(|e| e.to_string())?;
+
+    Job::Images(image_settings)
+        .try_run()
+        .map_err(|e| e.to_string())
+}
+
+/// Add an image run to the job queue, it starts once the jobs before it are finished
+#[tauri::command]
+pub fn queue_images(
+    app_state: State<AppState>,
+    image_settings: ImageSettings,
+) -> Result<u64, String> {
+    AppConfig::update_global_image_settings(image_settings.clone(), &app_state.app_handle)
+        .map_err(|e| e.to_string())?;
+
+    Ok(JobQueueManager::enqueue(Job::Images(image_settings)))
+}
+
+/// Process new images in the input directory with the settings as they appear
+#[tauri::command]
+pub fn watch_images(
+    app_state: State<AppState>,
+    image_settings: ImageSettings,
 ) -> Result<(), String> {
     AppConfig::update_global_image_settings(image_settings.clone(), &app_state.app_handle)
         .map_err(|e| e.to_string())?;
 
-    handle_images(&image_settings).map_err(|e| e.to_string())?;
+    WatchFolderManager::start(Job::Images(image_settings)).map_err(|e| e.to_string())
+}
 
-    Ok(())
+/// Find the images a run with the settings would process and the outputs it would write, without
+/// processing them
+#[tauri::command(async)]
+pub fn plan_images(image_settings: ImageSettings) -> Result<RunPlan, String> {
+    plan_image_run(&image_settings).map_err(|e| e.to_string())
+}
+
+/// Write the FFmpeg commands a run with the settings would execute to a `.sh` or `.bat` script
+/// instead of running them, returning the number of commands
+#[tauri::command(async)]
+pub fn export_image_script(
+    image_settings: ImageSettings,
+    script_path: PathBuf,
+) -> Result<usize, String> {
+    export_images_script(&image_settings, &script_path).map_err(|e| e.to_string())
+}
+
+/// Process a single image with the settings, returning the path of the preview image
+#[tauri::command(async)]
+pub fn preview_image(
+    image_settings: ImageSettings,
+    image_path: PathBuf,
+) -> Result<PathBuf, String> {
+    process_preview_image(&image_settings, &image_path).map_err(|e| e.to_string())
+}
+
+/// Calculate the logo size and position on images of each resolution, e.g. for a preview canvas
+#[tauri::command]
+pub fn get_image_logo_placements(
+    image_settings: ImageSettings,
+    resolutions: Vec<Resolution>,
+) -> Result<Vec<LogoPlacement>, String> {
+    resolve_image_brand_kit(&image_settings)
+        .and_then(|image_settings| calculate_logo_placements(&image_settings, resolutions))
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -88,6 +427,77 @@ pub fn get_supported_image_formats() -> Result<Vec<String>, String> {
     Ok(formats)
 }
 
+#[tauri::command]
+pub fn get_image_presets() -> Result<Vec<ImagePreset>, String> {
+    Ok(AppConfig::global().image_presets)
+}
+
+/// Save image settings as a preset, or replace the preset with the same name
+#[tauri::command]
+pub fn save_image_preset(
+    app_state: State<AppState>,
+    name: String,
+    image_settings: ImageSettings,
+) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("A preset requires a name".to_string());
+    }
+
+    let mut image_presets = AppConfig::global().image_presets;
+    match image_presets.iter_mut().find(|preset| preset.name == name) {
+        Some(existing_preset) => existing_preset.settings = image_settings,
+        None => image_presets.push(ImagePreset {
+            name,
+            settings: image_settings,
+        }),
+    }
+
+    AppConfig::update_global_image_presets(image_presets, &app_state.app_handle)
+        .map_err(|e| e.to_string())
+}
+
+/// Make the settings of a preset the current image settings, returning them for the form
+#[tauri::command]
+pub fn apply_image_preset(
+    app_state: State<AppState>,
+    name: String,
+) -> Result<ImageSettings, String> {
+    let image_settings = AppConfig::global()
+        .image_presets
+        .into_iter()
+        .find(|preset| preset.name == name)
+        .map(|preset| preset.settings)
+        .ok_or_else(|| format!("The preset {} doesn't exist", name))?;
+
+    AppConfig::update_global_image_settings(image_settings.clone(), &app_state.app_handle)
+        .map_err(|e| e.to_string())?;
+    Ok(image_settings)
+}
+
+/// List the image settings which differ from the preset, e.g. to mark the preset as modified
+#[tauri::command]
+pub fn diff_image_preset_settings(
+    name: String,
+    image_settings: ImageSettings,
+) -> Result<Vec<SettingDiff>, String> {
+    let preset = AppConfig::global()
+        .image_presets
+        .into_iter()
+        .find(|preset| preset.name == name)
+        .ok_or_else(|| format!("The preset {} doesn't exist", name))?;
+
+    diff_settings(&image_settings, &preset.settings).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_image_preset(app_state: State<AppState>, name: String) -> Result<(), String> {
+    let mut image_presets = AppConfig::global().image_presets;
+    image_presets.retain(|preset| preset.name != name);
+
+    AppConfig::update_global_image_presets(image_presets, &app_state.app_handle)
+        .map_err(|e| e.to_string())
+}
+
 /* -------------------------------------------------------------------------- */
 /*                                   VIDEOS                                   */
 /* -------------------------------------------------------------------------- */
@@ -95,13 +505,82 @@ pub fn get_supported_image_formats() -> Result<Vec<String>, String> {
 pub fn process_videos(
     app_state: State<AppState>,
     video_settings: VideoSettings,
+) -> Result<RunReport, String> {
+    AppConfig::update_global_video_settings(video_settings.clone(), &app_state.app_handle)
+        .map_err(|e| e.to_string())?;
+
+    Job::Videos(video_settings)
+        .try_run()
+        .map_err(|e| e.to_string())
+}
+
+/// Add a video run to the job queue, it starts once the jobs before it are finished
+#[tauri::command]
+pub fn queue_videos(
+    app_state: State<AppState>,
+    video_settings: VideoSettings,
+) -> Result<u64, String> {
+    AppConfig::update_global_video_settings(video_settings.clone(), &app_state.app_handle)
+        .map_err(|e| e.to_string())?;
+
+    Ok(JobQueueManager::enqueue(Job::Videos(video_settings)))
+}
+
+/// Process new videos in the input directory with the settings as they appear
+#[tauri::command]
+pub fn watch_videos(
+    app_state: State<AppState>,
+    video_settings: VideoSettings,
 ) -> Result<(), String> {
     AppConfig::update_global_video_settings(video_settings.clone(), &app_state.app_handle)
         .map_err(|e| e.to_string())?;
 
-    handle_videos(&video_settings).map_err(|e| e.to_string())?;
+    WatchFolderManager::start(Job::Videos(video_settings)).map_err(|e| e.to_string())
+}
 
-    Ok(())
+/// Get all streams of a media file, so the streams to keep can be selected before processing
+#[tauri::command(async)]
+pub fn probe_file(path: PathBuf) -> Result<Vec<StreamInfo>, String> {
+    probe_streams(&path).map_err(|e| e.to_string())
+}
+
+/// Find the videos a run with the settings would process and the outputs it would write, without
+/// processing them
+#[tauri::command(async)]
+pub fn plan_videos(video_settings: VideoSettings) -> Result<RunPlan, String> {
+    plan_video_run(&video_settings).map_err(|e| e.to_string())
+}
+
+/// Write the FFmpeg commands a run with the settings would execute to a `.sh` or `.bat` script
+/// instead of running them, returning the number of commands
+#[tauri::command(async)]
+pub fn export_video_script(
+    video_settings: VideoSettings,
+    script_path: PathBuf,
+) -> Result<usize, String> {
+    export_videos_script(&video_settings, &script_path).map_err(|e| e.to_string())
+}
+
+/// Process the first seconds of a single video with the settings, returning the path of the
+/// preview clip
+#[tauri::command(async)]
+pub fn preview_video(
+    video_settings: VideoSettings,
+    video_path: PathBuf,
+    duration_seconds: f64,
+) -> Result<PathBuf, String> {
+    process_preview_video(&video_settings, &video_path, duration_seconds).map_err(|e| e.to_string())
+}
+
+/// Calculate the logo size and position on videos of each resolution, e.g. for a preview canvas
+#[tauri::command]
+pub fn get_video_logo_placements(
+    video_settings: VideoSettings,
+    resolutions: Vec<Resolution>,
+) -> Result<Vec<LogoPlacement>, String> {
+    resolve_video_brand_kit(&video_settings)
+        .and_then(|video_settings| calculate_logo_placements(&video_settings, resolutions))
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -114,6 +593,22 @@ pub fn get_supported_video_formats() -> Result<Vec<String>, String> {
     Ok(formats)
 }
 
+/// Find the combinations of the video settings which fail when the videos are processed
+#[tauri::command]
+pub fn validate_video_settings(
+    video_settings: VideoSettings,
+) -> Result<Vec<VideoSettingsConflict>, String> {
+    resolve_video_brand_kit(&video_settings)
+        .map(|video_settings| find_video_settings_conflicts(&video_settings))
+        .map_err(|e| e.to_string())
+}
+
+/// Get the hardware encoder families which work on this machine for a codec
+#[tauri::command(async)]
+pub fn get_available_hardware_accelerations(codec: String) -> Vec<HardwareAcceleration> {
+    HardwareEncoderManager::available_accelerations(&codec)
+}
+
 #[tauri::command]
 pub fn get_supported_video_codecs() -> Result<Vec<String>, String> {
     let codecs = VIDEO_CODEC_REGISTRY
@@ -123,3 +618,74 @@ pub fn get_supported_video_codecs() -> Result<Vec<String>, String> {
         .collect();
     Ok(codecs)
 }
+
+#[tauri::command]
+pub fn get_video_presets() -> Result<Vec<VideoPreset>, String> {
+    Ok(AppConfig::global().video_presets)
+}
+
+/// Save video settings as a preset, or replace the preset with the same name
+#[tauri::command]
+pub fn save_video_preset(
+    app_state: State<AppState>,
+    name: String,
+    video_settings: VideoSettings,
+) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("A preset requires a name".to_string());
+    }
+
+    let mut video_presets = AppConfig::global().video_presets;
+    match video_presets.iter_mut().find(|preset| preset.name == name) {
+        Some(existing_preset) => existing_preset.settings = video_settings,
+        None => video_presets.push(VideoPreset {
+            name,
+            settings: video_settings,
+        }),
+    }
+
+    AppConfig::update_global_video_presets(video_presets, &app_state.app_handle)
+        .map_err(|e| e.to_string())
+}
+
+/// Make the settings of a preset the current video settings, returning them for the form
+#[tauri::command]
+pub fn apply_video_preset(
+    app_state: State<AppState>,
+    name: String,
+) -> Result<VideoSettings, String> {
+    let video_settings = AppConfig::global()
+        .video_presets
+        .into_iter()
+        .find(|preset| preset.name == name)
+        .map(|preset| preset.settings)
+        .ok_or_else(|| format!("The preset {} doesn't exist", name))?;
+
+    AppConfig::update_global_video_settings(video_settings.clone(), &app_state.app_handle)
+        .map_err(|e| e.to_string())?;
+    Ok(video_settings)
+}
+
+/// List the video settings which differ from the preset, e.g. to mark the preset as modified
+#[tauri::command]
+pub fn diff_video_preset_settings(
+    name: String,
+    video_settings: VideoSettings,
+) -> Result<Vec<SettingDiff>, String> {
+    let preset = AppConfig::global()
+        .video_presets
+        .into_iter()
+        .find(|preset| preset.name == name)
+        .ok_or_else(|| format!("The preset {} doesn't exist", name))?;
+
+    diff_settings(&video_settings, &preset.settings).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_video_preset(app_state: State<AppState>, name: String) -> Result<(), String> {
+    let mut video_presets = AppConfig::global().video_presets;
+    video_presets.retain(|preset| preset.name != name);
+
+    AppConfig::update_global_video_presets(video_presets, &app_state.app_handle)
+        .map_err(|e| e.to_string())
+}
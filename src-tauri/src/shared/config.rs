@@ -1,15 +1,46 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{OnceLock, RwLock};
 use std::{error::Error, fs};
 use tauri::{AppHandle, Manager};
 use ts_rs::TS;
 
-use crate::image::image_formats::image_format;
-use crate::video::video_codecs::video_codec;
+use crate::image::image_formats::{image_format, FormatQualitySettings};
+use crate::image::image_metadata::MetadataMode;
+use crate::shared::brand_kit::BrandKit;
+use crate::shared::config_migrations::{config_version, migrate_config, CURRENT_CONFIG_VERSION};
+use crate::shared::ffmpeg_file_log::FfmpegLogLevel;
+use crate::shared::filename_template::{SequenceNaming, DEFAULT_FILENAME_TEMPLATE};
+use crate::shared::input_filter::InputFilter;
+use crate::shared::layer_structs::LayersSettings;
+use crate::shared::media_structs::{
+    Border, ColorAdjustments, ExportSize, ResizeMode, ResizeTarget, Resolution, ScaleAlgorithm,
+};
+use crate::shared::output_collision::CollisionStrategy;
+use crate::shared::output_mapping::OutputMapping;
+use crate::shared::settings_overrides::SettingsOverrides;
+use crate::shared::source_disposition::SourceDisposition;
+use crate::video::hardware_encoders::HardwareAcceleration;
+use crate::video::video_codecs::{video_codec, EncoderSpeed, VideoQualityMode};
 use crate::video::video_formats::video_format;
+use crate::video::video_streams::StreamMapping;
 use crate::Corner;
 
+/// Files below this size can't contain media, they're usually failed downloads
+const DEFAULT_MIN_FILE_SIZE: u32 = 1024;
+
+/// Two readers keep an HDD busy without making it seek between many files
+const DEFAULT_MAX_DISK_READERS: u32 = 2;
+
+/// Video encoders use several threads each, so two processes keep the CPU busy
+const DEFAULT_MAX_CONCURRENT_VIDEO_PROCESSES: u32 = 2;
+
+/// Quality level of the video quality modes, the default of libx264
+const DEFAULT_CRF: u32 = 23;
+
+/// Bitrate of the video bitrate modes in kilobits per second, suited to 1080p H.264
+const DEFAULT_BITRATE_KBPS: u32 = 8000;
+
 /// Custom serialization for `PathBuf`
 #[allow(clippy::ptr_arg)]
 fn serialize_pathbuf<S>(path: &PathBuf, serializer: S) -> Result<S::Ok, S::Error>
@@ -48,11 +79,47 @@ where
     Ok(opt.map(PathBuf::from))
 }
 
+/// Settings imported from a file, with the files they refer to which don't exist on this machine
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedSettings {
+    pub config: AppConfig,
+    /// The missing logos and output mappings, e.g. "Logo of brand kit Studio: /logos/studio.png"
+    pub missing_files: Vec<String>,
+}
+
+/// Image settings saved under a name, e.g. "Instagram" or "Client proofs"
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct ImagePreset {
+    pub name: String,
+    pub settings: ImageSettings,
+}
+
+/// Video settings saved under a name, e.g. "Archive HEVC"
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct VideoPreset {
+    pub name: String,
+    pub settings: VideoSettings,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub struct AppConfig {
+    pub brand_kits: Vec<BrandKit>,
+    /// Check for a new release of the app on startup, off until the user enables it since the
+    /// check sends a request to GitHub
+    pub check_for_updates_on_startup: bool,
+    /// Version of the config structure, which decides the migrations an older config needs
+    pub config_version: u32,
+    pub image_presets: Vec<ImagePreset>,
     pub image_settings: ImageSettings,
+    pub video_presets: Vec<VideoPreset>,
     pub video_settings: VideoSettings,
 }
 
@@ -61,11 +128,36 @@ pub struct AppConfig {
 #[serde(rename_all = "camelCase")]
 pub struct ImageSettings {
     pub add_logo: bool,
-    pub clear_files_input_directory: bool,
+    /// Fill color behind transparent areas in output formats without transparency, e.g. JPEG
+    pub background_color: String,
+    /// Run on half of the CPU cores with a lower FFmpeg priority, so the machine stays usable
+    pub background_mode: bool,
+    /// Frame around each output image, drawn below the overlay layers
+    pub border: Option<Border>,
+    pub brand_kit: Option<String>,
     pub clear_files_output_directory: bool,
-    #[serde(alias = "favorite_formats")] // Deprecated field names
+    /// What happens to inputs whose output file already exists
+    pub collision_strategy: CollisionStrategy,
+    pub color_adjustments: ColorAdjustments,
+    /// Write a contact sheet of the output images per folder
+    pub create_contact_sheets: bool,
+    /// Number of hash bits in which images may differ to count as duplicates, 0 only matches
+    /// images which look identical
+    pub duplicate_max_distance: u32,
+    /// Embed a small EXIF thumbnail in JPEG outputs, which file browsers use for fast previews
+    pub embed_exif_thumbnail: bool,
+    /// Glob patterns of input files to skip, e.g. `*_edited.jpg`
+    pub exclude_patterns: Vec<String>,
+    /// Sizes which each image is exported in, replacing the single output when set
+    pub export_sizes: Vec<ExportSize>,
+    pub ffmpeg_log_level: FfmpegLogLevel,
+    pub filename_template: String,
     pub format_favorite_list: Vec<String>,
     pub format: String,
+    pub format_quality: FormatQualitySettings,
+    pub hydrate_cloud_placeholders: bool,
+    /// Glob patterns of input files to process, e.g. `*_final.jpg`, empty processes all files
+    pub include_patterns: Vec<String>,
     #[serde(
         serialize_with = "serialize_pathbuf",
         deserialize_with = "deserialize_pathbuf"
@@ -73,7 +165,10 @@ pub struct ImageSettings {
     #[ts(type = "string")]
     pub input_directory: PathBuf,
     pub keep_child_folders_structure_in_output_directory: bool,
+    pub layers: LayersSettings,
     pub logo_corner: Corner,
+    pub logo_key_color: Option<String>,
+    pub logo_key_tolerance: u32,
     #[serde(
         serialize_with = "serialize_optional_pathbuf",
         deserialize_with = "deserialize_optional_pathbuf"
@@ -83,16 +178,63 @@ pub struct ImageSettings {
     pub logo_scale: u32,
     pub logo_x_offset_scale: i32,
     pub logo_y_offset_scale: i32,
+    /// Maximum number of FFmpeg processes running at the same time, 0 disables the limit
+    pub max_concurrent_processes: u32,
+    /// Maximum number of FFmpeg processes reading an input directory on an HDD at the same time,
+    /// 0 disables the limit
+    pub max_disk_readers: u32,
+    /// Inputs larger than this number of megabytes are skipped, 0 disables the check
+    pub max_file_size_mb: u32,
+    pub metadata_mode: MetadataMode,
+    /// Files smaller than this number of bytes are skipped, 0 disables the check
+    pub min_file_size: u32,
+    /// Inputs with a lower height are skipped, 0 disables the check
+    pub min_input_height: u32,
+    /// Inputs with a lower width are skipped, 0 disables the check
+    pub min_input_width: u32,
     pub min_pixel_count: u32,
+    /// Skip inputs last modified before this date, in the `YYYY-MM-DD` format
+    pub modified_after: Option<String>,
+    /// Skip inputs last modified after this date, in the `YYYY-MM-DD` format
+    pub modified_before: Option<String>,
+    pub never_upscale: bool,
     #[serde(
         serialize_with = "serialize_pathbuf",
         deserialize_with = "deserialize_pathbuf"
     )]
     #[ts(type = "string")]
     pub output_directory: PathBuf,
-    pub overwrite_existing_files_output_directory: bool,
+    /// CSV file which maps inputs to their own output folder or name, e.g. per product
+    #[ts(type = "string | null")]
+    pub output_mapping_path: Option<PathBuf>,
+    /// Keep the HDR colors of HDR sources in AVIF outputs, other outputs are tone mapped to SDR
+    pub preserve_hdr: bool,
+    /// Move skipped empty and near-empty files to the quarantine folder
+    pub quarantine_tiny_files: bool,
+    /// Keep the FFmpeg command of every work unit of a run, so it can be reproduced manually
+    pub record_ffmpeg_commands: bool,
+    /// Stop the run when a logo is enlarged so much that it looks blurry, instead of warning
+    pub reject_upscaled_logos: bool,
+    pub resize_aspect_ratio: Resolution,
+    pub resize_mode: ResizeMode,
+    pub resize_output_resolution: Option<Resolution>,
+    pub resize_pad_color: String,
+    pub review_flagged_files: bool,
+    pub scale_algorithm: ScaleAlgorithm,
     pub search_child_folders: bool,
+    pub sequence_naming: Option<SequenceNaming>,
+    /// Strength of the sharpening applied after resizing, from 0 to 1.5
+    pub sharpen_amount: Option<f32>,
     pub should_convert_format: bool,
+    /// Process only one copy of images which look the same
+    pub skip_duplicate_images: bool,
+    /// Skip inputs which were already processed with the same settings
+    pub skip_marked_files: bool,
+    /// What happens to an input file once all its outputs are written
+    pub source_disposition: SourceDisposition,
+    /// Write a log with a JSON record per processed file to the output directory, for auditing
+    /// the outputs of a run
+    pub write_run_log: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -100,14 +242,37 @@ pub struct ImageSettings {
 #[serde(rename_all = "camelCase")]
 pub struct VideoSettings {
     pub add_logo: bool,
-    pub clear_files_input_directory: bool,
+    /// Number of times GIF, WebP and APNG outputs play, 0 loops them forever
+    pub animation_loop_count: u32,
+    /// Run on half of the CPU cores with a lower FFmpeg priority, so the machine stays usable
+    pub background_mode: bool,
+    /// Bitrate in kilobits per second of the target bitrate and constrained quality modes
+    pub bitrate_kbps: u32,
+    pub brand_kit: Option<String>,
     pub clear_files_output_directory: bool,
-    #[serde(alias = "favorite_codecs")] // Deprecated field names
     pub codec_favorite_list: Vec<String>,
     pub codec: String,
-    #[serde(alias = "favorite_formats")] // Deprecated field names
+    /// What happens to inputs whose output file already exists
+    pub collision_strategy: CollisionStrategy,
+    pub color_adjustments: ColorAdjustments,
+    /// Quality level of the constant and constrained quality modes, in the scale of the encoder
+    pub crf: u32,
+    pub encoder_speed: EncoderSpeed,
+    /// Threads of each video encoder, 0 divides the CPU cores over the concurrent processes
+    pub encoder_threads: u32,
+    /// Glob patterns of input files to skip, e.g. `*_edited.jpg`
+    pub exclude_patterns: Vec<String>,
+    /// Renditions which each video is exported in, replacing the single output when set
+    pub export_sizes: Vec<ExportSize>,
+    pub ffmpeg_log_level: FfmpegLogLevel,
+    pub filename_template: String,
     pub format_favorite_list: Vec<String>,
     pub format: String,
+    /// Hardware encoder family to encode with, falling back to the configured encoder
+    pub hardware_acceleration: HardwareAcceleration,
+    pub hydrate_cloud_placeholders: bool,
+    /// Glob patterns of input files to process, e.g. `*_final.jpg`, empty processes all files
+    pub include_patterns: Vec<String>,
     #[serde(
         serialize_with = "serialize_pathbuf",
         deserialize_with = "deserialize_pathbuf"
@@ -115,7 +280,10 @@ pub struct VideoSettings {
     #[ts(type = "string")]
     pub input_directory: PathBuf,
     pub keep_child_folders_structure_in_output_directory: bool,
+    pub layers: LayersSettings,
     pub logo_corner: Corner,
+    pub logo_key_color: Option<String>,
+    pub logo_key_tolerance: u32,
     #[serde(
         serialize_with = "serialize_optional_pathbuf",
         deserialize_with = "deserialize_optional_pathbuf"
@@ -125,48 +293,295 @@ pub struct VideoSettings {
     pub logo_scale: u32,
     pub logo_x_offset_scale: i32,
     pub logo_y_offset_scale: i32,
+    /// Maximum number of FFmpeg processes running at the same time, 0 disables the limit
+    pub max_concurrent_processes: u32,
+    /// Maximum number of FFmpeg processes reading an input directory on an HDD at the same time,
+    /// 0 disables the limit
+    pub max_disk_readers: u32,
+    /// Inputs larger than this number of megabytes are skipped, 0 disables the check
+    pub max_file_size_mb: u32,
+    /// Files smaller than this number of bytes are skipped, 0 disables the check
+    pub min_file_size: u32,
+    /// Inputs with a lower height are skipped, 0 disables the check
+    pub min_input_height: u32,
+    /// Inputs with a lower width are skipped, 0 disables the check
+    pub min_input_width: u32,
     pub min_pixel_count: u32,
+    /// Skip inputs last modified before this date, in the `YYYY-MM-DD` format
+    pub modified_after: Option<String>,
+    /// Skip inputs last modified after this date, in the `YYYY-MM-DD` format
+    pub modified_before: Option<String>,
+    pub never_upscale: bool,
     #[serde(
         serialize_with = "serialize_pathbuf",
         deserialize_with = "deserialize_pathbuf"
     )]
     #[ts(type = "string")]
     pub output_directory: PathBuf,
-    pub overwrite_existing_files_output_directory: bool,
+    /// CSV file which maps inputs to their own output folder or name, e.g. per product
+    #[ts(type = "string | null")]
+    pub output_mapping_path: Option<PathBuf>,
+    /// Pixel format of the outputs, e.g. `yuv420p10le`, `None` picks one from the source and the
+    /// encoder
+    pub pixel_format: Option<String>,
+    /// Second of each output video which is also written as a JPEG poster next to it, `None`
+    /// writes no posters
+    pub poster_timestamp: Option<f64>,
+    /// Keep the HDR colors of HDR sources when the encoder can write them, other outputs are
+    /// tone mapped to SDR
+    pub preserve_hdr: bool,
+    pub quality_mode: VideoQualityMode,
+    /// Move skipped empty and near-empty files to the quarantine folder
+    pub quarantine_tiny_files: bool,
+    /// Keep the FFmpeg command of every work unit of a run, so it can be reproduced manually
+    pub record_ffmpeg_commands: bool,
+    /// Stop the run when a logo is enlarged so much that it looks blurry, instead of warning
+    pub reject_upscaled_logos: bool,
+    pub resize_aspect_ratio: Resolution,
+    pub resize_mode: ResizeMode,
+    pub resize_output_resolution: Option<Resolution>,
+    pub resize_pad_color: String,
+    pub review_flagged_files: bool,
     pub search_child_folders: bool,
     pub should_convert_codec: bool,
     pub should_convert_format: bool,
+    /// Skip inputs which were already processed with the same settings
+    pub skip_marked_files: bool,
+    /// What happens to an input file once all its outputs are written
+    pub source_disposition: SourceDisposition,
+    pub stream_mapping: StreamMapping,
+    /// Frame rate the videos are converted to, `None` keeps the frame rate of each video, or uses
+    /// 15 frames per second for GIF, WebP and APNG outputs
+    pub target_fps: Option<f64>,
+    /// Second of each video where the processed section ends, `None` processes until the end
+    pub trim_end: Option<f64>,
+    /// Second of each video where the processed section starts, `None` starts at the beginning
+    pub trim_start: Option<f64>,
+    /// Write a log with a JSON record per processed file to the output directory, for auditing
+    /// the outputs of a run
+    pub write_run_log: bool,
+}
+
+impl ImageSettings {
+    pub fn resize_target(&self) -> ResizeTarget {
+        ResizeTarget {
+            resize_mode: self.resize_mode,
+            min_pixel_count: self.min_pixel_count,
+            aspect_ratio: self.resize_aspect_ratio.clone(),
+            output_resolution: self.resize_output_resolution.clone(),
+            never_upscale: self.never_upscale,
+        }
+    }
+
+    /// Get the resize target of every export size, in the configured order
+    ///
+    /// The exact output resolution is ignored, since it would give every size the same resolution.
+    pub fn export_resize_targets(&self) -> Vec<ResizeTarget> {
+        self.export_sizes
+            .iter()
+            .map(|export_size| ResizeTarget {
+                min_pixel_count: export_size.min_pixel_count,
+                output_resolution: None,
+                ..self.resize_target()
+            })
+            .collect()
+    }
+
+    /// Get the filter which decides which input files are processed
+    pub fn input_filter(&self) -> Result<InputFilter, Box<dyn Error + Send + Sync>> {
+        InputFilter::new(
+            &self.include_patterns,
+            &self.exclude_patterns,
+            self.modified_after.as_deref(),
+            self.modified_before.as_deref(),
+        )
+    }
+
+    /// Get the rules of the output mapping file, empty when no file is set
+    pub fn output_mapping(&self) -> Result<OutputMapping, Box<dyn Error + Send + Sync>> {
+        match &self.output_mapping_path {
+            Some(output_mapping_path) => OutputMapping::load(output_mapping_path),
+            None => Ok(OutputMapping::default()),
+        }
+    }
+}
+
+impl VideoSettings {
+    pub fn resize_target(&self) -> ResizeTarget {
+        ResizeTarget {
+            resize_mode: self.resize_mode,
+            min_pixel_count: self.min_pixel_count,
+            aspect_ratio: self.resize_aspect_ratio.clone(),
+            output_resolution: self.resize_output_resolution.clone(),
+            never_upscale: self.never_upscale,
+        }
+    }
+
+    /// Get the resize target of every export size, in the configured order
+    ///
+    /// The exact output resolution is ignored, since it would give every rendition the same
+    /// resolution.
+    pub fn export_resize_targets(&self) -> Vec<ResizeTarget> {
+        self.export_sizes
+            .iter()
+            .map(|export_size| ResizeTarget {
+                min_pixel_count: export_size.min_pixel_count,
+                output_resolution: None,
+                ..self.resize_target()
+            })
+            .collect()
+    }
+
+    /// Get the filter which decides which input files are processed
+    pub fn input_filter(&self) -> Result<InputFilter, Box<dyn Error + Send + Sync>> {
+        InputFilter::new(
+            &self.include_patterns,
+            &self.exclude_patterns,
+            self.modified_after.as_deref(),
+            self.modified_before.as_deref(),
+        )
+    }
+
+    /// Get the rules of the output mapping file, empty when no file is set
+    pub fn output_mapping(&self) -> Result<OutputMapping, Box<dyn Error + Send + Sync>> {
+        match &self.output_mapping_path {
+            Some(output_mapping_path) => OutputMapping::load(output_mapping_path),
+            None => Ok(OutputMapping::default()),
+        }
+    }
+
+    /// Check that the trim settings describe a section which starts before it ends, and that the
+    /// poster timestamp is a valid second
+    ///
+    /// Non-finite seconds, e.g. `NaN`, are rejected since FFmpeg can't seek to them.
+    pub fn validate_trim(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let is_valid_second = |second: f64| second.is_finite() && second >= 0.0;
+        if !self.trim_start.is_none_or(is_valid_second)
+            || !self.trim_end.is_none_or(is_valid_second)
+        {
+            return Err("The trim start and end must be 0 seconds or more".into());
+        }
+        if !self.poster_timestamp.is_none_or(is_valid_second) {
+            return Err("The poster timestamp must be 0 seconds or more".into());
+        }
+        if self
+            .trim_end
+            .is_some_and(|trim_end| trim_end <= self.trim_start.unwrap_or(0.0))
+        {
+            return Err("The trim end must be after the trim start".into());
+        }
+        Ok(())
+    }
+
+    /// Get the number of threads of each encoder, `None` leaves it to FFmpeg
+    ///
+    /// Without a thread count the cores are divided over the concurrent processes, so a few
+    /// processes each use many threads. FFmpeg uses every core per process when the processes
+    /// aren't limited.
+    pub fn threads_per_encoder(&self) -> Option<u32> {
+        if self.encoder_threads > 0 {
+            return Some(self.encoder_threads);
+        }
+
+        (self.max_concurrent_processes > 0)
+            .then(|| (num_cpus::get() as u32 / self.max_concurrent_processes).max(1))
+    }
+
+    /// Get the start and end second of the processed section of a video of the duration
+    pub fn trim_range(&self, duration: f64) -> (f64, f64) {
+        let trim_start = self.trim_start.unwrap_or(0.0).min(duration);
+        let trim_end = self
+            .trim_end
+            .unwrap_or(duration)
+            .clamp(trim_start, duration);
+        (trim_start, trim_end)
+    }
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
+            brand_kits: Vec::new(),
+            check_for_updates_on_startup: false,
+            config_version: CURRENT_CONFIG_VERSION,
+            image_presets: Vec::new(),
             image_settings: ImageSettings {
                 add_logo: false,
-                clear_files_input_directory: false,
+                background_color: "white".to_string(),
+                background_mode: false,
+                border: None,
+                brand_kit: None,
                 clear_files_output_directory: false,
+                collision_strategy: CollisionStrategy::Skip,
+                color_adjustments: ColorAdjustments::default(),
+                create_contact_sheets: false,
+                duplicate_max_distance: 4,
+                embed_exif_thumbnail: false,
+                exclude_patterns: Vec::new(),
+                export_sizes: Vec::new(),
+                ffmpeg_log_level: FfmpegLogLevel::Off,
+                filename_template: DEFAULT_FILENAME_TEMPLATE.to_string(),
                 format_favorite_list: vec![
                     image_format::JPEG.extensions[0].to_string(),
                     image_format::PNG.extensions[0].to_string(),
                     image_format::WEBP.extensions[0].to_string(),
                 ],
                 format: image_format::PNG.extensions[0].to_string(),
+                format_quality: FormatQualitySettings::default(),
+                hydrate_cloud_placeholders: false,
+                include_patterns: Vec::new(),
                 input_directory: PathBuf::from("input"),
                 keep_child_folders_structure_in_output_directory: false,
+                layers: LayersSettings::default(),
                 logo_corner: Corner::TopLeft,
+                logo_key_color: None,
+                logo_key_tolerance: 10,
                 logo_path: None,
                 logo_scale: 10,
                 logo_x_offset_scale: 0,
                 logo_y_offset_scale: 0,
+                max_concurrent_processes: 0,
+                max_disk_readers: DEFAULT_MAX_DISK_READERS,
+                max_file_size_mb: 0,
+                metadata_mode: MetadataMode::Strip,
+                min_file_size: DEFAULT_MIN_FILE_SIZE,
+                min_input_height: 0,
+                min_input_width: 0,
                 min_pixel_count: 1080,
+                modified_after: None,
+                modified_before: None,
+                never_upscale: false,
                 output_directory: PathBuf::from("output"),
-                overwrite_existing_files_output_directory: false,
+                output_mapping_path: None,
+                preserve_hdr: false,
+                quarantine_tiny_files: false,
+                record_ffmpeg_commands: false,
+                reject_upscaled_logos: false,
+                resize_aspect_ratio: Resolution {
+                    width: 16,
+                    height: 9,
+                },
+                resize_mode: ResizeMode::Fit,
+                resize_output_resolution: None,
+                resize_pad_color: "black".to_string(),
+                review_flagged_files: false,
+                scale_algorithm: ScaleAlgorithm::FastBilinear,
                 search_child_folders: false,
+                sequence_naming: None,
+                sharpen_amount: None,
                 should_convert_format: false,
+                skip_duplicate_images: false,
+                skip_marked_files: false,
+                source_disposition: SourceDisposition::Keep,
+                write_run_log: false,
             },
+            video_presets: Vec::new(),
             video_settings: VideoSettings {
                 add_logo: false,
-                clear_files_input_directory: false,
+                animation_loop_count: 0,
+                background_mode: false,
+                bitrate_kbps: DEFAULT_BITRATE_KBPS,
+                brand_kit: None,
                 clear_files_output_directory: false,
                 codec_favorite_list: vec![
                     video_codec::H264.name.to_string(),
@@ -174,32 +589,154 @@ impl Default for AppConfig {
                     video_codec::VP9.name.to_string(),
                 ],
                 codec: video_codec::H264.name.to_string(),
+                collision_strategy: CollisionStrategy::Skip,
+                color_adjustments: ColorAdjustments::default(),
+                crf: DEFAULT_CRF,
+                encoder_speed: EncoderSpeed::Medium,
+                encoder_threads: 0,
+                exclude_patterns: Vec::new(),
+                export_sizes: Vec::new(),
+                ffmpeg_log_level: FfmpegLogLevel::Off,
+                filename_template: DEFAULT_FILENAME_TEMPLATE.to_string(),
                 format_favorite_list: vec![
                     video_format::MKV.extensions[0].to_string(),
                     video_format::MOV.extensions[0].to_string(),
                     video_format::MP4.extensions[0].to_string(),
                 ],
                 format: video_format::MP4.extensions[0].to_string(),
+                hardware_acceleration: HardwareAcceleration::Off,
+                hydrate_cloud_placeholders: false,
+                include_patterns: Vec::new(),
                 input_directory: PathBuf::from("input"),
                 keep_child_folders_structure_in_output_directory: false,
+                layers: LayersSettings::default(),
                 logo_corner: Corner::TopLeft,
+                logo_key_color: None,
+                logo_key_tolerance: 10,
                 logo_path: None,
                 logo_scale: 10,
                 logo_x_offset_scale: 0,
                 logo_y_offset_scale: 0,
+                max_concurrent_processes: DEFAULT_MAX_CONCURRENT_VIDEO_PROCESSES,
+                max_disk_readers: DEFAULT_MAX_DISK_READERS,
+                max_file_size_mb: 0,
+                min_file_size: DEFAULT_MIN_FILE_SIZE,
+                min_input_height: 0,
+                min_input_width: 0,
                 min_pixel_count: 1080,
+                modified_after: None,
+                modified_before: None,
+                never_upscale: false,
                 output_directory: PathBuf::from("output"),
-                overwrite_existing_files_output_directory: false,
+                output_mapping_path: None,
+                pixel_format: None,
+                poster_timestamp: None,
+                preserve_hdr: false,
+                quality_mode: VideoQualityMode::EncoderDefault,
+                quarantine_tiny_files: false,
+                record_ffmpeg_commands: false,
+                reject_upscaled_logos: false,
+                resize_aspect_ratio: Resolution {
+                    width: 16,
+                    height: 9,
+                },
+                resize_mode: ResizeMode::Fit,
+                resize_output_resolution: None,
+                resize_pad_color: "black".to_string(),
+                review_flagged_files: false,
                 search_child_folders: false,
                 should_convert_codec: false,
                 should_convert_format: false,
+                skip_marked_files: false,
+                source_disposition: SourceDisposition::Keep,
+                stream_mapping: StreamMapping::default(),
+                target_fps: None,
+                trim_end: None,
+                trim_start: None,
+                write_run_log: false,
             },
         }
     }
 }
 
-// Global configuration instance with RwLock for thread-safe mutation
+/// Add imported items to a list, replacing the items with the same name
+fn merge_named<T>(items: &mut Vec<T>, imported_items: Vec<T>, name: impl Fn(&T) -> String) {
+    for imported_item in imported_items {
+        let imported_name = name(&imported_item);
+        match items.iter_mut().find(|item| name(item) == imported_name) {
+            Some(item) => *item = imported_item,
+            None => items.push(imported_item),
+        }
+    }
+}
+
+/// Find the logos and output mappings of a config which don't exist, described by the settings
+/// which refer to them
+fn find_missing_files(config: &AppConfig) -> Vec<String> {
+    let mut referenced_files: Vec<(String, &Option<PathBuf>)> = vec![
+        (
+            "Logo of the image settings".to_string(),
+            &config.image_settings.logo_path,
+        ),
+        (
+            "Output mapping of the image settings".to_string(),
+            &config.image_settings.output_mapping_path,
+        ),
+        (
+            "Logo of the video settings".to_string(),
+            &config.video_settings.logo_path,
+        ),
+        (
+            "Output mapping of the video settings".to_string(),
+            &config.video_settings.output_mapping_path,
+        ),
+    ];
+    for brand_kit in &config.brand_kits {
+        referenced_files.push((
+            format!("Logo of brand kit {}", brand_kit.name),
+            &brand_kit.logo_path,
+        ));
+    }
+    for preset in &config.image_presets {
+        referenced_files.push((
+            format!("Logo of image preset {}", preset.name),
+            &preset.settings.logo_path,
+        ));
+        referenced_files.push((
+            format!("Output mapping of image preset {}", preset.name),
+            &preset.settings.output_mapping_path,
+        ));
+    }
+    for preset in &config.video_presets {
+        referenced_files.push((
+            format!("Logo of video preset {}", preset.name),
+            &preset.settings.logo_path,
+        ));
+        referenced_files.push((
+            format!("Output mapping of video preset {}", preset.name),
+            &preset.settings.output_mapping_path,
+        ));
+    }
+
+    referenced_files
+        .into_iter()
+        .filter_map(|(description, path)| {
+            let path = path.as_ref()?;
+            (!path.exists()).then(|| format!("{}: {}", description, path.display()))
+        })
+        .collect()
+}
+
+/// Identifier of the app, which must match the identifier in `tauri.conf.json`
+const APP_IDENTIFIER: &str = "com.sjoer.add-logo-processor";
+
+const CONFIG_FILE_NAME: &str = "config.json";
+
+// Global configuration instance with RwLock for thread-safe mutation, it holds the saved settings
+// without the overrides of the session
 static CONFIG: OnceLock<RwLock<AppConfig>> = OnceLock::new();
+// Settings overridden for the session, which apply on top of the global configuration
+static SESSION_OVERRIDES: OnceLock<SettingsOverrides> = OnceLock::new();
 
 impl AppConfig {
     /// Initialize the global configuration with app handle
@@ -211,14 +748,51 @@ impl AppConfig {
         Ok(())
     }
 
-    /// Get a clone of the global configuration instance
-    pub fn global() -> AppConfig {
+    /// Initialize the global configuration without an app, e.g. of the headless mode
+    ///
+    /// The config file is found in the same folder Tauri's path resolver uses.
+    pub fn init_headless() -> Result<(), Box<dyn Error>> {
+        let config_dir = dirs::config_dir().ok_or("Failed to get config directory")?;
+        let config = Self::load_or_create_default_at(
+            &config_dir.join(APP_IDENTIFIER).join(CONFIG_FILE_NAME),
+        )?;
         CONFIG
+            .set(RwLock::new(config))
+            .map_err(|_| "Failed to set global config")?;
+        Ok(())
+    }
+
+    /// Get a clone of the global configuration instance, with the settings overrides of the
+    /// session applied
+    pub fn global() -> AppConfig {
+        let mut config = CONFIG
             .get()
             .expect("Config not initialized. Call AppConfig::init() first.")
             .read()
             .unwrap()
-            .clone()
+            .clone();
+        if let Some(session_overrides) = SESSION_OVERRIDES.get() {
+            session_overrides.apply(&mut config);
+        }
+        config
+    }
+
+    /// Set the settings overrides of the session, they apply to the global config without ever
+    /// being saved
+    pub fn set_session_overrides(
+        settings_overrides: SettingsOverrides,
+    ) -> Result<(), Box<dyn Error>> {
+        SESSION_OVERRIDES
+            .set(settings_overrides)
+            .map_err(|_| "Failed to set the session overrides")?;
+        Ok(())
+    }
+
+    /// Remove the settings overrides of the session from a config which is about to be saved
+    fn remove_session_overrides(&mut self, saved_config: &AppConfig) {
+        if let Some(session_overrides) = SESSION_OVERRIDES.get() {
+            session_overrides.restore(self, saved_config);
+        }
     }
 
     /// Update only image settings in global config and save
@@ -232,7 +806,9 @@ impl AppConfig {
 
         {
             let mut config = config_lock.write().unwrap();
+            let saved_config = config.clone();
             config.image_settings = image_settings;
+            config.remove_session_overrides(&saved_config);
         }
 
         // Save the updated config
@@ -251,7 +827,85 @@ impl AppConfig {
 
         {
             let mut config = config_lock.write().unwrap();
+            let saved_config = config.clone();
             config.video_settings = video_settings;
+            config.remove_session_overrides(&saved_config);
+        }
+
+        // Save the updated config
+        let config = config_lock.read().unwrap();
+        config.save(app_handle)
+    }
+
+    /// Replace the brand kits in global config and save
+    pub fn update_global_brand_kits(
+        brand_kits: Vec<BrandKit>,
+        app_handle: &AppHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let config_lock = CONFIG
+            .get()
+            .expect("Config not initialized. Call AppConfig::init() first.");
+
+        {
+            let mut config = config_lock.write().unwrap();
+            config.brand_kits = brand_kits;
+        }
+
+        // Save the updated config
+        let config = config_lock.read().unwrap();
+        config.save(app_handle)
+    }
+
+    /// Replace the image presets in global config and save
+    pub fn update_global_image_presets(
+        image_presets: Vec<ImagePreset>,
+        app_handle: &AppHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let config_lock = CONFIG
+            .get()
+            .expect("Config not initialized. Call AppConfig::init() first.");
+
+        {
+            let mut config = config_lock.write().unwrap();
+            config.image_presets = image_presets;
+        }
+
+        // Save the updated config
+        let config = config_lock.read().unwrap();
+        config.save(app_handle)
+    }
+
+    /// Replace the video presets in global config and save
+    pub fn update_global_video_presets(
+        video_presets: Vec<VideoPreset>,
+        app_handle: &AppHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let config_lock = CONFIG
+            .get()
+            .expect("Config not initialized. Call AppConfig::init() first.");
+
+        {
+            let mut config = config_lock.write().unwrap();
+            config.video_presets = video_presets;
+        }
+
+        // Save the updated config
+        let config = config_lock.read().unwrap();
+        config.save(app_handle)
+    }
+
+    /// Set whether to check for updates on startup in global config and save
+    pub fn update_global_check_for_updates_on_startup(
+        check_for_updates_on_startup: bool,
+        app_handle: &AppHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let config_lock = CONFIG
+            .get()
+            .expect("Config not initialized. Call AppConfig::init() first.");
+
+        {
+            let mut config = config_lock.write().unwrap();
+            config.check_for_updates_on_startup = check_for_updates_on_startup;
         }
 
         // Save the updated config
@@ -261,22 +915,27 @@ impl AppConfig {
 
     /// Load configuration from file or create default
     pub fn load_or_create_default(app_handle: &AppHandle) -> Result<AppConfig, Box<dyn Error>> {
-        let config_path = Self::get_config_path(app_handle)?;
+        Self::load_or_create_default_at(&Self::get_config_path(app_handle)?)
+    }
 
+    /// Load configuration from the file at a path or create default
+    fn load_or_create_default_at(config_path: &Path) -> Result<AppConfig, Box<dyn Error>> {
         let config = if config_path.exists() {
-            let config_str = fs::read_to_string(&config_path)?;
-            match serde_json::from_str::<AppConfig>(&config_str) {
-                Ok(config) => config,
-                Err(_) => {
-                    // Deserialization failed, attempt migration
+            let config_str = fs::read_to_string(config_path)?;
+            let current_config: serde_json::Value = serde_json::from_str(&config_str)?;
+            let is_outdated = config_version(&current_config) < CURRENT_CONFIG_VERSION;
+            match serde_json::from_value::<AppConfig>(current_config.clone()) {
+                Ok(config) if !is_outdated => config,
+                _ => {
+                    // The config is of an older version or deserialization failed, migrate it
                     let mut config = AppConfig::default();
-                    config.migrate_current_config(app_handle)?;
+                    config.migrate_current_config(current_config, config_path)?;
                     config
                 }
             }
         } else {
             let default_config = AppConfig::default();
-            default_config.save(app_handle)?;
+            default_config.save_to(config_path)?;
             default_config
         };
 
@@ -305,20 +964,34 @@ impl AppConfig {
 
     /// Migrates the current config to a newer version of the AppConfig struct
     ///
-    /// This function creates a new default AppConfig and merges in existing values from the current config file,
-    /// effectively migrating the current config to the new config structure.
+    /// This function runs the migrations from the version of the current config to the current
+    /// version, then creates a new default AppConfig and merges in the migrated values, effectively
+    /// migrating the current config to the new config structure.
     ///
     /// ## Note
-    /// Config settings which are renamed are migrated by reading the old setting's value
-    /// using serde (e.g. `#[serde(alias = "<old-name>", alias = "<another-old-name>")]` above a settings name)
-    /// and are saved using the new name (done when saving the new config to `config.json`).
-    fn migrate_current_config(&mut self, app_handle: &AppHandle) -> Result<(), Box<dyn Error>> {
-        let config_path = Self::get_config_path(app_handle)?;
-        let config_str = fs::read_to_string(&config_path)?;
-        let current_config: serde_json::Value = serde_json::from_str(&config_str)?;
+    /// Config settings which are renamed or restructured are migrated by a migration of
+    /// `config_migrations`, which bumps `CURRENT_CONFIG_VERSION`. Settings which are only added
+    /// are filled in with their defaults.
+    fn migrate_current_config(
+        &mut self,
+        mut current_config: serde_json::Value,
+        config_path: &Path,
+    ) -> Result<(), Box<dyn Error>> {
+        migrate_config(&mut current_config);
+        *self = Self::merge_with_default_config(&current_config)?;
+        self.save_to(config_path)?;
 
+        Ok(())
+    }
+
+    /// Create a config from the default config with the values of another config merged in, so
+    /// configs of older versions which miss settings can be read
+    fn merge_with_default_config(
+        current_config: &serde_json::Value,
+    ) -> Result<AppConfig, Box<dyn Error>> {
         // Create default config and merge in current values
-        let mut app_config_json = serde_json::to_value(AppConfig::default())?;
+        let default_config_json = serde_json::to_value(AppConfig::default())?;
+        let mut app_config_json = default_config_json.clone();
 
         if let Some(obj) = app_config_json.as_object_mut() {
             if let Some(current_obj) = current_config.as_object() {
@@ -338,26 +1011,124 @@ impl AppConfig {
                     obj.insert(key.clone(), value.clone());
                 }
             }
+
+            // Presets hold complete settings, so the settings added since a preset was saved are
+            // filled in with their defaults
+            for (presets_key, settings_key) in [
+                ("imagePresets", "imageSettings"),
+                ("videoPresets", "videoSettings"),
+            ] {
+                let (
+                    Some(serde_json::Value::Array(presets)),
+                    Some(serde_json::Value::Object(default_settings)),
+                ) = (
+                    obj.get_mut(presets_key),
+                    default_config_json.get(settings_key),
+                )
+                else {
+                    continue;
+                };
+                for preset in presets {
+                    if let Some(serde_json::Value::Object(preset_settings)) =
+                        preset.get_mut("settings")
+                    {
+                        for (setting_key, default_value) in default_settings {
+                            preset_settings
+                                .entry(setting_key.clone())
+                                .or_insert_with(|| default_value.clone());
+                        }
+                    }
+                }
+            }
         }
 
-        let new_config: AppConfig = serde_json::from_value(app_config_json)?;
-        *self = new_config;
-        self.save(app_handle)?;
+        Ok(serde_json::from_value(app_config_json)?)
+    }
 
+    /// Write the brand kits, presets and settings to a file which can be imported on another machine
+    pub fn export_to_file(path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut config_json = serde_json::to_value(Self::global())?;
+        // Whether to check for updates is a preference of each machine
+        if let Some(obj) = config_json.as_object_mut() {
+            obj.remove("checkForUpdatesOnStartup");
+        }
+
+        fs::write(path, serde_json::to_string_pretty(&config_json)?)?;
         Ok(())
     }
 
+    /// Import the settings of an exported file into global config and save
+    ///
+    /// Imported brand kits and presets replace the ones with the same name. The input and output
+    /// directories of this machine are kept, since the directories of the machine the file was
+    /// exported on rarely exist on this one. The logos and output mappings which don't exist on
+    /// this machine are returned, so they can be fixed before a run fails on them.
+    pub fn import_from_file(
+        path: &Path,
+        app_handle: &AppHandle,
+    ) -> Result<ImportedSettings, Box<dyn Error>> {
+        let imported_str = fs::read_to_string(path)?;
+        let mut imported_json: serde_json::Value = serde_json::from_str(&imported_str)
+            .map_err(|e| format!("The settings file {} is invalid: {}", path.display(), e))?;
+        migrate_config(&mut imported_json);
+        let imported_config = Self::merge_with_default_config(&imported_json)?;
+        let missing_files = find_missing_files(&imported_config);
+
+        let config_lock = CONFIG
+            .get()
+            .expect("Config not initialized. Call AppConfig::init() first.");
+
+        {
+            let mut config = config_lock.write().unwrap();
+            merge_named(
+                &mut config.brand_kits,
+                imported_config.brand_kits,
+                |brand_kit| brand_kit.name.clone(),
+            );
+            merge_named(
+                &mut config.image_presets,
+                imported_config.image_presets,
+                |preset| preset.name.clone(),
+            );
+            merge_named(
+                &mut config.video_presets,
+                imported_config.video_presets,
+                |preset| preset.name.clone(),
+            );
+            config.image_settings = ImageSettings {
+                input_directory: config.image_settings.input_directory.clone(),
+                output_directory: config.image_settings.output_directory.clone(),
+                ..imported_config.image_settings
+            };
+            config.video_settings = VideoSettings {
+                input_directory: config.video_settings.input_directory.clone(),
+                output_directory: config.video_settings.output_directory.clone(),
+                ..imported_config.video_settings
+            };
+        }
+
+        // Save the updated config
+        config_lock.read().unwrap().save(app_handle)?;
+        Ok(ImportedSettings {
+            config: Self::global(),
+            missing_files,
+        })
+    }
+
     /// Save configuration to file
     fn save(&self, app_handle: &AppHandle) -> Result<(), Box<dyn Error>> {
-        let config_path = Self::get_config_path(app_handle)?;
+        self.save_to(&Self::get_config_path(app_handle)?)
+    }
 
+    /// Save configuration to the file at a path
+    fn save_to(&self, config_path: &Path) -> Result<(), Box<dyn Error>> {
         // Create config directory if it doesn't exist
         if let Some(parent) = config_path.parent() {
             fs::create_dir_all(parent)?;
         }
 
         let config_str = serde_json::to_string_pretty(self)?;
-        fs::write(&config_path, config_str)?;
+        fs::write(config_path, config_str)?;
         Ok(())
     }
 
@@ -368,6 +1139,6 @@ impl AppConfig {
             .app_config_dir()
             .map_err(|e| format!("Failed to get config directory: {}", e))?;
 
-        Ok(config_dir.join("config.json"))
+        Ok(config_dir.join(CONFIG_FILE_NAME))
     }
 }
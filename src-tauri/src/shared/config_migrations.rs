@@ -0,0 +1,211 @@
+use log::info;
+use serde_json::{Map, Value};
+
+/// Version of the config which this build writes
+pub const CURRENT_CONFIG_VERSION: u32 = 4;
+
+/// Migrations of the config, where the migration at index `i` migrates version `i + 1` to `i + 2`
+///
+/// A migration is added whenever a setting is renamed or restructured. Settings which are only
+/// added need no migration, since they're filled in with their defaults after migrating.
+const MIGRATIONS: [fn(&mut Value); (CURRENT_CONFIG_VERSION - 1) as usize] =
+    [migrate_v1_to_v2, migrate_v2_to_v3, migrate_v3_to_v4];
+
+/// Get the version of a config, configs written before versioning are version 1
+pub fn config_version(config: &Value) -> u32 {
+    config
+        .get("configVersion")
+        .and_then(Value::as_u64)
+        .map_or(1, |config_version| (config_version as u32).max(1))
+}
+
+/// Migrate a config step by step from its version to the current version
+///
+/// Configs of a newer version, e.g. written by a newer build, are left as they are.
+pub fn migrate_config(config: &mut Value) {
+    let config_version = config_version(config);
+    if config_version >= CURRENT_CONFIG_VERSION {
+        return;
+    }
+
+    for (from_version, migration) in MIGRATIONS
+        .iter()
+        .enumerate()
+        .skip(config_version as usize - 1)
+    {
+        info!(
+            "Migrating the config from version {} to {}",
+            from_version + 1,
+            from_version + 2
+        );
+        migration(config);
+    }
+
+    if let Some(config) = config.as_object_mut() {
+        config.insert("configVersion".to_string(), CURRENT_CONFIG_VERSION.into());
+    }
+}
+
+/// Apply a migration of the settings to the image and video settings, and to the settings of the
+/// presets
+fn migrate_settings(config: &mut Value, migrate: impl Fn(&mut Map<String, Value>)) {
+    for settings_key in ["imageSettings", "videoSettings"] {
+        if let Some(Value::Object(settings)) = config.get_mut(settings_key) {
+            migrate(settings);
+        }
+    }
+    for presets_key in ["imagePresets", "videoPresets"] {
+        if let Some(Value::Array(presets)) = config.get_mut(presets_key) {
+            for preset in presets {
+                if let Some(Value::Object(settings)) = preset.get_mut("settings") {
+                    migrate(settings);
+                }
+            }
+        }
+    }
+}
+
+/// Rename a setting, keeping the value of the new name when both exist
+fn rename_setting(settings: &mut Map<String, Value>, old_name: &str, new_name: &str) {
+    if let Some(value) = settings.remove(old_name) {
+        settings.entry(new_name).or_insert(value);
+    }
+}
+
+/// The favorite formats and codecs were renamed to favorite lists
+fn migrate_v1_to_v2(config: &mut Value) {
+    migrate_settings(config, |settings| {
+        rename_setting(settings, "favorite_formats", "formatFavoriteList");
+        rename_setting(settings, "favorite_codecs", "codecFavoriteList");
+    });
+}
+
+/// The overwrite setting was replaced by the collision strategy
+fn migrate_v2_to_v3(config: &mut Value) {
+    migrate_settings(config, |settings| {
+        if let Some(overwrite) = settings.remove("overwriteExistingFilesOutputDirectory") {
+            let collision_strategy = if overwrite.as_bool() == Some(true) {
+                "overwrite"
+            } else {
+                "skip"
+            };
+            settings
+                .entry("collisionStrategy")
+                .or_insert(collision_strategy.into());
+        }
+    });
+}
+
+/// The clear input directory setting was replaced by the source disposition
+///
+/// The old setting never touched the inputs, so it maps to keeping them whatever its value was.
+/// Disposing of the inputs is only done once it's chosen explicitly.
+fn migrate_v3_to_v4(config: &mut Value) {
+    migrate_settings(config, |settings| {
+        if settings.remove("clearFilesInputDirectory").is_some() {
+            settings.entry("sourceDisposition").or_insert("keep".into());
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn migrates_v1_to_v2() {
+        let mut config = json!({
+            "imageSettings": { "favorite_formats": ["png"] },
+            "videoPresets": [{ "settings": { "favorite_codecs": ["libx264"] } }],
+        });
+        migrate_v1_to_v2(&mut config);
+        assert_eq!(
+            config,
+            json!({
+                "imageSettings": { "formatFavoriteList": ["png"] },
+                "videoPresets": [{ "settings": { "codecFavoriteList": ["libx264"] } }],
+            })
+        );
+    }
+
+    #[test]
+    fn migrates_v1_to_v2_keeping_the_new_name() {
+        let mut config = json!({
+            "imageSettings": { "favorite_formats": ["png"], "formatFavoriteList": ["jpg"] },
+        });
+        migrate_v1_to_v2(&mut config);
+        assert_eq!(
+            config,
+            json!({ "imageSettings": { "formatFavoriteList": ["jpg"] } })
+        );
+    }
+
+    #[test]
+    fn migrates_v2_to_v3() {
+        let mut config = json!({
+            "imageSettings": { "overwriteExistingFilesOutputDirectory": true },
+            "videoSettings": { "overwriteExistingFilesOutputDirectory": false },
+        });
+        migrate_v2_to_v3(&mut config);
+        assert_eq!(
+            config,
+            json!({
+                "imageSettings": { "collisionStrategy": "overwrite" },
+                "videoSettings": { "collisionStrategy": "skip" },
+            })
+        );
+    }
+
+    #[test]
+    fn migrates_v3_to_v4_keeping_the_inputs() {
+        let mut config = json!({
+            "imageSettings": { "clearFilesInputDirectory": true },
+            "videoSettings": { "clearFilesInputDirectory": false },
+            "imagePresets": [{ "settings": { "clearFilesInputDirectory": true } }],
+        });
+        migrate_v3_to_v4(&mut config);
+        assert_eq!(
+            config,
+            json!({
+                "imageSettings": { "sourceDisposition": "keep" },
+                "videoSettings": { "sourceDisposition": "keep" },
+                "imagePresets": [{ "settings": { "sourceDisposition": "keep" } }],
+            })
+        );
+    }
+
+    #[test]
+    fn migrates_v1_to_current() {
+        let mut config = json!({
+            "imageSettings": {
+                "favorite_formats": ["png"],
+                "overwriteExistingFilesOutputDirectory": true,
+                "clearFilesInputDirectory": true,
+            },
+        });
+        migrate_config(&mut config);
+        assert_eq!(
+            config,
+            json!({
+                "configVersion": CURRENT_CONFIG_VERSION,
+                "imageSettings": {
+                    "formatFavoriteList": ["png"],
+                    "collisionStrategy": "overwrite",
+                    "sourceDisposition": "keep",
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn leaves_newer_configs_unchanged() {
+        let mut config = json!({
+            "configVersion": CURRENT_CONFIG_VERSION + 1,
+            "imageSettings": { "clearFilesInputDirectory": true },
+        });
+        let original = config.clone();
+        migrate_config(&mut config);
+        assert_eq!(config, original);
+    }
+}
@@ -0,0 +1,141 @@
+use log::info;
+use std::path::Path;
+use std::sync::{Condvar, Mutex};
+
+lazy_static::lazy_static! {
+    static ref DISK_READER_MANAGER: DiskReaderManager = DiskReaderManager::new();
+}
+
+/// Limits the FFmpeg processes reading the input at the same time
+///
+/// Each process seeks to its own file, which is free on SSDs but makes a spinning disk spend most
+/// of its time seeking. The limit only applies when the input directory is on an HDD, so it's
+/// independent from the number of CPU workers.
+pub struct DiskReaderManager {
+    state: Mutex<DiskReaderState>,
+    reader_finished: Condvar,
+}
+
+struct DiskReaderState {
+    /// `None` when the number of readers isn't limited
+    max_readers: Option<usize>,
+    active_readers: usize,
+}
+
+/// Permission for a process to read the input, which is released when dropped
+pub struct DiskReaderPermit;
+
+impl DiskReaderManager {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(DiskReaderState {
+                max_readers: None,
+                active_readers: 0,
+            }),
+            reader_finished: Condvar::new(),
+        }
+    }
+
+    /// Limit the readers to `max_disk_readers` when the input directory is on an HDD
+    ///
+    /// A limit of 0 or an input directory on another type of disk removes the limit.
+    pub fn configure(input_directory: &Path, max_disk_readers: u32) {
+        let max_readers = if max_disk_readers > 0 && is_rotational_disk(input_directory) {
+            info!(
+                "Input directory is on an HDD, limiting the disk readers to {}",
+                max_disk_readers
+            );
+            Some(max_disk_readers as usize)
+        } else {
+            None
+        };
+
+        let mut state = DISK_READER_MANAGER.state.lock().unwrap();
+        state.max_readers = max_readers;
+        DISK_READER_MANAGER.reader_finished.notify_all();
+    }
+
+    /// Wait until a process may read the input
+    pub fn acquire() -> DiskReaderPermit {
+        let mut state = DISK_READER_MANAGER
+            .reader_finished
+            .wait_while(DISK_READER_MANAGER.state.lock().unwrap(), |state| {
+                state
+                    .max_readers
+                    .is_some_and(|max_readers| state.active_readers >= max_readers)
+            })
+            .unwrap();
+        state.active_readers += 1;
+        DiskReaderPermit
+    }
+}
+
+impl Drop for DiskReaderPermit {
+    fn drop(&mut self) {
+        let mut state = DISK_READER_MANAGER.state.lock().unwrap();
+        state.active_readers -= 1;
+        DISK_READER_MANAGER.reader_finished.notify_one();
+    }
+}
+
+/// Check if a path is on a spinning disk, using the `rotational` flag of its block device
+#[cfg(target_os = "linux")]
+fn is_rotational_disk(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    let device = metadata.dev();
+    let major = ((device >> 8) & 0xfff) | ((device >> 32) & !0xfff);
+    let minor = (device & 0xff) | ((device >> 12) & !0xff);
+
+    // Partitions have no queue of their own, it belongs to the disk they're on
+    let device_directory = Path::new("/sys/dev/block").join(format!("{}:{}", major, minor));
+    [
+        device_directory.join("queue/rotational"),
+        device_directory.join("../queue/rotational"),
+    ]
+    .iter()
+    .find_map(|rotational_path| std::fs::read_to_string(rotational_path).ok())
+    .is_some_and(|rotational| rotational.trim() == "1")
+}
+
+/// Check if a path is on a spinning disk, using the media type of the disk of its drive letter
+#[cfg(target_os = "windows")]
+fn is_rotational_disk(path: &Path) -> bool {
+    use std::os::windows::process::CommandExt;
+    use std::path::{Component, Prefix};
+
+    use crate::shared::external_command::{external_command, ExternalBinary};
+
+    let Ok(path) = std::fs::canonicalize(path) else {
+        return false;
+    };
+    let drive_letter = match path.components().next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => letter as char,
+            _ => return false,
+        },
+        _ => return false,
+    };
+
+    let query = format!(
+        "Get-PhysicalDisk | Where-Object DeviceId -eq (Get-Partition -DriveLetter {}).DiskNumber \
+         | Select-Object -ExpandProperty MediaType",
+        drive_letter
+    );
+    let Ok(mut command) = external_command(ExternalBinary::Powershell) else {
+        return false;
+    };
+    command
+        .args(["-NoProfile", "-Command", &query])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "HDD")
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn is_rotational_disk(_path: &Path) -> bool {
+    false
+}
@@ -0,0 +1,163 @@
+use log::{info, warn};
+use std::error::Error;
+use std::path::Path;
+
+use crate::shared::ffmpeg_structs::CodecType;
+use crate::shared::media_structs::Resolution;
+use crate::shared::progress_handler::ProgressSink;
+use crate::shared::run_report::RunReportManager;
+
+/// Share of the estimated output size which is kept free on top, since the estimate is rough
+const FREE_SPACE_MARGIN: f64 = 0.2;
+
+/// Bits per pixel of every frame of a video encoded at a quality level, which is about what
+/// inter-frame codecs need at their default quality
+const VIDEO_BITS_PER_PIXEL: f64 = 0.15;
+/// Bits per pixel of every frame of a video with an intra-frame codec, e.g. ProRes
+const INTRA_VIDEO_BITS_PER_PIXEL: f64 = 3.0;
+
+/// Fail when the volume of the output directory has too little free space for the outputs
+///
+/// Checking this before processing prevents runs which fail halfway with FFmpeg write errors.
+/// The run fails when the estimated outputs don't fit. It only warns, before the encoding starts,
+/// when they fit but not with the margin for the rough estimate.
+pub fn check_free_space(
+    output_directory: &Path,
+    estimated_output_size: u64,
+    progress: &dyn ProgressSink,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let required_size = (estimated_output_size as f64 * (1.0 + FREE_SPACE_MARGIN)) as u64;
+    let free_size = match available_space(output_directory) {
+        Ok(free_size) => free_size,
+        Err(e) => {
+            warn!(
+                "Failed to read the free space of {}: {}",
+                output_directory.display(),
+                e
+            );
+            return Ok(());
+        }
+    };
+
+    info!(
+        "The outputs need about {} of the {} free in {}",
+        format_size(required_size),
+        format_size(free_size),
+        output_directory.display()
+    );
+
+    if free_size < estimated_output_size {
+        return Err(format!(
+            "Not enough free space for the outputs in {}: about {} is needed, but only {} is free",
+            output_directory.display(),
+            format_size(estimated_output_size),
+            format_size(free_size)
+        )
+        .into());
+    }
+
+    if free_size < required_size {
+        let message = format!(
+            "The outputs may not fit in {}: they need about {}, but only {} is free",
+            output_directory.display(),
+            format_size(required_size),
+            format_size(free_size)
+        );
+        warn!("{}", message);
+        progress.set_status(format!("Warning: {}", message));
+        RunReportManager::add_warning(message);
+    }
+
+    Ok(())
+}
+
+/// Estimate the size of an output image from its resolution and format
+pub fn estimate_image_output_size(resolution: &Resolution, format: &str) -> u64 {
+    let bytes_per_pixel = match format.to_lowercase().as_str() {
+        "avif" | "heic" | "heif" => 0.2,
+        "webp" => 0.3,
+        "jpg" | "jpeg" | "gif" => 0.5,
+        "png" => 2.0,
+        "bmp" | "tif" | "tiff" => 4.0,
+        _ => 1.0,
+    };
+    (resolution.width as f64 * resolution.height as f64 * bytes_per_pixel) as u64
+}
+
+/// Estimate the size of an output video from its bitrate, or from its resolution and number of
+/// frames when it's encoded at a quality level
+pub fn estimate_video_output_size(
+    resolution: &Resolution,
+    frame_count: usize,
+    duration_seconds: f64,
+    bitrate_kbps: Option<u32>,
+    codec_type: Option<CodecType>,
+) -> u64 {
+    if let Some(bitrate_kbps) = bitrate_kbps {
+        return (bitrate_kbps as f64 * 1000.0 / 8.0 * duration_seconds) as u64;
+    }
+
+    let bits_per_pixel = match codec_type {
+        Some(CodecType::Intra) => INTRA_VIDEO_BITS_PER_PIXEL,
+        _ => VIDEO_BITS_PER_PIXEL,
+    };
+    (resolution.width as f64 * resolution.height as f64 * frame_count as f64 * bits_per_pixel / 8.0)
+        as u64
+}
+
+/// Format a number of bytes for messages, e.g. `1.5 GB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+    let mut size = bytes as f64;
+    let mut unit_index = 0;
+    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit_index += 1;
+    }
+
+    if unit_index == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit_index])
+    }
+}
+
+/// Get the bytes which are free for the current user on the volume of a path
+#[cfg(target_os = "windows")]
+fn available_space(path: &Path) -> std::io::Result<u64> {
+    use std::os::windows::ffi::OsStrExt;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetDiskFreeSpaceExW(
+            directory_name: *const u16,
+            free_bytes_available: *mut u64,
+            total_number_of_bytes: *mut u64,
+            total_number_of_free_bytes: *mut u64,
+        ) -> i32;
+    }
+
+    let wide_path: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut free_bytes_available = 0u64;
+    // SAFETY: the path is null terminated and the other arguments may be null
+    let succeeded = unsafe {
+        GetDiskFreeSpaceExW(
+            wide_path.as_ptr(),
+            &mut free_bytes_available,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    if succeeded == 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(free_bytes_available)
+}
+
+/// Get the bytes which are free for the current user on the volume of a path
+#[cfg(not(target_os = "windows"))]
+fn available_space(path: &Path) -> std::io::Result<u64> {
+    let stat = nix::sys::statvfs::statvfs(path)?;
+    Ok(stat.blocks_available() as u64 * stat.fragment_size() as u64)
+}
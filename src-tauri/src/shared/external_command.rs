@@ -0,0 +1,146 @@
+use ffmpeg_sidecar::{
+    command::FfmpegCommand,
+    download::{download_ffmpeg_package, ffmpeg_download_url, unpack_ffmpeg},
+    paths::{sidecar_dir, sidecar_path},
+};
+use log::info;
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Folders of the system tools on Unix-like systems, which only an administrator can write to
+#[cfg(not(target_os = "windows"))]
+const UNIX_SYSTEM_DIRECTORIES: &[&str] = &["/usr/bin", "/bin", "/usr/sbin", "/sbin"];
+
+/// Folders of package managers which the FFmpeg tools may also be installed in, e.g. ffprobe on
+/// macOS, where the FFmpeg download doesn't include it
+#[cfg(not(target_os = "windows"))]
+const UNIX_PACKAGE_DIRECTORIES: &[&str] = &["/usr/local/bin", "/opt/homebrew/bin"];
+
+/// A binary which the app runs
+///
+/// Binaries are never looked up on the `PATH`, since anyone who can write to one of its folders
+/// could replace them, which is a hijack risk on shared machines. The FFmpeg tools are run from
+/// the folder of the app, where FFmpeg is downloaded to, and system tools from the system folders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalBinary {
+    Ffmpeg,
+    Ffprobe,
+    FileExplorer,
+    #[cfg(target_os = "windows")]
+    Powershell,
+    #[cfg(not(target_os = "windows"))]
+    Renice,
+    #[cfg(target_os = "windows")]
+    Taskkill,
+}
+
+impl ExternalBinary {
+    fn file_name(&self) -> &'static str {
+        match self {
+            Self::Ffmpeg => "ffmpeg",
+            Self::Ffprobe => "ffprobe",
+            #[cfg(target_os = "windows")]
+            Self::FileExplorer => "explorer",
+            #[cfg(target_os = "macos")]
+            Self::FileExplorer => "open",
+            #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+            Self::FileExplorer => "xdg-open",
+            #[cfg(target_os = "windows")]
+            Self::Powershell => "powershell",
+            #[cfg(not(target_os = "windows"))]
+            Self::Renice => "renice",
+            #[cfg(target_os = "windows")]
+            Self::Taskkill => "taskkill",
+        }
+    }
+
+    /// Folders which the binary may be run from, in order of preference
+    fn trusted_directories(&self) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+        let mut directories = Vec::new();
+        match self {
+            Self::Ffmpeg | Self::Ffprobe => {
+                directories.push(sidecar_dir()?);
+                #[cfg(not(target_os = "windows"))]
+                directories.extend(UNIX_PACKAGE_DIRECTORIES.iter().map(PathBuf::from));
+            }
+            #[cfg(target_os = "windows")]
+            Self::FileExplorer => directories.push(windows_directory()),
+            #[cfg(target_os = "windows")]
+            Self::Powershell => directories.push(
+                windows_directory()
+                    .join("System32")
+                    .join("WindowsPowerShell")
+                    .join("v1.0"),
+            ),
+            #[cfg(target_os = "windows")]
+            Self::Taskkill => directories.push(windows_directory().join("System32")),
+            #[cfg(not(target_os = "windows"))]
+            Self::FileExplorer | Self::Renice => {}
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        directories.extend(UNIX_SYSTEM_DIRECTORIES.iter().map(PathBuf::from));
+
+        Ok(directories)
+    }
+
+    /// Resolve the absolute path of the binary in its trusted folders
+    pub fn path(&self) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+        let file_name = Path::new(self.file_name()).with_extension(std::env::consts::EXE_EXTENSION);
+
+        self.trusted_directories()?
+            .into_iter()
+            .map(|directory| directory.join(&file_name))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                format!(
+                    "{} was not found in the folder of the app or the system folders",
+                    file_name.display()
+                )
+                .into()
+            })
+    }
+}
+
+/// Create a command which runs a binary from its trusted folders
+pub fn external_command(binary: ExternalBinary) -> Result<Command, Box<dyn Error + Send + Sync>> {
+    let path = binary.path()?;
+    info!("Running external command: {}", path.display());
+    Ok(Command::new(path))
+}
+
+/// Create an FFmpeg command which runs FFmpeg from its trusted folders
+pub fn ffmpeg_command() -> Result<FfmpegCommand, Box<dyn Error + Send + Sync>> {
+    let path = ExternalBinary::Ffmpeg.path()?;
+    info!("Running external command: {}", path.display());
+    Ok(FfmpegCommand::new_with_path(path))
+}
+
+/// Download FFmpeg into the folder of the app, unless it's already there
+///
+/// An FFmpeg on the `PATH` doesn't count, since it's never run.
+pub fn install_ffmpeg() -> Result<(), Box<dyn Error + Send + Sync>> {
+    if sidecar_path()?.is_file() {
+        return Ok(());
+    }
+
+    info!("Downloading FFmpeg into the folder of the app...");
+    let destination = sidecar_dir()?;
+    let archive_path = download_ffmpeg_package(ffmpeg_download_url()?, &destination)?;
+    unpack_ffmpeg(&archive_path, &destination)?;
+
+    ExternalBinary::Ffmpeg.path()?;
+    Ok(())
+}
+
+/// Get the Windows folder from the system root, falling back to its default location
+#[cfg(target_os = "windows")]
+fn windows_directory() -> PathBuf {
+    std::env::var_os("SystemRoot")
+        .map(PathBuf::from)
+        .filter(|directory| directory.is_absolute())
+        .unwrap_or_else(|| PathBuf::from(r"C:\Windows"))
+}
@@ -0,0 +1,109 @@
+use log::{info, warn};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use crate::shared::external_command::{external_command, ExternalBinary};
+
+/// The encoders and filters of the FFmpeg build the app runs with
+///
+/// Builds differ in the libraries they include, e.g. `libaom-av1` or the `zscale` filter, so
+/// features which depend on them check here before using them.
+#[derive(Debug)]
+pub struct FfmpegCapabilities {
+    encoders: HashSet<String>,
+    filters: HashSet<String>,
+    /// Pixel formats of the encoders, queried when they're first needed
+    encoder_pixel_formats: Mutex<HashMap<String, Vec<String>>>,
+}
+
+impl FfmpegCapabilities {
+    /// Query FFmpeg for its encoders and filters, a failed query reports nothing as available
+    fn detect() -> Self {
+        let capabilities = Self {
+            encoders: list_ffmpeg_names("-encoders"),
+            filters: list_ffmpeg_names("-filters"),
+            encoder_pixel_formats: Mutex::new(HashMap::new()),
+        };
+        info!(
+            "Detected {} FFmpeg encoders and {} filters",
+            capabilities.encoders.len(),
+            capabilities.filters.len()
+        );
+        capabilities
+    }
+
+    pub fn has_encoder(&self, encoder: &str) -> bool {
+        self.encoders.contains(encoder)
+    }
+
+    pub fn has_filter(&self, filter: &str) -> bool {
+        self.filters.contains(filter)
+    }
+
+    /// Get the pixel formats an encoder accepts, empty when FFmpeg doesn't list them
+    pub fn get_encoder_pixel_formats(&self, encoder: &str) -> Vec<String> {
+        if !self.has_encoder(encoder) {
+            return Vec::new();
+        }
+
+        self.encoder_pixel_formats
+            .lock()
+            .unwrap()
+            .entry(encoder.to_string())
+            .or_insert_with(|| list_encoder_pixel_formats(encoder))
+            .clone()
+    }
+}
+
+/// Get the pixel formats from the help of an encoder, which lists them after `Supported pixel formats:`
+fn list_encoder_pixel_formats(encoder: &str) -> Vec<String> {
+    let output = match external_command(ExternalBinary::Ffmpeg).and_then(|mut command| {
+        Ok(command
+            .args(["-hide_banner", "-h", &format!("encoder={}", encoder)])
+            .output()?)
+    }) {
+        Ok(output) => output,
+        Err(e) => {
+            warn!(
+                "Failed to query FFmpeg for the pixel formats of {}: {}",
+                encoder, e
+            );
+            return Vec::new();
+        }
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Supported pixel formats:"))
+        .map(|pixel_formats| {
+            pixel_formats
+                .split_whitespace()
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Get the names of an FFmpeg listing, e.g. `-encoders`, which prints a name per line after its flags
+fn list_ffmpeg_names(listing: &str) -> HashSet<String> {
+    let output = match external_command(ExternalBinary::Ffmpeg)
+        .and_then(|mut command| Ok(command.args(["-hide_banner", listing]).output()?))
+    {
+        Ok(output) => output,
+        Err(e) => {
+            warn!("Failed to query FFmpeg for its {}: {}", listing, e);
+            return HashSet::new();
+        }
+    };
+
+    // Legend lines also match, but their second word is never a valid name
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(str::to_string)
+        .collect()
+}
+
+lazy_static::lazy_static! {
+    pub static ref FFMPEG_CAPABILITIES: FfmpegCapabilities = FfmpegCapabilities::detect();
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use ts_rs::TS;
+
+use crate::shared::ffmpeg_processor::format_command_line;
+use crate::shared::ffmpeg_structs::FfmpegBatchCommand;
+
+/// The FFmpeg command of a work unit as it was run
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct FfmpegCommandRecord {
+    pub input_paths: Vec<PathBuf>,
+    pub output_paths: Vec<PathBuf>,
+    /// Arguments of the command, without the FFmpeg executable
+    pub args: Vec<String>,
+    /// The command as a command line which can be copied into a terminal
+    pub command_line: String,
+}
+
+#[derive(Debug, Default)]
+struct FfmpegCommandHistory {
+    enabled: bool,
+    commands: Vec<FfmpegCommandRecord>,
+}
+
+// Global FFmpeg command history manager
+lazy_static::lazy_static! {
+    static ref FFMPEG_COMMAND_HISTORY: Mutex<FfmpegCommandHistory> =
+        Mutex::new(FfmpegCommandHistory::default());
+}
+
+pub struct FfmpegCommandHistoryManager;
+
+impl FfmpegCommandHistoryManager {
+    /// Start recording the commands of a run, which replace the commands of the previous run
+    pub fn start(enabled: bool) {
+        let mut history = FFMPEG_COMMAND_HISTORY.lock().unwrap();
+        history.enabled = enabled;
+        history.commands.clear();
+    }
+
+    /// Record the command of a work unit before it's spawned, since spawning adds arguments
+    pub fn record(ffmpeg_batch_command: &FfmpegBatchCommand, args: &[String]) {
+        let mut history = FFMPEG_COMMAND_HISTORY.lock().unwrap();
+        if !history.enabled {
+            return;
+        }
+
+        history.commands.push(FfmpegCommandRecord {
+            input_paths: ffmpeg_batch_command.input_paths.clone(),
+            output_paths: ffmpeg_batch_command.output_paths.clone(),
+            args: args.to_vec(),
+            command_line: format_command_line(&ffmpeg_batch_command.command),
+        });
+    }
+
+    /// Get the commands of the last run in the order they started, empty when the run didn't
+    /// record its commands
+    pub fn get_last_commands() -> Vec<FfmpegCommandRecord> {
+        FFMPEG_COMMAND_HISTORY.lock().unwrap().commands.clone()
+    }
+}
@@ -0,0 +1,183 @@
+use ffmpeg_sidecar::{command::FfmpegCommand, event::LogLevel};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Component, Path, PathBuf},
+    time::SystemTime,
+};
+use ts_rs::TS;
+
+/// Folder in the output directory which collects the FFmpeg log of every processed file
+const FFMPEG_LOGS_FOLDER: &str = "_logs";
+
+/// Total size of the FFmpeg logs, the oldest logs are removed once it's exceeded
+const MAX_TOTAL_LOG_SIZE: u64 = 100 * 1024 * 1024;
+
+/// The FFmpeg messages which are written to the log of each file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum FfmpegLogLevel {
+    /// Don't write log files
+    Off,
+    Error,
+    Warning,
+    Info,
+    /// Run FFmpeg with debug output, which is slower and produces large logs
+    Debug,
+}
+
+impl FfmpegLogLevel {
+    fn includes(self, level: &LogLevel) -> bool {
+        let message_level = match level {
+            LogLevel::Fatal | LogLevel::Error => FfmpegLogLevel::Error,
+            LogLevel::Warning => FfmpegLogLevel::Warning,
+            LogLevel::Info => FfmpegLogLevel::Info,
+            // Debug messages aren't recognized by the log parser
+            LogLevel::Unknown => FfmpegLogLevel::Debug,
+        };
+        self >= message_level
+    }
+}
+
+/// The folder which the FFmpeg logs of a run are written to
+#[derive(Debug, Clone)]
+pub struct FfmpegLogDirectory {
+    directory: PathBuf,
+    output_directory: PathBuf,
+    level: FfmpegLogLevel,
+}
+
+impl FfmpegLogDirectory {
+    /// Prepare the log folder in the output directory, returns `None` when logging is off
+    ///
+    /// The oldest logs of previous runs are removed when the logs exceed their size limit.
+    pub fn new(
+        output_directory: &Path,
+        level: FfmpegLogLevel,
+    ) -> Result<Option<Self>, Box<dyn Error + Send + Sync>> {
+        if level == FfmpegLogLevel::Off {
+            return Ok(None);
+        }
+
+        let directory = output_directory.join(FFMPEG_LOGS_FOLDER);
+        fs::create_dir_all(&directory)?;
+        remove_oldest_logs(&directory, MAX_TOTAL_LOG_SIZE)?;
+
+        Ok(Some(Self {
+            directory,
+            output_directory: output_directory.to_path_buf(),
+            level,
+        }))
+    }
+
+    /// Raise the verbosity of FFmpeg when debug messages are logged
+    pub fn apply_ffmpeg_args(&self, cmd: &mut FfmpegCommand) {
+        if self.level == FfmpegLogLevel::Debug {
+            cmd.args(["-loglevel", "level+debug"]);
+        }
+    }
+
+    /// Open the log of an output file, which starts every attempt with its command line
+    ///
+    /// The logs mirror the folders of the outputs, so outputs with the same file name in
+    /// different subfolders get their own log. Retries of the same file, e.g. with a fallback
+    /// encoder, are appended to the same log.
+    pub fn open_log(
+        &self,
+        output_path: &Path,
+        command_line: &str,
+    ) -> Result<FfmpegFileLog, Box<dyn Error + Send + Sync>> {
+        let log_path = self.log_path(output_path);
+        if let Some(parent) = log_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let log_file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_path)?;
+
+        let mut writer = BufWriter::new(log_file);
+        writeln!(
+            writer,
+            "[{}] {}",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+            command_line
+        )?;
+
+        Ok(FfmpegFileLog {
+            writer,
+            level: self.level,
+        })
+    }
+
+    /// Get the log path of an output file from its path relative to the output directory
+    ///
+    /// Outputs which are mapped outside the output directory keep their full path below the log
+    /// folder.
+    fn log_path(&self, output_path: &Path) -> PathBuf {
+        let relative_path: PathBuf = output_path
+            .strip_prefix(&self.output_directory)
+            .unwrap_or(output_path)
+            .components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .collect();
+
+        let mut log_path = self.directory.join(relative_path).into_os_string();
+        log_path.push(".log");
+        PathBuf::from(log_path)
+    }
+}
+
+/// The log file of a single FFmpeg process
+pub struct FfmpegFileLog {
+    writer: BufWriter<File>,
+    level: FfmpegLogLevel,
+}
+
+impl FfmpegFileLog {
+    pub fn write(&mut self, level: &LogLevel, message: &str) {
+        if self.level.includes(level) {
+            // A log which can't be written must not fail the processing
+            let _ = writeln!(self.writer, "{}", message);
+        }
+    }
+}
+
+/// Remove the oldest log files until the logs fit in the maximum total size
+fn remove_oldest_logs(
+    directory: &Path,
+    max_total_size: u64,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut log_files: Vec<(SystemTime, u64, PathBuf)> = walkdir::WalkDir::new(directory)
+        .into_iter()
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let metadata = entry.metadata().ok()?;
+            metadata.is_file().then(|| {
+                (
+                    metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                    metadata.len(),
+                    entry.into_path(),
+                )
+            })
+        })
+        .collect();
+    log_files.sort_by_key(|(modified, _, _)| *modified);
+
+    let mut total_size: u64 = log_files.iter().map(|(_, size, _)| size).sum();
+    for (_, size, path) in log_files {
+        if total_size <= max_total_size {
+            break;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => total_size -= size,
+            Err(e) => warn!("Failed to remove the log {}: {}", path.display(), e),
+        }
+    }
+
+    Ok(())
+}
@@ -1,24 +1,96 @@
-use std::error::Error;
+use std::{error::Error, fmt};
 
-use ffmpeg_sidecar::child::FfmpegChild;
+use ffmpeg_sidecar::{child::FfmpegChild, log_parser::parse_time_str};
 use log::error;
 
 use crate::shared::{
-    process_manager::ProcessManager,
-    progress_handler::{ProgressManager, ProgressMode},
+    ffmpeg_file_log::FfmpegFileLog, process_manager::ProcessManager, progress_handler::ProgressSink,
 };
 
+/// An FFmpeg process which exited unsuccessfully, with the details needed to diagnose it
+#[derive(Debug)]
+pub struct FfmpegProcessError {
+    pub exit_code: Option<i32>,
+    /// Timestamp of the input in seconds which was reached before the process failed
+    pub last_timestamp: Option<f64>,
+    pub error_messages: Vec<String>,
+}
+
+impl fmt::Display for FfmpegProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "FFmpeg process failed with exit code: {:?}",
+            self.exit_code
+        )
+    }
+}
+
+impl Error for FfmpegProcessError {}
+
+/// Parts of FFmpeg error messages which come from the data of an input file, e.g. a corrupt or
+/// truncated image, rather than from the command or the machine
+const INPUT_ERROR_PATTERNS: [&str; 8] = [
+    "invalid data found",
+    "error while decoding",
+    "error opening input",
+    "could not find codec parameters",
+    "no such file or directory",
+    "corrupt",
+    "truncated",
+    "error submitting packet to decoder",
+];
+
+/// Parts of FFmpeg error messages which come from an encoder which couldn't be set up, e.g. a
+/// hardware encoder without a device or driver, rather than from the inputs
+const ENCODER_INIT_ERROR_PATTERNS: [&str; 10] = [
+    "error while opening encoder",
+    "could not open encoder",
+    "error initializing output stream",
+    "unknown encoder",
+    "no capable devices found",
+    "device creation failed",
+    "cannot load",
+    "openencodesessionex failed",
+    "failed to initialise",
+    "no device available",
+];
+
+impl FfmpegProcessError {
+    /// Check if the process failed on the data of an input file, so the other inputs of the
+    /// process succeed without it
+    pub fn is_input_error(&self) -> bool {
+        self.has_error_message(&INPUT_ERROR_PATTERNS)
+    }
+
+    /// Check if the process failed to set up its encoder, so another encoder may succeed
+    pub fn is_encoder_init_error(&self) -> bool {
+        self.has_error_message(&ENCODER_INIT_ERROR_PATTERNS)
+    }
+
+    fn has_error_message(&self, patterns: &[&str]) -> bool {
+        self.error_messages.iter().any(|message| {
+            let message = message.to_lowercase();
+            patterns.iter().any(|pattern| message.contains(pattern))
+        })
+    }
+}
+
 /// Logger that processes FFmpeg events and waits for completion
+///
+/// The encoded frames are reported to `frame_progress`, which is `None` when progress is
+/// reported once per finished batch instead. The messages are also written to `file_log`.
 pub fn ffmpeg_logger(
     mut ffmpeg_child: FfmpegChild,
-    progress_mode: ProgressMode,
+    frame_progress: Option<&dyn ProgressSink>,
+    file_log: Option<FfmpegFileLog>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     // Register the ffmpeg process to the process manager
     let pid = ffmpeg_child.as_inner().id();
     let process_id = ProcessManager::register_process_by_pid(pid);
 
     // Process FFmpeg output without holding any locks
-    let result = process_ffmpeg_output(&mut ffmpeg_child, progress_mode);
+    let result = process_ffmpeg_output(&mut ffmpeg_child, frame_progress, file_log);
 
     // Unregister after completion
     ProcessManager::unregister_process(process_id);
@@ -29,18 +101,25 @@ pub fn ffmpeg_logger(
 /// Process FFmpeg output without any mutex operations
 fn process_ffmpeg_output(
     ffmpeg_child: &mut FfmpegChild,
-    progress_mode: ProgressMode,
+    frame_progress: Option<&dyn ProgressSink>,
+    mut file_log: Option<FfmpegFileLog>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let mut last_frame_count: usize = 0;
+    let mut last_timestamp: Option<f64> = None;
+    let mut error_messages = Vec::new();
 
     // Iterate over FFmpeg output events
     for event in ffmpeg_child.iter()? {
         match event {
             ffmpeg_sidecar::event::FfmpegEvent::Log(level, msg) => {
+                if let Some(file_log) = file_log.as_mut() {
+                    file_log.write(&level, &msg);
+                }
                 match level {
                     ffmpeg_sidecar::event::LogLevel::Error
                     | ffmpeg_sidecar::event::LogLevel::Fatal => {
                         error!("FFmpeg: {}", msg);
+                        error_messages.push(msg);
                     }
                     _ => {
                         // Only log warnings and above to reduce overhead
@@ -51,11 +130,13 @@ fn process_ffmpeg_output(
                 }
             }
             ffmpeg_sidecar::event::FfmpegEvent::Progress(progress) => {
+                last_timestamp = parse_time_str(&progress.time).or(last_timestamp);
+
                 // Only track per-frame progress for video mode
-                if matches!(progress_mode, ProgressMode::PerFrame) {
+                if let Some(frame_progress) = frame_progress {
                     let current_frame_count = progress.frame as usize;
                     let frame_count_increase = current_frame_count - last_frame_count;
-                    ProgressManager::increment_progress(frame_count_increase);
+                    frame_progress.increment(frame_count_increase);
                     last_frame_count = current_frame_count;
                 }
             }
@@ -70,7 +151,21 @@ fn process_ffmpeg_output(
     let output = ffmpeg_child.wait()?;
 
     if !output.success() {
-        return Err(format!("FFmpeg process failed with exit code: {:?}", output.code()).into());
+        // The frames of a failed process are encoded again when it's retried, or never finished
+        if let Some(frame_progress) = frame_progress {
+            frame_progress.decrement(last_frame_count);
+        }
+        if let Some(file_log) = file_log.as_mut() {
+            file_log.write(
+                &ffmpeg_sidecar::event::LogLevel::Fatal,
+                &format!("Exited with code: {:?}", output.code()),
+            );
+        }
+        return Err(Box::new(FfmpegProcessError {
+            exit_code: output.code(),
+            last_timestamp,
+            error_messages,
+        }));
     }
 
     Ok(())
@@ -1,27 +1,155 @@
+use ffmpeg_sidecar::command::FfmpegCommand;
+use log::warn;
 use std::error::Error;
+use std::path::PathBuf;
 
 use crate::shared::{
+    background_mode::spawn_ffmpeg,
+    disk_readers::DiskReaderManager,
+    ffmpeg_command_history::FfmpegCommandHistoryManager,
+    ffmpeg_file_log::FfmpegLogDirectory,
     ffmpeg_logger::ffmpeg_logger,
+    ffmpeg_script::ScriptFormat,
     ffmpeg_structs::FfmpegBatchCommand,
-    progress_handler::{ProgressManager, ProgressMode},
+    file_utils::partial_output_path,
+    process_limiter::ProcessLimiter,
+    process_manager::{check_process_cancelled, ProcessManager},
+    progress_handler::{ProgressMode, ProgressSink},
+    run_log::RunLogManager,
+    run_report::RunReportManager,
 };
 
+/// Format an FFmpeg command as a command line which can be copied into a terminal
+pub fn format_command_line(command: &FfmpegCommand) -> String {
+    let script_format = ScriptFormat::native();
+    let mut command_line = String::from("ffmpeg");
+    for arg in command.get_args() {
+        command_line.push(' ');
+        command_line.push_str(&script_format.quote(&arg.to_string_lossy()));
+    }
+    command_line
+}
+
+/// Replace the outputs with the partial files FFmpeg finished writing
+pub fn finish_partial_outputs(
+    output_paths: &[PathBuf],
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    for output_path in output_paths {
+        std::fs::rename(partial_output_path(output_path), output_path).map_err(|e| {
+            format!(
+                "Failed to move the finished output to {}: {}",
+                output_path.display(),
+                e
+            )
+        })?;
+    }
+    Ok(())
+}
+
 pub fn spawn_ffmpeg_process(
     ffmpeg_batch_command: &mut FfmpegBatchCommand,
     progress_mode: ProgressMode,
+    progress: &dyn ProgressSink,
+    log_directory: Option<&FfmpegLogDirectory>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let ffmpeg_child = ffmpeg_batch_command.command.spawn()?;
+    let process_start = std::time::Instant::now();
+
+    // The log is named after the path of the first output, which is unique within the run
+    let file_log = log_directory.and_then(|log_directory| {
+        log_directory.apply_ffmpeg_args(&mut ffmpeg_batch_command.command);
+        let output_path = ffmpeg_batch_command.output_paths.first()?;
+        let command_line = format_command_line(&ffmpeg_batch_command.command);
+        log_directory
+            .open_log(output_path, &command_line)
+            .map_err(|e| {
+                warn!(
+                    "Failed to open the FFmpeg log of {}: {}",
+                    output_path.display(),
+                    e
+                )
+            })
+            .ok()
+    });
+
+    let frame_progress = match progress_mode {
+        ProgressMode::Batch => None,
+        ProgressMode::PerFrame => Some(progress),
+    };
+
+    // The process reads its inputs until it exits, the run may be cancelled while waiting for it
+    let _process_permit = ProcessLimiter::acquire();
+    let _disk_reader_permit = DiskReaderManager::acquire();
+    ProcessManager::wait_while_paused()?;
+    check_process_cancelled()?;
+
+    // The outputs are written to partial files, which replace the outputs once the process
+    // succeeded, so an interrupted or failed process never leaves a half-written output
+    let partial_output_paths: Vec<PathBuf> = ffmpeg_batch_command
+        .output_paths
+        .iter()
+        .map(|output_path| partial_output_path(output_path))
+        .collect();
+
+    // Spawning the process adds arguments, the run log and the command history hold the
+    // arguments of the batch
+    let ffmpeg_args: Vec<String> = ffmpeg_batch_command
+        .command
+        .get_args()
+        .map(|arg| arg.to_string_lossy().to_string())
+        .collect();
+    FfmpegCommandHistoryManager::record(ffmpeg_batch_command, &ffmpeg_args);
+
+    // The files are in flight until the process exits, so a cancelled run can report them
+    RunReportManager::record_files_started(ffmpeg_batch_command.batch_size);
+    progress.start_batch(&ffmpeg_batch_command.input_paths);
+    let result = match spawn_ffmpeg(&mut ffmpeg_batch_command.command) {
+        Ok(mut ffmpeg_child) => {
+            ProcessManager::register_output_paths(
+                ffmpeg_child.as_inner().id(),
+                partial_output_paths.clone(),
+            );
+            ffmpeg_logger(ffmpeg_child, frame_progress, file_log)
+        }
+        Err(e) => Err(e.into()),
+    };
+    RunReportManager::record_files_stopped(ffmpeg_batch_command.batch_size);
+    progress.finish_batch(&ffmpeg_batch_command.input_paths);
+    if let Err(e) = result {
+        RunLogManager::record_batch(
+            ffmpeg_batch_command,
+            &ffmpeg_args,
+            process_start.elapsed(),
+            Some(&*e),
+        );
+        for partial_output_path in &partial_output_paths {
+            let _ = std::fs::remove_file(partial_output_path);
+        }
+        return Err(e);
+    }
+
+    finish_partial_outputs(&ffmpeg_batch_command.output_paths)?;
+    RunLogManager::record_batch(
+        ffmpeg_batch_command,
+        &ffmpeg_args,
+        process_start.elapsed(),
+        None,
+    );
 
-    ffmpeg_logger(ffmpeg_child, progress_mode)?;
+    RunReportManager::record_file_durations(
+        process_start.elapsed(),
+        ffmpeg_batch_command.batch_size,
+    );
 
+    progress.increment_bytes(ffmpeg_batch_command.input_size);
     match progress_mode {
         ProgressMode::Batch => {
-            // Increment progress for image process
-            ProgressManager::increment_progress(ffmpeg_batch_command.batch_size);
+            // The progress of images is weighed by their size, since larger images take longer
+            progress.increment(ffmpeg_batch_command.input_size as usize);
+            progress.increment_alternative(ffmpeg_batch_command.batch_size);
         }
         ProgressMode::PerFrame => {
             // Increment alternative progress for video process
-            ProgressManager::increment_alternative_progress(1);
+            progress.increment_alternative(1);
         }
     }
 
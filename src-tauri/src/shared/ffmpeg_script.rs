@@ -0,0 +1,262 @@
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use crate::shared::ffmpeg_structs::FfmpegBatchCommand;
+use crate::shared::file_utils::partial_output_path;
+
+/// Shell a command line or script is written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptFormat {
+    /// A `.sh` script for POSIX shells
+    Shell,
+    /// A `.bat` script for the Windows command prompt
+    Batch,
+}
+
+impl ScriptFormat {
+    /// The shell of the terminal on this system
+    pub fn native() -> Self {
+        if cfg!(target_os = "windows") {
+            Self::Batch
+        } else {
+            Self::Shell
+        }
+    }
+
+    fn from_path(script_path: &Path) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let extension = script_path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("sh") => Ok(Self::Shell),
+            Some("bat") | Some("cmd") => Ok(Self::Batch),
+            _ => Err(format!(
+                "Unsupported script type {}, expected a .sh or .bat file",
+                script_path.display()
+            )
+            .into()),
+        }
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            Self::Shell => "#!/bin/sh\n",
+            Self::Batch => "@echo off\r\n",
+        }
+    }
+
+    fn line_ending(self) -> &'static str {
+        match self {
+            Self::Shell => "\n",
+            Self::Batch => "\r\n",
+        }
+    }
+
+    fn path_separator(self) -> char {
+        match self {
+            Self::Shell => '/',
+            Self::Batch => '\\',
+        }
+    }
+
+    /// Quote an argument for the shell, arguments without special characters are kept as they are
+    pub fn quote(self, arg: &str) -> String {
+        let is_plain = !arg.is_empty()
+            && arg.chars().all(|c| {
+                c.is_ascii_alphanumeric()
+                    || match self {
+                        Self::Shell => "-_./:=,+@".contains(c),
+                        Self::Batch => r"-_./:\".contains(c),
+                    }
+            });
+        if is_plain {
+            return arg.to_string();
+        }
+
+        match self {
+            // Nothing is expanded within single quotes, a single quote ends and restarts them
+            Self::Shell => format!("'{}'", arg.replace('\'', r"'\''")),
+            // Percent signs are expanded within double quotes as well, FFmpeg arguments contain
+            // no double quotes
+            Self::Batch => format!("\"{}\"", arg.replace('%', "%%")),
+        }
+    }
+
+    /// Set a script variable to a value
+    fn set_variable(self, name: &str, value: &str) -> String {
+        match self {
+            Self::Shell => format!("{}={}", name, self.quote(value)),
+            Self::Batch => format!("set \"{}={}\"", name, value.replace('%', "%%")),
+        }
+    }
+
+    /// Set a script variable to a folder next to the script, so the script can be moved along
+    /// with the folder
+    fn set_script_relative_variable(self, name: &str, file_name: &str) -> String {
+        match self {
+            Self::Shell => format!("{}=\"$(dirname \"$0\")\"/{}", name, self.quote(file_name)),
+            Self::Batch => format!("set \"{}=%~dp0{}\"", name, file_name.replace('%', "%%")),
+        }
+    }
+
+    /// Quote a path within the folder of a script variable, e.g. `"$INPUT_DIR"'/a b.jpg'`
+    fn quote_variable_path(self, name: &str, relative_path: &Path) -> String {
+        let relative_path: String = relative_path
+            .components()
+            .map(|component| {
+                format!(
+                    "{}{}",
+                    self.path_separator(),
+                    component.as_os_str().to_string_lossy()
+                )
+            })
+            .collect();
+        match self {
+            Self::Shell if relative_path.is_empty() => format!("\"${}\"", name),
+            Self::Shell => format!("\"${}\"{}", name, self.quote(&relative_path)),
+            Self::Batch => format!("\"%{}%{}\"", name, relative_path.replace('%', "%%")),
+        }
+    }
+
+    fn create_directory(self, directory: &str) -> String {
+        match self {
+            Self::Shell => format!("mkdir -p {}", directory),
+            Self::Batch => format!("if not exist {0} mkdir {0}", directory),
+        }
+    }
+
+    fn move_file(self, from: &str, to: &str) -> String {
+        match self {
+            Self::Shell => format!("mv -f {} {}", from, to),
+            Self::Batch => format!("move /Y {} {} >nul", from, to),
+        }
+    }
+}
+
+/// Folders of a run which a script refers to by variables, so it can be edited for another
+/// machine in one place
+struct ScriptDirectories<'a> {
+    format: ScriptFormat,
+    /// Variable names with their folders, the deepest folder first
+    directories: Vec<(&'static str, &'a Path)>,
+}
+
+impl ScriptDirectories<'_> {
+    /// Quote an argument, with the paths within a folder of a variable made relative to it
+    fn quote(&self, arg: &str) -> String {
+        for (name, directory) in &self.directories {
+            if let Ok(relative_path) = Path::new(arg).strip_prefix(directory) {
+                return self.format.quote_variable_path(name, relative_path);
+            }
+        }
+        self.format.quote(arg)
+    }
+
+    fn quote_path(&self, path: &Path) -> String {
+        self.quote(&path.to_string_lossy())
+    }
+}
+
+/// Folder next to an exported script for the logos its commands overlay, e.g. `run_logos` for
+/// `run.sh`
+pub fn script_logo_directory(script_path: &Path) -> PathBuf {
+    let file_stem = script_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    script_path.with_file_name(format!("{}_logos", file_stem))
+}
+
+/// Write the FFmpeg commands of a run to a script, which runs them with the `ffmpeg` of the
+/// machine it runs on
+///
+/// The script is a `.sh` or `.bat` script by the extension of the path. The input and output
+/// directories are set at the top of the script as `INPUT_DIR` and `OUTPUT_DIR`, and the logos
+/// are found next to the script. Every command writes to partial files like a run, which replace
+/// the outputs once the command succeeded, and a failed command doesn't stop the script.
+pub fn write_ffmpeg_script(
+    script_path: &Path,
+    input_directory: &Path,
+    output_directory: &Path,
+    ffmpeg_batch_commands: &[FfmpegBatchCommand],
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let script_format = ScriptFormat::from_path(script_path)?;
+    let line_ending = script_format.line_ending();
+
+    let logo_directory = script_logo_directory(script_path);
+    let logo_directory_name = logo_directory
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+
+    let mut directories = vec![
+        ("INPUT_DIR", input_directory),
+        ("OUTPUT_DIR", output_directory),
+        ("LOGO_DIR", logo_directory.as_path()),
+    ];
+    directories.sort_by_key(|(_, directory)| std::cmp::Reverse(directory.components().count()));
+    let script_directories = ScriptDirectories {
+        format: script_format,
+        directories,
+    };
+
+    let mut script = String::from(script_format.header());
+    for line in [
+        script_format.set_variable("INPUT_DIR", &input_directory.to_string_lossy()),
+        script_format.set_variable("OUTPUT_DIR", &output_directory.to_string_lossy()),
+        script_format.set_script_relative_variable("LOGO_DIR", &logo_directory_name),
+    ] {
+        script.push_str(&line);
+        script.push_str(line_ending);
+    }
+    script.push_str(line_ending);
+
+    let output_directories: BTreeSet<&Path> = ffmpeg_batch_commands
+        .iter()
+        .flat_map(|ffmpeg_batch_command| &ffmpeg_batch_command.output_paths)
+        .filter_map(|output_path| output_path.parent())
+        .collect();
+    for output_directory in output_directories {
+        script.push_str(
+            &script_format.create_directory(&script_directories.quote_path(output_directory)),
+        );
+        script.push_str(line_ending);
+    }
+
+    for ffmpeg_batch_command in ffmpeg_batch_commands {
+        let mut line = String::from("ffmpeg");
+        for arg in ffmpeg_batch_command.command.get_args() {
+            line.push(' ');
+            line.push_str(&script_directories.quote(&arg.to_string_lossy()));
+        }
+        for output_path in &ffmpeg_batch_command.output_paths {
+            let partial_output_path = partial_output_path(output_path);
+            line.push_str(" && ");
+            line.push_str(&script_format.move_file(
+                &script_directories.quote_path(&partial_output_path),
+                &script_directories.quote_path(output_path),
+            ));
+        }
+        script.push_str(line_ending);
+        script.push_str(&line);
+        script.push_str(line_ending);
+    }
+
+    std::fs::write(script_path, script).map_err(|e| {
+        format!(
+            "Failed to write the FFmpeg script {}: {}",
+            script_path.display(),
+            e
+        )
+    })?;
+
+    // The shell script can be run directly
+    #[cfg(unix)]
+    if script_format == ScriptFormat::Shell {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(script_path, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    Ok(())
+}
@@ -1,9 +1,15 @@
+use std::path::PathBuf;
+
 use ffmpeg_sidecar::command::FfmpegCommand;
 
 #[derive(Debug)]
 pub struct FfmpegBatchCommand {
     pub command: FfmpegCommand,
     pub batch_size: usize,
+    /// Size of the input files in bytes
+    pub input_size: u64,
+    pub input_paths: Vec<PathBuf>,
+    pub output_paths: Vec<PathBuf>,
 }
 /* -------------------------------------------------------------------------- */
 /*                                   FORMAT                                   */
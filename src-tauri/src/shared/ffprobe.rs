@@ -0,0 +1,170 @@
+use log::warn;
+use std::{collections::HashMap, error::Error, path::Path};
+
+use crate::shared::external_command::{external_command, ExternalBinary};
+use crate::shared::hdr::HdrMetadata;
+
+/// Run ffprobe on a media file and return its JSON output
+///
+/// The `-show_format` and `-show_streams` sections are always requested, `extra_args` are
+/// added before the input path.
+pub fn run_ffprobe(
+    path: &Path,
+    extra_args: &[&str],
+) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
+    let output = external_command(ExternalBinary::Ffprobe)?
+        .args([
+            "-v",
+            "quiet",
+            "-print_format",
+            "json",
+            "-show_format",
+            "-show_streams",
+        ])
+        .args(extra_args)
+        .arg(path.to_str().ok_or("Invalid media file path")?)
+        .output()?;
+
+    let probe_result: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    Ok(probe_result)
+}
+
+/// Get the first video stream of an ffprobe result
+pub fn find_video_stream(probe_result: &serde_json::Value) -> Option<&serde_json::Value> {
+    probe_result["streams"].as_array().and_then(|streams| {
+        streams
+            .iter()
+            .find(|stream| stream["codec_type"].as_str() == Some("video"))
+    })
+}
+
+/// Check if an FFmpeg pixel format contains an alpha channel
+pub fn pixel_format_has_alpha(pixel_format: &str) -> bool {
+    // Palette formats can contain a transparent color (e.g. GIF and palette PNG)
+    pixel_format == "pal8"
+        || pixel_format.starts_with("yuva")
+        || pixel_format.starts_with("ya")
+        || pixel_format.starts_with("gbrap")
+        || ["rgba", "bgra", "argb", "abgr"]
+            .iter()
+            .any(|alpha_format| pixel_format.contains(alpha_format))
+}
+
+lazy_static::lazy_static! {
+    /// Bits per color channel of the pixel formats of the FFmpeg build, from their descriptors
+    static ref PIXEL_FORMAT_BIT_DEPTHS: HashMap<String, u32> = list_pixel_format_bit_depths();
+}
+
+/// Get the bits per color channel of an FFmpeg pixel format, e.g. 10 for `yuv420p10le`, `p010le`
+/// and `gray10le` and 16 for `rgb48le`
+///
+/// The bit depth is read from the descriptor of the pixel format. Formats which ffprobe doesn't
+/// describe fall back to the bit depth after their last `p`, and formats without one, e.g.
+/// `yuv420p` and `nv12`, have 8 bits.
+pub fn pixel_format_bit_depth(pixel_format: &str) -> u32 {
+    if let Some(bit_depth) = PIXEL_FORMAT_BIT_DEPTHS.get(pixel_format) {
+        return *bit_depth;
+    }
+
+    pixel_format
+        .rsplit_once('p')
+        .and_then(|(_, bit_depth)| {
+            bit_depth
+                .trim_end_matches("le")
+                .trim_end_matches("be")
+                .parse()
+                .ok()
+        })
+        .filter(|bit_depth| *bit_depth > 8)
+        .unwrap_or(8)
+}
+
+/// Get the largest component bit depth of each pixel format from `ffprobe -show_pixel_formats`
+///
+/// Hardware formats, e.g. `vaapi`, don't describe their components and are left out.
+fn list_pixel_format_bit_depths() -> HashMap<String, u32> {
+    let probe_result: serde_json::Value =
+        match external_command(ExternalBinary::Ffprobe).and_then(|mut command| {
+            let output = command
+                .args([
+                    "-v",
+                    "quiet",
+                    "-print_format",
+                    "json",
+                    "-show_pixel_formats",
+                ])
+                .output()?;
+            Ok(serde_json::from_slice(&output.stdout)?)
+        }) {
+            Ok(probe_result) => probe_result,
+            Err(e) => {
+                warn!("Failed to query ffprobe for the pixel formats: {}", e);
+                return HashMap::new();
+            }
+        };
+
+    probe_result["pixel_formats"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|pixel_format| {
+            let name = pixel_format["name"].as_str()?;
+            let bit_depth = pixel_format["components"]
+                .as_array()?
+                .iter()
+                .filter_map(|component| component["bit_depth"].as_u64())
+                .max()?;
+            Some((name.to_string(), u32::try_from(bit_depth).ok()?))
+        })
+        .collect()
+}
+
+/// Get the transfer characteristics of a media file with HDR colors, e.g. `smpte2084` (PQ)
+///
+/// Returns `None` for SDR media and media which couldn't be probed.
+pub fn read_hdr_transfer(path: &Path) -> Option<String> {
+    let probe_result = run_ffprobe(path, &[]).ok()?;
+    stream_hdr_transfer(find_video_stream(&probe_result)?)
+}
+
+/// Get the transfer characteristics of an ffprobe stream with HDR colors, `None` for SDR streams
+pub fn stream_hdr_transfer(stream: &serde_json::Value) -> Option<String> {
+    const HDR_TRANSFERS: [&str; 2] = ["smpte2084", "arib-std-b67"];
+
+    let transfer = stream["color_transfer"].as_str()?;
+    HDR_TRANSFERS
+        .contains(&transfer)
+        .then(|| transfer.to_string())
+}
+
+/// Get the mastering display and content light metadata of an HDR video stream
+///
+/// Containers like MP4 store the metadata with the stream, otherwise it's read from the first
+/// frame, e.g. from the SEI messages of HEVC.
+pub fn read_hdr_metadata(path: &Path, stream: &serde_json::Value) -> Option<HdrMetadata> {
+    let stream_metadata = HdrMetadata::from_side_data(&stream["side_data_list"]);
+    if !stream_metadata.is_empty() {
+        return Some(stream_metadata);
+    }
+
+    let probe_result = run_ffprobe(
+        path,
+        &[
+            "-select_streams",
+            "v:0",
+            "-show_frames",
+            "-read_intervals",
+            "%+#1",
+        ],
+    )
+    .ok()?;
+    let frame_metadata = HdrMetadata::from_side_data(&probe_result["frames"][0]["side_data_list"]);
+    (!frame_metadata.is_empty()).then_some(frame_metadata)
+}
+
+/// Check if an image file contains an alpha channel, `None` if it couldn't be determined
+pub fn image_has_alpha_channel(path: &Path) -> Option<bool> {
+    let probe_result = run_ffprobe(path, &[]).ok()?;
+    let pixel_format = find_video_stream(&probe_result)?["pix_fmt"].as_str()?;
+    Some(pixel_format_has_alpha(pixel_format))
+}
@@ -1,16 +1,95 @@
+use crate::shared::external_command::{external_command, ExternalBinary};
 use crate::shared::process_manager::check_process_cancelled;
 use remove_dir_all::remove_dir_all;
-use std::fs::{read_dir, remove_file};
-use std::process::Command;
+use std::fs::{copy, read_dir, remove_file, rename, File, Metadata};
+use std::time::SystemTime;
 use std::{
     error::Error,
     fs::{create_dir_all, metadata},
     path::{Path, PathBuf},
 };
 
-pub fn read_file_size(file_path: &PathBuf) -> Result<u64, Box<dyn Error + Send + Sync>> {
-    let metadata = metadata(file_path)?;
-    Ok(metadata.len())
+/// Size, modification time and local availability of a file, read with a single metadata call
+#[derive(Debug, Clone)]
+pub struct FileInfo {
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    pub is_cloud_placeholder: bool,
+}
+
+impl FileInfo {
+    pub fn read(file_path: &Path) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let metadata = metadata(file_path)?;
+        Ok(Self {
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+            is_cloud_placeholder: is_cloud_placeholder(&metadata),
+        })
+    }
+
+    /// The size used to schedule work, placeholders don't count as their data isn't local yet
+    pub fn scheduling_size(&self) -> u64 {
+        if self.is_cloud_placeholder {
+            0
+        } else {
+            self.size
+        }
+    }
+}
+
+/// Check if a file is a cloud placeholder (e.g. OneDrive Files-On-Demand) without local data
+#[cfg(target_os = "windows")]
+fn is_cloud_placeholder(metadata: &Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+
+    const FILE_ATTRIBUTE_OFFLINE: u32 = 0x1000;
+    const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x40000;
+    const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x400000;
+
+    metadata.file_attributes()
+        & (FILE_ATTRIBUTE_OFFLINE
+            | FILE_ATTRIBUTE_RECALL_ON_OPEN
+            | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS)
+        != 0
+}
+
+/// Other platforms have no placeholder attribute. Their allocated blocks can't tell either, since
+/// FUSE and network filesystems report no blocks for complete files, so files there are only
+/// checked for a stable size and modification time.
+#[cfg(not(target_os = "windows"))]
+fn is_cloud_placeholder(_metadata: &Metadata) -> bool {
+    false
+}
+
+/// Check if another process holds a file open exclusively, e.g. while it's still being copied
+#[cfg(target_os = "windows")]
+pub fn is_file_locked(file_path: &Path) -> bool {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+
+    match std::fs::OpenOptions::new()
+        .read(true)
+        .share_mode(0)
+        .open(file_path)
+    {
+        Ok(_) => false,
+        Err(e) => e.raw_os_error() == Some(ERROR_SHARING_VIOLATION),
+    }
+}
+
+/// Check if another process holds a file open exclusively, other platforms only use advisory
+/// locks so writers can't be detected this way
+#[cfg(not(target_os = "windows"))]
+pub fn is_file_locked(_file_path: &Path) -> bool {
+    false
+}
+
+/// Download the data of a cloud placeholder file by reading it completely
+pub fn hydrate_file(file_path: &Path) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut file = File::open(file_path)?;
+    std::io::copy(&mut file, &mut std::io::sink())?;
+    Ok(())
 }
 
 pub fn read_file_type(file_path: &Path) -> String {
@@ -36,6 +115,39 @@ pub fn clear_and_create_folder(folder_path: &Path) -> Result<(), Box<dyn Error +
     Ok(())
 }
 
+/// Move all contents of the output directory to the trash, or create it if it doesn't exist
+///
+/// Outputs are often mixed with files of the user when directories are swapped by mistake, so they
+/// are moved to the trash or recycle bin where they can be restored instead of being deleted.
+/// Entries for which `keep` returns true stay in the folder, e.g. the logs of earlier runs.
+pub fn trash_and_create_folder(
+    folder_path: &Path,
+    keep: impl Fn(&Path) -> bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if !folder_path.exists() {
+        create_dir_all(folder_path)?;
+        return Ok(());
+    }
+
+    let entry_paths = read_dir(folder_path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .filter(|entry_path| !entry_path.as_ref().is_ok_and(|entry_path| keep(entry_path)))
+        .collect::<Result<Vec<PathBuf>, _>>()?;
+    if entry_paths.is_empty() {
+        return Ok(());
+    }
+
+    check_process_cancelled()?;
+    trash::delete_all(&entry_paths).map_err(|e| {
+        format!(
+            "Failed to move the contents of {} to the trash: {}",
+            folder_path.display(),
+            e
+        )
+    })?;
+    Ok(())
+}
+
 /// Recursively clear all contents of a directory without deleting the directory itself
 fn clear_directory_contents(dir_path: &Path) -> Result<(), Box<dyn Error + Send + Sync>> {
     for entry in read_dir(dir_path)? {
@@ -52,6 +164,196 @@ fn clear_directory_contents(dir_path: &Path) -> Result<(), Box<dyn Error + Send
     Ok(())
 }
 
+/// Folder in the output directory which collects the input files which can't be processed
+const QUARANTINE_FOLDER: &str = "_quarantine";
+
+/// Suffix of the folder beside the input directory which collects the input files which were
+/// processed, it's kept outside the output directory so clearing the output directory keeps it
+const PROCESSED_FOLDER_SUFFIX: &str = "_processed";
+
+/// Move an input file to the quarantine folder, keeping its path relative to the input directory
+///
+/// Returns the path of the file in the quarantine folder.
+pub fn move_to_quarantine(
+    file_path: &Path,
+    input_directory: &Path,
+    output_directory: &Path,
+) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+    move_to_output_folder(
+        file_path,
+        input_directory,
+        &output_directory.join(QUARANTINE_FOLDER),
+    )
+}
+
+/// Get the processed folder of an input directory, e.g. `photos_processed` beside `photos`
+pub fn processed_folder_path(
+    input_directory: &Path,
+) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+    let folder_name = input_directory
+        .file_name()
+        .ok_or("The processed folder can't be placed beside a root input directory")?;
+    Ok(input_directory.with_file_name(format!(
+        "{}{}",
+        folder_name.to_string_lossy(),
+        PROCESSED_FOLDER_SUFFIX
+    )))
+}
+
+/// Move a processed input file to the processed folder beside the input directory, keeping its
+/// path relative to the input directory
+///
+/// Returns the path of the file in the processed folder.
+pub fn move_to_processed_folder(
+    file_path: &Path,
+    input_directory: &Path,
+) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+    move_to_output_folder(
+        file_path,
+        input_directory,
+        &processed_folder_path(input_directory)?,
+    )
+}
+
+fn move_to_output_folder(
+    file_path: &Path,
+    input_directory: &Path,
+    folder_path: &Path,
+) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+    let relative_path = get_relative_path(input_directory, file_path).or_else(|_| {
+        file_path
+            .file_name()
+            .map(PathBuf::from)
+            .ok_or("Invalid file name")
+    })?;
+    let moved_path = folder_path.join(relative_path);
+    if let Some(parent) = moved_path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    // Renaming fails across drives, in which case the file is copied instead
+    if rename(file_path, &moved_path).is_err() {
+        copy(file_path, &moved_path)?;
+        remove_file(file_path)?;
+    }
+
+    Ok(moved_path)
+}
+
+/// Get the path FFmpeg writes an output to until it's finished, e.g. `photo.part.jpg` for
+/// `photo.jpg`
+///
+/// The extension stays last, since FFmpeg picks the format of the output by its extension.
+pub fn partial_output_path(output_path: &Path) -> PathBuf {
+    let file_name = match (output_path.file_stem(), output_path.extension()) {
+        (Some(file_stem), Some(extension)) => format!(
+            "{}.part.{}",
+            file_stem.to_string_lossy(),
+            extension.to_string_lossy()
+        ),
+        _ => format!(
+            "{}.part",
+            output_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+        ),
+    };
+    output_path.with_file_name(file_name)
+}
+
+/// Return the output files which do not exist or are empty after processing
+pub fn find_missing_output_files(output_paths: &[PathBuf]) -> Vec<PathBuf> {
+    output_paths
+        .iter()
+        .filter(|path| metadata(path).map(|m| m.len() == 0).unwrap_or(true))
+        .cloned()
+        .collect()
+}
+
+/// Convert a path to a `file://` URL, percent-encoding the characters which aren't allowed
+pub fn path_to_file_url(path: &Path) -> String {
+    let absolute_path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let path_string = absolute_path.to_string_lossy().replace('\\', "/");
+
+    let mut url = String::from("file://");
+    // Windows paths start with a drive letter instead of a slash
+    if !path_string.starts_with('/') {
+        url.push('/');
+    }
+    for byte in path_string.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                url.push(byte as char)
+            }
+            _ => url.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    url
+}
+
+/// Check that the input and output directories don't overlap in a way which loses files
+///
+/// An output directory inside the input directory is allowed, since its files are skipped when
+/// the child folders of the input directory are read.
+pub fn validate_directory_overlap(
+    input_directory: &Path,
+    output_directory: &Path,
+    clear_files_output_directory: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let input_directory = normalize_path(input_directory);
+    let output_directory = normalize_path(output_directory);
+
+    if input_directory == output_directory {
+        return Err(
+            "The output directory can't be the input directory, the outputs would \
+                    replace or be processed as inputs"
+                .into(),
+        );
+    }
+    if clear_files_output_directory && input_directory.starts_with(&output_directory) {
+        return Err(
+            "The input directory is inside the output directory, clearing the output \
+                    directory would remove the inputs"
+                .into(),
+        );
+    }
+    Ok(())
+}
+
+/// Get the path of a directory inside the parent directory as a child of `parent_directory`, so it
+/// compares equal to the paths found by walking the parent directory
+pub fn nested_directory_path(parent_directory: &Path, directory: &Path) -> Option<PathBuf> {
+    let relative_path = normalize_path(directory)
+        .strip_prefix(normalize_path(parent_directory))
+        .ok()?
+        .to_path_buf();
+    (!relative_path.as_os_str().is_empty()).then(|| parent_directory.join(relative_path))
+}
+
+/// Resolve a path which may not exist yet, by resolving its deepest existing ancestor
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut missing_components = Vec::new();
+    let mut existing_path = path;
+    loop {
+        if let Ok(canonical_path) = existing_path.canonicalize() {
+            return missing_components
+                .iter()
+                .rev()
+                .fold(canonical_path, |normalized_path, component| {
+                    normalized_path.join(component)
+                });
+        }
+        match (existing_path.parent(), existing_path.file_name()) {
+            (Some(parent), Some(file_name)) => {
+                missing_components.push(file_name);
+                existing_path = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
 /// Extract the relative path by comparing the file path to the base path
 pub fn get_relative_path(
     base_directory: &Path,
@@ -63,28 +365,8 @@ pub fn get_relative_path(
 
 /// Open a provided path in the native file explorer of an operating system
 pub fn show_in_file_explorer(path: &Path) -> Result<(), String> {
-    #[cfg(target_os = "windows")]
-    {
-        Command::new("explorer")
-            .arg(path)
-            .spawn()
-            .map_err(|e| format!("Failed to open Windows file explorer: {}", e))?;
-    }
-
-    #[cfg(target_os = "macos")]
-    {
-        Command::new("open")
-            .arg(&path)
-            .spawn()
-            .map_err(|e| format!("Failed to open MacOS file explorer: {}", e))?;
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        Command::new("xdg-open")
-            .arg(&path)
-            .spawn()
-            .map_err(|e| format!("Failed to open Linux file explorer: {}", e))?;
-    }
+    external_command(ExternalBinary::FileExplorer)
+        .and_then(|mut command| Ok(command.arg(path).spawn()?))
+        .map_err(|e| format!("Failed to open the file explorer: {}", e))?;
     Ok(())
 }
@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    path::{Component, Path, PathBuf},
+};
+use ts_rs::TS;
+
+use crate::shared::media_structs::Resolution;
+
+/// Template which reproduces the original file name with the target extension
+pub const DEFAULT_FILENAME_TEMPLATE: &str = "{stem}.{ext}";
+
+/// The placeholders which can be used in a filename template
+const PLACEHOLDERS: [&str; 5] = ["stem", "ext", "width", "height", "date"];
+
+/// A validated template for the output path of a media file, relative to its output directory
+///
+/// Supported placeholders are `{stem}`, `{ext}`, `{width}`, `{height}` and `{date}`, e.g.
+/// `{stem}_{width}x{height}_watermarked.{ext}` or `{date}/{stem}.{ext}`.
+#[derive(Debug, Clone)]
+pub struct FilenameTemplate {
+    template: String,
+    /// The date is fixed when the run starts, so a run past midnight writes to a single folder
+    date: String,
+}
+
+impl Default for FilenameTemplate {
+    fn default() -> Self {
+        Self::new(DEFAULT_FILENAME_TEMPLATE).expect("The default filename template is valid")
+    }
+}
+
+impl FilenameTemplate {
+    pub fn new(template: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let template = template.trim();
+        if template.is_empty() {
+            return Err("The filename template can't be empty".into());
+        }
+        if !template.contains("{stem}") {
+            return Err(
+                "The filename template requires {stem}, otherwise files overwrite each other"
+                    .into(),
+            );
+        }
+        if !template.contains("{ext}") {
+            return Err(
+                "The filename template requires {ext}, otherwise outputs lose their extension"
+                    .into(),
+            );
+        }
+
+        expand_placeholders(template, |placeholder| {
+            PLACEHOLDERS
+                .contains(&placeholder)
+                .then(String::new)
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown placeholder in the filename template: {{{}}}",
+                        placeholder
+                    )
+                })
+        })?;
+
+        // Templates can create subfolders, but may not write outside the output directory
+        let is_relative = Path::new(template)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+        if !is_relative {
+            return Err("The filename template must be a relative path without '..'".into());
+        }
+
+        Ok(Self {
+            template: template.to_string(),
+            date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+        })
+    }
+
+    /// Render the output path of a media file relative to its output directory
+    ///
+    /// Fails when the template uses the resolution and it isn't known yet, e.g. before probing.
+    pub fn render(
+        &self,
+        source_path: &Path,
+        resolution: Option<&Resolution>,
+        extension: &str,
+    ) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+        let file_stem = source_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or("Invalid file name")?;
+
+        self.render_with_stem(file_stem, resolution, extension)
+    }
+
+    /// Render the output path with a replacement for the stem of the source file
+    pub fn render_with_stem(
+        &self,
+        file_stem: &str,
+        resolution: Option<&Resolution>,
+        extension: &str,
+    ) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+        // Expanded in a single pass, so placeholders in file names are kept as they are
+        let relative_path = expand_placeholders(&self.template, |placeholder| {
+            let resolution = || resolution.ok_or("The resolution is unknown".to_string());
+            Ok(match placeholder {
+                "stem" => file_stem.to_string(),
+                "ext" => extension.to_string(),
+                "width" => resolution()?.width.to_string(),
+                "height" => resolution()?.height.to_string(),
+                _ => self.date.clone(),
+            })
+        })?;
+
+        Ok(PathBuf::from(relative_path))
+    }
+}
+
+/// Names outputs as a numbered sequence instead of after their source files, e.g. `wedding_0001`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceNaming {
+    pub prefix: String,
+    pub start_index: u32,
+    /// Minimum number of digits, shorter numbers are padded with zeros
+    pub zero_padding: u32,
+}
+
+impl SequenceNaming {
+    /// Get the file stem of the output at a position in the sequence
+    pub fn file_stem(&self, position: usize) -> String {
+        format!(
+            "{}{:0width$}",
+            self.prefix,
+            self.start_index as usize + position,
+            width = self.zero_padding as usize
+        )
+    }
+}
+
+/// Replace every `{placeholder}` in a template with the value returned for it
+fn expand_placeholders(
+    template: &str,
+    mut value_for: impl FnMut(&str) -> Result<String, String>,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let mut expanded = String::with_capacity(template.len());
+    let mut remaining = template;
+
+    while let Some(start) = remaining.find('{') {
+        let end = remaining[start..]
+            .find('}')
+            .ok_or("Unclosed placeholder in the filename template")?
+            + start;
+        expanded.push_str(&remaining[..start]);
+        expanded.push_str(&value_for(&remaining[start + 1..end])?);
+        remaining = &remaining[end + 1..];
+    }
+    expanded.push_str(remaining);
+
+    Ok(expanded)
+}
@@ -0,0 +1,289 @@
+use std::error::Error;
+
+use crate::shared::{
+    layer_structs::{BlendMode, LayerKind, LayersSettings},
+    logo_structs::Logo,
+    media_structs::{Border, ColorAdjustments, Position, ResizeMode, Resolution},
+};
+
+/// Convert a color setting (`#RRGGBB`, `0xRRGGBB` or an FFmpeg color name) to an FFmpeg color
+pub fn parse_ffmpeg_color(color: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let color = color.trim();
+    let color = match color.strip_prefix('#') {
+        Some(hex) => format!("0x{}", hex),
+        None => color.to_string(),
+    };
+
+    // Only allow plain color values so the color can't break out of the filter graph
+    if color.is_empty() || !color.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!("Invalid color: {}", color).into());
+    }
+
+    Ok(color)
+}
+
+/// The filters which resize a media input to its target resolution
+#[derive(Debug, Clone)]
+pub struct ResizeFilter {
+    pub resize_mode: ResizeMode,
+    pub scale_flags: Option<&'static str>,
+    pub pad_color: String,
+    /// Strength of the unsharp filter applied after scaling, no sharpening when unset
+    pub sharpen_amount: Option<f32>,
+    /// Color adjustment filters applied after scaling, before the media is padded
+    pub color_filter: Option<String>,
+    /// Border drawn around the media, with FFmpeg colors
+    pub border: Option<Border>,
+}
+
+impl ResizeFilter {
+    pub fn new(
+        resize_mode: ResizeMode,
+        scale_flags: Option<&'static str>,
+        pad_color: &str,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(Self {
+            resize_mode,
+            scale_flags,
+            pad_color: parse_ffmpeg_color(pad_color)?,
+            sharpen_amount: None,
+            color_filter: None,
+            border: None,
+        })
+    }
+
+    /// Sharpen the media after scaling, which counters the softness of downscaled media
+    pub fn with_sharpening(mut self, sharpen_amount: Option<f32>) -> Self {
+        self.sharpen_amount = sharpen_amount.filter(|amount| *amount > 0.0);
+        self
+    }
+
+    /// Adjust the colors of the media, the pad color and the overlay layers are left as is
+    pub fn with_color_adjustments(mut self, color_adjustments: &ColorAdjustments) -> Self {
+        self.color_filter = color_adjustments.to_filter();
+        self
+    }
+
+    /// Draw a border around the media, inside the target resolution
+    pub fn with_border(
+        mut self,
+        border: Option<&Border>,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        self.border = border
+            .map(|border| -> Result<Border, Box<dyn Error + Send + Sync>> {
+                Ok(Border {
+                    color: parse_ffmpeg_color(&border.color)?,
+                    padding_color: parse_ffmpeg_color(&border.padding_color)?,
+                    ..border.clone()
+                })
+            })
+            .transpose()?
+            .filter(|border| border.width > 0 || border.padding > 0);
+        Ok(self)
+    }
+
+    /// Check if the border is left out at a target resolution, since the media is too small to
+    /// fit it
+    pub fn is_border_skipped(&self, target_resolution: &Resolution) -> bool {
+        self.border.as_ref().is_some_and(|border| {
+            let frame_size = 2 * (border.width + border.padding);
+            target_resolution.width <= frame_size || target_resolution.height <= frame_size
+        })
+    }
+
+    /// Get the filter chain which resizes the media to exactly the target resolution
+    ///
+    /// A border shrinks the media so the framed media still has the target resolution, which keeps
+    /// the overlay layers in place. Media which is too small to fit the border gets no border.
+    fn to_filter(&self, target_resolution: &Resolution) -> String {
+        let Some(border) = self
+            .border
+            .as_ref()
+            .filter(|_| !self.is_border_skipped(target_resolution))
+        else {
+            return self.to_resize_filter(target_resolution);
+        };
+
+        let frame_size = 2 * (border.width + border.padding);
+        let inner_resolution = Resolution {
+            width: target_resolution.width - frame_size,
+            height: target_resolution.height - frame_size,
+        };
+
+        // The inner resolution has another aspect ratio than the target resolution of Fit mode,
+        // so the media is fitted inside it and padded instead of being distorted
+        let inner_filter = match self.resize_mode {
+            ResizeMode::Fit => ResizeFilter {
+                resize_mode: ResizeMode::PadToAspect,
+                pad_color: if border.padding > 0 {
+                    border.padding_color.clone()
+                } else {
+                    border.color.clone()
+                },
+                ..self.clone()
+            }
+            .to_resize_filter(&inner_resolution),
+            _ => self.to_resize_filter(&inner_resolution),
+        };
+        format!(
+            "{},pad=iw+{padding2}:ih+{padding2}:{padding}:{padding}:color={padding_color},\
+             pad={width}:{height}:{border}:{border}:color={color}",
+            inner_filter,
+            padding = border.padding,
+            padding2 = 2 * border.padding,
+            padding_color = border.padding_color,
+            width = target_resolution.width,
+            height = target_resolution.height,
+            border = border.width,
+            color = border.color,
+        )
+    }
+
+    fn to_resize_filter(&self, target_resolution: &Resolution) -> String {
+        let (width, height) = (target_resolution.width, target_resolution.height);
+
+        let mut scale_filter = format!("scale={}:{}", width, height);
+        match self.resize_mode {
+            ResizeMode::Fit | ResizeMode::Stretch => {}
+            ResizeMode::Fill => scale_filter.push_str(":force_original_aspect_ratio=increase"),
+            ResizeMode::PadToAspect => {
+                scale_filter.push_str(":force_original_aspect_ratio=decrease")
+            }
+        }
+        if let Some(flags) = self.scale_flags {
+            scale_filter.push_str(&format!(":flags={}", flags));
+        }
+        if let Some(amount) = self.sharpen_amount {
+            // FFmpeg limits the luma amount of the unsharp filter to 1.5
+            scale_filter.push_str(&format!(",unsharp=5:5:{:.2}", amount.min(1.5)));
+        }
+        if let Some(color_filter) = &self.color_filter {
+            scale_filter.push_str(&format!(",{}", color_filter));
+        }
+
+        match self.resize_mode {
+            ResizeMode::Fit | ResizeMode::Stretch => scale_filter,
+            ResizeMode::Fill => format!("{},crop={}:{}", scale_filter, width, height),
+            ResizeMode::PadToAspect => format!(
+                "{},pad={}:{}:(ow-iw)/2:(oh-ih)/2:color={}",
+                scale_filter, width, height, self.pad_color
+            ),
+        }
+    }
+}
+
+/// An overlay input of the FFmpeg command which is drawn on top of the media
+#[derive(Debug, Clone)]
+pub struct OverlayLayer<'a> {
+    pub input_index: usize,
+    pub position: &'a Position,
+    pub blend_mode: BlendMode,
+}
+
+/// Collect the overlay layers which are available for a command, in the configured order
+///
+/// Layers which are configured but have no input in the command (e.g. no logo) are skipped.
+pub fn collect_overlay_layers<'a>(
+    layers_settings: &LayersSettings,
+    logo: Option<(&'a Logo, usize)>,
+) -> Vec<OverlayLayer<'a>> {
+    layers_settings
+        .layers
+        .iter()
+        .filter_map(|layer| match layer.kind {
+            LayerKind::Logo => logo.map(|(logo, input_index)| OverlayLayer {
+                input_index,
+                position: &logo.position,
+                blend_mode: layer.blend_mode,
+            }),
+        })
+        .collect()
+}
+
+/// Build the filter graph for a single media input
+///
+/// The media of the `[input_label]` pad is prepared by the input filter and resized to the target
+/// resolution, after which the overlay layers are applied from bottom to top. The result is
+/// available under the `[output_label]` pad, which also keeps the intermediate pads unique.
+pub fn build_media_filter(
+    input_label: &str,
+    input_filter: Option<&str>,
+    target_resolution: &Resolution,
+    resize_filter: &ResizeFilter,
+    layers: &[OverlayLayer],
+    output_label: &str,
+) -> String {
+    let mut scale_filter = resize_filter.to_filter(target_resolution);
+    if let Some(input_filter) = input_filter {
+        scale_filter = format!("{},{}", input_filter, scale_filter);
+    }
+
+    if layers.is_empty() {
+        return format!("[{}]{}[{}]", input_label, scale_filter, output_label);
+    }
+
+    let mut filter_parts = Vec::with_capacity(layers.len() + 1);
+    let mut current_label = format!("base_{}", output_label);
+    filter_parts.push(format!(
+        "[{}]{}[{}]",
+        input_label, scale_filter, current_label
+    ));
+
+    for (layer_index, layer) in layers.iter().enumerate() {
+        let next_label = if layer_index == layers.len() - 1 {
+            output_label.to_string()
+        } else {
+            format!("layer_{}_{}", output_label, layer_index)
+        };
+        let unique_id = format!("{}_{}", output_label, layer_index);
+
+        filter_parts.push(build_overlay_filter(
+            &current_label,
+            layer,
+            target_resolution,
+            &next_label,
+            &unique_id,
+        ));
+        current_label = next_label;
+    }
+
+    filter_parts.join(";")
+}
+
+/// Build the filter which draws a single layer on top of the `[base_label]` pad
+fn build_overlay_filter(
+    base_label: &str,
+    layer: &OverlayLayer,
+    frame_resolution: &Resolution,
+    output_label: &str,
+    unique_id: &str,
+) -> String {
+    match layer.blend_mode.ffmpeg_blend_mode() {
+        // Without format=auto the overlay converts to yuv420p, which drops the alpha of RGBA media
+        // and halves the chroma resolution of the layer
+        None => format!(
+            "[{}][{}:v]overlay={}:{}:format=auto[{}]",
+            base_label, layer.input_index, layer.position.x, layer.position.y, output_label
+        ),
+        Some(blend_mode) => {
+            // The blend filter needs equally sized inputs, so the layer is first placed on a
+            // canvas with a color that doesn't change the media where the layer is absent
+            format!(
+                "color=c={neutral}:s={width}x{height},format=rgba[canvas{id}];\
+                 [canvas{id}][{input}:v]overlay={x}:{y}:format=auto,format=gbrp[blendlayer{id}];\
+                 [{base}]format=gbrp[blendbase{id}];\
+                 [blendbase{id}][blendlayer{id}]blend=all_mode={mode}:shortest=1[{output}]",
+                neutral = layer.blend_mode.neutral_color(),
+                width = frame_resolution.width,
+                height = frame_resolution.height,
+                id = unique_id,
+                input = layer.input_index,
+                x = layer.position.x,
+                y = layer.position.y,
+                base = base_label,
+                mode = blend_mode,
+                output = output_label,
+            )
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use ffmpeg_sidecar::command::FfmpegCommand;
+use serde::{Deserialize, Serialize};
+
+use crate::shared::ffmpeg_capabilities::FFMPEG_CAPABILITIES;
+
+/// Tone map HDR colors to SDR, after which the colors are BT.709
+const TONE_MAP_FILTER: &str = "zscale=t=linear:npl=100,format=gbrpf32le,zscale=p=bt709,\
+                               tonemap=tonemap=hable:desat=0,zscale=t=bt709:m=bt709:r=tv,\
+                               format=yuv420p";
+
+/// Get the filter which converts HDR colors to SDR, `None` when FFmpeg lacks the filters for it
+///
+/// Without tone mapping the colors are converted as is, which looks washed out.
+pub fn tone_map_filter() -> Option<&'static str> {
+    (FFMPEG_CAPABILITIES.has_filter("zscale") && FFMPEG_CAPABILITIES.has_filter("tonemap"))
+        .then_some(TONE_MAP_FILTER)
+}
+
+/// Tag an HDR output with the BT.2020 colors and the transfer of its source
+pub fn apply_hdr_color_args(hdr_transfer: &str, cmd: &mut FfmpegCommand) {
+    cmd.args([
+        "-color_primaries",
+        "bt2020",
+        "-color_trc",
+        hdr_transfer,
+        "-colorspace",
+        "bt2020nc",
+    ]);
+}
+
+/// Chromaticity coordinates of a color, as CIE 1931 x and y
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Chromaticity {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// The display an HDR video was mastered on, which players use to map its colors to theirs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MasteringDisplay {
+    pub red: Chromaticity,
+    pub green: Chromaticity,
+    pub blue: Chromaticity,
+    pub white_point: Chromaticity,
+    /// Luminance in cd/m²
+    pub min_luminance: f64,
+    pub max_luminance: f64,
+}
+
+/// The brightest pixel and the brightest frame average of an HDR video, in cd/m²
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentLight {
+    pub max_content: u32,
+    pub max_average: u32,
+}
+
+/// The static HDR metadata of a video, which is lost when it isn't written to the output
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HdrMetadata {
+    pub mastering_display: Option<MasteringDisplay>,
+    pub content_light: Option<ContentLight>,
+}
+
+impl HdrMetadata {
+    /// Read the metadata from the `side_data_list` of an ffprobe stream or frame
+    pub fn from_side_data(side_data_list: &serde_json::Value) -> Self {
+        let find_side_data = |side_data_type: &str| {
+            side_data_list.as_array().and_then(|side_data_list| {
+                side_data_list
+                    .iter()
+                    .find(|side_data| side_data["side_data_type"].as_str() == Some(side_data_type))
+            })
+        };
+
+        let mastering_display =
+            find_side_data("Mastering display metadata").and_then(|side_data| {
+                let chromaticity = |color: &str| {
+                    Some(Chromaticity {
+                        x: parse_rational(&side_data[format!("{}_x", color)])?,
+                        y: parse_rational(&side_data[format!("{}_y", color)])?,
+                    })
+                };
+                Some(MasteringDisplay {
+                    red: chromaticity("red")?,
+                    green: chromaticity("green")?,
+                    blue: chromaticity("blue")?,
+                    white_point: chromaticity("white_point")?,
+                    min_luminance: parse_rational(&side_data["min_luminance"])?,
+                    max_luminance: parse_rational(&side_data["max_luminance"])?,
+                })
+            });
+        let content_light = find_side_data("Content light level metadata").and_then(|side_data| {
+            Some(ContentLight {
+                max_content: side_data["max_content"].as_u64()?.try_into().ok()?,
+                max_average: side_data["max_average"].as_u64()?.try_into().ok()?,
+            })
+        });
+
+        Self {
+            mastering_display,
+            content_light,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mastering_display.is_none() && self.content_light.is_none()
+    }
+
+    /// Write the metadata with the options of the encoder
+    ///
+    /// Other HDR encoders, e.g. the hardware encoders, take the metadata from the decoded frames.
+    pub fn apply_encoder_args(&self, encoder: &str, cmd: &mut FfmpegCommand) {
+        match encoder {
+            "libx265" => {
+                // x265 takes chromaticities in units of 0.00002 and luminance in units of 0.0001 cd/m²
+                let mut params = Vec::new();
+                if let Some(display) = &self.mastering_display {
+                    let point = |color: &Chromaticity| {
+                        format!(
+                            "({},{})",
+                            (color.x * 50000.0).round(),
+                            (color.y * 50000.0).round()
+                        )
+                    };
+                    params.push(format!(
+                        "master-display=G{}B{}R{}WP{}L({},{})",
+                        point(&display.green),
+                        point(&display.blue),
+                        point(&display.red),
+                        point(&display.white_point),
+                        (display.max_luminance * 10000.0).round(),
+                        (display.min_luminance * 10000.0).round()
+                    ));
+                }
+                if let Some(content_light) = &self.content_light {
+                    params.push(format!(
+                        "max-cll={},{}",
+                        content_light.max_content, content_light.max_average
+                    ));
+                }
+                if !params.is_empty() {
+                    cmd.args(["-x265-params", &params.join(":")]);
+                }
+            }
+            "libsvtav1" => {
+                let mut params = Vec::new();
+                if let Some(display) = &self.mastering_display {
+                    let point = |color: &Chromaticity| format!("({},{})", color.x, color.y);
+                    params.push(format!(
+                        "mastering-display=G{}B{}R{}WP{}L({},{})",
+                        point(&display.green),
+                        point(&display.blue),
+                        point(&display.red),
+                        point(&display.white_point),
+                        display.max_luminance,
+                        display.min_luminance
+                    ));
+                }
+                if let Some(content_light) = &self.content_light {
+                    params.push(format!(
+                        "content-light={},{}",
+                        content_light.max_content, content_light.max_average
+                    ));
+                }
+                if !params.is_empty() {
+                    cmd.args(["-svtav1-params", &params.join(":")]);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Parse an ffprobe rational, e.g. `34000/50000`
+fn parse_rational(value: &serde_json::Value) -> Option<f64> {
+    let value = value.as_str()?;
+    match value.split_once('/') {
+        Some((numerator, denominator)) => {
+            let denominator: f64 = denominator.parse().ok()?;
+            (denominator != 0.0).then_some(numerator.parse::<f64>().ok()? / denominator)
+        }
+        None => value.parse().ok(),
+    }
+}
@@ -0,0 +1,176 @@
+use std::error::Error;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use crate::shared::external_command::install_ffmpeg;
+use crate::shared::run_report::RunReport;
+use crate::shared::settings_overrides::SettingsOverrides;
+use crate::{handle_images, handle_videos, AppConfig, GlobalProgressSink};
+
+const USAGE: &str = "\
+Usage: add-logo-processor-cli (--images | --videos) [options]
+
+Options:
+    --input <directory>     Directory of the files to process
+    --output <directory>    Directory the outputs are written to
+    --preset <name>         Process with the settings of a saved preset
+
+Without options the settings of the app are used. The overrides of the app, e.g.
+--image-format <format> or ADD_LOGO_IMAGE_FORMAT, apply as well.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HeadlessMediaType {
+    Images,
+    Videos,
+}
+
+#[derive(Debug)]
+struct HeadlessArgs {
+    media_type: HeadlessMediaType,
+    input_directory: Option<PathBuf>,
+    output_directory: Option<PathBuf>,
+    preset: Option<String>,
+    /// Arguments which are left for the settings overrides
+    override_args: Vec<String>,
+}
+
+impl HeadlessArgs {
+    fn parse(args: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        let mut media_type = None;
+        let mut input_directory = None;
+        let mut output_directory = None;
+        let mut preset = None;
+        let mut override_args = Vec::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value_of = |name: &str| {
+                args.next()
+                    .ok_or_else(|| format!("The argument {} requires a value", name))
+            };
+            match arg.as_str() {
+                "--images" | "--videos" if media_type.is_some() => {
+                    return Err("Only one of --images and --videos can be passed".into());
+                }
+                "--images" => media_type = Some(HeadlessMediaType::Images),
+                "--videos" => media_type = Some(HeadlessMediaType::Videos),
+                "--input" => input_directory = Some(PathBuf::from(value_of("--input")?)),
+                "--output" => output_directory = Some(PathBuf::from(value_of("--output")?)),
+                "--preset" => preset = Some(value_of("--preset")?),
+                _ => override_args.push(arg),
+            }
+        }
+
+        Ok(Self {
+            media_type: media_type.ok_or("One of --images and --videos is required")?,
+            input_directory,
+            output_directory,
+            preset,
+            override_args,
+        })
+    }
+}
+
+/// Process the files of a run from the command line without the window, showing the progress in
+/// the terminal
+///
+/// The exit code is 0 when every file was processed, 1 when files failed or the run failed, and
+/// 2 when the arguments are invalid.
+pub fn run_headless(args: Vec<String>) -> ExitCode {
+    let headless_args = match HeadlessArgs::parse(args) {
+        Ok(headless_args) => headless_args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run_headless_processing(headless_args) {
+        Ok(report) => {
+            println!(
+                "Wrote {} outputs in {:.1}s, {} files were skipped and {} failed",
+                report.output_paths.len(),
+                report.total_duration_seconds,
+                report.skipped_files.len(),
+                report.failed_files.len()
+            );
+            for failed_file in &report.failed_files {
+                eprintln!(
+                    "Failed: {}: {}",
+                    failed_file.path.display(),
+                    failed_file.error
+                );
+            }
+            if report.failed_files.is_empty() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Err(e) => {
+            eprintln!("Processing failed: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_headless_processing(
+    headless_args: HeadlessArgs,
+) -> Result<RunReport, Box<dyn Error + Send + Sync>> {
+    AppConfig::init_headless().map_err(|e| e.to_string())?;
+    install_ffmpeg()?;
+
+    let mut config = AppConfig::global();
+    if let Some(preset_name) = &headless_args.preset {
+        let preset_missing = || format!("The preset {} doesn't exist", preset_name);
+        match headless_args.media_type {
+            HeadlessMediaType::Images => {
+                config.image_settings = config
+                    .image_presets
+                    .iter()
+                    .find(|preset| preset.name == *preset_name)
+                    .map(|preset| preset.settings.clone())
+                    .ok_or_else(preset_missing)?;
+            }
+            HeadlessMediaType::Videos => {
+                config.video_settings = config
+                    .video_presets
+                    .iter()
+                    .find(|preset| preset.name == *preset_name)
+                    .map(|preset| preset.settings.clone())
+                    .ok_or_else(preset_missing)?;
+            }
+        }
+    }
+
+    SettingsOverrides::from_env_and_args(headless_args.override_args)
+        .map_err(|e| e.to_string())?
+        .apply(&mut config);
+
+    match headless_args.media_type {
+        HeadlessMediaType::Images => {
+            let mut image_settings = config.image_settings;
+            if let Some(input_directory) = headless_args.input_directory {
+                image_settings.input_directory = input_directory;
+            }
+            if let Some(output_directory) = headless_args.output_directory {
+                image_settings.output_directory = output_directory;
+            }
+            // Nobody is there to review flagged files
+            image_settings.review_flagged_files = false;
+            handle_images(&image_settings, &GlobalProgressSink)
+        }
+        HeadlessMediaType::Videos => {
+            let mut video_settings = config.video_settings;
+            if let Some(input_directory) = headless_args.input_directory {
+                video_settings.input_directory = input_directory;
+            }
+            if let Some(output_directory) = headless_args.output_directory {
+                video_settings.output_directory = output_directory;
+            }
+            // Nobody is there to review flagged files
+            video_settings.review_flagged_files = false;
+            handle_videos(&video_settings, &GlobalProgressSink)
+        }
+    }
+}
@@ -0,0 +1,322 @@
+use log::{info, warn};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::convert::Infallible;
+use std::error::Error;
+use std::fs;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use warp::http::StatusCode;
+use warp::reply::{Json, WithStatus};
+use warp::{Filter, Rejection};
+
+use crate::shared::commands::cancel_process;
+use crate::shared::job_queue::{Job, JobQueueManager};
+use crate::shared::progress_handler::ProgressManager;
+use crate::AppConfig;
+
+/// Port the API listens on when `ADD_LOGO_HTTP_API_PORT` isn't set
+const DEFAULT_PORT: u16 = 47823;
+
+const PORT_ENVIRONMENT_VARIABLE: &str = "ADD_LOGO_HTTP_API_PORT";
+
+/// File in the app data directory holding the token of the API
+const TOKEN_FILE_NAME: &str = "http_api_token";
+
+/// Largest body of a request in bytes, the settings of a job fit in a fraction of it
+const MAX_BODY_SIZE: u64 = 64 * 1024;
+
+/// Settings a request may override, the settings which clear the output directory or dispose of
+/// the inputs can only be changed in the app
+const ALLOWED_SETTINGS: [&str; 24] = [
+    "addLogo",
+    "brandKit",
+    "codec",
+    "excludePatterns",
+    "exportSizes",
+    "filenameTemplate",
+    "format",
+    "includePatterns",
+    "inputDirectory",
+    "keepChildFoldersStructureInOutputDirectory",
+    "logoCorner",
+    "logoPath",
+    "logoScale",
+    "logoXOffsetScale",
+    "logoYOffsetScale",
+    "outputDirectory",
+    "resizeAspectRatio",
+    "resizeMode",
+    "resizeOutputResolution",
+    "searchChildFolders",
+    "shouldConvertCodec",
+    "shouldConvertFormat",
+    "targetFps",
+    "writeRunLog",
+];
+
+/// A request which isn't allowed to use the API
+#[derive(Debug)]
+struct Unauthorized {
+    status: StatusCode,
+    message: &'static str,
+}
+
+impl warp::reject::Reject for Unauthorized {}
+
+/// Start the local REST API, so other tools, e.g. DAM systems and scripts, can submit jobs to the
+/// running app
+///
+/// The API only listens on the loopback address, and every request must send the token of the
+/// install, stored in the `http_api_token` file of the app data directory, as a bearer token:
+/// - `POST /process/images` and `POST /process/videos` queue a job, the body holds the settings
+///   which differ from the settings of the app, e.g. `{ "inputDirectory": "/photos" }`
+/// - `GET /progress` returns the progress of the running job, or `null` when nothing runs
+/// - `POST /cancel` cancels the running job
+pub fn start_http_api(app_handle: &AppHandle) -> Result<(), Box<dyn Error>> {
+    let port = match std::env::var(PORT_ENVIRONMENT_VARIABLE) {
+        Ok(port) => port
+            .parse::<u16>()
+            .map_err(|e| format!("{} is not a valid port: {}", PORT_ENVIRONMENT_VARIABLE, e))?,
+        Err(_) => DEFAULT_PORT,
+    };
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get data directory: {}", e))?;
+    let token = Arc::new(load_or_create_token(&data_dir.join(TOKEN_FILE_NAME))?);
+
+    let process_images = warp::path!("process" / "images")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_BODY_SIZE))
+        .and(warp::body::json())
+        .map(|overrides: Value| {
+            let image_settings = AppConfig::global().image_settings;
+            json_reply(
+                merge_settings(&image_settings, overrides)
+                    .map(|image_settings| queue_job(Job::Images(image_settings))),
+                StatusCode::ACCEPTED,
+            )
+        });
+
+    let process_videos = warp::path!("process" / "videos")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_BODY_SIZE))
+        .and(warp::body::json())
+        .map(|overrides: Value| {
+            let video_settings = AppConfig::global().video_settings;
+            json_reply(
+                merge_settings(&video_settings, overrides)
+                    .map(|video_settings| queue_job(Job::Videos(video_settings))),
+                StatusCode::ACCEPTED,
+            )
+        });
+
+    let progress = warp::path!("progress")
+        .and(warp::get())
+        .map(|| warp::reply::json(&ProgressManager::get_progress()));
+
+    let cancel_app_handle = app_handle.clone();
+    let cancel = warp::path!("cancel").and(warp::post()).and_then(move || {
+        let app_handle = cancel_app_handle.clone();
+        async move {
+            // The cancellation waits for the processes to stop, which mustn't block the server
+            let result = tauri::async_runtime::spawn_blocking(move || cancel_process(app_handle))
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|result| result);
+            Ok::<_, Infallible>(json_reply(
+                result.map(|()| json!({ "cancelled": true })),
+                StatusCode::OK,
+            ))
+        }
+    });
+
+    let routes = authorize(port, token)
+        .and(process_images.or(process_videos).or(progress).or(cancel))
+        .recover(reply_unauthorized);
+    let (address, server) = warp::serve(routes)
+        .try_bind_ephemeral(SocketAddr::from((Ipv4Addr::LOCALHOST, port)))
+        .map_err(|e| format!("Failed to start the HTTP API on port {}: {}", port, e))?;
+    tauri::async_runtime::spawn(server);
+    info!("HTTP API listening on http://{}", address);
+
+    Ok(())
+}
+
+/// Read the token of the API, creating a random token on the first start
+fn load_or_create_token(token_path: &Path) -> Result<String, Box<dyn Error>> {
+    if let Ok(token) = fs::read_to_string(token_path) {
+        let token = token.trim();
+        if !token.is_empty() {
+            return Ok(token.to_string());
+        }
+    }
+
+    let mut token_bytes = [0u8; 32];
+    getrandom::fill(&mut token_bytes)
+        .map_err(|e| format!("Failed to generate the HTTP API token: {}", e))?;
+    let token: String = token_bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    if let Some(parent) = token_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(token_path, &token)?;
+    // Only the user may read the token
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(token_path, fs::Permissions::from_mode(0o600))?;
+    }
+    info!("Created the HTTP API token in {}", token_path.display());
+
+    Ok(token)
+}
+
+/// Only let requests through which carry the token and were addressed to the API itself
+///
+/// Web pages can send requests to the loopback address as well, they're recognized by their
+/// origin, or by a host of another name when a DNS name is rebound to the loopback address.
+fn authorize(
+    port: u16,
+    token: Arc<String>,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>("host")
+        .and(warp::header::optional::<String>("origin"))
+        .and(warp::header::optional::<String>("authorization"))
+        .and_then(
+            move |host: Option<String>, origin: Option<String>, authorization: Option<String>| {
+                let result = check_request(
+                    port,
+                    &token,
+                    host.as_deref(),
+                    origin.as_deref(),
+                    authorization.as_deref(),
+                );
+                async move { result.map_err(warp::reject::custom) }
+            },
+        )
+        .untuple_one()
+}
+
+fn check_request(
+    port: u16,
+    token: &str,
+    host: Option<&str>,
+    origin: Option<&str>,
+    authorization: Option<&str>,
+) -> Result<(), Unauthorized> {
+    let allowed_hosts = [format!("127.0.0.1:{}", port), format!("localhost:{}", port)];
+    if !host.is_some_and(|host| allowed_hosts.iter().any(|allowed| allowed == host)) {
+        return Err(Unauthorized {
+            status: StatusCode::FORBIDDEN,
+            message: "The request isn't addressed to the loopback address",
+        });
+    }
+    if let Some(origin) = origin {
+        if !allowed_hosts
+            .iter()
+            .any(|allowed| origin == format!("http://{}", allowed))
+        {
+            return Err(Unauthorized {
+                status: StatusCode::FORBIDDEN,
+                message: "Requests from other origins aren't allowed",
+            });
+        }
+    }
+
+    let request_token =
+        authorization.and_then(|authorization| authorization.strip_prefix("Bearer "));
+    if !request_token.is_some_and(|request_token| tokens_match(request_token, token)) {
+        return Err(Unauthorized {
+            status: StatusCode::UNAUTHORIZED,
+            message: "The request doesn't carry the token of the API",
+        });
+    }
+
+    Ok(())
+}
+
+/// Compare the tokens in constant time, so the token can't be guessed from the response times
+fn tokens_match(request_token: &str, token: &str) -> bool {
+    request_token.len() == token.len()
+        && request_token
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+async fn reply_unauthorized(rejection: Rejection) -> Result<WithStatus<Json>, Rejection> {
+    let Some(unauthorized) = rejection.find::<Unauthorized>() else {
+        return Err(rejection);
+    };
+    warn!(
+        "Rejected a request of the HTTP API: {}",
+        unauthorized.message
+    );
+    Ok(warp::reply::with_status(
+        warp::reply::json(&json!({ "error": unauthorized.message })),
+        unauthorized.status,
+    ))
+}
+
+fn queue_job(job: Job) -> Value {
+    let job_id = JobQueueManager::enqueue(job);
+    info!("Queued job {} from the HTTP API", job_id);
+    json!({ "jobId": job_id })
+}
+
+/// Override the settings with the settings of a request, by their names in the config
+///
+/// Only the settings of `ALLOWED_SETTINGS` can be overridden.
+fn merge_settings<T: Serialize + DeserializeOwned>(
+    settings: &T,
+    overrides: Value,
+) -> Result<T, String> {
+    let Value::Object(overrides) = overrides else {
+        return Err("The body must hold an object of settings".to_string());
+    };
+
+    let mut settings_json = serde_json::to_value(settings).map_err(|e| e.to_string())?;
+    let Some(settings_obj) = settings_json.as_object_mut() else {
+        return Err("The settings can't be merged with the body".to_string());
+    };
+    for (setting_name, value) in overrides {
+        if !ALLOWED_SETTINGS.contains(&setting_name.as_str()) {
+            return Err(format!(
+                "The setting {} can't be changed through the HTTP API",
+                setting_name
+            ));
+        }
+        if !settings_obj.contains_key(&setting_name) {
+            return Err(format!(
+                "The body holds the unknown setting {}",
+                setting_name
+            ));
+        }
+        settings_obj.insert(setting_name, value);
+    }
+
+    serde_json::from_value(settings_json)
+        .map_err(|e| format!("The body holds an invalid setting: {}", e))
+}
+
+fn json_reply(result: Result<Value, String>, success_status: StatusCode) -> WithStatus<Json> {
+    match result {
+        Ok(value) => warp::reply::with_status(warp::reply::json(&value), success_status),
+        Err(e) => {
+            warn!("Rejected a request of the HTTP API: {}", e);
+            warp::reply::with_status(
+                warp::reply::json(&json!({ "error": e })),
+                StatusCode::BAD_REQUEST,
+            )
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use chrono::{Local, NaiveDate};
+use glob::{MatchOptions, Pattern};
+use std::error::Error;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::shared::file_utils::{get_relative_path, FileInfo};
+
+/// Wildcards don't match across folders, so `*` stays within one folder and `**` spans folders
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Filters on the path and modification date which decide which input files are processed
+#[derive(Debug, Default)]
+pub struct InputFilter {
+    include_patterns: Vec<Pattern>,
+    exclude_patterns: Vec<Pattern>,
+    modified_after: Option<SystemTime>,
+    modified_before: Option<SystemTime>,
+}
+
+impl InputFilter {
+    /// Create the filter from glob patterns, e.g. `**/RAW/**`, and dates in the `YYYY-MM-DD` format
+    ///
+    /// Both dates are inclusive.
+    pub fn new(
+        include_patterns: &[String],
+        exclude_patterns: &[String],
+        modified_after: Option<&str>,
+        modified_before: Option<&str>,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(Self {
+            include_patterns: parse_patterns(include_patterns)?,
+            exclude_patterns: parse_patterns(exclude_patterns)?,
+            modified_after: modified_after
+                .map(|date| start_of_day(date, 0))
+                .transpose()?,
+            modified_before: modified_before
+                .map(|date| start_of_day(date, 1))
+                .transpose()?,
+        })
+    }
+
+    /// Check if an input file passes the filters
+    pub fn matches(&self, path: &Path, input_directory: &Path) -> bool {
+        let matches_pattern =
+            |pattern: &Pattern| matches_input_pattern(pattern, path, input_directory);
+
+        if !self.include_patterns.is_empty() && !self.include_patterns.iter().any(matches_pattern) {
+            return false;
+        }
+        if self.exclude_patterns.iter().any(matches_pattern) {
+            return false;
+        }
+
+        if self.modified_after.is_none() && self.modified_before.is_none() {
+            return true;
+        }
+        // Files without a modification date can't be placed in the date range
+        let Some(modified) = FileInfo::read(path).ok().and_then(|info| info.modified) else {
+            return false;
+        };
+        self.modified_after.is_none_or(|after| modified >= after)
+            && self.modified_before.is_none_or(|before| modified < before)
+    }
+}
+
+/// Parse a glob pattern of input files
+pub fn parse_input_pattern(pattern: &str) -> Result<Pattern, Box<dyn Error + Send + Sync>> {
+    Pattern::new(pattern.trim())
+        .map_err(|e| format!("Invalid file pattern \"{}\": {}", pattern, e).into())
+}
+
+/// Check if an input file matches a glob pattern
+///
+/// Patterns without a `/` match the file name, other patterns match the path relative to the
+/// input directory.
+pub fn matches_input_pattern(pattern: &Pattern, path: &Path, input_directory: &Path) -> bool {
+    if pattern.as_str().contains('/') {
+        let relative_path = get_relative_path(input_directory, path)
+            .unwrap_or_else(|_| path.to_path_buf())
+            .to_string_lossy()
+            .replace('\\', "/");
+        pattern.matches_with(&relative_path, MATCH_OPTIONS)
+    } else {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        pattern.matches_with(&file_name, MATCH_OPTIONS)
+    }
+}
+
+fn parse_patterns(patterns: &[String]) -> Result<Vec<Pattern>, Box<dyn Error + Send + Sync>> {
+    patterns
+        .iter()
+        .filter(|pattern| !pattern.trim().is_empty())
+        .map(|pattern| parse_input_pattern(pattern))
+        .collect()
+}
+
+/// Get the local midnight which starts the day `day_offset` days after the date
+fn start_of_day(date: &str, day_offset: u64) -> Result<SystemTime, Box<dyn Error + Send + Sync>> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|e| format!("Invalid date \"{}\": {}", date, e))?;
+    let start_of_day = date
+        .checked_add_days(chrono::Days::new(day_offset))
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .and_then(|date_time| date_time.and_local_timezone(Local).earliest())
+        .ok_or_else(|| format!("Date is out of range: {}", date))?;
+    Ok(start_of_day.into())
+}
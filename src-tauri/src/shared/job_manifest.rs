@@ -0,0 +1,358 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::shared::file_utils::{find_missing_output_files, partial_output_path};
+use crate::shared::processing_marker::ProcessingMarker;
+use crate::shared::run_report::RunReportManager;
+
+const JOB_MANIFEST_FILE_NAME: &str = ".add-logo-job.json";
+
+/// The planned outputs of a run and which of them are finished, kept in the output directory
+///
+/// The manifest is updated whenever an input finishes and removed once the run completes, so a
+/// manifest left behind by a crashed or cancelled run tells the next run exactly which inputs
+/// are done, also when existing outputs are overwritten or named as a numbered sequence.
+#[derive(Debug)]
+struct JobManifest {
+    /// Marker of the settings of the run, a run with other settings starts over
+    processing_marker: String,
+    entries: BTreeMap<PathBuf, JobManifestEntry>,
+}
+
+#[derive(Debug)]
+struct JobManifestEntry {
+    output_paths: Vec<PathBuf>,
+    completed: bool,
+}
+
+/// A line of the manifest file, which holds a record per line so updates are appended instead of
+/// rewriting the whole manifest
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "camelCase")]
+enum JobManifestRecord {
+    /// The first record of the manifest
+    #[serde(rename_all = "camelCase")]
+    Started { processing_marker: String },
+    #[serde(rename_all = "camelCase")]
+    Planned {
+        input_path: PathBuf,
+        output_paths: Vec<PathBuf>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Finished { input_path: PathBuf },
+}
+
+impl JobManifest {
+    /// Get the records which describe the whole manifest
+    fn records(&self) -> Vec<JobManifestRecord> {
+        let mut records = vec![JobManifestRecord::Started {
+            processing_marker: self.processing_marker.clone(),
+        }];
+        for (input_path, entry) in &self.entries {
+            records.push(JobManifestRecord::Planned {
+                input_path: input_path.clone(),
+                output_paths: entry.output_paths.clone(),
+            });
+            if entry.completed {
+                records.push(JobManifestRecord::Finished {
+                    input_path: input_path.clone(),
+                });
+            }
+        }
+        records
+    }
+
+    fn apply(&mut self, record: JobManifestRecord) {
+        match record {
+            JobManifestRecord::Started { processing_marker } => {
+                self.processing_marker = processing_marker;
+            }
+            JobManifestRecord::Planned {
+                input_path,
+                output_paths,
+            } => {
+                self.entries.insert(
+                    input_path,
+                    JobManifestEntry {
+                        output_paths,
+                        completed: false,
+                    },
+                );
+            }
+            JobManifestRecord::Finished { input_path } => {
+                if let Some(entry) = self.entries.get_mut(&input_path) {
+                    entry.completed = true;
+                }
+            }
+        }
+    }
+}
+
+struct ActiveJobManifest {
+    path: PathBuf,
+    manifest: JobManifest,
+    /// The manifest file opened for appending, `None` when it couldn't be written
+    file: Option<File>,
+}
+
+impl ActiveJobManifest {
+    /// Write the whole manifest, through a partial file so a crash never leaves a truncated
+    /// manifest, and open it for appending the updates of the run
+    fn create(path: PathBuf, manifest: JobManifest) -> Self {
+        let partial_path = partial_output_path(&path);
+        let result = std::fs::write(&partial_path, serialize_records(&manifest.records()))
+            .and_then(|_| std::fs::rename(&partial_path, &path))
+            .and_then(|_| OpenOptions::new().append(true).open(&path));
+        let file = match result {
+            Ok(file) => Some(file),
+            Err(e) => {
+                warn!("Failed to save the job manifest {}: {}", path.display(), e);
+                None
+            }
+        };
+
+        Self {
+            path,
+            manifest,
+            file,
+        }
+    }
+
+    /// Apply records to the manifest and append them to its file
+    ///
+    /// The records are written at once, a record which a crash cuts off is ignored when the
+    /// manifest is read.
+    fn append(&mut self, records: Vec<JobManifestRecord>) {
+        let contents = serialize_records(&records);
+        for record in records {
+            self.manifest.apply(record);
+        }
+
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+        if let Err(e) = file.write_all(&contents) {
+            warn!(
+                "Failed to save the job manifest {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// Serialize records as JSON lines
+fn serialize_records(records: &[JobManifestRecord]) -> Vec<u8> {
+    let mut contents = Vec::new();
+    for record in records {
+        // Records only hold strings and paths, which always serialize
+        if serde_json::to_writer(&mut contents, record).is_ok() {
+            contents.push(b'\n');
+        }
+    }
+    contents
+}
+
+// Global job manifest manager
+lazy_static::lazy_static! {
+    static ref ACTIVE_JOB_MANIFEST: Mutex<Option<ActiveJobManifest>> = Mutex::new(None);
+}
+
+pub struct JobManifestManager;
+
+impl JobManifestManager {
+    /// Start the manifest of a run in the output directory, continuing the manifest of an
+    /// interrupted run with the same settings
+    ///
+    /// Runs which aren't resumable, e.g. of numbered outputs whose numbers depend on all inputs,
+    /// always start over.
+    pub fn start(output_directory: &Path, processing_marker: &ProcessingMarker, resumable: bool) {
+        let path = output_directory.join(JOB_MANIFEST_FILE_NAME);
+
+        let manifest = match read_interrupted_manifest(&path, processing_marker, resumable) {
+            Some(manifest) => {
+                info!(
+                    "Resuming the interrupted run of {}, {} inputs are already finished",
+                    output_directory.display(),
+                    manifest.entries.len()
+                );
+                manifest
+            }
+            None => JobManifest {
+                processing_marker: processing_marker.as_str().to_string(),
+                entries: BTreeMap::new(),
+            },
+        };
+
+        *ACTIVE_JOB_MANIFEST.lock().unwrap() = Some(ActiveJobManifest::create(path, manifest));
+    }
+
+    /// Get the inputs which the interrupted run continued by the active manifest finished
+    pub fn finished_inputs() -> HashSet<PathBuf> {
+        ACTIVE_JOB_MANIFEST
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|active| active.manifest.entries.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Read the inputs which an interrupted run in the output directory finished without starting
+    /// a manifest, e.g. for a plan
+    pub fn read_finished_inputs(
+        output_directory: &Path,
+        processing_marker: &ProcessingMarker,
+        resumable: bool,
+    ) -> HashSet<PathBuf> {
+        let path = output_directory.join(JOB_MANIFEST_FILE_NAME);
+        read_interrupted_manifest(&path, processing_marker, resumable)
+            .map(|manifest| manifest.entries.into_keys().collect())
+            .unwrap_or_default()
+    }
+
+    /// Record the inputs which are about to be processed with their output paths
+    pub fn record_planned_inputs<'a>(
+        planned_inputs: impl IntoIterator<Item = (&'a Path, &'a [PathBuf])>,
+    ) {
+        let mut active = ACTIVE_JOB_MANIFEST.lock().unwrap();
+        let Some(active) = active.as_mut() else {
+            return;
+        };
+
+        active.append(
+            planned_inputs
+                .into_iter()
+                .map(|(input_path, output_paths)| JobManifestRecord::Planned {
+                    input_path: input_path.to_path_buf(),
+                    output_paths: output_paths.to_vec(),
+                })
+                .collect(),
+        );
+    }
+
+    /// Record inputs whose outputs are all written
+    pub fn record_finished_inputs<'a>(input_paths: impl IntoIterator<Item = &'a Path>) {
+        let mut active = ACTIVE_JOB_MANIFEST.lock().unwrap();
+        let Some(active) = active.as_mut() else {
+            return;
+        };
+
+        active.append(
+            input_paths
+                .into_iter()
+                .map(|input_path| JobManifestRecord::Finished {
+                    input_path: input_path.to_path_buf(),
+                })
+                .collect(),
+        );
+    }
+
+    /// Remove the manifest of a run which completed, so the next run starts over
+    pub fn finish() {
+        let Some(active) = ACTIVE_JOB_MANIFEST.lock().unwrap().take() else {
+            return;
+        };
+
+        if let Err(e) = std::fs::remove_file(&active.path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!(
+                    "Failed to remove the job manifest {}: {}",
+                    active.path.display(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// Remove the paths of inputs which the interrupted run already finished
+pub fn skip_finished_inputs(
+    paths: Vec<PathBuf>,
+    finished_inputs: &HashSet<PathBuf>,
+) -> Vec<PathBuf> {
+    if finished_inputs.is_empty() {
+        return paths;
+    }
+
+    let total_count = paths.len();
+    let unfinished_paths: Vec<PathBuf> = paths
+        .into_iter()
+        .filter(|path| {
+            if finished_inputs.contains(path) {
+                RunReportManager::record_skipped_file(
+                    path.clone(),
+                    "Already processed by the interrupted run".to_string(),
+                );
+                return false;
+            }
+            true
+        })
+        .collect();
+
+    let skipped_count = total_count - unfinished_paths.len();
+    if skipped_count > 0 {
+        info!(
+            "Skipped {} files which the interrupted run already processed",
+            skipped_count
+        );
+    }
+
+    unfinished_paths
+}
+
+/// Read the manifest of an interrupted run with the same settings, keeping only the finished
+/// inputs whose outputs are all still there
+fn read_interrupted_manifest(
+    path: &Path,
+    processing_marker: &ProcessingMarker,
+    resumable: bool,
+) -> Option<JobManifest> {
+    if !resumable {
+        return None;
+    }
+
+    let mut manifest = read_manifest(path)
+        .filter(|manifest| manifest.processing_marker == processing_marker.as_str())?;
+    manifest.entries.retain(|_, entry| {
+        entry.completed && find_missing_output_files(&entry.output_paths).is_empty()
+    });
+    Some(manifest)
+}
+
+/// Read a manifest by applying its records in order
+///
+/// A manifest which doesn't start with its settings is ignored. Later records which can't be
+/// read, e.g. the last record of a crashed run, are skipped.
+fn read_manifest(path: &Path) -> Option<JobManifest> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    let mut lines = contents.lines();
+
+    let processing_marker = match lines.next().map(serde_json::from_str) {
+        Some(Ok(JobManifestRecord::Started { processing_marker })) => processing_marker,
+        _ => {
+            warn!("Ignoring the unreadable job manifest {}", path.display());
+            return None;
+        }
+    };
+
+    let mut manifest = JobManifest {
+        processing_marker,
+        entries: BTreeMap::new(),
+    };
+    for line in lines {
+        match serde_json::from_str(line) {
+            Ok(record) => manifest.apply(record),
+            Err(e) => warn!(
+                "Skipping an unreadable record of the job manifest {}: {}",
+                path.display(),
+                e
+            ),
+        }
+    }
+    Some(manifest)
+}
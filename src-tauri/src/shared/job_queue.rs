@@ -0,0 +1,329 @@
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use crate::{
+    image::image_handler::handle_images,
+    shared::{progress_handler::GlobalProgressSink, run_report::RunReport},
+    video::video_handler::handle_videos,
+    ImageSettings, VideoSettings,
+};
+
+/// A processing run with the settings it was queued with
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(tag = "kind", content = "settings", rename_all = "camelCase")]
+pub enum Job {
+    Images(ImageSettings),
+    Videos(VideoSettings),
+}
+
+impl Job {
+    /// Run the job once the run which is in progress is finished
+    pub fn run(&self) -> Result<RunReport, Box<dyn Error + Send + Sync>> {
+        let _run_guard = RUN_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        self.run_locked()
+    }
+
+    /// Run the job, failing when another run is in progress
+    pub fn try_run(&self) -> Result<RunReport, Box<dyn Error + Send + Sync>> {
+        let _run_guard = try_lock_run()?;
+        self.run_locked()
+    }
+
+    /// Run the job with the run lock which the caller claimed, e.g. with [`try_lock_run`]
+    pub fn run_claimed(
+        &self,
+        _run_guard: MutexGuard<'static, ()>,
+    ) -> Result<RunReport, Box<dyn Error + Send + Sync>> {
+        self.run_locked()
+    }
+
+    fn run_locked(&self) -> Result<RunReport, Box<dyn Error + Send + Sync>> {
+        match self {
+            Job::Images(image_settings) => handle_images(image_settings, &GlobalProgressSink),
+            Job::Videos(video_settings) => handle_videos(video_settings, &GlobalProgressSink),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedJob {
+    pub id: u64,
+    pub job: Job,
+}
+
+/// The running job and the jobs waiting after it, in the order they run
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct JobQueueState {
+    pub running_job: Option<QueuedJob>,
+    pub pending_jobs: Vec<QueuedJob>,
+}
+
+/// Jobs written to disk, so jobs which didn't start before the app quit can be restored
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredJobs {
+    pending_jobs: Vec<QueuedJob>,
+}
+
+#[derive(Debug, Default)]
+struct JobQueue {
+    pending_jobs: VecDeque<QueuedJob>,
+    /// Jobs from the previous session, kept apart until the user restores or discards them
+    restorable_jobs: Vec<QueuedJob>,
+    next_id: u64,
+    is_running: bool,
+    /// The job which the worker is running
+    running_job: Option<QueuedJob>,
+    /// Set when the app exits, the worker then doesn't start another job
+    is_shutting_down: bool,
+    store_path: Option<PathBuf>,
+}
+
+impl JobQueue {
+    /// Write the pending and restorable jobs to disk, so both survive a restart
+    fn save(&self) {
+        let Some(store_path) = &self.store_path else {
+            return;
+        };
+
+        let stored_jobs = StoredJobs {
+            pending_jobs: self
+                .restorable_jobs
+                .iter()
+                .chain(self.pending_jobs.iter())
+                .cloned()
+                .collect(),
+        };
+
+        let result = serde_json::to_string_pretty(&stored_jobs)
+            .map_err(|e| e.to_string())
+            .and_then(|jobs_str| fs::write(store_path, jobs_str).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            warn!("Failed to save pending jobs: {}", e);
+        }
+    }
+}
+
+// Global job queue manager
+lazy_static::lazy_static! {
+    static ref JOB_QUEUE: Arc<Mutex<JobQueue>> = Arc::new(Mutex::new(JobQueue::default()));
+    /// Held for the whole of a run, so the job queue, the watch folder and runs started directly
+    /// never process at the same time
+    static ref RUN_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Claim the run lock for work which must not overlap a run, failing when a run is in progress
+pub fn try_lock_run() -> Result<MutexGuard<'static, ()>, Box<dyn Error + Send + Sync>> {
+    match RUN_LOCK.try_lock() {
+        Ok(run_guard) => Ok(run_guard),
+        Err(TryLockError::Poisoned(e)) => Ok(e.into_inner()),
+        Err(TryLockError::WouldBlock) => Err("Another run is in progress".into()),
+    }
+}
+
+pub struct JobQueueManager;
+
+impl JobQueueManager {
+    /// Load the jobs which were still pending when the app last quit
+    pub fn init(app_handle: &AppHandle) -> Result<(), Box<dyn Error>> {
+        let data_dir = app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get data directory: {}", e))?;
+        fs::create_dir_all(&data_dir)?;
+        let store_path = data_dir.join("pending_jobs.json");
+
+        let stored_jobs = if store_path.exists() {
+            fs::read_to_string(&store_path)
+                .ok()
+                .and_then(|jobs_str| serde_json::from_str::<StoredJobs>(&jobs_str).ok())
+                .unwrap_or_else(|| {
+                    warn!("Failed to read pending jobs, they are discarded");
+                    StoredJobs::default()
+                })
+        } else {
+            StoredJobs::default()
+        };
+
+        if !stored_jobs.pending_jobs.is_empty() {
+            info!(
+                "Found {} pending jobs from the previous session",
+                stored_jobs.pending_jobs.len()
+            );
+        }
+
+        let mut queue = JOB_QUEUE.lock().unwrap();
+        queue.next_id = stored_jobs
+            .pending_jobs
+            .iter()
+            .map(|queued_job| queued_job.id + 1)
+            .max()
+            .unwrap_or(0);
+        queue.restorable_jobs = stored_jobs.pending_jobs;
+        queue.store_path = Some(store_path);
+
+        Ok(())
+    }
+
+    /// Add a job to the queue and start it when no other job is running
+    pub fn enqueue(job: Job) -> u64 {
+        let id = {
+            let mut queue = JOB_QUEUE.lock().unwrap();
+            let id = queue.next_id;
+            queue.next_id += 1;
+            queue.pending_jobs.push_back(QueuedJob { id, job });
+            queue.save();
+            id
+        };
+
+        Self::start_worker();
+        id
+    }
+
+    /// Get the jobs which are waiting to start
+    pub fn get_pending_jobs() -> Vec<QueuedJob> {
+        JOB_QUEUE
+            .lock()
+            .unwrap()
+            .pending_jobs
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Get the running job and the pending jobs
+    pub fn get_state() -> JobQueueState {
+        let queue = JOB_QUEUE.lock().unwrap();
+        JobQueueState {
+            running_job: queue.running_job.clone(),
+            pending_jobs: queue.pending_jobs.iter().cloned().collect(),
+        }
+    }
+
+    /// Check if a job is the one which is running
+    pub fn is_running_job(id: u64) -> bool {
+        JOB_QUEUE
+            .lock()
+            .unwrap()
+            .running_job
+            .as_ref()
+            .is_some_and(|running_job| running_job.id == id)
+    }
+
+    /// Move a job which hasn't started yet to another position among the pending jobs, positions
+    /// past the end move it to the end
+    pub fn move_pending_job(id: u64, position: usize) -> Result<(), String> {
+        let mut queue = JOB_QUEUE.lock().unwrap();
+        let index = queue
+            .pending_jobs
+            .iter()
+            .position(|queued_job| queued_job.id == id)
+            .ok_or_else(|| format!("Job {} is not pending", id))?;
+
+        let queued_job = queue.pending_jobs.remove(index).unwrap();
+        let position = position.min(queue.pending_jobs.len());
+        queue.pending_jobs.insert(position, queued_job);
+
+        queue.save();
+        Ok(())
+    }
+
+    /// Remove a job which hasn't started yet
+    pub fn remove_pending_job(id: u64) -> Result<(), String> {
+        let mut queue = JOB_QUEUE.lock().unwrap();
+        let job_count = queue.pending_jobs.len();
+        queue.pending_jobs.retain(|queued_job| queued_job.id != id);
+
+        if queue.pending_jobs.len() == job_count {
+            return Err(format!("Job {} is not pending", id));
+        }
+
+        queue.save();
+        Ok(())
+    }
+
+    /// Get the jobs from the previous session which can be restored
+    pub fn get_restorable_jobs() -> Vec<QueuedJob> {
+        JOB_QUEUE.lock().unwrap().restorable_jobs.clone()
+    }
+
+    /// Add the jobs from the previous session to the queue
+    pub fn restore_jobs() {
+        {
+            let mut queue = JOB_QUEUE.lock().unwrap();
+            let restorable_jobs = std::mem::take(&mut queue.restorable_jobs);
+            info!("Restoring {} pending jobs", restorable_jobs.len());
+            queue.pending_jobs.extend(restorable_jobs);
+            queue.save();
+        }
+
+        Self::start_worker();
+    }
+
+    /// Discard the jobs from the previous session
+    pub fn discard_restorable_jobs() {
+        let mut queue = JOB_QUEUE.lock().unwrap();
+        queue.restorable_jobs.clear();
+        queue.save();
+    }
+
+    /// Keep the job which was interrupted by the app exiting, so it can be restored next session
+    pub fn shutdown() {
+        let mut queue = JOB_QUEUE.lock().unwrap();
+        queue.is_shutting_down = true;
+        if let Some(running_job) = queue.running_job.take() {
+            info!(
+                "Keeping interrupted job {} for the next session",
+                running_job.id
+            );
+            queue.pending_jobs.push_front(running_job);
+        }
+        queue.save();
+    }
+
+    /// Run the pending jobs one after another on a background thread
+    fn start_worker() {
+        {
+            let mut queue = JOB_QUEUE.lock().unwrap();
+            if queue.is_running || queue.is_shutting_down {
+                return;
+            }
+            queue.is_running = true;
+        }
+
+        std::thread::spawn(|| loop {
+            let queued_job = {
+                let mut queue = JOB_QUEUE.lock().unwrap();
+                if queue.is_shutting_down {
+                    queue.is_running = false;
+                    return;
+                }
+                let Some(queued_job) = queue.pending_jobs.pop_front() else {
+                    queue.is_running = false;
+                    return;
+                };
+                queue.running_job = Some(queued_job.clone());
+                queue.save();
+                queued_job
+            };
+
+            info!("Starting queued job {}", queued_job.id);
+            if let Err(e) = queued_job.job.run() {
+                error!("Queued job {} failed: {}", queued_job.id, e);
+            }
+            JOB_QUEUE.lock().unwrap().running_job = None;
+        });
+    }
+}
@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use ts_rs::TS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum LayerKind {
+    Logo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum BlendMode {
+    Normal,
+    Multiply,
+    Screen,
+}
+
+impl BlendMode {
+    /// Get the FFmpeg `blend` filter mode, `None` for a regular alpha overlay
+    pub fn ffmpeg_blend_mode(&self) -> Option<&'static str> {
+        match self {
+            BlendMode::Normal => None,
+            BlendMode::Multiply => Some("multiply"),
+            BlendMode::Screen => Some("screen"),
+        }
+    }
+
+    /// Get the color which leaves the underlying pixels unchanged when blended with this mode
+    pub fn neutral_color(&self) -> &'static str {
+        match self {
+            BlendMode::Normal | BlendMode::Screen => "black",
+            BlendMode::Multiply => "white",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct LayerSettings {
+    pub kind: LayerKind,
+    pub blend_mode: BlendMode,
+}
+
+/// Overlay layers in the order they're drawn on top of the media, from bottom to top
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct LayersSettings {
+    pub layers: Vec<LayerSettings>,
+}
+
+impl LayersSettings {
+    /// Check that the logo is drawn exactly once, an empty list would silently drop the logo and
+    /// a repeated logo layer would draw it twice
+    pub fn validate(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let logo_layer_count = self
+            .layers
+            .iter()
+            .filter(|layer| layer.kind == LayerKind::Logo)
+            .count();
+        if logo_layer_count != 1 {
+            return Err(format!(
+                "The overlay layers must contain exactly one logo layer, found {}",
+                logo_layer_count
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+impl Default for LayersSettings {
+    fn default() -> Self {
+        Self {
+            layers: vec![LayerSettings {
+                kind: LayerKind::Logo,
+                blend_mode: BlendMode::Normal,
+            }],
+        }
+    }
+}
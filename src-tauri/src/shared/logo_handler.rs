@@ -1,20 +1,49 @@
+use log::warn;
 use rayon::prelude::*;
-use std::{error::Error, path::PathBuf};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    error::Error,
+    path::{Path, PathBuf},
+};
+use ts_rs::TS;
 
 use crate::{
+    image::image_formats::image_format,
     shared::{
-        file_utils::clear_and_create_folder, logo_processor::process_logo, logo_structs::Logo,
-        media_structs::Resolution, process_manager::check_process_cancelled,
+        ffprobe::image_has_alpha_channel,
+        file_utils::clear_and_create_folder,
+        layer_structs::{BlendMode, LayerKind, LayersSettings},
+        logo_overrides::LogoOverride,
+        logo_processor::process_logo,
+        logo_structs::{Logo, LogoColorKey},
+        media_structs::{Position, Resolution},
+        process_manager::check_process_cancelled,
+        progress_handler::ProgressSink,
+        run_report::RunReportManager,
     },
+    video::video_codecs::VIDEO_CODEC_REGISTRY,
     Corner, ImageSettings, VideoSettings,
 };
 
+/// Smallest logo side in pixels which is still recognizable on the media
+const MIN_LOGO_PIXEL_SIZE: u32 = 16;
+
+/// Largest factor a raster logo is enlarged by before it visibly blurs
+const MAX_LOGO_UPSCALE_FACTOR: f64 = 1.5;
+
 pub trait LogoSettings {
     fn logo_path(&self) -> &Option<PathBuf>;
     fn logo_scale(&self) -> u32;
     fn logo_corner(&self) -> Corner;
     fn logo_x_offset_scale(&self) -> i32;
     fn logo_y_offset_scale(&self) -> i32;
+    fn logo_key_color(&self) -> &Option<String>;
+    fn logo_key_tolerance(&self) -> u32;
+    fn layers(&self) -> &LayersSettings;
+    /// Pixel multiple the logo size and position snap to, so the logo aligns with chroma samples
+    fn logo_alignment(&self) -> u32;
+    fn reject_upscaled_logos(&self) -> bool;
 }
 
 impl LogoSettings for ImageSettings {
@@ -33,6 +62,21 @@ impl LogoSettings for ImageSettings {
     fn logo_y_offset_scale(&self) -> i32 {
         self.logo_y_offset_scale
     }
+    fn logo_key_color(&self) -> &Option<String> {
+        &self.logo_key_color
+    }
+    fn logo_key_tolerance(&self) -> u32 {
+        self.logo_key_tolerance
+    }
+    fn layers(&self) -> &LayersSettings {
+        &self.layers
+    }
+    fn logo_alignment(&self) -> u32 {
+        1
+    }
+    fn reject_upscaled_logos(&self) -> bool {
+        self.reject_upscaled_logos
+    }
 }
 
 impl LogoSettings for VideoSettings {
@@ -51,48 +95,248 @@ impl LogoSettings for VideoSettings {
     fn logo_y_offset_scale(&self) -> i32 {
         self.logo_y_offset_scale
     }
+    fn logo_key_color(&self) -> &Option<String> {
+        &self.logo_key_color
+    }
+    fn logo_key_tolerance(&self) -> u32 {
+        self.logo_key_tolerance
+    }
+    fn layers(&self) -> &LayersSettings {
+        &self.layers
+    }
+    fn logo_alignment(&self) -> u32 {
+        // Videos which keep their codec may subsample chroma, so they're aligned as well
+        if !self.should_convert_codec || VIDEO_CODEC_REGISTRY.subsamples_chroma(&self.codec) {
+            2
+        } else {
+            1
+        }
+    }
+    fn reject_upscaled_logos(&self) -> bool {
+        self.reject_upscaled_logos
+    }
 }
 
-pub fn handle_logos<T: LogoSettings>(
-    settings: &T,
-    unique_resolutions: Vec<Resolution>,
-) -> Result<Vec<Logo>, Box<dyn Error + Send + Sync>> {
-    // Create a fixed folder structure in the application root
+/// Size and position of the logo on media of a resolution
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct LogoPlacement {
+    pub media_resolution: Resolution,
+    pub resolution: Resolution,
+    pub position: Position,
+}
+
+/// Folder of the processed logos of the active run, in the application root
+pub fn temp_logo_directory() -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
     let app_root = std::env::current_exe()?
         .parent()
         .ok_or("Failed to get application directory")?
         .to_path_buf();
 
-    let output_directory = app_root.join("temp_processed_images");
+    Ok(app_root.join("temp_processed_images"))
+}
+
+/// Create and process a logo for every unique combination of media resolution and logo override
+///
+/// The processed logos are written to `logo_directory`, which is cleared first.
+pub fn handle_logos<T: LogoSettings>(
+    settings: &T,
+    logo_targets: Vec<(Resolution, Option<LogoOverride>)>,
+    logo_directory: &Path,
+    progress: &dyn ProgressSink,
+) -> Result<Vec<Logo>, Box<dyn Error + Send + Sync>> {
+    let output_directory = logo_directory.to_path_buf();
 
     let _ = clear_and_create_folder(&output_directory);
 
+    let color_key = settings
+        .logo_key_color()
+        .as_deref()
+        .map(|color| LogoColorKey::new(color, settings.logo_key_tolerance()))
+        .transpose()?;
+
     let mut logos = Vec::new();
-    for resolution in &unique_resolutions {
+    for (resolution, logo_override) in logo_targets {
         check_process_cancelled()?;
 
+        let logo_path = logo_override
+            .as_ref()
+            .and_then(|logo_override| logo_override.logo_path.clone())
+            .or_else(|| settings.logo_path().clone())
+            .ok_or("Logo path is required")?;
+        let logo_scale = logo_override
+            .as_ref()
+            .and_then(|logo_override| logo_override.logo_scale)
+            .unwrap_or(settings.logo_scale());
+        let logo_corner = logo_override
+            .as_ref()
+            .and_then(|logo_override| logo_override.logo_corner)
+            .unwrap_or(settings.logo_corner());
+
+        if !logo_path.is_file() {
+            return Err(format!("Logo file not found: {}", logo_path.display()).into());
+        }
+
         let logo = Logo::new(
-            settings
-                .logo_path()
-                .clone()
-                .ok_or("Logo path is required")?,
-            settings.logo_scale(),
-            settings.logo_corner(),
+            logo_path,
+            logo_scale,
+            logo_corner,
             settings.logo_x_offset_scale(),
             settings.logo_y_offset_scale(),
-            resolution.clone(),
+            resolution,
+            logo_override,
         )
         .map_err(|e| -> Box<dyn Error + Send + Sync> {
             format!("Failed to create logo: {}", e).into()
-        })?;
+        })?
+        .aligned(settings.logo_alignment());
         logos.push(logo);
     }
+
+    validate_logos(settings, &logos, progress)?;
+
     let output_dir_clone = output_directory.clone();
-    logos
-        .par_iter_mut()
-        .try_for_each(|logo| -> Result<(), Box<dyn Error + Send + Sync>> {
-            process_logo(logo, &output_dir_clone)
+    logos.par_iter_mut().enumerate().try_for_each(
+        |(logo_index, logo)| -> Result<(), Box<dyn Error + Send + Sync>> {
+            process_logo(logo, logo_index, &output_dir_clone, color_key.as_ref())
                 .map_err(|e| format!("Failed to process logo: {}", e).into())
-        })?;
+        },
+    )?;
     Ok(logos)
 }
+
+/// Calculate where the logo is placed on media of each resolution, without processing anything
+///
+/// Logo overrides of folders aren't applied, since the media has no folder.
+pub fn calculate_logo_placements<T: LogoSettings>(
+    settings: &T,
+    media_resolutions: Vec<Resolution>,
+) -> Result<Vec<LogoPlacement>, Box<dyn Error + Send + Sync>> {
+    let logo_path = settings
+        .logo_path()
+        .clone()
+        .ok_or("Logo path is required")?;
+    if !logo_path.is_file() {
+        return Err(format!("Logo file not found: {}", logo_path.display()).into());
+    }
+
+    media_resolutions
+        .into_iter()
+        .map(|media_resolution| {
+            let logo = Logo::new(
+                logo_path.clone(),
+                settings.logo_scale(),
+                settings.logo_corner(),
+                settings.logo_x_offset_scale(),
+                settings.logo_y_offset_scale(),
+                media_resolution,
+                None,
+            )?
+            .aligned(settings.logo_alignment());
+            Ok(LogoPlacement {
+                media_resolution: logo.compatible_image_resolution,
+                resolution: logo.resolution,
+                position: logo.position,
+            })
+        })
+        .collect()
+}
+
+/// Find the processed logo for a media resolution and the logo override of its folder
+pub fn find_logo<'a>(
+    logos: &'a [Logo],
+    resolution: &Resolution,
+    logo_override: &Option<LogoOverride>,
+) -> Option<&'a Logo> {
+    logos.iter().find(|logo| {
+        logo.compatible_image_resolution == *resolution && logo.logo_override == *logo_override
+    })
+}
+
+/// Validate the scaled logos against the media they're placed on before processing
+///
+/// Logos which don't fit the media are an error, other issues are reported as warnings.
+fn validate_logos<T: LogoSettings>(
+    settings: &T,
+    logos: &[Logo],
+    progress: &dyn ProgressSink,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    for logo in logos {
+        let media_resolution = &logo.compatible_image_resolution;
+
+        if logo.resolution.width > media_resolution.width
+            || logo.resolution.height > media_resolution.height
+        {
+            return Err(format!(
+                "The scaled logo ({}) is larger than the media it's placed on ({}), lower the logo scale",
+                logo.resolution, media_resolution
+            )
+            .into());
+        }
+
+        // SVG logos are rendered at the scaled size, so only raster logos blur
+        let upscale_factor = logo.resolution.width as f64 / logo.source_resolution.width as f64;
+        if !is_svg_logo(logo) && upscale_factor > MAX_LOGO_UPSCALE_FACTOR {
+            let message = format!(
+                "The logo {} is {} but drawn at {} on media of {} and will look blurry, use a larger logo",
+                logo.file_path.display(),
+                logo.source_resolution,
+                logo.resolution,
+                media_resolution
+            );
+            if settings.reject_upscaled_logos() {
+                return Err(message.into());
+            }
+            report_logo_warning(progress, message);
+        }
+
+        if logo.resolution.width.min(logo.resolution.height) < MIN_LOGO_PIXEL_SIZE {
+            report_logo_warning(
+                progress,
+                format!(
+                    "The scaled logo is only {} on media of {} and may be unrecognizable",
+                    logo.resolution, media_resolution
+                ),
+            );
+        }
+    }
+
+    let uses_alpha_overlay = settings
+        .layers()
+        .layers
+        .iter()
+        .any(|layer| layer.kind == LayerKind::Logo && layer.blend_mode == BlendMode::Normal);
+
+    if uses_alpha_overlay && settings.logo_key_color().is_none() {
+        let unique_logo_paths: HashSet<&PathBuf> =
+            logos.iter().map(|logo| &logo.file_path).collect();
+        for logo_path in unique_logo_paths {
+            if image_has_alpha_channel(logo_path) == Some(false) {
+                report_logo_warning(progress, format!(
+                    "The logo {} has no alpha channel and will be drawn as an opaque rectangle, use a transparent logo or set a logo key color",
+                    logo_path.display()
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn is_svg_logo(logo: &Logo) -> bool {
+    logo.file_path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            image_format::SVG
+                .extensions
+                .contains(&extension.to_lowercase().as_str())
+        })
+}
+
+fn report_logo_warning(progress: &dyn ProgressSink, message: String) {
+    warn!("{}", message);
+    progress.set_status(format!("Warning: {}", message));
+    RunReportManager::add_warning(message);
+}
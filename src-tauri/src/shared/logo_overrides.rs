@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::Corner;
+
+/// Sidecar file in an input subdirectory which overrides the logo settings for all media under it
+pub const LOGO_OVERRIDE_FILE_NAME: &str = ".logo.json";
+
+/// Logo settings from a `.logo.json` sidecar, unset fields fall back to the parent folders and
+/// finally to the regular settings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogoOverride {
+    pub logo_path: Option<PathBuf>,
+    pub logo_corner: Option<Corner>,
+    pub logo_scale: Option<u32>,
+}
+
+impl LogoOverride {
+    /// Read the sidecar file of a directory, `None` if the directory doesn't have one
+    fn read_from_directory(directory: &Path) -> Result<Option<Self>, Box<dyn Error + Send + Sync>> {
+        let sidecar_path = directory.join(LOGO_OVERRIDE_FILE_NAME);
+        if !sidecar_path.is_file() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&sidecar_path)?;
+        let mut logo_override: Self =
+            serde_json::from_str(&content).map_err(|e| -> Box<dyn Error + Send + Sync> {
+                format!(
+                    "Invalid logo override file {}: {}",
+                    sidecar_path.display(),
+                    e
+                )
+                .into()
+            })?;
+
+        // Relative logo paths are relative to the folder of the sidecar file
+        if let Some(logo_path) = &logo_override.logo_path {
+            if logo_path.is_relative() {
+                logo_override.logo_path = Some(directory.join(logo_path));
+            }
+        }
+
+        Ok(Some(logo_override))
+    }
+
+    /// Fill the unset fields with the values of a parent folder's override
+    fn inherit_from(self, parent: &Self) -> Self {
+        Self {
+            logo_path: self.logo_path.or_else(|| parent.logo_path.clone()),
+            logo_corner: self.logo_corner.or(parent.logo_corner),
+            logo_scale: self.logo_scale.or(parent.logo_scale),
+        }
+    }
+}
+
+/// The merged logo overrides of every media folder in a run
+#[derive(Debug, Default)]
+pub struct LogoOverrides {
+    by_directory: HashMap<PathBuf, LogoOverride>,
+}
+
+impl LogoOverrides {
+    /// Load the sidecar files which apply to the folders of the provided media files
+    ///
+    /// Only subdirectories of the input directory are searched, the input directory itself is
+    /// configured by the regular settings.
+    pub fn load<'a>(
+        input_directory: &Path,
+        media_paths: impl Iterator<Item = &'a Path>,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut sidecar_cache: HashMap<PathBuf, Option<LogoOverride>> = HashMap::new();
+        let mut by_directory = HashMap::new();
+
+        for media_path in media_paths {
+            let Some(media_directory) = media_path.parent() else {
+                continue;
+            };
+            if by_directory.contains_key(media_directory) {
+                continue;
+            }
+
+            // Walk up to the input directory, the nearest sidecar takes precedence
+            let mut merged_override: Option<LogoOverride> = None;
+            for directory in media_directory.ancestors() {
+                if directory == input_directory || !directory.starts_with(input_directory) {
+                    break;
+                }
+
+                if !sidecar_cache.contains_key(directory) {
+                    let sidecar = LogoOverride::read_from_directory(directory)?;
+                    sidecar_cache.insert(directory.to_path_buf(), sidecar);
+                }
+
+                if let Some(parent_override) = &sidecar_cache[directory] {
+                    merged_override = Some(match merged_override {
+                        Some(child_override) => child_override.inherit_from(parent_override),
+                        None => parent_override.clone(),
+                    });
+                }
+            }
+
+            if let Some(merged_override) = merged_override {
+                by_directory.insert(media_directory.to_path_buf(), merged_override);
+            }
+        }
+
+        if !by_directory.is_empty() {
+            log::info!(
+                "Found logo overrides for {} media folders",
+                by_directory.len()
+            );
+        }
+
+        Ok(Self { by_directory })
+    }
+
+    /// Get the logo override which applies to a media file
+    pub fn for_media(&self, media_path: &Path) -> Option<LogoOverride> {
+        media_path
+            .parent()
+            .and_then(|directory| self.by_directory.get(directory))
+            .cloned()
+    }
+}
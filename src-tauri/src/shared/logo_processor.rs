@@ -1,25 +1,44 @@
 use std::{error::Error, path::Path};
 
-use ffmpeg_sidecar::command::FfmpegCommand;
+use resvg::{tiny_skia, usvg};
 
 use crate::{
-    image::image_struct::{apply_image_format_specific_args, read_image_resolution},
+    image::{
+        image_formats::{image_format, FormatQualitySettings},
+        image_struct::{apply_image_format_specific_args, read_image_resolution},
+    },
     shared::{
-        ffmpeg_logger::ffmpeg_logger, logo_structs::Logo, media_structs::Resolution,
-        progress_handler::ProgressMode,
+        external_command::ffmpeg_command,
+        ffmpeg_logger::ffmpeg_logger,
+        logo_structs::{Logo, LogoColorKey},
+        media_structs::Resolution,
     },
 };
 
 pub fn process_logo(
     logo: &mut Logo,
+    logo_index: usize,
     output_directory: &Path,
+    color_key: Option<&LogoColorKey>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let file_stem = logo.file_path.file_stem().unwrap().to_str().unwrap();
-    let file_extension = logo.file_path.extension().unwrap().to_str().unwrap();
+    let source_extension = logo.file_path.extension().unwrap().to_str().unwrap();
+    let is_svg = image_format::SVG
+        .extensions
+        .contains(&source_extension.to_lowercase().as_str());
+
+    // Keyed logos need an alpha channel and SVG logos are rasterized, so both are written as PNG
+    let file_extension = if color_key.is_some() || is_svg {
+        image_format::PNG.extensions[0]
+    } else {
+        source_extension
+    };
+    // The index keeps logos with the same resolution but different overrides apart
     let new_filename = format!(
-        "{}_{}_{}x{}.{}",
+        "{}_{}_{}_{}x{}.{}",
         file_stem,
         "logo",
+        logo_index,
         logo.compatible_image_resolution.width,
         logo.compatible_image_resolution.height,
         file_extension
@@ -27,8 +46,25 @@ pub fn process_logo(
 
     let output_path = output_directory.join(new_filename);
 
-    // Resize logo using FFmpeg
-    resize_logo(&logo.file_path, &output_path, &logo.resolution)?;
+    if is_svg {
+        // Render the SVG at the exact logo size instead of scaling a rasterized default size
+        match color_key {
+            Some(color_key) => {
+                let raster_path = output_path.with_extension("raster.png");
+                rasterize_svg(&logo.file_path, &raster_path, &logo.resolution)?;
+                resize_logo(
+                    &raster_path,
+                    &output_path,
+                    &logo.resolution,
+                    Some(color_key),
+                )?;
+            }
+            None => rasterize_svg(&logo.file_path, &output_path, &logo.resolution)?,
+        }
+    } else {
+        // Resize logo using FFmpeg
+        resize_logo(&logo.file_path, &output_path, &logo.resolution, color_key)?;
+    }
 
     // Overwrite the original logo path with the resized one to be used by images and videos in their processes
     logo.file_path = output_path;
@@ -36,44 +72,91 @@ pub fn process_logo(
     Ok(())
 }
 
+/// Render an SVG file to a PNG file at the provided resolution
+fn rasterize_svg(
+    input_path: &Path,
+    output_path: &Path,
+    resolution: &Resolution,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let svg_data = std::fs::read(input_path)?;
+
+    let mut options = usvg::Options {
+        resources_dir: input_path.parent().map(Path::to_path_buf),
+        ..usvg::Options::default()
+    };
+    options.fontdb_mut().load_system_fonts();
+
+    let tree = usvg::Tree::from_data(&svg_data, &options)
+        .map_err(|e| format!("Failed to parse SVG logo: {}", e))?;
+
+    let mut pixmap = tiny_skia::Pixmap::new(resolution.width, resolution.height)
+        .ok_or("Invalid logo resolution")?;
+
+    // The logo resolution keeps the aspect ratio of the SVG, scaling the axes separately only
+    // absorbs the rounding to whole pixels
+    let svg_size = tree.size();
+    let transform = tiny_skia::Transform::from_scale(
+        resolution.width as f32 / svg_size.width(),
+        resolution.height as f32 / svg_size.height(),
+    );
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+    pixmap.save_png(output_path)?;
+
+    Ok(())
+}
+
 fn resize_logo(
     input_path: &std::path::PathBuf,
     output_path: &std::path::PathBuf,
     resolution: &Resolution,
+    color_key: Option<&LogoColorKey>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     // Check if resizing is needed
     let current_resolution = read_image_resolution(input_path)?;
     if current_resolution.width == resolution.width
         && current_resolution.height == resolution.height
+        && color_key.is_none()
     {
         std::fs::copy(input_path, output_path)?;
         return Ok(());
     }
 
     // Get file extension to determine format-specific settings
-    let file_extension = input_path
+    let file_extension = output_path
         .extension()
         .and_then(|ext| ext.to_str())
         .unwrap_or("png");
 
-    let mut ffmpeg_command = FfmpegCommand::new();
+    // Key out the background before scaling, so the scaler blends the edges with transparency
+    let mut filters = Vec::new();
+    if let Some(color_key) = color_key {
+        filters.push(color_key.to_filter());
+    }
+    filters.push(format!("scale={}:{}", resolution.width, resolution.height));
+
+    let mut ffmpeg_command = ffmpeg_command()?;
     ffmpeg_command.args([
         "-y", // Overwrite output file
         "-i",
         input_path.to_str().ok_or("Invalid input path")?,
         "-vf",
-        &format!("scale={}:{}", resolution.width, resolution.height),
+        &filters.join(","),
         "-q:v",
         "2", // High quality
     ]);
 
-    apply_image_format_specific_args(file_extension, &mut ffmpeg_command);
+    apply_image_format_specific_args(
+        file_extension,
+        &FormatQualitySettings::default(),
+        &mut ffmpeg_command,
+    );
 
     let ffmpeg_child = ffmpeg_command
         .output(output_path.to_str().ok_or("Invalid output path")?)
         .spawn()?;
 
-    ffmpeg_logger(ffmpeg_child, ProgressMode::Batch)?;
+    ffmpeg_logger(ffmpeg_child, None, None)?;
 
     Ok(())
 }
@@ -1,22 +1,26 @@
-use std::{
-    error::Error,
-    path::{Path, PathBuf},
-};
+use std::{error::Error, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
     image::image_struct::read_image_resolution,
-    shared::media_structs::{calculate_resize_dimensions, Position, Resolution},
+    shared::{
+        filter_builder::parse_ffmpeg_color,
+        logo_overrides::LogoOverride,
+        media_structs::{calculate_resize_dimensions, Position, Resolution},
+    },
     Corner,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Logo {
     pub file_path: PathBuf,
+    /// Resolution of the logo file, before it's scaled to the media
+    pub source_resolution: Resolution,
     pub resolution: Resolution,
     pub compatible_image_resolution: Resolution,
     pub position: Position,
+    pub logo_override: Option<LogoOverride>,
 }
 
 impl Logo {
@@ -27,9 +31,14 @@ impl Logo {
         x_offset_scale: i32,
         y_offset_scale: i32,
         compatible_image_resolution: Resolution,
-    ) -> Result<Self, Box<dyn Error>> {
-        let resolution =
-            transform_resolution_with_scale(&file_path, &compatible_image_resolution, scale);
+        logo_override: Option<LogoOverride>,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let source_resolution = read_image_resolution(&file_path)?;
+        let resolution = transform_resolution_with_scale(
+            &source_resolution,
+            &compatible_image_resolution,
+            scale,
+        );
 
         let position = calculate_position(
             corner,
@@ -41,11 +50,61 @@ impl Logo {
 
         Ok(Self {
             file_path,
+            source_resolution,
             resolution,
             compatible_image_resolution,
             position,
+            logo_override,
         })
     }
+
+    /// Round the logo size and position down to a multiple of the alignment, e.g. even values
+    /// for `yuv420p` video
+    ///
+    /// Odd values put the logo edges between chroma samples, which blurs and shifts its colors.
+    /// Rounding down keeps the logo inside the media.
+    pub fn aligned(mut self, alignment: u32) -> Self {
+        let align_size = |size: u32| (size - size % alignment).max(alignment);
+        let align_offset = |offset: u32| offset - offset % alignment;
+
+        self.resolution = Resolution {
+            width: align_size(self.resolution.width),
+            height: align_size(self.resolution.height),
+        };
+        self.position = Position {
+            x: align_offset(self.position.x),
+            y: align_offset(self.position.y),
+        };
+        self
+    }
+}
+
+/// Solid background color of a logo which is keyed out to make it transparent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogoColorKey {
+    pub color: String,
+    pub similarity: f32,
+}
+
+impl LogoColorKey {
+    /// Create a color key from a color setting (`#RRGGBB`, `0xRRGGBB` or an FFmpeg color name)
+    /// and a tolerance percentage
+    pub fn new(color: &str, tolerance: u32) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let color = parse_ffmpeg_color(color).map_err(|e| -> Box<dyn Error + Send + Sync> {
+            format!("Invalid logo key color: {}", e).into()
+        })?;
+
+        Ok(Self {
+            color,
+            // FFmpeg's colorkey similarity ranges from 0.01 to 1.0
+            similarity: (tolerance as f32 / 100.0).clamp(0.01, 1.0),
+        })
+    }
+
+    /// Get the FFmpeg filter which makes the keyed color transparent
+    pub fn to_filter(&self) -> String {
+        format!("colorkey={}:{:.2}:0.05", self.color, self.similarity)
+    }
 }
 
 fn calculate_position(
@@ -102,16 +161,14 @@ fn calculate_position(
 }
 
 fn transform_resolution_with_scale(
-    logo_path: &Path,
+    logo_resolution: &Resolution,
     resolution: &Resolution,
     scale: u32,
 ) -> Resolution {
-    let logo_resolution = read_image_resolution(logo_path).unwrap();
-
     let min_pixel_count = if resolution.width < resolution.height {
         resolution.width * scale / 100
     } else {
         resolution.height * scale / 100
     };
-    calculate_resize_dimensions(&logo_resolution, &min_pixel_count)
+    calculate_resize_dimensions(logo_resolution, &min_pixel_count)
 }
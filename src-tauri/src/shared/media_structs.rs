@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use std::{
+    collections::HashSet,
+    error::Error,
+    fmt,
+    path::{Component, Path},
+};
 use ts_rs::TS;
 
 pub fn calculate_resize_dimensions(original: &Resolution, min_pixel_count: &u32) -> Resolution {
@@ -27,10 +32,12 @@ pub trait Media {
     type FileType;
 
     // Required methods that must be implemented by concrete types
+    fn get_file_path(&self) -> &Path;
     fn get_resolution(&self) -> &Resolution;
     fn get_file_size(&self) -> u64;
     fn get_file_type(&self) -> &Self::FileType;
     fn set_resolution(&mut self, resolution: Resolution);
+    fn set_adjustment(&mut self, adjustment: MediaAdjustment);
 
     /// Calculate the aspect ration of the media file by using the original resolution
     fn calculate_aspect_ratio(&self) -> f64 {
@@ -38,12 +45,262 @@ pub trait Media {
         resolution.width as f64 / resolution.height as f64
     }
 
-    fn resize_dimensions(&mut self, min_pixel_count: &u32) {
-        let new_resolution = calculate_resize_dimensions(self.get_resolution(), min_pixel_count);
+    /// Crop and rotate the media file before it's resized
+    fn apply_adjustment(&mut self, adjustment: MediaAdjustment) {
+        let adjusted_resolution = adjustment.adjusted_resolution(self.get_resolution());
+        self.set_resolution(adjusted_resolution);
+        self.set_adjustment(adjustment);
+    }
+
+    /// Set the output resolution of the media file for the provided resize target
+    fn resize_to_target(&mut self, resize_target: &ResizeTarget) {
+        let new_resolution = resize_target.calculate_resolution(self.get_resolution());
         self.set_resolution(new_resolution);
     }
 }
 
+/// How media is resized to its target resolution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum ResizeMode {
+    /// Scale the shortest side to the minimum pixel count, keeping the aspect ratio
+    Fit,
+    /// Scale to cover the target aspect ratio and crop the overflow from the center
+    Fill,
+    /// Scale to the target aspect ratio, distorting the media
+    Stretch,
+    /// Scale to fit inside the target aspect ratio and pad the remaining area with a color
+    PadToAspect,
+}
+
+/// The scaling algorithm FFmpeg uses to resize media
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum ScaleAlgorithm {
+    /// Fastest scaling, downscaled media looks soft
+    FastBilinear,
+    Bilinear,
+    /// Sharper than bilinear at a small cost in speed
+    Bicubic,
+    /// Sharpest scaling, best suited for downscaling photos
+    Lanczos,
+}
+
+impl ScaleAlgorithm {
+    /// Get the value of the `flags` option of the FFmpeg scale filter
+    pub fn ffmpeg_flags(&self) -> &'static str {
+        match self {
+            ScaleAlgorithm::FastBilinear => "fast_bilinear",
+            ScaleAlgorithm::Bilinear => "bilinear",
+            ScaleAlgorithm::Bicubic => "bicubic",
+            ScaleAlgorithm::Lanczos => "lanczos",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum ColorTone {
+    Original,
+    Grayscale,
+    Sepia,
+}
+
+/// Global color adjustments, so all exports of a brand get a consistent look
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(default, rename_all = "camelCase")]
+pub struct ColorAdjustments {
+    /// Brightness from -1 to 1, 0 keeps the brightness
+    pub brightness: f32,
+    /// Contrast from 0 to 2, 1 keeps the contrast
+    pub contrast: f32,
+    /// Saturation from 0 (gray) to 3, 1 keeps the saturation
+    pub saturation: f32,
+    pub tone: ColorTone,
+}
+
+impl Default for ColorAdjustments {
+    fn default() -> Self {
+        Self {
+            brightness: 0.0,
+            contrast: 1.0,
+            saturation: 1.0,
+            tone: ColorTone::Original,
+        }
+    }
+}
+
+impl ColorAdjustments {
+    /// Get the filter chain which applies the adjustments, `None` when nothing is adjusted
+    pub fn to_filter(&self) -> Option<String> {
+        let mut filters = Vec::new();
+
+        let brightness = self.brightness.clamp(-1.0, 1.0);
+        let contrast = self.contrast.clamp(0.0, 2.0);
+        let saturation = self.saturation.clamp(0.0, 3.0);
+        if brightness != 0.0 || contrast != 1.0 || saturation != 1.0 {
+            filters.push(format!(
+                "eq=brightness={:.2}:contrast={:.2}:saturation={:.2}",
+                brightness, contrast, saturation
+            ));
+        }
+
+        match self.tone {
+            ColorTone::Original => {}
+            ColorTone::Grayscale => filters.push("hue=s=0".to_string()),
+            // The common sepia matrix, which tints the luminance of the media brown
+            ColorTone::Sepia => filters.push(
+                "colorchannelmixer=.393:.769:.189:0:.349:.686:.168:0:.272:.534:.131".to_string(),
+            ),
+        }
+
+        if filters.is_empty() {
+            None
+        } else {
+            Some(filters.join(","))
+        }
+    }
+}
+
+/// A frame drawn around each output, in pixels of the output
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct Border {
+    pub color: String,
+    /// Space between the media and the border
+    pub padding: u32,
+    pub padding_color: String,
+    pub width: u32,
+}
+
+/// An additional output size of each image or video, written to a subfolder of the output directory
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSize {
+    /// Name of the subfolder the outputs of this size are written to, e.g. `web`
+    pub name: String,
+    pub min_pixel_count: u32,
+}
+
+/// Check that every export size writes to its own subfolder inside the output directory
+pub fn validate_export_sizes(
+    export_sizes: &[ExportSize],
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut names = HashSet::new();
+    for export_size in export_sizes {
+        let name = export_size.name.as_str();
+        let is_folder_name = !name.is_empty()
+            && matches!(
+                Path::new(name).components().collect::<Vec<_>>().as_slice(),
+                [Component::Normal(_)]
+            );
+        if !is_folder_name {
+            return Err(format!(
+                "Invalid export size name, it must be a folder name: {}",
+                name
+            )
+            .into());
+        }
+        if !names.insert(name) {
+            return Err(format!("Export size names must be unique: {}", name).into());
+        }
+    }
+    Ok(())
+}
+
+/// The resize settings which determine the output resolution of media
+#[derive(Debug, Clone)]
+pub struct ResizeTarget {
+    pub resize_mode: ResizeMode,
+    pub min_pixel_count: u32,
+    pub aspect_ratio: Resolution,
+    /// Exact output resolution, which takes precedence over the minimum pixel count and aspect ratio
+    pub output_resolution: Option<Resolution>,
+    /// Keep media at its native scale when the target would blow it up
+    pub never_upscale: bool,
+}
+
+impl ResizeTarget {
+    /// Calculate the output resolution of a media file
+    pub fn calculate_resolution(&self, original: &Resolution) -> Resolution {
+        let target = self.calculate_unclamped_resolution(original);
+
+        if self.never_upscale {
+            self.limit_to_native_scale(original, target)
+        } else {
+            target
+        }
+    }
+
+    fn calculate_unclamped_resolution(&self, original: &Resolution) -> Resolution {
+        // Zero sized targets would divide by zero, so they're ignored
+        let output_resolution = self
+            .output_resolution
+            .as_ref()
+            .filter(|resolution| resolution.width > 0 && resolution.height > 0);
+        let aspect_ratio = Some(&self.aspect_ratio)
+            .filter(|aspect_ratio| aspect_ratio.width > 0 && aspect_ratio.height > 0);
+
+        match (self.resize_mode, output_resolution, aspect_ratio) {
+            (ResizeMode::Fit, Some(output_resolution), _) => {
+                fit_inside_resolution(original, output_resolution)
+            }
+            (ResizeMode::Fit, None, _) | (_, None, None) => {
+                calculate_resize_dimensions(original, &self.min_pixel_count)
+            }
+            (_, Some(output_resolution), _) => output_resolution.clone(),
+            (_, None, Some(aspect_ratio)) => {
+                calculate_resize_dimensions(aspect_ratio, &self.min_pixel_count)
+            }
+        }
+    }
+
+    /// Shrink the target so the media itself isn't scaled up, the crop or padding of the resize
+    /// mode is kept
+    fn limit_to_native_scale(&self, original: &Resolution, target: Resolution) -> Resolution {
+        if original.width == 0 || original.height == 0 {
+            return target;
+        }
+
+        let width_scale = target.width as f64 / original.width as f64;
+        let height_scale = target.height as f64 / original.height as f64;
+        let media_scale = match self.resize_mode {
+            ResizeMode::Fit | ResizeMode::PadToAspect => width_scale.min(height_scale),
+            ResizeMode::Fill | ResizeMode::Stretch => width_scale.max(height_scale),
+        };
+
+        if media_scale <= 1.0 {
+            return target;
+        }
+
+        Resolution {
+            width: ((target.width as f64 / media_scale).round() as u32).max(1),
+            height: ((target.height as f64 / media_scale).round() as u32).max(1),
+        }
+    }
+}
+
+/// Scale a resolution to the largest size which fits inside the bounds, keeping the aspect ratio
+fn fit_inside_resolution(original: &Resolution, bounds: &Resolution) -> Resolution {
+    if original.width == 0 || original.height == 0 {
+        return bounds.clone();
+    }
+
+    let scale = (bounds.width as f64 / original.width as f64)
+        .min(bounds.height as f64 / original.height as f64);
+
+    Resolution {
+        width: ((original.width as f64 * scale).round() as u32).clamp(1, bounds.width),
+        height: ((original.height as f64 * scale).round() as u32).clamp(1, bounds.height),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash, TS)]
 #[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
 pub struct Resolution {
@@ -57,7 +314,7 @@ impl fmt::Display for Resolution {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub enum Corner {
@@ -73,3 +330,94 @@ pub struct Position {
     pub x: u32,
     pub y: u32,
 }
+
+/// Area of the original media in pixels
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct CropArea {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Manual crop and clockwise rotation which are applied to media before it's resized
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct MediaAdjustment {
+    pub crop: Option<CropArea>,
+    pub rotation_degrees: u32,
+}
+
+impl MediaAdjustment {
+    /// Check if the adjustment can be applied to media with the provided resolution
+    pub fn validate(&self, resolution: &Resolution) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if !self.rotation_degrees.is_multiple_of(90) {
+            return Err(format!(
+                "Rotation must be a multiple of 90 degrees, got {}",
+                self.rotation_degrees
+            )
+            .into());
+        }
+
+        if let Some(crop) = &self.crop {
+            if crop.width == 0
+                || crop.height == 0
+                || crop.x + crop.width > resolution.width
+                || crop.y + crop.height > resolution.height
+            {
+                return Err(format!(
+                    "Crop area {}x{} at {},{} doesn't fit the media resolution {}",
+                    crop.width, crop.height, crop.x, crop.y, resolution
+                )
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Calculate the resolution of media after the adjustment
+    pub fn adjusted_resolution(&self, resolution: &Resolution) -> Resolution {
+        let (width, height) = match &self.crop {
+            Some(crop) => (crop.width, crop.height),
+            None => (resolution.width, resolution.height),
+        };
+
+        if self.rotation_degrees % 180 == 90 {
+            Resolution {
+                width: height,
+                height: width,
+            }
+        } else {
+            Resolution { width, height }
+        }
+    }
+
+    /// Get the FFmpeg filters which crop and rotate the media, `None` if nothing changes
+    pub fn to_filter(&self) -> Option<String> {
+        let mut filters = Vec::new();
+
+        if let Some(crop) = &self.crop {
+            filters.push(format!(
+                "crop={}:{}:{}:{}",
+                crop.width, crop.height, crop.x, crop.y
+            ));
+        }
+
+        match self.rotation_degrees % 360 {
+            90 => filters.push("transpose=clock".to_string()),
+            180 => filters.push("hflip,vflip".to_string()),
+            270 => filters.push("transpose=cclock".to_string()),
+            _ => {}
+        }
+
+        if filters.is_empty() {
+            None
+        } else {
+            Some(filters.join(","))
+        }
+    }
+}
@@ -1,9 +1,20 @@
-use log::{error, info};
+use log::{error, info, warn};
 use rayon::prelude::*;
 use std::error::Error;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 
+use crate::shared::file_utils::{
+    hydrate_file, is_file_locked, move_to_quarantine, nested_directory_path, FileInfo,
+};
+use crate::shared::filename_template::FilenameTemplate;
+use crate::shared::input_filter::InputFilter;
+use crate::shared::media_structs::{Media, Resolution};
+use crate::shared::natural_sort::natural_path_cmp;
+use crate::shared::output_collision::CollisionStrategy;
+use crate::shared::output_mapping::OutputMapping;
 use crate::shared::process_manager::check_process_cancelled;
+use crate::shared::run_report::RunReportManager;
 
 /// Trait for media-specific validation logic
 pub trait MediaValidator {
@@ -13,11 +24,32 @@ pub trait MediaValidator {
     /// Get the target file extension based on settings
     fn get_target_extension(&self) -> &str;
 
-    /// Check if existing files should be overwritten
-    fn should_overwrite_existing(&self) -> bool;
+    /// Get the template which names the output files
+    fn get_filename_template(&self) -> &FilenameTemplate;
+
+    /// Get the filter on the path and modification date of input files
+    fn get_input_filter(&self) -> &InputFilter;
+
+    /// Get the rules which place the outputs of specific inputs in their own location
+    fn get_output_mapping(&self) -> &OutputMapping;
+
+    /// Check if outputs are named as a numbered sequence instead of after their source files
+    fn should_number_outputs(&self) -> bool;
+
+    /// Get the subfolders each input writes an output to, empty for a single output
+    fn get_output_subfolders(&self) -> Vec<&str>;
+
+    /// Get what happens to inputs whose output file already exists
+    fn get_collision_strategy(&self) -> CollisionStrategy;
 
     /// Check if child folder structure should be preserved
     fn should_keep_folder_structure(&self) -> bool;
+
+    /// Get the smallest width and height of inputs to process, 0 disables the check
+    fn get_min_input_resolution(&self) -> Resolution;
+
+    /// Get the largest file size in bytes of inputs to process, 0 disables the check
+    fn get_max_file_size(&self) -> u64;
 }
 
 /// Determine if a media file should be written to the output directory
@@ -27,32 +59,53 @@ pub fn should_write_to_output<V: MediaValidator>(
     output_directory: &Path,
     validator: &V,
 ) -> bool {
-    if validator.should_overwrite_existing() {
+    // Only skipped inputs are decided here, the other strategies resolve the output path
+    if validator.get_collision_strategy() != CollisionStrategy::Skip {
+        return true;
+    }
+
+    // Numbered outputs are only named once all files are known
+    if validator.should_number_outputs() {
         return true;
     }
 
-    let file_stem = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("unknown");
+    let mapped_output = validator.get_output_mapping().find(path, input_directory);
 
-    let target_extension = validator.get_target_extension();
-    let target_filename = format!("{}.{}", file_stem, target_extension);
+    // Output names which depend on the resolution are only known after probing the file
+    let filename_template = validator.get_filename_template();
+    let target_filename = match mapped_output.and_then(|output| output.file_stem.as_deref()) {
+        Some(file_stem) => {
+            filename_template.render_with_stem(file_stem, None, validator.get_target_extension())
+        }
+        None => filename_template.render(path, None, validator.get_target_extension()),
+    };
+    let Ok(target_filename) = target_filename else {
+        return true;
+    };
 
-    if validator.should_keep_folder_structure() {
+    let mut target_output_directory = output_directory.to_path_buf();
+    if let Some(mapped_output) = mapped_output {
+        target_output_directory = output_directory.join(&mapped_output.subfolder);
+    } else if validator.should_keep_folder_structure() {
         if let Ok(relative_path) =
             crate::shared::file_utils::get_relative_path(input_directory, path)
         {
             let relative_dir_path = relative_path.parent().unwrap_or(Path::new(""));
-            let target_output_path = output_directory
-                .join(relative_dir_path)
-                .join(target_filename);
-            return !target_output_path.exists();
+            target_output_directory = output_directory.join(relative_dir_path);
         }
     }
 
-    let target_output_path = output_directory.join(target_filename);
-    !target_output_path.exists()
+    // The input is written when any of its outputs is missing
+    let output_subfolders = validator.get_output_subfolders();
+    if output_subfolders.is_empty() {
+        return !target_output_directory.join(target_filename).exists();
+    }
+    output_subfolders.iter().any(|subfolder| {
+        !target_output_directory
+            .join(subfolder)
+            .join(&target_filename)
+            .exists()
+    })
 }
 
 /// Check if a path is a valid media file that should be processed
@@ -64,6 +117,7 @@ pub fn is_valid_media_path<V: MediaValidator>(
 ) -> bool {
     path.is_file()
         && V::is_supported_extension(path)
+        && validator.get_input_filter().matches(path, input_directory)
         && should_write_to_output(path, input_directory, output_directory, validator)
 }
 
@@ -106,12 +160,271 @@ where
         .collect()
 }
 
+/// Handle cloud placeholder files whose data isn't available locally
+///
+/// Placeholders are downloaded when `hydrate` is enabled, otherwise they're skipped because
+/// FFmpeg fails when the download stalls halfway through a run.
+pub fn handle_cloud_placeholders(
+    paths: Vec<PathBuf>,
+    hydrate: bool,
+) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+    paths
+        .into_par_iter()
+        .filter_map(|path| {
+            if let Err(e) = check_process_cancelled() {
+                return Some(Err(e));
+            }
+
+            let is_cloud_placeholder = FileInfo::read(&path)
+                .map(|file_info| file_info.is_cloud_placeholder)
+                .unwrap_or(false);
+            if !is_cloud_placeholder {
+                return Some(Ok(path));
+            }
+
+            let reason = if hydrate {
+                match hydrate_file(&path) {
+                    Ok(()) => {
+                        info!("Downloaded cloud placeholder file: {}", path.display());
+                        return Some(Ok(path));
+                    }
+                    Err(e) => format!("Failed to download cloud placeholder file: {}", e),
+                }
+            } else {
+                "Cloud placeholder file is not available offline".to_string()
+            };
+
+            warn!("Skipping {}: {}", path.display(), reason);
+            RunReportManager::record_skipped_file(path, reason);
+            None
+        })
+        .collect()
+}
+
+/// Skip files which are too small to contain media, e.g. failed downloads or placeholder files
+///
+/// FFmpeg fails on these files with errors which don't point at the cause, so they're skipped
+/// during discovery. When `quarantine` is set, they're moved to the quarantine folder of the
+/// output directory.
+pub fn handle_tiny_files(
+    paths: Vec<PathBuf>,
+    min_file_size: u32,
+    quarantine: bool,
+    input_directory: &Path,
+    output_directory: &Path,
+) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+    if min_file_size == 0 {
+        return Ok(paths);
+    }
+
+    paths
+        .into_par_iter()
+        .filter_map(|path| {
+            if let Err(e) = check_process_cancelled() {
+                return Some(Err(e));
+            }
+
+            let file_size = match FileInfo::read(&path) {
+                Ok(file_info) if file_info.size < min_file_size as u64 => file_info.size,
+                _ => return Some(Ok(path)),
+            };
+
+            let reason = if file_size == 0 {
+                "Empty file".to_string()
+            } else {
+                format!("Near-empty file of {} bytes", file_size)
+            };
+            warn!("Skipping {}: {}", path.display(), reason);
+
+            if quarantine {
+                match move_to_quarantine(&path, input_directory, output_directory) {
+                    Ok(quarantine_path) => {
+                        info!(
+                            "Moved {} to the quarantine folder: {}",
+                            path.display(),
+                            quarantine_path.display()
+                        );
+                    }
+                    Err(e) => warn!("Failed to quarantine {}: {}", path.display(), e),
+                }
+            }
+
+            RunReportManager::record_skipped_file(path, reason);
+            None
+        })
+        .collect()
+}
+
+/// Skip media outside the input limits of the settings, e.g. tiny thumbnails or gigantic scans
+pub fn skip_media_outside_input_limits<T: Media, V: MediaValidator>(
+    mut media_list: Vec<T>,
+    validator: &V,
+) -> Vec<T> {
+    let min_resolution = validator.get_min_input_resolution();
+    let max_file_size = validator.get_max_file_size();
+
+    media_list.retain(|media| {
+        let resolution = media.get_resolution();
+        let reason = if resolution.width < min_resolution.width
+            || resolution.height < min_resolution.height
+        {
+            format!(
+                "Resolution of {} is below the minimum input resolution",
+                resolution
+            )
+        } else if max_file_size > 0 && media.get_file_size() > max_file_size {
+            format!(
+                "File size of {} bytes is above the maximum file size",
+                media.get_file_size()
+            )
+        } else {
+            return true;
+        };
+
+        warn!("Skipping {}: {}", media.get_file_path().display(), reason);
+        RunReportManager::record_skipped_file(media.get_file_path().to_path_buf(), reason);
+        false
+    });
+
+    media_list
+}
+
+/// Time a file's size and modification time must stay unchanged before it counts as complete
+const FILE_STABILITY_INTERVAL: Duration = Duration::from_secs(2);
+/// Time between the checks of files which were modified within the stability interval
+const FILE_STABILITY_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Longest time to wait for files which are still being written before they're skipped
+const GROWING_FILE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Split the paths into complete files and files which are still being written
+///
+/// Only files modified within the stability interval are watched for changes, so runs on
+/// settled folders don't wait. The watched files are checked until they settle or the stability
+/// interval has passed, so files which were already complete don't wait for the whole interval.
+pub fn partition_growing_files(
+    paths: Vec<PathBuf>,
+) -> Result<(Vec<PathBuf>, Vec<PathBuf>), Box<dyn Error + Send + Sync>> {
+    check_process_cancelled()?;
+
+    let now = SystemTime::now();
+    let (recent_files, settled_files): (Vec<_>, Vec<_>) = paths
+        .into_par_iter()
+        .map(|path| {
+            let file_info = FileInfo::read(&path).ok();
+            let needs_check = is_being_written(&path, file_info.as_ref(), now);
+            (path, file_info, needs_check)
+        })
+        .partition(|(_, _, needs_check)| *needs_check);
+
+    let mut stable_paths: Vec<PathBuf> = settled_files.into_iter().map(|(path, ..)| path).collect();
+    let mut growing_paths = Vec::new();
+
+    if recent_files.is_empty() {
+        return Ok((stable_paths, growing_paths));
+    }
+
+    let poll_start = Instant::now();
+    let mut watched_files: Vec<(PathBuf, Option<FileInfo>)> = recent_files
+        .into_iter()
+        .map(|(path, file_info, _)| (path, file_info))
+        .collect();
+
+    while !watched_files.is_empty() {
+        check_process_cancelled()?;
+        std::thread::sleep(FILE_STABILITY_POLL_INTERVAL);
+        let is_interval_over = poll_start.elapsed() >= FILE_STABILITY_INTERVAL;
+
+        let now = SystemTime::now();
+        let mut still_watched_files = Vec::new();
+        for (path, previous_info) in watched_files {
+            let Ok(current_info) = FileInfo::read(&path) else {
+                warn!("Skipping {}: file was removed", path.display());
+                RunReportManager::record_skipped_file(path, "File was removed".to_string());
+                continue;
+            };
+
+            let is_unchanged = previous_info.is_some_and(|previous_info| {
+                previous_info.size == current_info.size
+                    && previous_info.modified == current_info.modified
+            });
+
+            if is_unchanged && !is_being_written(&path, Some(&current_info), now) {
+                stable_paths.push(path);
+            } else if is_interval_over {
+                growing_paths.push(path);
+            } else {
+                still_watched_files.push((path, Some(current_info)));
+            }
+        }
+        watched_files = still_watched_files;
+    }
+
+    if !growing_paths.is_empty() {
+        info!(
+            "Deferring {} files which are still being written",
+            growing_paths.len()
+        );
+    }
+
+    Ok((stable_paths, growing_paths))
+}
+
+/// Check if a file may still be written, when it was modified within the stability interval or
+/// another program holds it open
+pub fn is_file_being_written(path: &Path) -> bool {
+    is_being_written(path, FileInfo::read(path).ok().as_ref(), SystemTime::now())
+}
+
+fn is_being_written(path: &Path, file_info: Option<&FileInfo>, now: SystemTime) -> bool {
+    let is_recent = file_info
+        .and_then(|file_info| file_info.modified)
+        .is_some_and(|modified| {
+            now.duration_since(modified)
+                .map_or(true, |age| age < FILE_STABILITY_INTERVAL)
+        });
+    is_recent || is_file_locked(path)
+}
+
+/// Wait until files which were still being written are complete
+///
+/// Files which keep changing past the timeout are skipped and recorded in the run report.
+pub fn wait_for_growing_files(
+    mut growing_paths: Vec<PathBuf>,
+) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+    let wait_start = Instant::now();
+    let mut complete_paths = Vec::new();
+
+    while !growing_paths.is_empty() {
+        if wait_start.elapsed() > GROWING_FILE_TIMEOUT {
+            for path in growing_paths {
+                warn!("Skipping {}: file is still being written", path.display());
+                RunReportManager::record_skipped_file(
+                    path,
+                    "File was still being written".to_string(),
+                );
+            }
+            break;
+        }
+
+        let (stable_paths, still_growing_paths) = partition_growing_files(growing_paths)?;
+        complete_paths.extend(stable_paths);
+        growing_paths = still_growing_paths;
+    }
+
+    Ok(complete_paths)
+}
+
 /// Sort media list by file size in descending order
 pub fn sort_by_file_size<T>(media_list: &mut [T])
 where
     T: crate::shared::media_structs::Media,
 {
-    media_list.sort_by_key(|b| std::cmp::Reverse(b.get_file_size()));
+    // Files of equal size keep a natural path order, so runs don't depend on the directory order
+    media_list.sort_by(|a, b| {
+        b.get_file_size()
+            .cmp(&a.get_file_size())
+            .then_with(|| natural_path_cmp(a.get_file_path(), b.get_file_path()))
+    });
 }
 
 /// Recursively read media paths using jwalk
@@ -122,9 +435,21 @@ pub fn read_media_paths_recursive<V: MediaValidator>(
 ) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
     let walk_start = std::time::Instant::now();
 
+    // An output directory inside the input directory isn't read, so outputs aren't processed again
+    let nested_output_directory = nested_directory_path(directory, output_directory);
+
     let valid_paths: Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> =
         jwalk::WalkDir::new(directory)
             .skip_hidden(false)
+            .process_read_dir(move |_, _, _, children| {
+                if let Some(nested_output_directory) = &nested_output_directory {
+                    children.retain(|entry| {
+                        entry
+                            .as_ref()
+                            .map_or(true, |entry| entry.path() != *nested_output_directory)
+                    });
+                }
+            })
             .into_iter()
             .filter_map(|entry| {
                 if let Err(e) = check_process_cancelled() {
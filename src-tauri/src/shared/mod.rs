@@ -1,14 +1,53 @@
+pub mod background_mode;
+pub mod brand_kit;
 pub mod commands;
 pub mod config;
+pub mod config_migrations;
+pub mod disk_readers;
+pub mod disk_space;
+pub mod external_command;
+pub mod ffmpeg_capabilities;
+pub mod ffmpeg_command_history;
+pub mod ffmpeg_file_log;
 pub mod ffmpeg_logger;
 pub mod ffmpeg_processor;
+pub mod ffmpeg_script;
 pub mod ffmpeg_structs;
+pub mod ffprobe;
 pub mod file_utils;
+pub mod filename_template;
+pub mod filter_builder;
+pub mod hdr;
+pub mod headless;
+#[cfg(feature = "http-api")]
+pub mod http_api;
+pub mod input_filter;
+pub mod job_manifest;
+pub mod job_queue;
+pub mod layer_structs;
 pub mod logo_handler;
+pub mod logo_overrides;
 pub mod logo_processor;
 pub mod logo_structs;
 pub mod media_structs;
 pub mod media_validator;
+pub mod natural_sort;
+pub mod output_collision;
+pub mod output_mapping;
+pub mod process_limiter;
 pub mod process_manager;
+pub mod processing_marker;
 pub mod progress_handler;
 pub mod progress_terminal_bar;
+pub mod project_config;
+pub mod review_queue;
+pub mod run_history;
+pub mod run_log;
+pub mod run_plan;
+pub mod run_report;
+pub mod settings_overrides;
+pub mod settings_payload;
+pub mod shutdown;
+pub mod source_disposition;
+pub mod update_check;
+pub mod watch_folder;
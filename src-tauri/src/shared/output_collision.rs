@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+/// What a run does with an input whose output file already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum CollisionStrategy {
+    /// Replace the existing file
+    Overwrite,
+    /// Keep the existing file and skip the input
+    Skip,
+    /// Write the output next to the existing file with a numbered suffix, e.g. `photo_1.jpg`
+    RenameWithSuffix,
+    /// Stop the run before anything is processed
+    Error,
+}
+
+/// Output paths claimed by the inputs of a run, so two inputs of the run never write to the same
+/// output
+#[derive(Debug, Default)]
+pub struct OutputPathClaims {
+    claimed_paths: HashSet<PathBuf>,
+}
+
+impl OutputPathClaims {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the paths to write the outputs of an input to with the collision strategy and claim
+    /// them for the run, `None` when the input is skipped
+    ///
+    /// Most skipped inputs are filtered out while reading the input paths. Inputs whose output
+    /// names are only known once they're probed or numbered are skipped here, when every output
+    /// exists. An input with a missing output writes all of its outputs, like while reading.
+    pub fn resolve_output_collisions(
+        &mut self,
+        output_paths: Vec<PathBuf>,
+        collision_strategy: CollisionStrategy,
+    ) -> Result<Option<Vec<PathBuf>>, Box<dyn Error + Send + Sync>> {
+        let is_skipped = collision_strategy == CollisionStrategy::Skip
+            && !output_paths.is_empty()
+            && output_paths.iter().all(|output_path| {
+                output_path.exists() && !self.claimed_paths.contains(output_path)
+            });
+        if is_skipped {
+            return Ok(None);
+        }
+
+        output_paths
+            .into_iter()
+            .map(|output_path| self.resolve_output_collision(output_path, collision_strategy))
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+
+    /// Get the path to write an output to with the collision strategy and claim it for the run
+    ///
+    /// An output claimed by another input of the run is never overwritten, it's renamed with a
+    /// suffix unless the strategy stops the run.
+    fn resolve_output_collision(
+        &mut self,
+        output_path: PathBuf,
+        collision_strategy: CollisionStrategy,
+    ) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+        let output_path = if self.claimed_paths.contains(&output_path) {
+            match collision_strategy {
+                CollisionStrategy::Error => {
+                    return Err(format!(
+                        "More than one input of the run writes to the output {}",
+                        output_path.display()
+                    )
+                    .into())
+                }
+                _ => self.find_free_suffix_path(&output_path),
+            }
+        } else if !output_path.exists() {
+            output_path
+        } else {
+            match collision_strategy {
+                CollisionStrategy::Overwrite | CollisionStrategy::Skip => output_path,
+                CollisionStrategy::Error => {
+                    return Err(
+                        format!("The output {} already exists", output_path.display()).into(),
+                    )
+                }
+                CollisionStrategy::RenameWithSuffix => self.find_free_suffix_path(&output_path),
+            }
+        };
+
+        self.claimed_paths.insert(output_path.clone());
+        Ok(output_path)
+    }
+
+    /// Get the first path with a numbered suffix, e.g. `photo_1.jpg`, which neither exists nor is
+    /// claimed by the run
+    fn find_free_suffix_path(&self, output_path: &Path) -> PathBuf {
+        let file_stem = output_path
+            .file_stem()
+            .map(|file_stem| file_stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let extension = output_path
+            .extension()
+            .map(|extension| format!(".{}", extension.to_string_lossy()))
+            .unwrap_or_default();
+
+        (1..)
+            .map(|suffix| {
+                output_path.with_file_name(format!("{}_{}{}", file_stem, suffix, extension))
+            })
+            .find(|renamed_path| {
+                !renamed_path.exists() && !self.claimed_paths.contains(renamed_path)
+            })
+            .expect("a free suffix exists")
+    }
+}
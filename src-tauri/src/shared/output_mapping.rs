@@ -0,0 +1,139 @@
+use glob::Pattern;
+use std::error::Error;
+use std::path::{Component, Path, PathBuf};
+
+use crate::shared::input_filter::{matches_input_pattern, parse_input_pattern};
+
+/// Where the outputs of a mapped input are written
+#[derive(Debug, Clone)]
+pub struct MappedOutput {
+    /// Folder relative to the output directory
+    pub subfolder: PathBuf,
+    /// Replacement for the stem of the source file, `None` keeps the file name
+    pub file_stem: Option<String>,
+}
+
+#[derive(Debug)]
+struct OutputMappingRule {
+    pattern: Pattern,
+    output: MappedOutput,
+}
+
+/// Rules from a CSV file which place the outputs of matching inputs in their own location
+///
+/// Each row maps an input path or glob pattern to an output path relative to the output
+/// directory, e.g. `shoot/IMG_0412.jpg,SKU-1042/front` or `*_back.jpg,backs/`. An output ending
+/// with a `/` is a folder which keeps the file name. The first matching row is used.
+#[derive(Debug, Default)]
+pub struct OutputMapping {
+    rules: Vec<OutputMappingRule>,
+}
+
+impl OutputMapping {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            format!(
+                "Failed to read the output mapping {}: {}",
+                path.display(),
+                e
+            )
+        })?;
+        Self::parse(&content)
+    }
+
+    fn parse(content: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut rules = Vec::new();
+        for (line_index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields = parse_csv_line(line);
+            let [input, output] = fields.as_slice() else {
+                return Err(format!(
+                    "Line {} of the output mapping must have an input and an output column",
+                    line_index + 1
+                )
+                .into());
+            };
+            // The header row is optional
+            if rules.is_empty() && input.eq_ignore_ascii_case("input") {
+                continue;
+            }
+
+            rules.push(OutputMappingRule {
+                pattern: parse_input_pattern(input)?,
+                output: parse_mapped_output(output)
+                    .map_err(|e| format!("Line {} of the output mapping: {}", line_index + 1, e))?,
+            });
+        }
+
+        Ok(Self { rules })
+    }
+
+    /// Find where the outputs of an input are written, `None` when no row matches it
+    pub fn find(&self, path: &Path, input_directory: &Path) -> Option<&MappedOutput> {
+        self.rules
+            .iter()
+            .find(|rule| matches_input_pattern(&rule.pattern, path, input_directory))
+            .map(|rule| &rule.output)
+    }
+}
+
+fn parse_mapped_output(output: &str) -> Result<MappedOutput, Box<dyn Error + Send + Sync>> {
+    let output = output.trim().replace('\\', "/");
+    let output_path = Path::new(&output);
+
+    // Mapped outputs may not be written outside the output directory
+    let is_relative = output_path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if output.is_empty() || !is_relative {
+        return Err(format!(
+            "The output \"{}\" must be a relative path without '..'",
+            output
+        )
+        .into());
+    }
+
+    if output.ends_with('/') {
+        return Ok(MappedOutput {
+            subfolder: output_path.to_path_buf(),
+            file_stem: None,
+        });
+    }
+
+    Ok(MappedOutput {
+        subfolder: output_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default(),
+        file_stem: output_path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_string()),
+    })
+}
+
+/// Split a CSV line into its fields, fields in double quotes may contain commas
+fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut is_quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '"' if is_quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => is_quoted = !is_quoted,
+            ',' if !is_quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            _ => field.push(char),
+        }
+    }
+    fields.push(field.trim().to_string());
+
+    fields
+}
@@ -0,0 +1,74 @@
+use log::info;
+use std::sync::{Condvar, Mutex};
+
+lazy_static::lazy_static! {
+    static ref PROCESS_LIMITER: ProcessLimiter = ProcessLimiter::new();
+}
+
+/// Limits the FFmpeg processes running at the same time
+///
+/// The work is spread over as many threads as the CPU has, which is right for small image
+/// batches, but every video encoder runs several threads of its own, so a thread per process
+/// overwhelms the machine on video runs.
+pub struct ProcessLimiter {
+    state: Mutex<ProcessLimiterState>,
+    process_finished: Condvar,
+}
+
+struct ProcessLimiterState {
+    /// `None` when the number of processes isn't limited
+    max_processes: Option<usize>,
+    active_processes: usize,
+}
+
+/// Permission for an FFmpeg process to run, which is released when dropped
+pub struct ProcessPermit;
+
+impl ProcessLimiter {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(ProcessLimiterState {
+                max_processes: None,
+                active_processes: 0,
+            }),
+            process_finished: Condvar::new(),
+        }
+    }
+
+    /// Limit the processes to `max_concurrent_processes`, 0 removes the limit
+    pub fn configure(max_concurrent_processes: u32) {
+        let max_processes = (max_concurrent_processes > 0).then(|| {
+            info!(
+                "Limiting the FFmpeg processes to {}",
+                max_concurrent_processes
+            );
+            max_concurrent_processes as usize
+        });
+
+        let mut state = PROCESS_LIMITER.state.lock().unwrap();
+        state.max_processes = max_processes;
+        PROCESS_LIMITER.process_finished.notify_all();
+    }
+
+    /// Wait until an FFmpeg process may run
+    pub fn acquire() -> ProcessPermit {
+        let mut state = PROCESS_LIMITER
+            .process_finished
+            .wait_while(PROCESS_LIMITER.state.lock().unwrap(), |state| {
+                state
+                    .max_processes
+                    .is_some_and(|max_processes| state.active_processes >= max_processes)
+            })
+            .unwrap();
+        state.active_processes += 1;
+        ProcessPermit
+    }
+}
+
+impl Drop for ProcessPermit {
+    fn drop(&mut self) {
+        let mut state = PROCESS_LIMITER.state.lock().unwrap();
+        state.active_processes -= 1;
+        PROCESS_LIMITER.process_finished.notify_one();
+    }
+}
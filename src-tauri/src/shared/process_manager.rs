@@ -1,6 +1,7 @@
 use log::{info, warn};
 use std::collections::HashMap;
 use std::error::Error;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
@@ -15,16 +16,21 @@ lazy_static::lazy_static! {
 
 pub struct ProcessManager {
     pub process_ids: HashMap<u64, u32>,
+    /// Files which the processes are writing by their system PID, deleted when they're killed
+    output_paths: HashMap<u32, Vec<PathBuf>>,
     next_id: u64,
     cancel_flag: Arc<AtomicBool>,
+    is_paused: bool,
 }
 
 impl ProcessManager {
     fn new() -> Self {
         Self {
             process_ids: HashMap::new(),
+            output_paths: HashMap::new(),
             next_id: 0,
             cancel_flag: Arc::new(AtomicBool::new(false)),
+            is_paused: false,
         }
     }
 
@@ -34,6 +40,13 @@ impl ProcessManager {
         let id = manager.next_id;
         manager.next_id += 1;
         manager.process_ids.insert(id, pid);
+
+        // A process which starts just as the run is paused is suspended right away
+        if manager.is_paused {
+            if let Err(e) = Self::set_process_suspended(pid, true) {
+                warn!("Failed to pause process {} (PID: {}): {}", id, pid, e);
+            }
+        }
         info!(
             "Registered process with ID {} (PID: {}). Total active: {}",
             id,
@@ -43,10 +56,17 @@ impl ProcessManager {
         id
     }
 
+    /// Track the files a process writes, which are deleted when the process is killed mid-write
+    pub fn register_output_paths(pid: u32, output_paths: Vec<PathBuf>) {
+        let mut manager = PROCESS_MANAGER.lock().unwrap();
+        manager.output_paths.insert(pid, output_paths);
+    }
+
     /// Remove a completed process by its unique ID
     pub fn unregister_process(id: u64) {
         let mut manager = PROCESS_MANAGER.lock().unwrap();
         if let Some(pid) = manager.process_ids.remove(&id) {
+            manager.output_paths.remove(&pid);
             info!(
                 "Unregistered process with ID {} (PID: {}). Remaining: {}",
                 id,
@@ -68,12 +88,72 @@ impl ProcessManager {
         info!("Cancellation requested for all operations");
     }
 
+    /// Reset the cancellation of the last run, e.g. before a plan which holds the run lock
+    pub fn reset_cancel() {
+        let manager = PROCESS_MANAGER.lock().unwrap();
+        manager.cancel_flag.store(false, Ordering::Relaxed);
+    }
+
     /// Check if cancellation has been requested
     pub fn is_cancelled() -> bool {
         let manager = PROCESS_MANAGER.lock().unwrap();
         manager.cancel_flag.load(Ordering::Relaxed)
     }
 
+    /// Suspend all active processes, processes which start while paused are suspended right away
+    pub fn pause_all_processes() -> Result<(), Box<dyn Error>> {
+        Self::set_all_processes_suspended(true)
+    }
+
+    /// Continue all processes suspended by a pause
+    pub fn resume_all_processes() -> Result<(), Box<dyn Error>> {
+        Self::set_all_processes_suspended(false)
+    }
+
+    /// Check if the running processes are paused
+    pub fn is_paused() -> bool {
+        let manager = PROCESS_MANAGER.lock().unwrap();
+        manager.is_paused
+    }
+
+    /// Wait until the run is resumed, so no new processes start while it's paused
+    pub fn wait_while_paused() -> Result<(), Box<dyn Error + Send + Sync>> {
+        while Self::is_paused() {
+            check_process_cancelled()?;
+            std::thread::sleep(std::time::Duration::from_millis(200));
+        }
+        Ok(())
+    }
+
+    fn set_all_processes_suspended(suspended: bool) -> Result<(), Box<dyn Error>> {
+        let mut manager = PROCESS_MANAGER.lock().unwrap();
+        if manager.is_paused == suspended {
+            return Ok(());
+        }
+        manager.is_paused = suspended;
+
+        let action = if suspended { "pause" } else { "resume" };
+        info!(
+            "Requested to {} {} active processes",
+            action,
+            manager.process_ids.len()
+        );
+
+        let mut errors = Vec::new();
+        for (id, pid) in manager.process_ids.iter() {
+            if let Err(e) = Self::set_process_suspended(*pid, suspended) {
+                warn!("Failed to {} process {} (PID: {}): {}", action, id, pid, e);
+                errors.push(format!("Process {} (PID: {}): {}", id, pid, e));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Failed to {} some processes: {}", action, errors.join("; ")).into())
+        }
+    }
+
     /// Kill all active processes immediately using OS-level termination
     pub fn kill_all_processes() -> Result<(), Box<dyn Error>> {
         let mut manager = PROCESS_MANAGER.lock().unwrap();
@@ -106,8 +186,29 @@ impl ProcessManager {
             }
         }
 
+        // The killed processes leave truncated files, which would be skipped as existing outputs
+        // by the next run
+        let mut removed_count = 0;
+        for pid in manager.process_ids.values() {
+            for output_path in manager.output_paths.get(pid).into_iter().flatten() {
+                match remove_partial_output(output_path) {
+                    Ok(true) => removed_count += 1,
+                    Ok(false) => {}
+                    Err(e) => warn!(
+                        "Failed to remove the partial output {}: {}",
+                        output_path.display(),
+                        e
+                    ),
+                }
+            }
+        }
+        if removed_count > 0 {
+            info!("Removed {} partial outputs", removed_count);
+        }
+
         // Clear the process list
         manager.process_ids.clear();
+        manager.output_paths.clear();
 
         if !errors.is_empty() {
             warn!(
@@ -128,8 +229,10 @@ impl ProcessManager {
     pub fn clear() {
         let mut manager = PROCESS_MANAGER.lock().unwrap();
         manager.process_ids.clear();
-        // Reset the cancel flag when clearing
+        manager.output_paths.clear();
+        // Reset the cancel flag and the pause when clearing
         manager.cancel_flag.store(false, Ordering::Relaxed);
+        manager.is_paused = false;
         info!("Process manager cleared and cancel flag reset");
     }
 
@@ -142,10 +245,10 @@ impl ProcessManager {
     /// Kill a process by its system PID using OS-specific methods
     #[cfg(target_os = "windows")]
     fn kill_process_by_pid(pid: u32) -> Result<(), Box<dyn Error>> {
-        use std::process::Command;
+        use crate::shared::external_command::{external_command, ExternalBinary};
 
         // Use taskkill with /F (force) flag on Windows
-        let output = Command::new("taskkill")
+        let output = external_command(ExternalBinary::Taskkill)?
             .args(["/F", "/PID", &pid.to_string()])
             .creation_flags(0x08000000) // CREATE_NO_WINDOW
             .output()?;
@@ -170,6 +273,83 @@ impl ProcessManager {
         signal::kill(Pid::from_raw(pid as i32), Signal::SIGKILL)?;
         Ok(())
     }
+
+    /// Suspend or resume a process by its system PID with `NtSuspendProcess`, which suspends all
+    /// of its threads
+    #[cfg(target_os = "windows")]
+    fn set_process_suspended(pid: u32, suspended: bool) -> Result<(), Box<dyn Error>> {
+        use std::ffi::c_void;
+
+        const PROCESS_SUSPEND_RESUME: u32 = 0x0800;
+
+        #[link(name = "kernel32")]
+        extern "system" {
+            fn OpenProcess(
+                desired_access: u32,
+                inherit_handle: i32,
+                process_id: u32,
+            ) -> *mut c_void;
+            fn CloseHandle(handle: *mut c_void) -> i32;
+        }
+        #[link(name = "ntdll")]
+        extern "system" {
+            fn NtSuspendProcess(process_handle: *mut c_void) -> i32;
+            fn NtResumeProcess(process_handle: *mut c_void) -> i32;
+        }
+
+        // SAFETY: the handle is only used after checking it was opened, and closed once
+        let status = unsafe {
+            let handle = OpenProcess(PROCESS_SUSPEND_RESUME, 0, pid);
+            if handle.is_null() {
+                return Err(std::io::Error::last_os_error().into());
+            }
+            let status = if suspended {
+                NtSuspendProcess(handle)
+            } else {
+                NtResumeProcess(handle)
+            };
+            CloseHandle(handle);
+            status
+        };
+
+        // Negative NTSTATUS values are errors
+        if status < 0 {
+            return Err(format!("NTSTATUS {:#x}", status).into());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn set_process_suspended(pid: u32, suspended: bool) -> Result<(), Box<dyn Error>> {
+        use nix::sys::signal::{self, Signal};
+        use nix::unistd::Pid;
+
+        let signal = if suspended {
+            Signal::SIGSTOP
+        } else {
+            Signal::SIGCONT
+        };
+        signal::kill(Pid::from_raw(pid as i32), signal)?;
+        Ok(())
+    }
+}
+
+/// Remove a file a killed process was writing, returning if there was a file to remove
+///
+/// A killed process may hold on to the file for a moment (Windows doesn't delete open files), so
+/// the removal is retried a few times.
+fn remove_partial_output(path: &Path) -> std::io::Result<bool> {
+    const ATTEMPTS: u32 = 10;
+
+    for attempt in 1..=ATTEMPTS {
+        match std::fs::remove_file(path) {
+            Ok(()) => return Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) if attempt == ATTEMPTS => return Err(e),
+            Err(_) => std::thread::sleep(std::time::Duration::from_millis(100)),
+        }
+    }
+    Ok(false)
 }
 
 /// Custom error type for cancellation
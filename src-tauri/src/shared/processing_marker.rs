@@ -0,0 +1,138 @@
+use log::info;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use crate::shared::{
+    ffprobe::run_ffprobe, process_manager::check_process_cancelled, run_report::RunReportManager,
+};
+
+const MARKER_PREFIX: &str = "processed-by-add-logo v";
+
+/// Settings which decide what the output looks like, the other settings only decide where and how
+/// a run works
+///
+/// New settings don't change the marker until they're added here, so the outputs of earlier runs
+/// are still recognized.
+const VISUAL_SETTINGS: [&str; 32] = [
+    "addLogo",
+    "animationLoopCount",
+    "backgroundColor",
+    "border",
+    "codec",
+    "colorAdjustments",
+    "exportSizes",
+    "format",
+    "formatQuality",
+    "layers",
+    "logoCorner",
+    "logoKeyColor",
+    "logoKeyTolerance",
+    "logoPath",
+    "logoScale",
+    "logoXOffsetScale",
+    "logoYOffsetScale",
+    "neverUpscale",
+    "pixelFormat",
+    "preserveHdr",
+    "resizeAspectRatio",
+    "resizeMode",
+    "resizeOutputResolution",
+    "resizePadColor",
+    "scaleAlgorithm",
+    "sharpenAmount",
+    "shouldConvertCodec",
+    "shouldConvertFormat",
+    "streamMapping",
+    "targetFps",
+    "trimEnd",
+    "trimStart",
+];
+
+/// A marker written into every output file, which identifies the settings it was processed with
+///
+/// Inputs carrying the marker of the current settings are already processed, so they can be
+/// skipped to prevent adding the logo twice when folders get mixed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessingMarker(String);
+
+impl ProcessingMarker {
+    pub fn for_settings<T: Serialize>(settings: &T) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut settings_value = serde_json::to_value(settings)?;
+        if let Some(settings_object) = settings_value.as_object_mut() {
+            settings_object.retain(|key, _| VISUAL_SETTINGS.contains(&key.as_str()));
+        }
+
+        // The keys of JSON objects are sorted, so equal settings always hash the same
+        let settings_hash = fnv1a_hash(settings_value.to_string().as_bytes());
+        Ok(Self(format!("{}{:016x}", MARKER_PREFIX, settings_hash)))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn matches(&self, comment: &str) -> bool {
+        comment.trim() == self.0
+    }
+}
+
+/// FNV-1a hash, which unlike the standard hasher is stable across Rust versions
+fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+/// Read the comment of a media container with ffprobe
+pub fn read_container_comment(path: &Path) -> Option<String> {
+    let probe_result = run_ffprobe(path, &[]).ok()?;
+    // Matroska stores its tags in uppercase
+    probe_result["format"]["tags"]
+        .as_object()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("comment"))
+        .and_then(|(_, value)| value.as_str())
+        .map(str::to_string)
+}
+
+/// Remove the paths of files which carry the marker of the current settings
+pub fn skip_marked_files(
+    paths: Vec<PathBuf>,
+    marker: &ProcessingMarker,
+    read_marker: fn(&Path) -> Option<String>,
+) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+    let total_count = paths.len();
+
+    let unmarked_paths: Vec<PathBuf> = paths
+        .into_par_iter()
+        .filter_map(|path| {
+            if let Err(e) = check_process_cancelled() {
+                return Some(Err(e));
+            }
+
+            if read_marker(&path).is_some_and(|comment| marker.matches(&comment)) {
+                RunReportManager::record_skipped_file(
+                    path,
+                    "Already processed with the same settings".to_string(),
+                );
+                return None;
+            }
+
+            Some(Ok(path))
+        })
+        .collect::<Result<_, _>>()?;
+
+    info!(
+        "Skipped {} files which were already processed with the same settings",
+        total_count - unmarked_paths.len()
+    );
+
+    Ok(unmarked_paths)
+}
@@ -1,11 +1,30 @@
+use log::warn;
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
-use std::sync::{Arc, Mutex};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
 use ts_rs::TS;
 
 use crate::shared::progress_terminal_bar::TerminalProgressBar;
 
+/// Event the progress is emitted with, the payload is `null` when the progress is cleared
+pub const PROGRESS_EVENT: &str = "progress://update";
+
+/// Shortest time between two emitted progress updates, which keeps the frontend responsive
+const EMIT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// App handle the progress is emitted through, not set when running without the window
+static PROGRESS_APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+/// Shortest time between two samples of the rate, shorter samples are too noisy to estimate with
+const RATE_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Weight of a new sample in the smoothed rate, lower values follow changes of the rate slower
+const RATE_SMOOTHING_FACTOR: f64 = 0.2;
+
 #[derive(Debug, Clone, Copy)]
 pub enum ProgressMode {
     /// Increment progress once after completion (used for images)
@@ -14,6 +33,64 @@ pub enum ProgressMode {
     PerFrame,
 }
 
+/// Stage of a processing run, in the order the stages run
+///
+/// Files which were still being written are processed in a deferred pass after the encoding,
+/// which runs the stages from scanning to encoding again. The stage number goes back for that
+/// pass, and the time of the repeated stages is added to their earlier time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum ProgressStage {
+    /// Reading the input paths and filtering the files which are skipped
+    Scanning,
+    /// Reading the files and preparing their settings
+    Probing,
+    /// Creating the logos for the output resolutions
+    LogoPrep,
+    /// Running the FFmpeg processes which write the outputs
+    Encoding,
+    /// Checking that every output was written
+    Verifying,
+}
+
+impl ProgressStage {
+    pub const ALL: [Self; 5] = [
+        Self::Scanning,
+        Self::Probing,
+        Self::LogoPrep,
+        Self::Encoding,
+        Self::Verifying,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Scanning => "Scanning",
+            Self::Probing => "Probing",
+            Self::LogoPrep => "Preparing logos",
+            Self::Encoding => "Encoding",
+            Self::Verifying => "Verifying",
+        }
+    }
+
+    /// Position of the stage in the run, starting at 1
+    pub fn number(self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|stage| *stage == self)
+            .unwrap_or(0)
+            + 1
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct StageTiming {
+    pub stage: ProgressStage,
+    pub duration_seconds: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
@@ -28,11 +105,23 @@ pub struct ProgressInfo {
     #[ts(type = "number | null")]
     #[serde(serialize_with = "serialize_optional_duration_as_secs")]
     pub estimated_remaining: Option<Duration>,
-    pub items_per_second: f64,
+    /// Clock time at which the progress is estimated to finish, e.g. `14:32`
+    pub estimated_finish_time: Option<String>,
+    /// Smoothed rate at which the input files are read
+    pub megabytes_per_second: f64,
     pub status: String,
+    /// Stage of the run, `None` before the run entered its first stage
+    pub stage: Option<ProgressStage>,
+    /// Time spent in the stages which started, in the order they started, where the time of a
+    /// stage which ran more than once is accumulated
+    pub stage_timings: Vec<StageTiming>,
     pub alternative_current: usize,
     pub alternative_total: usize,
     pub alternative_unit: String,
+    /// Input files of the batches which are being processed
+    pub active_files: Vec<PathBuf>,
+    pub running_batch_count: usize,
+    pub finished_batch_count: usize,
 }
 
 fn serialize_duration_as_secs<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
@@ -55,6 +144,21 @@ where
     }
 }
 
+/// Format the clock time after the remaining duration, with the date when it isn't today
+///
+/// Uses a fixed 24-hour format, e.g. `14:32` or `2024-05-02 09:15`, which doesn't depend on the
+/// locale of the system. Returns `None` when the estimate is too far out to be a valid time.
+fn format_finish_time(remaining: Duration) -> Option<String> {
+    let now = chrono::Local::now();
+    let finish_time = now.checked_add_signed(chrono::TimeDelta::from_std(remaining).ok()?)?;
+
+    if finish_time.date_naive() == now.date_naive() {
+        Some(finish_time.format("%H:%M").to_string())
+    } else {
+        Some(finish_time.format("%Y-%m-%d %H:%M").to_string())
+    }
+}
+
 impl ProgressInfo {
     pub fn new(
         status: String,
@@ -70,12 +174,51 @@ impl ProgressInfo {
             percentage: 0.0,
             elapsed_time: Duration::from_secs(0),
             estimated_remaining: None,
-            items_per_second: 0.0,
+            estimated_finish_time: None,
+            megabytes_per_second: 0.0,
             status,
+            stage: None,
+            stage_timings: Vec::new(),
             alternative_current: 0,
             alternative_total: alternative_total.unwrap_or(0),
             alternative_unit: alternative_unit.unwrap_or("items".to_string()),
+            active_files: Vec::new(),
+            running_batch_count: 0,
+            finished_batch_count: 0,
+        }
+    }
+}
+
+/// Exponentially smoothed rate of a growing amount, which follows changes of the rate, e.g. when a
+/// run reaches larger files, without jumping on every update
+#[derive(Debug, Default)]
+struct RateEstimator {
+    last_sample: Option<(Instant, f64)>,
+    smoothed_rate: Option<f64>,
+}
+
+impl RateEstimator {
+    /// Sample the amount, returning the smoothed rate per second once the first sample interval
+    /// passed
+    fn update(&mut self, amount: f64) -> Option<f64> {
+        let now = Instant::now();
+        let Some((sample_time, sample_amount)) = self.last_sample else {
+            self.last_sample = Some((now, amount));
+            return None;
+        };
+
+        let sample_duration = now - sample_time;
+        if sample_duration >= RATE_SAMPLE_INTERVAL {
+            let rate = (amount - sample_amount).max(0.0) / sample_duration.as_secs_f64();
+            self.smoothed_rate = Some(match self.smoothed_rate {
+                Some(smoothed_rate) => {
+                    smoothed_rate + RATE_SMOOTHING_FACTOR * (rate - smoothed_rate)
+                }
+                None => rate,
+            });
+            self.last_sample = Some((now, amount));
         }
+        self.smoothed_rate
     }
 }
 
@@ -85,6 +228,17 @@ pub struct ProgressTracker {
     start_time: Instant,
     terminal_bar: Option<RefCell<TerminalProgressBar>>,
     is_finished: Arc<Mutex<bool>>,
+    /// Status which the paused status replaces, `None` when the run isn't paused
+    status_before_pause: Mutex<Option<String>>,
+    last_emit: Arc<Mutex<Option<Instant>>>,
+    /// Whether the latest progress is emitted once the emit interval passed, set when an update
+    /// was throttled
+    is_trailing_emit_scheduled: Arc<AtomicBool>,
+    rate_estimator: Mutex<RateEstimator>,
+    processed_bytes: Mutex<u64>,
+    byte_rate_estimator: Mutex<RateEstimator>,
+    /// Start of the running stage with the time the stage ran before, `None` when no stage runs
+    stage_start: Mutex<Option<(Instant, f64)>>,
 }
 
 impl ProgressTracker {
@@ -106,6 +260,13 @@ impl ProgressTracker {
             start_time: Instant::now(),
             terminal_bar: None,
             is_finished: Arc::new(Mutex::new(false)),
+            status_before_pause: Mutex::new(None),
+            last_emit: Arc::new(Mutex::new(None)),
+            is_trailing_emit_scheduled: Arc::new(AtomicBool::new(false)),
+            rate_estimator: Mutex::new(RateEstimator::default()),
+            processed_bytes: Mutex::new(0),
+            byte_rate_estimator: Mutex::new(RateEstimator::default()),
+            stage_start: Mutex::new(None),
         }
     }
 
@@ -123,45 +284,131 @@ impl ProgressTracker {
         let mut info = self.info.lock().unwrap();
         info.current += value;
         self.update_calculations(&mut info);
-        self.display_terminal_progress(&info);
+        self.publish(&mut info, false);
+    }
+
+    pub fn decrement(&self, value: usize) {
+        let mut info = self.info.lock().unwrap();
+        info.current = info.current.saturating_sub(value);
+        self.update_calculations(&mut info);
+        self.publish(&mut info, false);
     }
 
     pub fn set_current(&self, current: usize) {
         let mut info = self.info.lock().unwrap();
         info.current = current;
         self.update_calculations(&mut info);
-        self.display_terminal_progress(&info);
+        self.publish(&mut info, false);
     }
 
-    pub fn set_total(&self, total: usize) {
+    pub fn add_total(&self, total: usize) {
         let mut info = self.info.lock().unwrap();
-        info.total = total;
+        info.total += total;
         self.update_calculations(&mut info);
-        self.display_terminal_progress(&info);
+        self.publish(&mut info, false);
+    }
+
+    /// Add the size of processed input files to the read rate
+    pub fn increment_bytes(&self, bytes: u64) {
+        let mut info = self.info.lock().unwrap();
+        let mut processed_bytes = self.processed_bytes.lock().unwrap();
+        *processed_bytes += bytes;
+        if let Some(bytes_per_second) = self
+            .byte_rate_estimator
+            .lock()
+            .unwrap()
+            .update(*processed_bytes as f64)
+        {
+            info.megabytes_per_second = bytes_per_second / (1024.0 * 1024.0);
+        }
+        self.publish(&mut info, false);
     }
 
     pub fn increment_alternative(&self, value: usize) {
         let mut info = self.info.lock().unwrap();
         info.alternative_current += value;
-        self.display_terminal_progress(&info);
+        self.publish(&mut info, false);
     }
 
     pub fn set_alternative_current(&self, current: usize) {
         let mut info = self.info.lock().unwrap();
         info.alternative_current = current;
-        self.display_terminal_progress(&info);
+        self.publish(&mut info, false);
     }
 
-    pub fn set_alternative_total(&self, total: usize) {
+    pub fn add_alternative_total(&self, total: usize) {
         let mut info = self.info.lock().unwrap();
-        info.alternative_total = total;
-        self.display_terminal_progress(&info);
+        info.alternative_total += total;
+        self.publish(&mut info, false);
     }
 
     pub fn set_status(&self, status: String) {
         let mut info = self.info.lock().unwrap();
+
+        // The paused status stays until the run resumes, with the new status shown afterwards
+        if let Some(status_before_pause) = self.status_before_pause.lock().unwrap().as_mut() {
+            *status_before_pause = status;
+            return;
+        }
         info.status = status;
-        self.display_terminal_progress(&info);
+        self.publish(&mut info, true);
+    }
+
+    /// Add the input files of a batch to the files which are being processed
+    pub fn start_batch(&self, input_paths: &[PathBuf]) {
+        let mut info = self.info.lock().unwrap();
+        info.active_files.extend_from_slice(input_paths);
+        info.running_batch_count += 1;
+        self.publish(&mut info, false);
+    }
+
+    /// Remove the input files of a finished batch from the files which are being processed
+    pub fn finish_batch(&self, input_paths: &[PathBuf]) {
+        let mut info = self.info.lock().unwrap();
+        info.active_files
+            .retain(|active_file| !input_paths.contains(active_file));
+        info.running_batch_count = info.running_batch_count.saturating_sub(1);
+        info.finished_batch_count += 1;
+        self.publish(&mut info, false);
+    }
+
+    /// Enter a stage of the run, which ends the running stage
+    pub fn set_stage(&self, stage: ProgressStage) {
+        let mut info = self.info.lock().unwrap();
+        self.update_stage_timing(&mut info);
+
+        let previous_duration = match info
+            .stage_timings
+            .iter()
+            .find(|timing| timing.stage == stage)
+        {
+            Some(timing) => timing.duration_seconds,
+            None => {
+                info.stage_timings.push(StageTiming {
+                    stage,
+                    duration_seconds: 0.0,
+                });
+                0.0
+            }
+        };
+        info.stage = Some(stage);
+        *self.stage_start.lock().unwrap() = Some((Instant::now(), previous_duration));
+        self.publish(&mut info, true);
+    }
+
+    /// Show the paused status, restoring the previous status when the run resumes
+    pub fn set_paused(&self, paused: bool) {
+        let mut info = self.info.lock().unwrap();
+        let mut status_before_pause = self.status_before_pause.lock().unwrap();
+        if paused {
+            if status_before_pause.is_none() {
+                *status_before_pause =
+                    Some(std::mem::replace(&mut info.status, "Paused".to_string()));
+            }
+        } else if let Some(status) = status_before_pause.take() {
+            info.status = status;
+        }
+        self.publish(&mut info, true);
     }
 
     pub fn get_info(&self) -> ProgressInfo {
@@ -183,10 +430,15 @@ impl ProgressTracker {
             *finished = true;
         }
 
+        let mut info = self.info.lock().unwrap();
+        info.active_files.clear();
+        info.running_batch_count = 0;
+        self.update_stage_timing(&mut info);
+        *self.stage_start.lock().unwrap() = None;
         if let Some(ref bar_cell) = self.terminal_bar {
-            let info = self.info.lock().unwrap();
             bar_cell.borrow_mut().finish(&info.status);
         }
+        self.emit(&info);
     }
 
     pub fn redraw_terminal_progress(&self) {
@@ -203,17 +455,46 @@ impl ProgressTracker {
             0.0
         };
 
-        if info.elapsed_time.as_secs_f64() > 0.0 {
-            info.items_per_second = info.current as f64 / info.elapsed_time.as_secs_f64();
-        }
+        // The smoothed rate follows the rate of the current files, the average rate over the
+        // elapsed time is only used until the first sample interval passed
+        let smoothed_rate = self
+            .rate_estimator
+            .lock()
+            .unwrap()
+            .update(info.current as f64);
+        let rate = smoothed_rate.unwrap_or_else(|| {
+            let elapsed_seconds = info.elapsed_time.as_secs_f64();
+            if elapsed_seconds > 0.0 {
+                info.current as f64 / elapsed_seconds
+            } else {
+                0.0
+            }
+        });
 
-        if info.current > 0 && info.current < info.total && info.items_per_second > 0.0 {
-            let remaining_images = info.total - info.current;
-            let estimated_seconds = remaining_images as f64 / info.items_per_second;
+        if info.current > 0 && info.current < info.total && rate > 0.0 {
+            let estimated_seconds = (info.total - info.current) as f64 / rate;
             info.estimated_remaining = Some(Duration::from_secs_f64(estimated_seconds));
         } else {
             info.estimated_remaining = None;
         }
+        info.estimated_finish_time = info.estimated_remaining.and_then(format_finish_time);
+    }
+
+    /// Set the time of the running stage to the time it ran so far
+    fn update_stage_timing(&self, info: &mut ProgressInfo) {
+        let Some((stage_start, previous_duration)) = *self.stage_start.lock().unwrap() else {
+            return;
+        };
+        let Some(stage) = info.stage else {
+            return;
+        };
+        if let Some(timing) = info
+            .stage_timings
+            .iter_mut()
+            .find(|timing| timing.stage == stage)
+        {
+            timing.duration_seconds = previous_duration + stage_start.elapsed().as_secs_f64();
+        }
     }
 
     fn display_terminal_progress(&self, info: &ProgressInfo) {
@@ -221,6 +502,193 @@ impl ProgressTracker {
             bar_cell.borrow_mut().display(info);
         }
     }
+
+    /// Show the progress on the terminal and emit it, where updates within the emit interval of
+    /// the last emitted update are only emitted when forced, e.g. for a new status
+    ///
+    /// A throttled update is emitted with the latest progress once the interval passed, so the
+    /// frontend isn't left on an outdated progress when no further update follows.
+    fn publish(&self, info: &mut ProgressInfo, force_emit: bool) {
+        self.update_stage_timing(info);
+        self.display_terminal_progress(info);
+
+        let last_emit = *self.last_emit.lock().unwrap();
+        match last_emit {
+            Some(last_emit) if !force_emit && last_emit.elapsed() < EMIT_INTERVAL => {
+                self.schedule_trailing_emit(EMIT_INTERVAL - last_emit.elapsed());
+            }
+            _ => self.emit(info),
+        }
+    }
+
+    /// Emit the latest progress after the delay, unless a trailing emit is already scheduled
+    fn schedule_trailing_emit(&self, delay: Duration) {
+        if PROGRESS_APP_HANDLE.get().is_none()
+            || self.is_trailing_emit_scheduled.swap(true, Ordering::SeqCst)
+        {
+            return;
+        }
+
+        // The progress of a tracker which was replaced or cleared in the meantime isn't emitted
+        let info = Arc::downgrade(&self.info);
+        let last_emit = self.last_emit.clone();
+        let is_trailing_emit_scheduled = self.is_trailing_emit_scheduled.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(delay);
+            let Some(info) = info.upgrade() else {
+                return;
+            };
+            let info = info.lock().unwrap();
+            is_trailing_emit_scheduled.store(false, Ordering::SeqCst);
+            *last_emit.lock().unwrap() = Some(Instant::now());
+            emit_progress(Some(&info));
+        });
+    }
+
+    fn emit(&self, info: &ProgressInfo) {
+        *self.last_emit.lock().unwrap() = Some(Instant::now());
+        emit_progress(Some(info));
+    }
+}
+
+fn emit_progress(info: Option<&ProgressInfo>) {
+    let Some(app_handle) = PROGRESS_APP_HANDLE.get() else {
+        return;
+    };
+    if let Err(e) = app_handle.emit(PROGRESS_EVENT, info) {
+        warn!("Failed to emit the progress: {}", e);
+    }
+}
+
+/// Receiver of the progress of a processing run
+///
+/// The Tauri app reports to the global progress tracker through [`GlobalProgressSink`]. Other
+/// consumers like a CLI or tests pass their own implementation, which keeps runs independent of
+/// the global state.
+pub trait ProgressSink: Send + Sync {
+    fn start(
+        &self,
+        status: String,
+        total: Option<usize>,
+        unit: Option<String>,
+        alternative_total: Option<usize>,
+        alternative_unit: Option<String>,
+    );
+    fn set_status(&self, status: String);
+    fn set_stage(&self, stage: ProgressStage);
+    /// Add work to the total, e.g. of the files of a deferred pass after the first pass
+    fn add_total(&self, total: usize);
+    fn increment(&self, value: usize);
+    /// Take back progress which was counted for work that has to be done again
+    fn decrement(&self, value: usize);
+    fn add_alternative_total(&self, total: usize);
+    fn increment_alternative(&self, value: usize);
+    /// Add the size of processed input files, which gives the read rate
+    fn increment_bytes(&self, bytes: u64);
+    fn finish(&self);
+
+    /// Mark the input files of a batch as being processed
+    fn start_batch(&self, _input_paths: &[PathBuf]) {}
+
+    /// Mark the batch of the input files as finished, whether or not it succeeded
+    fn finish_batch(&self, _input_paths: &[PathBuf]) {}
+
+    /// Redraw the terminal display of the progress, if the sink has one
+    fn redraw(&self) {}
+}
+
+/// Progress sink which reports to the global progress tracker with a terminal display
+pub struct GlobalProgressSink;
+
+impl ProgressSink for GlobalProgressSink {
+    fn start(
+        &self,
+        status: String,
+        total: Option<usize>,
+        unit: Option<String>,
+        alternative_total: Option<usize>,
+        alternative_unit: Option<String>,
+    ) {
+        ProgressManager::start_progress_with_terminal(
+            status,
+            total,
+            unit,
+            alternative_total,
+            alternative_unit,
+        );
+    }
+
+    fn set_status(&self, status: String) {
+        ProgressManager::set_status(status);
+    }
+
+    fn set_stage(&self, stage: ProgressStage) {
+        ProgressManager::set_stage(stage);
+    }
+
+    fn add_total(&self, total: usize) {
+        ProgressManager::add_total(total);
+    }
+
+    fn increment(&self, value: usize) {
+        ProgressManager::increment_progress(value);
+    }
+
+    fn decrement(&self, value: usize) {
+        ProgressManager::decrement_progress(value);
+    }
+
+    fn add_alternative_total(&self, total: usize) {
+        ProgressManager::add_alternative_total(total);
+    }
+
+    fn increment_alternative(&self, value: usize) {
+        ProgressManager::increment_alternative_progress(value);
+    }
+
+    fn increment_bytes(&self, bytes: u64) {
+        ProgressManager::increment_bytes(bytes);
+    }
+
+    fn finish(&self) {
+        ProgressManager::finish_progress();
+    }
+
+    fn start_batch(&self, input_paths: &[PathBuf]) {
+        ProgressManager::start_batch(input_paths);
+    }
+
+    fn finish_batch(&self, input_paths: &[PathBuf]) {
+        ProgressManager::finish_batch(input_paths);
+    }
+
+    fn redraw(&self) {
+        ProgressManager::redraw_progress();
+    }
+}
+
+/// Progress sink which ignores the progress, for work outside a run like previews
+pub struct SilentProgressSink;
+
+impl ProgressSink for SilentProgressSink {
+    fn start(
+        &self,
+        _status: String,
+        _total: Option<usize>,
+        _unit: Option<String>,
+        _alternative_total: Option<usize>,
+        _alternative_unit: Option<String>,
+    ) {
+    }
+    fn set_status(&self, _status: String) {}
+    fn set_stage(&self, _stage: ProgressStage) {}
+    fn add_total(&self, _total: usize) {}
+    fn increment(&self, _value: usize) {}
+    fn decrement(&self, _value: usize) {}
+    fn add_alternative_total(&self, _total: usize) {}
+    fn increment_alternative(&self, _value: usize) {}
+    fn increment_bytes(&self, _bytes: u64) {}
+    fn finish(&self) {}
 }
 
 // Global progress manager
@@ -231,6 +699,11 @@ lazy_static::lazy_static! {
 pub struct ProgressManager;
 
 impl ProgressManager {
+    /// Emit the progress to the frontend, which listens to the progress event instead of polling
+    pub fn init(app_handle: &AppHandle) {
+        let _ = PROGRESS_APP_HANDLE.set(app_handle.clone());
+    }
+
     pub fn start_progress(
         status: String,
         total: Option<usize>,
@@ -241,6 +714,7 @@ impl ProgressManager {
         let tracker =
             ProgressTracker::new(status, total, unit, alternative_total, alternative_unit);
         let mut global = GLOBAL_PROGRESS.lock().unwrap();
+        tracker.emit(&tracker.get_info());
         *global = Some(tracker);
     }
 
@@ -255,6 +729,7 @@ impl ProgressManager {
             ProgressTracker::new(status, total, unit, alternative_total, alternative_unit)
                 .with_terminal_display();
         let mut global = GLOBAL_PROGRESS.lock().unwrap();
+        tracker.emit(&tracker.get_info());
         *global = Some(tracker);
     }
 
@@ -270,6 +745,7 @@ impl ProgressManager {
             ProgressTracker::new(status, total, unit, alternative_total, alternative_unit)
                 .with_custom_terminal_bar(bar);
         let mut global = GLOBAL_PROGRESS.lock().unwrap();
+        tracker.emit(&tracker.get_info());
         *global = Some(tracker);
     }
 
@@ -280,6 +756,13 @@ impl ProgressManager {
         }
     }
 
+    pub fn decrement_progress(value: usize) {
+        let global = GLOBAL_PROGRESS.lock().unwrap();
+        if let Some(tracker) = global.as_ref() {
+            tracker.decrement(value);
+        }
+    }
+
     pub fn set_progress(current: usize) {
         let global = GLOBAL_PROGRESS.lock().unwrap();
         if let Some(tracker) = global.as_ref() {
@@ -287,10 +770,10 @@ impl ProgressManager {
         }
     }
 
-    pub fn set_total(total: usize) {
+    pub fn add_total(total: usize) {
         let global = GLOBAL_PROGRESS.lock().unwrap();
         if let Some(tracker) = global.as_ref() {
-            tracker.set_total(total);
+            tracker.add_total(total);
         }
     }
 
@@ -301,6 +784,13 @@ impl ProgressManager {
         }
     }
 
+    pub fn increment_bytes(bytes: u64) {
+        let global = GLOBAL_PROGRESS.lock().unwrap();
+        if let Some(tracker) = global.as_ref() {
+            tracker.increment_bytes(bytes);
+        }
+    }
+
     pub fn set_alternative_current(current: usize) {
         let global = GLOBAL_PROGRESS.lock().unwrap();
         if let Some(tracker) = global.as_ref() {
@@ -308,10 +798,10 @@ impl ProgressManager {
         }
     }
 
-    pub fn set_alternative_total(total: usize) {
+    pub fn add_alternative_total(total: usize) {
         let global = GLOBAL_PROGRESS.lock().unwrap();
         if let Some(tracker) = global.as_ref() {
-            tracker.set_alternative_total(total);
+            tracker.add_alternative_total(total);
         }
     }
 
@@ -322,6 +812,20 @@ impl ProgressManager {
         }
     }
 
+    pub fn set_stage(stage: ProgressStage) {
+        let global = GLOBAL_PROGRESS.lock().unwrap();
+        if let Some(tracker) = global.as_ref() {
+            tracker.set_stage(stage);
+        }
+    }
+
+    pub fn set_paused(paused: bool) {
+        let global = GLOBAL_PROGRESS.lock().unwrap();
+        if let Some(tracker) = global.as_ref() {
+            tracker.set_paused(paused);
+        }
+    }
+
     pub fn get_progress() -> Option<ProgressInfo> {
         let global = GLOBAL_PROGRESS.lock().unwrap();
         global.as_ref().map(|tracker| tracker.get_info())
@@ -342,6 +846,21 @@ impl ProgressManager {
     pub fn clear_progress() {
         let mut global = GLOBAL_PROGRESS.lock().unwrap();
         *global = None;
+        emit_progress(None);
+    }
+
+    pub fn start_batch(input_paths: &[PathBuf]) {
+        let global = GLOBAL_PROGRESS.lock().unwrap();
+        if let Some(tracker) = global.as_ref() {
+            tracker.start_batch(input_paths);
+        }
+    }
+
+    pub fn finish_batch(input_paths: &[PathBuf]) {
+        let global = GLOBAL_PROGRESS.lock().unwrap();
+        if let Some(tracker) = global.as_ref() {
+            tracker.finish_batch(input_paths);
+        }
     }
 
     pub fn redraw_progress() {
@@ -1,6 +1,8 @@
 use std::io::{self, Write};
 use std::time::Duration;
 
+use crate::shared::disk_space::format_size;
+use crate::shared::progress_handler::ProgressStage;
 use crate::ProgressInfo;
 use crossterm::terminal;
 #[derive(Debug)]
@@ -62,8 +64,10 @@ impl TerminalProgressBar {
             ref unit,
             elapsed_time,
             estimated_remaining,
-            items_per_second,
+            ref estimated_finish_time,
+            megabytes_per_second,
             ref status,
+            stage,
             alternative_current,
             alternative_total,
             ref alternative_unit,
@@ -100,10 +104,18 @@ impl TerminalProgressBar {
             info_parts.push(format!("{:.1}%", percentage));
         }
 
-        info_parts.push(format!("{}/{} {}", current, total, unit));
+        if unit == "bytes" {
+            info_parts.push(format!(
+                "{}/{}",
+                format_size(current as u64),
+                format_size(total as u64)
+            ));
+        } else {
+            info_parts.push(format!("{}/{} {}", current, total, unit));
+        }
 
-        if self.show_rate && items_per_second > 0.0 {
-            info_parts.push(format!("{:.1} {}/s", items_per_second, unit));
+        if self.show_rate && megabytes_per_second > 0.0 {
+            info_parts.push(format!("{:.1} MB/s", megabytes_per_second));
         }
 
         if alternative_total > 0 {
@@ -118,13 +130,32 @@ impl TerminalProgressBar {
         }
 
         if self.show_eta {
-            if let Some(eta_duration) = estimated_remaining {
-                info_parts.push(format!("ETA: {}", Self::format_duration(eta_duration)));
+            match (estimated_remaining, estimated_finish_time) {
+                (Some(eta_duration), Some(finish_time)) => info_parts.push(format!(
+                    "ETA: {} ({})",
+                    finish_time,
+                    Self::format_duration(eta_duration)
+                )),
+                (Some(eta_duration), None) => {
+                    info_parts.push(format!("ETA: {}", Self::format_duration(eta_duration)))
+                }
+                (None, _) => {}
             }
         }
 
         let info_string = info_parts.join(" | ");
-        let progress_line = format!("{}: {} {}", status, bar, info_string);
+        let progress_line = match stage {
+            Some(stage) => format!(
+                "[{}/{} {}] {}: {} {}",
+                stage.number(),
+                ProgressStage::ALL.len(),
+                stage.label(),
+                status,
+                bar,
+                info_string
+            ),
+            None => format!("{}: {} {}", status, bar, info_string),
+        };
 
         if is_complete {
             // For completion, clear the persistent progress bar and print final message
@@ -0,0 +1,150 @@
+use log::info;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::error::Error;
+use std::path::Path;
+
+use crate::{ImageSettings, VideoSettings};
+
+/// File in the input directory whose settings override the global settings for that folder
+const PROJECT_CONFIG_FILE_NAME: &str = "project.json";
+
+/// Settings which a project config can override, which only decide how the project's media look
+/// and are named
+///
+/// A project config travels with the folder, so it can't change where files are written or
+/// whether files are cleared or disposed of.
+const PROJECT_SETTINGS: [&str; 43] = [
+    "addLogo",
+    "animationLoopCount",
+    "backgroundColor",
+    "bitrateKbps",
+    "border",
+    "brandKit",
+    "codec",
+    "colorAdjustments",
+    "crf",
+    "embedExifThumbnail",
+    "encoderSpeed",
+    "excludePatterns",
+    "exportSizes",
+    "filenameTemplate",
+    "format",
+    "formatQuality",
+    "includePatterns",
+    "layers",
+    "logoCorner",
+    "logoKeyColor",
+    "logoKeyTolerance",
+    "logoPath",
+    "logoScale",
+    "logoXOffsetScale",
+    "logoYOffsetScale",
+    "metadataMode",
+    "outputMappingPath",
+    "pixelFormat",
+    "posterTimestamp",
+    "qualityMode",
+    "resizeAspectRatio",
+    "resizeMode",
+    "resizeOutputResolution",
+    "resizePadColor",
+    "scaleAlgorithm",
+    "sequenceNaming",
+    "sharpenAmount",
+    "shouldConvertCodec",
+    "shouldConvertFormat",
+    "streamMapping",
+    "targetFps",
+    "trimEnd",
+    "trimStart",
+];
+
+/// Settings holding a path, relative paths of a project config are relative to the input directory
+const PATH_SETTINGS: [&str; 2] = ["logoPath", "outputMappingPath"];
+
+/// Apply the project config of the input directory to the image settings of a run
+pub fn resolve_image_project_config(
+    image_settings: &ImageSettings,
+) -> Result<ImageSettings, Box<dyn Error + Send + Sync>> {
+    apply_project_config(image_settings, &image_settings.input_directory)
+}
+
+/// Apply the project config of the input directory to the video settings of a run
+pub fn resolve_video_project_config(
+    video_settings: &VideoSettings,
+) -> Result<VideoSettings, Box<dyn Error + Send + Sync>> {
+    apply_project_config(video_settings, &video_settings.input_directory)
+}
+
+/// Override the settings with the settings of the `project.json` in the input directory
+///
+/// The project config holds any of the `PROJECT_SETTINGS` by their names in the config, e.g.
+/// `{ "logoPath": "logo.png", "format": "webp" }`, so a project can be handed off with the
+/// settings it's processed with. Other settings are rejected.
+fn apply_project_config<T: Clone + Serialize + DeserializeOwned>(
+    settings: &T,
+    input_directory: &Path,
+) -> Result<T, Box<dyn Error + Send + Sync>> {
+    let project_config_path = input_directory.join(PROJECT_CONFIG_FILE_NAME);
+    if !project_config_path.is_file() {
+        return Ok(settings.clone());
+    }
+
+    let project_config_str = std::fs::read_to_string(&project_config_path)?;
+    let project_config: Value = serde_json::from_str(&project_config_str).map_err(|e| {
+        format!(
+            "The project config {} is invalid: {}",
+            project_config_path.display(),
+            e
+        )
+    })?;
+    let Value::Object(project_settings) = project_config else {
+        return Err(format!(
+            "The project config {} must hold an object of settings",
+            project_config_path.display()
+        )
+        .into());
+    };
+
+    let mut settings_json = serde_json::to_value(settings)?;
+    let Some(settings_obj) = settings_json.as_object_mut() else {
+        return Err("The settings can't be merged with the project config".into());
+    };
+    for (setting_name, mut value) in project_settings {
+        if !PROJECT_SETTINGS.contains(&setting_name.as_str()) {
+            return Err(format!(
+                "The project config {} can't change the setting {}, only the settings of how the \
+                 project's media look and are named",
+                project_config_path.display(),
+                setting_name
+            )
+            .into());
+        }
+        if !settings_obj.contains_key(&setting_name) {
+            return Err(format!(
+                "The project config {} holds the unknown setting {}",
+                project_config_path.display(),
+                setting_name
+            )
+            .into());
+        }
+        if PATH_SETTINGS.contains(&setting_name.as_str()) {
+            if let Value::String(path) = &value {
+                value = Value::String(input_directory.join(path).to_string_lossy().to_string());
+            }
+        }
+        settings_obj.insert(setting_name, value);
+    }
+
+    info!("Using the project config {}", project_config_path.display());
+    serde_json::from_value(settings_json).map_err(|e| {
+        format!(
+            "The project config {} holds an invalid setting: {}",
+            project_config_path.display(),
+            e
+        )
+        .into()
+    })
+}
@@ -0,0 +1,325 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use ts_rs::TS;
+
+use crate::shared::{
+    media_structs::{Media, MediaAdjustment, Resolution},
+    process_manager::check_process_cancelled,
+    progress_handler::ProgressSink,
+    run_report::RunReportManager,
+};
+
+/// Longest side divided by the shortest side above which media is flagged
+const EXTREME_ASPECT_RATIO: f64 = 3.0;
+/// Upscale factor to the minimum pixel count above which media is flagged as tiny
+const TINY_RESOLUTION_UPSCALE_FACTOR: u32 = 4;
+/// Share of the media in a folder which must have the other orientation to flag a file
+const ORIENTATION_MAJORITY_SHARE: f64 = 0.8;
+/// Minimum number of media in a folder before orientations are compared
+const ORIENTATION_MIN_FOLDER_SIZE: usize = 3;
+
+const REVIEW_POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// Time a reviewer has to fetch the flagged files before they are skipped
+const REVIEWER_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time a reviewer has to decide on the flagged files before the undecided ones are skipped
+const REVIEW_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum ReviewFlag {
+    ExtremeAspectRatio,
+    TinyResolution,
+    SuspectedWrongOrientation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum ReviewAction {
+    Accept,
+    Adjust,
+    Skip,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewDecision {
+    pub action: ReviewAction,
+    pub adjustment: Option<MediaAdjustment>,
+}
+
+/// A flagged media file which waits for a decision of the user
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewItem {
+    pub path: PathBuf,
+    pub resolution: Resolution,
+    pub flags: Vec<ReviewFlag>,
+    pub decision: Option<ReviewDecision>,
+}
+
+#[derive(Debug)]
+struct ReviewQueue {
+    items: Vec<ReviewItem>,
+    finished: bool,
+    started_at: Instant,
+    /// Whether a reviewer fetched the items of the review
+    reviewer_connected: bool,
+}
+
+impl ReviewQueue {
+    fn is_complete(&self) -> bool {
+        self.finished || self.items.iter().all(|item| item.decision.is_some())
+    }
+
+    fn is_timed_out(&self) -> bool {
+        let elapsed = self.started_at.elapsed();
+        elapsed > REVIEW_TIMEOUT || (!self.reviewer_connected && elapsed > REVIEWER_CONNECT_TIMEOUT)
+    }
+}
+
+/// Flagged files once the review ended
+enum ReviewOutcome {
+    /// The reviewer finished the review, files without a decision are accepted
+    Reviewed(Vec<ReviewItem>),
+    /// Nobody reviewed the files in time, files without a decision are skipped
+    TimedOut(Vec<ReviewItem>),
+}
+
+// Global review queue manager
+lazy_static::lazy_static! {
+    static ref REVIEW_QUEUE: Arc<Mutex<Option<ReviewQueue>>> = Arc::new(Mutex::new(None));
+}
+
+pub struct ReviewQueueManager;
+
+impl ReviewQueueManager {
+    /// Get the items of the active review, empty when no review is waiting
+    pub fn get_items() -> Vec<ReviewItem> {
+        REVIEW_QUEUE
+            .lock()
+            .unwrap()
+            .as_mut()
+            .map(|queue| {
+                queue.reviewer_connected = true;
+                queue.items.clone()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Set the decision for a flagged media file
+    pub fn set_decision(path: &Path, decision: ReviewDecision) -> Result<(), String> {
+        let mut queue = REVIEW_QUEUE.lock().unwrap();
+        let queue = queue.as_mut().ok_or("No review is active")?;
+
+        let item = queue
+            .items
+            .iter_mut()
+            .find(|item| item.path == path)
+            .ok_or_else(|| format!("File is not in the review queue: {}", path.display()))?;
+
+        if decision.action == ReviewAction::Adjust {
+            let adjustment = decision
+                .adjustment
+                .as_ref()
+                .ok_or("An adjust decision requires an adjustment")?;
+            adjustment
+                .validate(&item.resolution)
+                .map_err(|e| e.to_string())?;
+        }
+
+        item.decision = Some(decision);
+        Ok(())
+    }
+
+    /// Finish the active review, files without a decision are accepted
+    pub fn finish_review() {
+        if let Some(queue) = REVIEW_QUEUE.lock().unwrap().as_mut() {
+            queue.finished = true;
+        }
+    }
+
+    /// Start a review and block until every item has a decision, the review is finished or
+    /// it timed out
+    ///
+    /// A review times out when no reviewer fetched the items within
+    /// [`REVIEWER_CONNECT_TIMEOUT`], e.g. for runs without a window, or when it's not finished
+    /// within [`REVIEW_TIMEOUT`].
+    fn wait_for_review(
+        items: Vec<ReviewItem>,
+    ) -> Result<ReviewOutcome, Box<dyn Error + Send + Sync>> {
+        *REVIEW_QUEUE.lock().unwrap() = Some(ReviewQueue {
+            items,
+            finished: false,
+            started_at: Instant::now(),
+            reviewer_connected: false,
+        });
+
+        loop {
+            if let Err(e) = check_process_cancelled() {
+                REVIEW_QUEUE.lock().unwrap().take();
+                return Err(e);
+            }
+
+            {
+                let mut queue = REVIEW_QUEUE.lock().unwrap();
+                if queue.as_ref().is_none_or(|queue| queue.is_complete()) {
+                    let items = queue.take().map(|queue| queue.items).unwrap_or_default();
+                    return Ok(ReviewOutcome::Reviewed(items));
+                }
+                if queue.as_ref().is_some_and(|queue| queue.is_timed_out()) {
+                    let items = queue.take().map(|queue| queue.items).unwrap_or_default();
+                    return Ok(ReviewOutcome::TimedOut(items));
+                }
+            }
+
+            std::thread::sleep(REVIEW_POLL_INTERVAL);
+        }
+    }
+}
+
+/// Flag media which likely produces bad output, hold it for review and apply the decisions
+///
+/// Skipped media is removed from the list and adjusted media is cropped and rotated before
+/// it's resized. When nobody reviews the flagged media in time, the undecided files are skipped
+/// and reported.
+pub fn review_flagged_media<T: Media>(
+    mut media_list: Vec<T>,
+    min_pixel_count: u32,
+    progress: &dyn ProgressSink,
+) -> Result<Vec<T>, Box<dyn Error + Send + Sync>> {
+    let items = find_flagged_media(&media_list, min_pixel_count);
+    if items.is_empty() {
+        return Ok(media_list);
+    }
+
+    info!("Holding {} flagged files for review", items.len());
+    progress.set_status(format!(
+        "Waiting for the review of {} flagged files...",
+        items.len()
+    ));
+
+    let (items, timed_out) = match ReviewQueueManager::wait_for_review(items)? {
+        ReviewOutcome::Reviewed(items) => (items, false),
+        ReviewOutcome::TimedOut(items) => (items, true),
+    };
+
+    let undecided_paths: HashSet<PathBuf> = items
+        .iter()
+        .filter(|item| timed_out && item.decision.is_none())
+        .map(|item| item.path.clone())
+        .collect();
+    if !undecided_paths.is_empty() {
+        warn!(
+            "Nobody reviewed {} flagged files in time, skipping them",
+            undecided_paths.len()
+        );
+    }
+
+    let decisions: HashMap<PathBuf, ReviewDecision> = items
+        .into_iter()
+        .filter_map(|item| item.decision.map(|decision| (item.path, decision)))
+        .collect();
+
+    media_list.retain_mut(|media| {
+        if undecided_paths.contains(media.get_file_path()) {
+            RunReportManager::record_skipped_file(
+                media.get_file_path().to_path_buf(),
+                "Flagged for review, but nobody reviewed it in time".to_string(),
+            );
+            return false;
+        }
+
+        let Some(decision) = decisions.get(media.get_file_path()) else {
+            return true;
+        };
+
+        match decision.action {
+            ReviewAction::Accept => true,
+            ReviewAction::Adjust => {
+                if let Some(adjustment) = &decision.adjustment {
+                    media.apply_adjustment(adjustment.clone());
+                }
+                true
+            }
+            ReviewAction::Skip => {
+                RunReportManager::record_skipped_file(
+                    media.get_file_path().to_path_buf(),
+                    "Skipped during review".to_string(),
+                );
+                false
+            }
+        }
+    });
+
+    Ok(media_list)
+}
+
+/// Analyze the media for extreme aspect ratios, tiny resolutions and outlier orientations
+fn find_flagged_media<T: Media>(media_list: &[T], min_pixel_count: u32) -> Vec<ReviewItem> {
+    // Count the portrait media per folder to find files which don't match their folder
+    let mut orientations_per_folder: HashMap<&Path, (usize, usize)> = HashMap::new();
+    for media in media_list {
+        let folder = media.get_file_path().parent().unwrap_or(Path::new(""));
+        let (portrait_count, total_count) = orientations_per_folder.entry(folder).or_default();
+        if is_portrait(media.get_resolution()) {
+            *portrait_count += 1;
+        }
+        *total_count += 1;
+    }
+
+    media_list
+        .iter()
+        .filter_map(|media| {
+            let resolution = media.get_resolution();
+            let long_side = resolution.width.max(resolution.height);
+            let short_side = resolution.width.min(resolution.height);
+            let mut flags = Vec::new();
+
+            if short_side == 0 || long_side as f64 / short_side as f64 > EXTREME_ASPECT_RATIO {
+                flags.push(ReviewFlag::ExtremeAspectRatio);
+            }
+
+            if short_side * TINY_RESOLUTION_UPSCALE_FACTOR < min_pixel_count {
+                flags.push(ReviewFlag::TinyResolution);
+            }
+
+            let folder = media.get_file_path().parent().unwrap_or(Path::new(""));
+            let (portrait_count, total_count) = orientations_per_folder[folder];
+            if total_count >= ORIENTATION_MIN_FOLDER_SIZE {
+                let other_orientation_count = if is_portrait(resolution) {
+                    total_count - portrait_count
+                } else {
+                    portrait_count
+                };
+                if other_orientation_count as f64 / total_count as f64 >= ORIENTATION_MAJORITY_SHARE
+                {
+                    flags.push(ReviewFlag::SuspectedWrongOrientation);
+                }
+            }
+
+            if flags.is_empty() {
+                return None;
+            }
+
+            Some(ReviewItem {
+                path: media.get_file_path().to_path_buf(),
+                resolution: resolution.clone(),
+                flags,
+                decision: None,
+            })
+        })
+        .collect()
+}
+
+fn is_portrait(resolution: &Resolution) -> bool {
+    resolution.height > resolution.width
+}
@@ -0,0 +1,205 @@
+use chrono::{DateTime, Local};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use ts_rs::TS;
+
+use crate::shared::job_queue::Job;
+use crate::shared::process_manager::CancellationError;
+use crate::shared::run_report::{RunId, RunReport, RunReportManager};
+
+/// Number of runs kept in the history, older runs are dropped
+const MAX_HISTORY_ENTRIES: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum RunOutcome {
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+/// A finished run with the settings it was started with
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct RunHistoryEntry {
+    /// Start time in RFC 3339 format
+    pub started_at: String,
+    pub job: Job,
+    pub outcome: RunOutcome,
+    /// The error a failed run stopped with
+    pub error: Option<String>,
+    pub total_duration_seconds: f64,
+    pub output_count: usize,
+    pub skipped_file_count: usize,
+    pub failed_file_count: usize,
+}
+
+/// Totals over the runs of the history
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct RunHistoryStats {
+    pub run_count: usize,
+    pub completed_run_count: usize,
+    pub cancelled_run_count: usize,
+    pub failed_run_count: usize,
+    pub total_duration_seconds: f64,
+    pub output_count: usize,
+    pub skipped_file_count: usize,
+    pub failed_file_count: usize,
+}
+
+#[derive(Debug, Default)]
+struct RunHistory {
+    /// Oldest run first
+    entries: Vec<RunHistoryEntry>,
+    store_path: Option<PathBuf>,
+}
+
+impl RunHistory {
+    fn save(&self) {
+        let Some(store_path) = &self.store_path else {
+            return;
+        };
+
+        let result = serde_json::to_string(&self.entries)
+            .map_err(|e| e.to_string())
+            .and_then(|history_str| fs::write(store_path, history_str).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            warn!("Failed to save the run history: {}", e);
+        }
+    }
+}
+
+// Global run history manager
+lazy_static::lazy_static! {
+    static ref RUN_HISTORY: Mutex<RunHistory> = Mutex::new(RunHistory::default());
+}
+
+pub struct RunHistoryManager;
+
+impl RunHistoryManager {
+    /// Load the history of the runs of previous sessions
+    pub fn init(app_handle: &AppHandle) -> Result<(), Box<dyn Error>> {
+        let data_dir = app_handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get data directory: {}", e))?;
+        fs::create_dir_all(&data_dir)?;
+        let store_path = data_dir.join("run_history.json");
+
+        let entries = if store_path.exists() {
+            fs::read_to_string(&store_path)
+                .ok()
+                .and_then(|history_str| {
+                    serde_json::from_str::<Vec<RunHistoryEntry>>(&history_str).ok()
+                })
+                .unwrap_or_else(|| {
+                    warn!("Failed to read the run history, it starts over");
+                    Vec::new()
+                })
+        } else {
+            Vec::new()
+        };
+        info!("Loaded {} runs of the run history", entries.len());
+
+        let mut history = RUN_HISTORY.lock().unwrap();
+        history.entries = entries;
+        history.store_path = Some(store_path);
+        Ok(())
+    }
+
+    /// Add a finished run to the history
+    ///
+    /// The report of a failed run is finished here, so its failed files and their diagnostics
+    /// can be reviewed. Runs which failed before they started have no report and count no files,
+    /// the report of another run is never taken.
+    pub fn record_run(
+        job: Job,
+        run_id: RunId,
+        started_at: DateTime<Local>,
+        result: &Result<RunReport, Box<dyn Error + Send + Sync>>,
+    ) {
+        let (outcome, error, report) = match result {
+            Ok(report) => (RunOutcome::Completed, None, Some(report.clone())),
+            // The cancellation already stored the partial report as the last report
+            Err(e) if e.is::<CancellationError>() => (
+                RunOutcome::Cancelled,
+                None,
+                RunReportManager::get_last_report_of(run_id),
+            ),
+            Err(e) => (
+                RunOutcome::Failed,
+                Some(e.to_string()),
+                RunReportManager::finish_report_of(run_id),
+            ),
+        };
+
+        let entry = RunHistoryEntry {
+            started_at: started_at.to_rfc3339(),
+            job,
+            outcome,
+            error,
+            total_duration_seconds: (Local::now() - started_at)
+                .to_std()
+                .unwrap_or_default()
+                .as_secs_f64(),
+            output_count: report
+                .as_ref()
+                .map_or(0, |report| report.output_paths.len()),
+            skipped_file_count: report
+                .as_ref()
+                .map_or(0, |report| report.skipped_files.len()),
+            failed_file_count: report
+                .as_ref()
+                .map_or(0, |report| report.failed_files.len()),
+        };
+
+        let mut history = RUN_HISTORY.lock().unwrap();
+        history.entries.push(entry);
+        let overflow_count = history.entries.len().saturating_sub(MAX_HISTORY_ENTRIES);
+        history.entries.drain(..overflow_count);
+        history.save();
+    }
+
+    /// Get the most recent runs, newest first
+    pub fn get_recent_runs(limit: usize) -> Vec<RunHistoryEntry> {
+        RUN_HISTORY
+            .lock()
+            .unwrap()
+            .entries
+            .iter()
+            .rev()
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Get the totals over the runs of the history
+    pub fn get_stats() -> RunHistoryStats {
+        let history = RUN_HISTORY.lock().unwrap();
+        history
+            .entries
+            .iter()
+            .fold(RunHistoryStats::default(), |mut stats, entry| {
+                stats.run_count += 1;
+                match entry.outcome {
+                    RunOutcome::Completed => stats.completed_run_count += 1,
+                    RunOutcome::Cancelled => stats.cancelled_run_count += 1,
+                    RunOutcome::Failed => stats.failed_run_count += 1,
+                }
+                stats.total_duration_seconds += entry.total_duration_seconds;
+                stats.output_count += entry.output_count;
+                stats.skipped_file_count += entry.skipped_file_count;
+                stats.failed_file_count += entry.failed_file_count;
+                stats
+            })
+    }
+}
@@ -0,0 +1,203 @@
+use log::{info, warn};
+use serde::Serialize;
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::shared::ffmpeg_structs::FfmpegBatchCommand;
+use crate::shared::process_manager::ProcessManager;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum RunLogStatus {
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+/// A line of the run log, which describes an attempt at processing an input file
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RunLogRecord<'a> {
+    /// Time the attempt finished in RFC 3339 format
+    finished_at: String,
+    input_path: &'a Path,
+    /// The outputs of the input, which are only written when the attempt succeeded
+    output_paths: Vec<&'a Path>,
+    /// Duration of the FFmpeg process, spread evenly over the files it processed
+    duration_seconds: f64,
+    /// Arguments of the FFmpeg process, which processes the other files of its batch as well
+    ffmpeg_args: &'a [String],
+    status: RunLogStatus,
+    error: Option<String>,
+}
+
+/// Start of the file names of run logs, which clearing the output directory keeps
+const RUN_LOG_PREFIX: &str = "run_log_";
+const RUN_LOG_EXTENSION: &str = "jsonl";
+
+struct ActiveRunLog {
+    path: PathBuf,
+    file: File,
+}
+
+/// Closes the run log when dropped, so a run which fails or is cancelled closes its log as well
+#[must_use = "the run log is closed when the guard is dropped"]
+pub struct RunLogGuard;
+
+impl Drop for RunLogGuard {
+    fn drop(&mut self) {
+        if let Some(active) = ACTIVE_RUN_LOG.lock().unwrap().take() {
+            info!("Wrote the run log {}", active.path.display());
+        }
+    }
+}
+
+// Global run log manager
+lazy_static::lazy_static! {
+    static ref ACTIVE_RUN_LOG: Mutex<Option<ActiveRunLog>> = Mutex::new(None);
+}
+
+pub struct RunLogManager;
+
+impl RunLogManager {
+    /// Start the run log of a run in the output directory, named after the start of the run
+    ///
+    /// Runs without the run log setting only stop the log of a previous run. The log is closed
+    /// when the returned guard is dropped.
+    pub fn start(output_directory: &Path, enabled: bool) -> RunLogGuard {
+        let mut active = ACTIVE_RUN_LOG.lock().unwrap();
+        *active = None;
+        if enabled {
+            match create_run_log(output_directory) {
+                Ok(run_log) => *active = Some(run_log),
+                Err(e) => warn!(
+                    "Failed to create the run log in {}: {}",
+                    output_directory.display(),
+                    e
+                ),
+            }
+        }
+        RunLogGuard
+    }
+
+    /// Record the files of a batch after its FFmpeg process exited, with the error it failed with
+    pub fn record_batch(
+        ffmpeg_batch_command: &FfmpegBatchCommand,
+        ffmpeg_args: &[String],
+        duration: Duration,
+        error: Option<&(dyn Error + Send + Sync)>,
+    ) {
+        let mut active = ACTIVE_RUN_LOG.lock().unwrap();
+        let Some(active) = active.as_mut() else {
+            return;
+        };
+
+        let status = match error {
+            None => RunLogStatus::Succeeded,
+            Some(_) if ProcessManager::is_cancelled() => RunLogStatus::Cancelled,
+            Some(_) => RunLogStatus::Failed,
+        };
+        let files = group_outputs_by_input(ffmpeg_batch_command);
+        let duration_seconds = duration.as_secs_f64() / files.len().max(1) as f64;
+        let finished_at = chrono::Local::now().to_rfc3339();
+
+        let mut lines = String::new();
+        for (input_path, output_paths) in files {
+            let record = RunLogRecord {
+                finished_at: finished_at.clone(),
+                input_path,
+                output_paths,
+                duration_seconds,
+                ffmpeg_args,
+                status,
+                error: error.map(|e| e.to_string()),
+            };
+            match serde_json::to_string(&record) {
+                Ok(line) => {
+                    lines.push_str(&line);
+                    lines.push('\n');
+                }
+                Err(e) => warn!("Failed to serialize the run log record: {}", e),
+            }
+        }
+
+        // The lines are written right away, so the log is complete up to a crash
+        if let Err(e) = active.file.write_all(lines.as_bytes()) {
+            warn!(
+                "Failed to write the run log {}: {}",
+                active.path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// Check if a path is a run log, e.g. to keep it when the output directory is cleared
+pub fn is_run_log_path(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|extension| extension == RUN_LOG_EXTENSION)
+        && path
+            .file_name()
+            .is_some_and(|file_name| file_name.to_string_lossy().starts_with(RUN_LOG_PREFIX))
+}
+
+/// Create a new run log, with a numbered suffix when a run started within the same second
+fn create_run_log(output_directory: &Path) -> std::io::Result<ActiveRunLog> {
+    let file_stem = format!(
+        "{}{}",
+        RUN_LOG_PREFIX,
+        chrono::Local::now().format("%Y%m%d_%H%M%S")
+    );
+    for suffix in 0.. {
+        let file_name = match suffix {
+            0 => format!("{}.{}", file_stem, RUN_LOG_EXTENSION),
+            _ => format!("{}_{}.{}", file_stem, suffix, RUN_LOG_EXTENSION),
+        };
+        let path = output_directory.join(file_name);
+        // A new file is created, so the log of another run is never appended to
+        match OpenOptions::new().append(true).create_new(true).open(&path) {
+            Ok(file) => return Ok(ActiveRunLog { path, file }),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!("a free suffix exists")
+}
+
+/// Pair every input of a batch with its outputs, in the order of the inputs
+///
+/// Image batches list the input of every output, a video batch has a single input for all its
+/// outputs.
+fn group_outputs_by_input(ffmpeg_batch_command: &FfmpegBatchCommand) -> Vec<(&Path, Vec<&Path>)> {
+    let input_paths = &ffmpeg_batch_command.input_paths;
+    let output_paths = &ffmpeg_batch_command.output_paths;
+
+    if input_paths.len() != output_paths.len() {
+        return input_paths
+            .iter()
+            .map(|input_path| {
+                (
+                    input_path.as_path(),
+                    output_paths.iter().map(PathBuf::as_path).collect(),
+                )
+            })
+            .collect();
+    }
+
+    let mut files: Vec<(&Path, Vec<&Path>)> = Vec::new();
+    for (input_path, output_path) in input_paths.iter().zip(output_paths) {
+        match files.last_mut() {
+            Some((last_input_path, last_output_paths)) if *last_input_path == input_path => {
+                last_output_paths.push(output_path);
+            }
+            _ => files.push((input_path, vec![output_path])),
+        }
+    }
+    files
+}
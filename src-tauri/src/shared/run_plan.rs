@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use ts_rs::TS;
+
+use crate::shared::run_report::SkippedFile;
+
+/// An input of a plan with the outputs a run would write for it
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedFile {
+    pub input_path: PathBuf,
+    pub output_paths: Vec<PathBuf>,
+}
+
+/// What a run with the settings would do, found without writing or moving any file
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct RunPlan {
+    pub media_type: String,
+    pub planned_files: Vec<PlannedFile>,
+    /// Number of FFmpeg commands the files would be processed with
+    pub estimated_batch_count: usize,
+    pub skipped_files: Vec<SkippedFile>,
+    pub warnings: Vec<String>,
+}
@@ -0,0 +1,448 @@
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use ts_rs::TS;
+
+use crate::shared::natural_sort::natural_path_cmp;
+use crate::shared::process_manager::CancellationError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum RunPhase {
+    Discovery,
+    StructCreation,
+    LogoPreparation,
+    Processing,
+    Verification,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseTiming {
+    pub phase: RunPhase,
+    pub duration_seconds: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct FileTimingStats {
+    pub sample_count: usize,
+    pub mean_seconds: f64,
+    pub min_seconds: f64,
+    pub p50_seconds: f64,
+    pub p90_seconds: f64,
+    pub p95_seconds: f64,
+    pub p99_seconds: f64,
+    pub max_seconds: f64,
+}
+
+impl FileTimingStats {
+    /// Calculate the aggregate statistics from a list of per-file durations in seconds
+    fn from_samples(samples: &[f64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        let mean_seconds = sorted.iter().sum::<f64>() / sorted.len() as f64;
+
+        Some(Self {
+            sample_count: sorted.len(),
+            mean_seconds,
+            min_seconds: sorted[0],
+            p50_seconds: percentile(&sorted, 50.0),
+            p90_seconds: percentile(&sorted, 90.0),
+            p95_seconds: percentile(&sorted, 95.0),
+            p99_seconds: percentile(&sorted, 99.0),
+            max_seconds: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// Nearest-rank percentile of an already sorted list
+fn percentile(sorted: &[f64], percentile: f64) -> f64 {
+    let rank = ((percentile / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Details of a single output file which aren't known before processing
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct FileReport {
+    pub output_path: PathBuf,
+    pub encoder: Option<String>,
+}
+
+/// A media file which was found but not processed
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedFile {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// A media file which failed to process
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct FailedFile {
+    pub path: PathBuf,
+    pub error: String,
+    /// Folder with the diagnostics of the failure, e.g. the FFmpeg command and failing frame
+    pub artifact_directory: Option<PathBuf>,
+}
+
+/// How far a run got before it was cancelled
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct CancellationSummary {
+    pub reason: String,
+    /// Files which were processed before the cancellation
+    pub completed_files: usize,
+    /// Files which were being processed, their FFmpeg processes were killed
+    pub killed_files: usize,
+    /// Files which were planned but never started
+    pub not_started_files: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct RunReport {
+    pub media_type: String,
+    pub total_duration_seconds: f64,
+    pub phase_timings: Vec<PhaseTiming>,
+    pub file_timing_stats: Option<FileTimingStats>,
+    pub warnings: Vec<String>,
+    pub output_paths: Vec<PathBuf>,
+    pub file_reports: Vec<FileReport>,
+    pub skipped_files: Vec<SkippedFile>,
+    pub failed_files: Vec<FailedFile>,
+    /// Set when the run was cancelled, the report then only covers the files processed so far
+    pub cancellation: Option<CancellationSummary>,
+}
+
+/// Identifies a run, so only the run which started a report finishes it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunId(u64);
+
+impl RunId {
+    /// Get the id of a new run
+    pub fn next() -> Self {
+        static NEXT_RUN_ID: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT_RUN_ID.fetch_add(1, Ordering::SeqCst))
+    }
+}
+
+#[derive(Debug)]
+struct RunReportBuilder {
+    run_id: RunId,
+    media_type: String,
+    start_time: Instant,
+    phase_timings: Vec<PhaseTiming>,
+    file_durations: Vec<f64>,
+    warnings: Vec<String>,
+    output_paths: Vec<PathBuf>,
+    file_reports: Vec<FileReport>,
+    skipped_files: Vec<SkippedFile>,
+    failed_files: Vec<FailedFile>,
+    planned_file_count: usize,
+    in_flight_file_count: usize,
+}
+
+impl RunReportBuilder {
+    fn new(run_id: RunId, media_type: String) -> Self {
+        Self {
+            run_id,
+            media_type,
+            start_time: Instant::now(),
+            phase_timings: Vec::new(),
+            file_durations: Vec::new(),
+            warnings: Vec::new(),
+            output_paths: Vec::new(),
+            file_reports: Vec::new(),
+            skipped_files: Vec::new(),
+            failed_files: Vec::new(),
+            planned_file_count: 0,
+            in_flight_file_count: 0,
+        }
+    }
+
+    fn record_phase(&mut self, phase: RunPhase, duration: Duration) {
+        // Phases which run more than once are accumulated into a single entry
+        if let Some(timing) = self.phase_timings.iter_mut().find(|t| t.phase == phase) {
+            timing.duration_seconds += duration.as_secs_f64();
+        } else {
+            self.phase_timings.push(PhaseTiming {
+                phase,
+                duration_seconds: duration.as_secs_f64(),
+            });
+        }
+    }
+
+    /// Sort the file lists in natural path order, since files finish in a nondeterministic order
+    fn sort_files(&mut self) {
+        self.output_paths.sort_by(|a, b| natural_path_cmp(a, b));
+        self.file_reports
+            .sort_by(|a, b| natural_path_cmp(&a.output_path, &b.output_path));
+        self.skipped_files
+            .sort_by(|a, b| natural_path_cmp(&a.path, &b.path));
+        self.failed_files
+            .sort_by(|a, b| natural_path_cmp(&a.path, &b.path));
+    }
+
+    fn build_cancellation_summary(&self, reason: String) -> CancellationSummary {
+        let completed_files = self.file_durations.len();
+        let killed_files = self.in_flight_file_count;
+        CancellationSummary {
+            reason,
+            completed_files,
+            killed_files,
+            not_started_files: self
+                .planned_file_count
+                .saturating_sub(completed_files + killed_files + self.failed_files.len()),
+        }
+    }
+
+    fn build(&self) -> RunReport {
+        RunReport {
+            media_type: self.media_type.clone(),
+            total_duration_seconds: self.start_time.elapsed().as_secs_f64(),
+            phase_timings: self.phase_timings.clone(),
+            file_timing_stats: FileTimingStats::from_samples(&self.file_durations),
+            warnings: self.warnings.clone(),
+            output_paths: self.output_paths.clone(),
+            file_reports: self.file_reports.clone(),
+            skipped_files: self.skipped_files.clone(),
+            failed_files: self.failed_files.clone(),
+            cancellation: None,
+        }
+    }
+}
+
+// Global run report manager
+lazy_static::lazy_static! {
+    static ref ACTIVE_RUN_REPORT: Arc<Mutex<Option<RunReportBuilder>>> = Arc::new(Mutex::new(None));
+    static ref LAST_RUN_REPORT: Arc<Mutex<Option<(RunId, RunReport)>>> = Arc::new(Mutex::new(None));
+}
+
+thread_local! {
+    /// Report of work on this thread which isn't a run, e.g. a plan, used instead of the active
+    /// run report
+    static SCOPED_RUN_REPORT: RefCell<Option<Arc<Mutex<RunReportBuilder>>>> =
+        const { RefCell::new(None) };
+}
+
+/// Update the report which the current thread records into
+fn with_active_report(update: impl FnOnce(&mut RunReportBuilder)) {
+    let scoped_report = SCOPED_RUN_REPORT.with(|scoped_report| scoped_report.borrow().clone());
+    match scoped_report {
+        Some(scoped_report) => update(&mut scoped_report.lock().unwrap()),
+        None => {
+            if let Some(builder) = ACTIVE_RUN_REPORT.lock().unwrap().as_mut() {
+                update(builder);
+            }
+        }
+    }
+}
+
+pub struct RunReportManager;
+
+impl RunReportManager {
+    /// Start collecting the report of a run, discarding any unfinished one
+    pub fn start_report(run_id: RunId, media_type: &str) {
+        let mut active = ACTIVE_RUN_REPORT.lock().unwrap();
+        *active = Some(RunReportBuilder::new(run_id, media_type.to_string()));
+    }
+
+    /// Record the duration of a pipeline phase
+    pub fn record_phase(phase: RunPhase, duration: Duration) {
+        with_active_report(|builder| {
+            builder.record_phase(phase, duration);
+        });
+    }
+
+    /// Record the processing duration of a work unit, spread evenly over the files it contained
+    pub fn record_file_durations(duration: Duration, file_count: usize) {
+        if file_count == 0 {
+            return;
+        }
+
+        let per_file_seconds = duration.as_secs_f64() / file_count as f64;
+        with_active_report(|builder| {
+            builder
+                .file_durations
+                .extend(std::iter::repeat_n(per_file_seconds, file_count));
+        });
+    }
+
+    /// Record the number of files which are about to be processed, e.g. once a work list is built
+    pub fn record_planned_files(file_count: usize) {
+        with_active_report(|builder| {
+            builder.planned_file_count += file_count;
+        });
+    }
+
+    /// Record files whose FFmpeg process started
+    pub fn record_files_started(file_count: usize) {
+        with_active_report(|builder| {
+            builder.in_flight_file_count += file_count;
+        });
+    }
+
+    /// Record files whose FFmpeg process exited, whether it succeeded or not
+    pub fn record_files_stopped(file_count: usize) {
+        with_active_report(|builder| {
+            builder.in_flight_file_count = builder.in_flight_file_count.saturating_sub(file_count);
+        });
+    }
+
+    /// Add a warning which the user should see after the run
+    pub fn add_warning(message: String) {
+        with_active_report(|builder| {
+            builder.warnings.push(message);
+        });
+    }
+
+    /// Record the output files which were produced by the run
+    pub fn record_output_paths(output_paths: Vec<PathBuf>) {
+        with_active_report(|builder| {
+            builder.output_paths.extend(output_paths);
+        });
+    }
+
+    /// Record the details of a single output file
+    pub fn record_file(file_report: FileReport) {
+        with_active_report(|builder| {
+            builder.file_reports.push(file_report);
+        });
+    }
+
+    /// Record a media file which is skipped, with the reason shown to the user
+    pub fn record_skipped_file(path: PathBuf, reason: String) {
+        with_active_report(|builder| {
+            builder.skipped_files.push(SkippedFile { path, reason });
+        });
+    }
+
+    /// Record a media file which failed to process
+    pub fn record_failed_file(failed_file: FailedFile) {
+        with_active_report(|builder| {
+            builder.failed_files.push(failed_file);
+        });
+    }
+
+    /// Finish the active run report and store it as the last run report
+    pub fn finish_report() -> Option<RunReport> {
+        let builder = ACTIVE_RUN_REPORT.lock().unwrap().take()?;
+        Some(store_last_report(builder, None))
+    }
+
+    /// Finish the active run report when it's the report of the run, so a run which failed
+    /// doesn't finish the report of a run which started after it
+    pub fn finish_report_of(run_id: RunId) -> Option<RunReport> {
+        let builder = {
+            let mut active = ACTIVE_RUN_REPORT.lock().unwrap();
+            if active.as_ref()?.run_id != run_id {
+                return None;
+            }
+            active.take()?
+        };
+        Some(store_last_report(builder, None))
+    }
+
+    /// Finish the active run report of a run which completed
+    ///
+    /// The report is only missing when the run was cancelled right as it completed, since the
+    /// cancellation takes the report.
+    pub fn finish_completed_report() -> Result<RunReport, Box<dyn Error + Send + Sync>> {
+        Self::finish_report().ok_or_else(|| CancellationError.into())
+    }
+
+    /// Run work which isn't a run, e.g. a plan, collecting its skipped files and warnings in a
+    /// report of its own
+    ///
+    /// The work runs on a thread pool of its own, so the parallel steps of the work record into
+    /// the report as well. The report of an active run isn't touched.
+    pub fn collect_report<T: Send>(
+        media_type: &str,
+        work: impl FnOnce() -> T + Send,
+    ) -> Result<(T, RunReport), Box<dyn Error + Send + Sync>> {
+        let report = Arc::new(Mutex::new(RunReportBuilder::new(
+            RunId::next(),
+            media_type.to_string(),
+        )));
+        let thread_report = report.clone();
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .start_handler(move |_| {
+                SCOPED_RUN_REPORT.with(|scoped_report| {
+                    *scoped_report.borrow_mut() = Some(thread_report.clone());
+                });
+            })
+            .build()?;
+
+        let result = thread_pool.install(work);
+
+        let mut builder = report.lock().unwrap();
+        builder.sort_files();
+        Ok((result, builder.build()))
+    }
+
+    /// Finish the active run report as a partial report of a cancelled run
+    ///
+    /// Must be called before the FFmpeg processes are killed, so the files they were processing
+    /// are counted as killed.
+    pub fn cancel_report(reason: &str) -> Option<RunReport> {
+        let builder = ACTIVE_RUN_REPORT.lock().unwrap().take()?;
+        Some(store_last_report(builder, Some(reason)))
+    }
+
+    pub fn get_last_report() -> Option<RunReport> {
+        LAST_RUN_REPORT
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|(_, report)| report.clone())
+    }
+
+    /// Get the last run report when it's the report of the run
+    pub fn get_last_report_of(run_id: RunId) -> Option<RunReport> {
+        LAST_RUN_REPORT
+            .lock()
+            .unwrap()
+            .as_ref()
+            .filter(|(last_run_id, _)| *last_run_id == run_id)
+            .map(|(_, report)| report.clone())
+    }
+
+    /// Check if a run is in progress
+    pub fn is_run_active() -> bool {
+        ACTIVE_RUN_REPORT.lock().unwrap().is_some()
+    }
+}
+
+/// Build the report of a finished run and store it as the last run report, with the reason when
+/// the run was cancelled
+fn store_last_report(mut builder: RunReportBuilder, cancel_reason: Option<&str>) -> RunReport {
+    builder.sort_files();
+    let mut report = builder.build();
+    report.cancellation =
+        cancel_reason.map(|reason| builder.build_cancellation_summary(reason.to_string()));
+
+    let mut last = LAST_RUN_REPORT.lock().unwrap();
+    *last = Some((builder.run_id, report.clone()));
+
+    report
+}
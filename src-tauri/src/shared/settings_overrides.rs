@@ -0,0 +1,201 @@
+use log::{info, warn};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use crate::AppConfig;
+
+/// A setting which can be overridden by an environment variable or a command-line argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OverriddenSetting {
+    InputDirectory,
+    OutputDirectory,
+    ImageFormat,
+    VideoFormat,
+    VideoCodec,
+    LogoPath,
+}
+
+impl OverriddenSetting {
+    const ALL: [Self; 6] = [
+        Self::InputDirectory,
+        Self::OutputDirectory,
+        Self::ImageFormat,
+        Self::VideoFormat,
+        Self::VideoCodec,
+        Self::LogoPath,
+    ];
+
+    fn argument(self) -> &'static str {
+        match self {
+            Self::InputDirectory => "--input-directory",
+            Self::OutputDirectory => "--output-directory",
+            Self::ImageFormat => "--image-format",
+            Self::VideoFormat => "--video-format",
+            Self::VideoCodec => "--video-codec",
+            Self::LogoPath => "--logo-path",
+        }
+    }
+
+    fn environment_variable(self) -> &'static str {
+        match self {
+            Self::InputDirectory => "ADD_LOGO_INPUT_DIRECTORY",
+            Self::OutputDirectory => "ADD_LOGO_OUTPUT_DIRECTORY",
+            Self::ImageFormat => "ADD_LOGO_IMAGE_FORMAT",
+            Self::VideoFormat => "ADD_LOGO_VIDEO_FORMAT",
+            Self::VideoCodec => "ADD_LOGO_VIDEO_CODEC",
+            Self::LogoPath => "ADD_LOGO_LOGO_PATH",
+        }
+    }
+
+    /// Set the value in the config, the directories and logo apply to images and videos
+    fn apply(self, value: &str, config: &mut AppConfig) {
+        match self {
+            Self::InputDirectory => {
+                config.image_settings.input_directory = PathBuf::from(value);
+                config.video_settings.input_directory = PathBuf::from(value);
+            }
+            Self::OutputDirectory => {
+                config.image_settings.output_directory = PathBuf::from(value);
+                config.video_settings.output_directory = PathBuf::from(value);
+            }
+            Self::ImageFormat => {
+                config.image_settings.format = value.to_string();
+                config.image_settings.should_convert_format = true;
+            }
+            Self::VideoFormat => {
+                config.video_settings.format = value.to_string();
+                config.video_settings.should_convert_format = true;
+            }
+            Self::VideoCodec => {
+                config.video_settings.codec = value.to_string();
+                config.video_settings.should_convert_codec = true;
+            }
+            Self::LogoPath => {
+                config.image_settings.logo_path = Some(PathBuf::from(value));
+                config.video_settings.logo_path = Some(PathBuf::from(value));
+            }
+        }
+    }
+
+    /// Put back the saved value where the config still holds the overridden value
+    fn restore(self, value: &str, config: &mut AppConfig, saved_config: &AppConfig) {
+        let (image_settings, video_settings) =
+            (&mut config.image_settings, &mut config.video_settings);
+        let (saved_image_settings, saved_video_settings) =
+            (&saved_config.image_settings, &saved_config.video_settings);
+        match self {
+            Self::InputDirectory => {
+                if image_settings.input_directory == Path::new(value) {
+                    image_settings.input_directory = saved_image_settings.input_directory.clone();
+                }
+                if video_settings.input_directory == Path::new(value) {
+                    video_settings.input_directory = saved_video_settings.input_directory.clone();
+                }
+            }
+            Self::OutputDirectory => {
+                if image_settings.output_directory == Path::new(value) {
+                    image_settings.output_directory = saved_image_settings.output_directory.clone();
+                }
+                if video_settings.output_directory == Path::new(value) {
+                    video_settings.output_directory = saved_video_settings.output_directory.clone();
+                }
+            }
+            Self::ImageFormat => {
+                if image_settings.format == value && image_settings.should_convert_format {
+                    image_settings.format = saved_image_settings.format.clone();
+                    image_settings.should_convert_format =
+                        saved_image_settings.should_convert_format;
+                }
+            }
+            Self::VideoFormat => {
+                if video_settings.format == value && video_settings.should_convert_format {
+                    video_settings.format = saved_video_settings.format.clone();
+                    video_settings.should_convert_format =
+                        saved_video_settings.should_convert_format;
+                }
+            }
+            Self::VideoCodec => {
+                if video_settings.codec == value && video_settings.should_convert_codec {
+                    video_settings.codec = saved_video_settings.codec.clone();
+                    video_settings.should_convert_codec = saved_video_settings.should_convert_codec;
+                }
+            }
+            Self::LogoPath => {
+                if image_settings.logo_path.as_deref() == Some(Path::new(value)) {
+                    image_settings.logo_path = saved_image_settings.logo_path.clone();
+                }
+                if video_settings.logo_path.as_deref() == Some(Path::new(value)) {
+                    video_settings.logo_path = saved_video_settings.logo_path.clone();
+                }
+            }
+        }
+    }
+}
+
+/// Settings overridden for a session, e.g. by an automated invocation, without editing the config
+/// file
+#[derive(Debug, Default)]
+pub struct SettingsOverrides {
+    overrides: Vec<(OverriddenSetting, String)>,
+}
+
+impl SettingsOverrides {
+    /// Read the overrides from the environment variables and the command-line arguments, where an
+    /// argument wins over the environment variable of the same setting
+    ///
+    /// Arguments are passed as `--input-directory <path>` or `--input-directory=<path>`.
+    pub fn from_env_and_args(
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut overrides = Self::default();
+        for setting in OverriddenSetting::ALL {
+            if let Ok(value) = std::env::var(setting.environment_variable()) {
+                overrides.set(setting, value);
+            }
+        }
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            };
+            let Some(setting) = OverriddenSetting::ALL
+                .into_iter()
+                .find(|setting| setting.argument() == name)
+            else {
+                warn!("Ignoring the unknown argument {}", arg);
+                continue;
+            };
+
+            let value = inline_value
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("The argument {} requires a value", name))?;
+            overrides.set(setting, value);
+        }
+
+        Ok(overrides)
+    }
+
+    fn set(&mut self, setting: OverriddenSetting, value: String) {
+        self.overrides
+            .retain(|(overridden, _)| *overridden != setting);
+        self.overrides.push((setting, value));
+    }
+
+    /// Apply the overrides to a config
+    pub fn apply(&self, config: &mut AppConfig) {
+        for (setting, value) in &self.overrides {
+            info!("Overriding {:?} with {}", setting, value);
+            setting.apply(value, config);
+        }
+    }
+
+    /// Remove the overrides from a config which is about to be saved, putting back the values of
+    /// the saved config where the config still holds an overridden value
+    pub fn restore(&self, config: &mut AppConfig, saved_config: &AppConfig) {
+        for (setting, value) in &self.overrides {
+            setting.restore(value, config, saved_config);
+        }
+    }
+}
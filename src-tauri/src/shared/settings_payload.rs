@@ -0,0 +1,389 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+use ts_rs::TS;
+
+use crate::AppConfig;
+
+/// A field of a settings payload which doesn't deserialize into the config types
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsPayloadError {
+    /// Path of the field, e.g. `imageSettings.formatQuality.jpegQuality`
+    pub path: String,
+    pub message: String,
+}
+
+/// Check if a settings payload of the UI deserializes into the app config
+///
+/// Serde stops at the first error, so each field of the payload is checked on its own in the
+/// default config. Invalid objects are searched for the nested fields which cause the error.
+pub fn find_settings_payload_errors(
+    json: &str,
+) -> Result<Vec<SettingsPayloadError>, Box<dyn Error + Send + Sync>> {
+    let payload: Value = serde_json::from_str(json)?;
+    let Err(payload_error) = deserialize_config(&payload) else {
+        return Ok(Vec::new());
+    };
+
+    let default_config = serde_json::to_value(AppConfig::default())?;
+    let mut errors = Vec::new();
+    find_invalid_fields(
+        &default_config,
+        &default_config,
+        &payload,
+        &mut Vec::new(),
+        &mut errors,
+    );
+
+    // The payload itself has the wrong shape, e.g. it isn't an object
+    if errors.is_empty() {
+        errors.push(SettingsPayloadError {
+            path: String::new(),
+            message: payload_error.to_string(),
+        });
+    }
+
+    Ok(errors)
+}
+
+fn deserialize_config(config: &Value) -> Result<AppConfig, serde_json::Error> {
+    serde_json::from_value(config.clone())
+}
+
+/// Check the fields of the payload at `path` one by one, by placing them in the default config
+fn find_invalid_fields(
+    default_config: &Value,
+    default_value: &Value,
+    payload_value: &Value,
+    path: &mut Vec<String>,
+    errors: &mut Vec<SettingsPayloadError>,
+) {
+    let (Value::Object(default_fields), Value::Object(payload_fields)) =
+        (default_value, payload_value)
+    else {
+        return;
+    };
+
+    for (field, default_field) in default_fields {
+        path.push(field.clone());
+
+        let payload_field = payload_fields.get(field);
+        let candidate_config = with_value_at(default_config, path, payload_field.cloned());
+        if let Err(e) = deserialize_config(&candidate_config) {
+            let error_count = errors.len();
+            match payload_field {
+                None => errors.push(payload_error(path, "Missing field".to_string())),
+                Some(Value::Array(items)) => {
+                    find_invalid_items(default_config, items, path, errors);
+                }
+                Some(payload_field) => {
+                    find_invalid_fields(default_config, default_field, payload_field, path, errors);
+                }
+            }
+
+            // The field is invalid as a whole, e.g. it has the wrong type
+            if errors.len() == error_count {
+                errors.push(payload_error(path, e.to_string()));
+            }
+        }
+
+        path.pop();
+    }
+}
+
+/// Check the items of an array field one by one, by placing each alone in the array
+fn find_invalid_items(
+    default_config: &Value,
+    items: &[Value],
+    path: &[String],
+    errors: &mut Vec<SettingsPayloadError>,
+) {
+    for (index, item) in items.iter().enumerate() {
+        let candidate_config =
+            with_value_at(default_config, path, Some(Value::Array(vec![item.clone()])));
+        if let Err(e) = deserialize_config(&candidate_config) {
+            let mut item_path = path.to_vec();
+            item_path.push(index.to_string());
+            errors.push(payload_error(&item_path, e.to_string()));
+        }
+    }
+}
+
+/// Copy the config with the value at the path replaced, `None` removes the field
+fn with_value_at(config: &Value, path: &[String], value: Option<Value>) -> Value {
+    let mut config = config.clone();
+    let Some((field, parent_path)) = path.split_last() else {
+        return config;
+    };
+
+    let parent = parent_path
+        .iter()
+        .try_fold(&mut config, |parent, field| parent.get_mut(field));
+    if let Some(Value::Object(parent_fields)) = parent {
+        match value {
+            Some(value) => parent_fields.insert(field.clone(), value),
+            None => parent_fields.remove(field),
+        };
+    }
+
+    config
+}
+
+fn payload_error(path: &[String], message: String) -> SettingsPayloadError {
+    SettingsPayloadError {
+        path: path.join("."),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+    use serde_json::json;
+
+    use crate::image::image_formats::FormatQualitySettings;
+    use crate::image::image_metadata::MetadataMode;
+    use crate::shared::brand_kit::BrandKit;
+    use crate::shared::config::{ImagePreset, VideoPreset};
+    use crate::shared::config_migrations::migrate_config;
+    use crate::shared::ffmpeg_file_log::FfmpegLogLevel;
+    use crate::shared::filename_template::SequenceNaming;
+    use crate::shared::layer_structs::{BlendMode, LayerKind, LayerSettings, LayersSettings};
+    use crate::shared::media_structs::{
+        Border, ColorAdjustments, ColorTone, Corner, ExportSize, ResizeMode, Resolution,
+        ScaleAlgorithm,
+    };
+    use crate::shared::output_collision::CollisionStrategy;
+    use crate::shared::source_disposition::SourceDisposition;
+    use crate::video::hardware_encoders::HardwareAcceleration;
+    use crate::video::video_codecs::{EncoderSpeed, VideoQualityMode};
+    use crate::video::video_streams::{StreamMapping, StreamSelection};
+    use crate::{ImageSettings, VideoSettings};
+
+    /// Check that the JSON deserializes into the type and serializes back into the same JSON
+    fn assert_round_trip<T: Serialize + DeserializeOwned>(json: Value) {
+        let value: T = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&value).unwrap(), json);
+    }
+
+    fn assert_value_round_trip<T: Serialize + DeserializeOwned>(value: &T) {
+        assert_round_trip::<T>(serde_json::to_value(value).unwrap());
+    }
+
+    /// Collect the fields of the JSON which aren't camelCase
+    fn find_non_camel_case_fields(json: &Value, path: &str, fields: &mut Vec<String>) {
+        match json {
+            Value::Object(object) => {
+                for (field, value) in object {
+                    let field_path = format!("{}.{}", path, field);
+                    if field.contains('_') || field.starts_with(char::is_uppercase) {
+                        fields.push(field_path.clone());
+                    }
+                    find_non_camel_case_fields(value, &field_path, fields);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    find_non_camel_case_fields(item, path, fields);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn default_image_settings() -> ImageSettings {
+        AppConfig::default().image_settings
+    }
+
+    fn default_video_settings() -> VideoSettings {
+        AppConfig::default().video_settings
+    }
+
+    #[test]
+    fn default_settings_round_trip() {
+        assert_value_round_trip(&AppConfig::default());
+        assert_value_round_trip(&default_image_settings());
+        assert_value_round_trip(&default_video_settings());
+        assert_value_round_trip(&LayersSettings::default());
+        assert_value_round_trip(&ColorAdjustments::default());
+        assert_value_round_trip(&FormatQualitySettings::default());
+        assert_value_round_trip(&StreamMapping::default());
+    }
+
+    #[test]
+    fn default_settings_have_camel_case_fields() {
+        let mut fields = Vec::new();
+        find_non_camel_case_fields(
+            &serde_json::to_value(AppConfig::default()).unwrap(),
+            "",
+            &mut fields,
+        );
+        assert!(
+            fields.is_empty(),
+            "Fields which aren't camelCase: {:?}",
+            fields
+        );
+    }
+
+    #[test]
+    fn default_settings_payload_has_no_errors() {
+        let payload = serde_json::to_string(&AppConfig::default()).unwrap();
+        assert!(find_settings_payload_errors(&payload).unwrap().is_empty());
+    }
+
+    #[test]
+    fn settings_payload_errors_have_the_path_of_the_field() {
+        let mut payload = serde_json::to_value(AppConfig::default()).unwrap();
+        payload["imageSettings"]["formatQuality"]["jpegQuality"] = json!("high");
+        let errors = find_settings_payload_errors(&payload.to_string()).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "imageSettings.formatQuality.jpegQuality");
+    }
+
+    #[test]
+    fn optional_settings_round_trip() {
+        let mut image_settings = default_image_settings();
+        image_settings.border = Some(Border {
+            color: "black".to_string(),
+            padding: 4,
+            padding_color: "white".to_string(),
+            width: 2,
+        });
+        image_settings.export_sizes = vec![ExportSize {
+            name: "web".to_string(),
+            min_pixel_count: 1080,
+        }];
+        image_settings.resize_output_resolution = Some(Resolution {
+            width: 1920,
+            height: 1080,
+        });
+        image_settings.sequence_naming = Some(SequenceNaming {
+            prefix: "wedding_".to_string(),
+            start_index: 1,
+            zero_padding: 4,
+        });
+        assert_value_round_trip(&image_settings);
+
+        let mut video_settings = default_video_settings();
+        video_settings.stream_mapping.audio = StreamSelection::Languages(vec!["eng".to_string()]);
+        video_settings.stream_mapping.subtitles = StreamSelection::None;
+        video_settings.target_fps = Some(30.0);
+        assert_value_round_trip(&video_settings);
+
+        let mut app_config = AppConfig::default();
+        app_config.image_presets.push(ImagePreset {
+            name: "Instagram".to_string(),
+            settings: image_settings,
+        });
+        app_config.video_presets.push(VideoPreset {
+            name: "Archive HEVC".to_string(),
+            settings: video_settings,
+        });
+        assert_value_round_trip(&app_config);
+    }
+
+    #[test]
+    fn brand_kit_fixture_round_trips() {
+        let brand_kit: BrandKit = serde_json::from_value(json!({
+            "name": "Client",
+            "logoCorner": "topLeft",
+            "logoScale": 20,
+            "metadataMode": "strip",
+        }))
+        .unwrap();
+        assert_eq!(brand_kit.logo_corner, Some(Corner::TopLeft));
+        assert_value_round_trip(&brand_kit);
+    }
+
+    #[test]
+    fn layer_settings_fixture_round_trips() {
+        assert_round_trip::<LayerSettings>(json!({ "kind": "logo", "blendMode": "multiply" }));
+    }
+
+    #[test]
+    fn enum_variants_are_camel_case() {
+        for json in ["keep", "archive", "trash"] {
+            assert_round_trip::<SourceDisposition>(json!(json));
+        }
+        for json in ["overwrite", "skip", "renameWithSuffix", "error"] {
+            assert_round_trip::<CollisionStrategy>(json!(json));
+        }
+        for json in ["fit", "fill", "stretch", "padToAspect"] {
+            assert_round_trip::<ResizeMode>(json!(json));
+        }
+        for json in ["fastBilinear", "bilinear", "bicubic", "lanczos"] {
+            assert_round_trip::<ScaleAlgorithm>(json!(json));
+        }
+        for json in ["original", "grayscale", "sepia"] {
+            assert_round_trip::<ColorTone>(json!(json));
+        }
+        for json in ["topLeft", "topRight", "bottomLeft", "bottomRight"] {
+            assert_round_trip::<Corner>(json!(json));
+        }
+        assert_round_trip::<LayerKind>(json!("logo"));
+        for json in ["normal", "multiply", "screen"] {
+            assert_round_trip::<BlendMode>(json!(json));
+        }
+        for json in ["off", "error", "warning", "info", "debug"] {
+            assert_round_trip::<FfmpegLogLevel>(json!(json));
+        }
+        for json in ["preserve", "strip"] {
+            assert_round_trip::<MetadataMode>(json!(json));
+        }
+        for json in ["fastest", "fast", "medium", "slow", "slowest"] {
+            assert_round_trip::<EncoderSpeed>(json!(json));
+        }
+        for json in [
+            "encoderDefault",
+            "constantQuality",
+            "targetBitrate",
+            "constrainedQuality",
+        ] {
+            assert_round_trip::<VideoQualityMode>(json!(json));
+        }
+        for json in ["auto", "nvenc", "qsv", "amf", "vaapi", "off"] {
+            assert_round_trip::<HardwareAcceleration>(json!(json));
+        }
+        assert_round_trip::<StreamSelection>(json!("all"));
+        assert_round_trip::<StreamSelection>(json!({ "languages": ["eng", "nld"] }));
+    }
+
+    #[test]
+    fn snake_case_fields_are_rejected() {
+        let mut payload = serde_json::to_value(default_image_settings()).unwrap();
+        let add_logo = payload.as_object_mut().unwrap().remove("addLogo").unwrap();
+        payload["add_logo"] = add_logo;
+        assert!(serde_json::from_value::<ImageSettings>(payload).is_err());
+    }
+
+    #[test]
+    fn legacy_setting_names_are_migrated() {
+        let mut config = serde_json::to_value(AppConfig::default()).unwrap();
+        config["configVersion"] = json!(1);
+        let image_settings = config["imageSettings"].as_object_mut().unwrap();
+        image_settings.remove("formatFavoriteList");
+        image_settings.remove("collisionStrategy");
+        image_settings.remove("sourceDisposition");
+        image_settings.insert("favorite_formats".to_string(), json!(["webp"]));
+        image_settings.insert(
+            "overwriteExistingFilesOutputDirectory".to_string(),
+            json!(true),
+        );
+        image_settings.insert("clearFilesInputDirectory".to_string(), json!(true));
+
+        migrate_config(&mut config);
+        let app_config: AppConfig = serde_json::from_value(config).unwrap();
+        assert_eq!(app_config.image_settings.format_favorite_list, vec!["webp"]);
+        assert_eq!(
+            app_config.image_settings.collision_strategy,
+            CollisionStrategy::Overwrite
+        );
+        assert_eq!(
+            app_config.image_settings.source_disposition,
+            SourceDisposition::Keep
+        );
+    }
+}
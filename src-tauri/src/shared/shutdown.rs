@@ -0,0 +1,89 @@
+use log::{error, info, warn};
+use std::{error::Error, fs};
+use tauri::{AppHandle, Manager};
+
+use crate::image::image_handler::temp_preview_directory;
+use crate::shared::{
+    job_queue::JobQueueManager,
+    logo_handler::temp_logo_directory,
+    process_manager::ProcessManager,
+    progress_handler::ProgressManager,
+    run_report::{RunReport, RunReportManager},
+    watch_folder::WatchFolderManager,
+};
+use crate::video::video_handler::temp_preview_video_directory;
+
+/// File in the log directory with the partial report of a run which was interrupted by an exit
+const INTERRUPTED_RUN_REPORT_FILE: &str = "interrupted_run_report.json";
+
+/// Shut down the processing subsystems when the app exits
+///
+/// The order matters: no new processes may start once the partial report is taken, and the
+/// report must be taken before the FFmpeg processes are killed so they're counted as killed.
+pub fn shutdown(app_handle: &AppHandle) {
+    info!("Application is exiting, shutting down the active run...");
+
+    // No run of the watched folder may start once the active run is cancelled
+    WatchFolderManager::shutdown();
+
+    ProcessManager::request_cancel();
+
+    if let Some(report) = RunReportManager::cancel_report("The app was closed during the run") {
+        if let Err(e) = write_interrupted_run_report(app_handle, &report) {
+            warn!("Failed to write the report of the interrupted run: {}", e);
+        }
+    }
+
+    if let Err(e) = ProcessManager::kill_all_processes() {
+        error!("Failed to kill FFmpeg processes on exit: {}", e);
+    }
+
+    JobQueueManager::shutdown();
+
+    for temp_directory in [
+        temp_logo_directory(),
+        temp_preview_directory(),
+        temp_preview_video_directory(),
+    ] {
+        match temp_directory {
+            Ok(temp_directory) if temp_directory.exists() => {
+                if let Err(e) = fs::remove_dir_all(&temp_directory) {
+                    warn!(
+                        "Failed to remove the temporary folder {}: {}",
+                        temp_directory.display(),
+                        e
+                    );
+                }
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to find the temporary folder: {}", e),
+        }
+    }
+
+    // Restores the terminal when the progress bar is drawn in it
+    ProgressManager::set_status("Operation cancelled".to_string());
+    ProgressManager::finish_progress();
+
+    info!("Shutdown complete");
+}
+
+/// Write the partial report next to the app log, since the in-memory report is lost on exit
+fn write_interrupted_run_report(
+    app_handle: &AppHandle,
+    report: &RunReport,
+) -> Result<(), Box<dyn Error>> {
+    let log_dir = app_handle
+        .path()
+        .app_log_dir()
+        .map_err(|e| format!("Failed to get log directory: {}", e))?;
+    fs::create_dir_all(&log_dir)?;
+
+    let report_path = log_dir.join(INTERRUPTED_RUN_REPORT_FILE);
+    fs::write(&report_path, serde_json::to_string_pretty(report)?)?;
+    info!(
+        "Wrote the report of the interrupted run to {}",
+        report_path.display()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+use crate::shared::file_utils::{find_missing_output_files, move_to_processed_folder};
+use crate::shared::run_report::RunReportManager;
+
+/// What happens to an input file once all its outputs are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum SourceDisposition {
+    /// Leave the input file where it is
+    Keep,
+    /// Move the input file to the `_processed` folder beside the input directory, e.g.
+    /// `photos_processed` for `photos`
+    Archive,
+    /// Move the input file to the trash or recycle bin
+    Trash,
+}
+
+/// Apply the source disposition to an input file whose outputs are finished
+///
+/// The input is only touched once every output is verified to exist, so an input is never lost
+/// when one of its outputs failed.
+pub fn dispose_source_file(
+    input_path: &Path,
+    output_paths: &[PathBuf],
+    source_disposition: SourceDisposition,
+    input_directory: &Path,
+) {
+    if source_disposition == SourceDisposition::Keep {
+        return;
+    }
+
+    let missing_output_paths = find_missing_output_files(output_paths);
+    if !missing_output_paths.is_empty() {
+        warn!(
+            "Kept {}, since {} of its outputs are missing",
+            input_path.display(),
+            missing_output_paths.len()
+        );
+        return;
+    }
+
+    let result = match source_disposition {
+        SourceDisposition::Keep => return,
+        SourceDisposition::Archive => {
+            move_to_processed_folder(input_path, input_directory).map(|processed_path| {
+                info!(
+                    "Moved {} to {}",
+                    input_path.display(),
+                    processed_path.display()
+                )
+            })
+        }
+        SourceDisposition::Trash => trash::delete(input_path).map_err(|e| e.into()),
+    };
+
+    if let Err(e) = result {
+        let message = format!(
+            "Failed to dispose of the processed input {}: {}",
+            input_path.display(),
+            e
+        );
+        warn!("{}", message);
+        RunReportManager::add_warning(message);
+    }
+}
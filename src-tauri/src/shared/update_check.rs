@@ -0,0 +1,97 @@
+use log::{info, warn};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    sync::{Arc, Mutex},
+};
+use ts_rs::TS;
+
+/// The latest release of the project on GitHub
+const RELEASE_FEED_URL: &str =
+    "https://api.github.com/repos/0DarkPhoenix/add-logo-processor-rust/releases/latest";
+
+/// A release of the app compared to the running version
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    pub current_version: String,
+    pub latest_version: String,
+    pub is_update_available: bool,
+    /// Release notes in Markdown
+    pub release_notes: String,
+    /// Page of the release which lists its downloads
+    pub download_url: String,
+    pub published_at: Option<String>,
+}
+
+/// The fields of a GitHub release which are used
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    body: Option<String>,
+    html_url: String,
+    published_at: Option<String>,
+}
+
+/// Query the release feed and compare the latest release with the running version
+///
+/// Updates are only reported, they're never downloaded or installed.
+pub fn check_for_updates(
+    current_version: &str,
+) -> Result<UpdateInfo, Box<dyn Error + Send + Sync>> {
+    let response = ureq::get(RELEASE_FEED_URL)
+        // GitHub rejects API requests without a user agent
+        .header("User-Agent", "add-logo-processor")
+        .header("Accept", "application/vnd.github+json")
+        .call()
+        .map_err(|e| format!("Failed to query the release feed: {}", e))?
+        .body_mut()
+        .read_to_string()
+        .map_err(|e| format!("Failed to read the release feed: {}", e))?;
+    let release: GithubRelease = serde_json::from_str(&response)?;
+
+    let latest_version = release.tag_name.trim_start_matches('v');
+    let is_update_available = Version::parse(latest_version)
+        .map_err(|e| format!("Invalid release version {}: {}", release.tag_name, e))?
+        > Version::parse(current_version)?;
+
+    Ok(UpdateInfo {
+        current_version: current_version.to_string(),
+        latest_version: latest_version.to_string(),
+        is_update_available,
+        release_notes: release.body.unwrap_or_default(),
+        download_url: release.html_url,
+        published_at: release.published_at,
+    })
+}
+
+lazy_static::lazy_static! {
+    static ref STARTUP_UPDATE: Arc<Mutex<Option<UpdateInfo>>> = Arc::new(Mutex::new(None));
+}
+
+pub struct UpdateCheckManager;
+
+impl UpdateCheckManager {
+    /// Check for updates in the background, so a slow network doesn't delay the startup
+    pub fn check_on_startup(current_version: String) {
+        std::thread::spawn(move || match check_for_updates(&current_version) {
+            Ok(update_info) => {
+                if update_info.is_update_available {
+                    info!(
+                        "Version {} is available, running {}",
+                        update_info.latest_version, update_info.current_version
+                    );
+                    *STARTUP_UPDATE.lock().unwrap() = Some(update_info);
+                }
+            }
+            Err(e) => warn!("Failed to check for updates: {}", e),
+        });
+    }
+
+    /// Get the update which was found by the check on startup
+    pub fn get_startup_update() -> Option<UpdateInfo> {
+        STARTUP_UPDATE.lock().unwrap().clone()
+    }
+}
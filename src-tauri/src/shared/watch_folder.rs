@@ -0,0 +1,189 @@
+use log::{error, info, warn};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::shared::job_queue::{try_lock_run, Job};
+use crate::shared::media_validator::is_file_being_written;
+use crate::shared::output_collision::CollisionStrategy;
+use crate::shared::process_manager::ProcessManager;
+use crate::shared::run_report::RunReportManager;
+
+/// Time without new files in the watched folder before a run starts, so a folder which is being
+/// filled is processed in one run instead of a run per file
+const WATCH_DEBOUNCE_INTERVAL: Duration = Duration::from_secs(3);
+
+struct WatchedFolder {
+    job: Job,
+    /// Dropping the watcher ends the thread which runs the job
+    _watcher: RecommendedWatcher,
+}
+
+// Global watch folder manager
+lazy_static::lazy_static! {
+    static ref WATCHED_FOLDER: Mutex<Option<WatchedFolder>> = Mutex::new(None);
+}
+
+pub struct WatchFolderManager;
+
+impl WatchFolderManager {
+    /// Watch the input directory of a job and run the job whenever new files appear in it
+    ///
+    /// The files which are already in the folder are processed right away. Outputs are never
+    /// cleared or overwritten by the runs, so every file is processed once.
+    pub fn start(job: Job) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut watched_folder = WATCHED_FOLDER.lock().unwrap();
+        if watched_folder.is_some() {
+            return Err("A folder is already being watched".into());
+        }
+
+        let job = watch_job(job);
+        let (input_directory, output_directory, search_child_folders) = match &job {
+            Job::Images(image_settings) => (
+                image_settings.input_directory.clone(),
+                image_settings.output_directory.clone(),
+                image_settings.search_child_folders,
+            ),
+            Job::Videos(video_settings) => (
+                video_settings.input_directory.clone(),
+                video_settings.output_directory.clone(),
+                video_settings.search_child_folders,
+            ),
+        };
+
+        let (sender, receiver) = mpsc::channel::<notify::Result<notify::Event>>();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        let recursive_mode = if search_child_folders {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        watcher.watch(&input_directory, recursive_mode)?;
+        info!("Watching {} for new files", input_directory.display());
+
+        let thread_job = job.clone();
+        std::thread::spawn(move || {
+            // The files which are already in the folder are processed by the first run
+            let mut last_change = Some(Instant::now());
+            let mut changed_paths: HashSet<PathBuf> = HashSet::new();
+
+            loop {
+                match receiver.recv_timeout(Duration::from_secs(1)) {
+                    Ok(Ok(event)) => {
+                        if is_new_input_event(&event, &output_directory) {
+                            last_change = Some(Instant::now());
+                            changed_paths.extend(
+                                event
+                                    .paths
+                                    .into_iter()
+                                    .filter(|path| !path.starts_with(&output_directory)),
+                            );
+                        }
+                        continue;
+                    }
+                    Ok(Err(e)) => warn!("Failed to watch {}: {}", input_directory.display(), e),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+
+                let is_settled = last_change
+                    .is_some_and(|last_change| last_change.elapsed() >= WATCH_DEBOUNCE_INTERVAL);
+                if !is_settled {
+                    continue;
+                }
+
+                // Files which are still being copied in are waited for, so a slow copy isn't
+                // processed before it's complete
+                changed_paths.retain(|path| path.is_file());
+                if changed_paths.iter().any(|path| is_file_being_written(path)) {
+                    continue;
+                }
+
+                // A run which is already active, e.g. of the job queue, is waited for
+                let Ok(run_guard) = try_lock_run() else {
+                    continue;
+                };
+
+                last_change = None;
+                changed_paths.clear();
+                info!("Processing new files in {}", input_directory.display());
+                if let Err(e) = thread_job.run_claimed(run_guard) {
+                    error!(
+                        "Processing new files in {} failed: {}",
+                        input_directory.display(),
+                        e
+                    );
+                }
+            }
+
+            info!("Stopped watching {}", input_directory.display());
+        });
+
+        *watched_folder = Some(WatchedFolder {
+            job,
+            _watcher: watcher,
+        });
+        Ok(())
+    }
+
+    /// Stop watching the folder, cancelling a run of it which is in progress
+    pub fn stop() -> Result<(), Box<dyn Error + Send + Sync>> {
+        let Some(watched_folder) = WATCHED_FOLDER.lock().unwrap().take() else {
+            return Err("No folder is being watched".into());
+        };
+        drop(watched_folder);
+
+        if RunReportManager::is_run_active() {
+            ProcessManager::request_cancel();
+            RunReportManager::cancel_report("The folder is no longer watched");
+            ProcessManager::kill_all_processes().map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Stop watching the folder when the app exits, without touching the active run
+    pub fn shutdown() {
+        WATCHED_FOLDER.lock().unwrap().take();
+    }
+
+    /// Get the job of the watched folder
+    pub fn get_watched_job() -> Option<Job> {
+        WATCHED_FOLDER
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|watched_folder| watched_folder.job.clone())
+    }
+}
+
+/// Turn off the settings which would process the files of the folder again on every run
+fn watch_job(job: Job) -> Job {
+    match job {
+        Job::Images(mut image_settings) => {
+            image_settings.clear_files_output_directory = false;
+            image_settings.collision_strategy = CollisionStrategy::Skip;
+            Job::Images(image_settings)
+        }
+        Job::Videos(mut video_settings) => {
+            video_settings.clear_files_output_directory = false;
+            video_settings.collision_strategy = CollisionStrategy::Skip;
+            Job::Videos(video_settings)
+        }
+    }
+}
+
+/// Check if an event adds or changes a file of the watched folder
+///
+/// Events of the output directory are ignored, since it may be inside the watched folder and the
+/// outputs of a run would start the next one.
+fn is_new_input_event(event: &notify::Event, output_directory: &Path) -> bool {
+    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+        && event
+            .paths
+            .iter()
+            .any(|path| !path.starts_with(output_directory))
+}
@@ -0,0 +1,81 @@
+use ffmpeg_sidecar::command::FfmpegCommand;
+
+use crate::VideoSettings;
+
+/// Frame rate of animated outputs when no target frame rate is set, since animated formats grow
+/// large at the frame rates of videos
+pub const DEFAULT_ANIMATION_FPS: f64 = 15.0;
+
+/// Short animated formats, which are exported with their own pipeline instead of the generic
+/// video command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimatedFormat {
+    Gif,
+    Webp,
+    Apng,
+}
+
+impl AnimatedFormat {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "gif" => Some(Self::Gif),
+            "webp" => Some(Self::Webp),
+            "apng" => Some(Self::Apng),
+            _ => None,
+        }
+    }
+
+    /// Build the filter which converts the frames of the `[input_label]` pad for the format into
+    /// the `[output_label]` pad
+    ///
+    /// GIF holds 256 colors per frame, so a palette is generated from the frames first, which
+    /// looks much better than the fixed palette FFmpeg otherwise uses.
+    pub fn build_filter(&self, input_label: &str, fps: f64, output_label: &str) -> String {
+        match self {
+            Self::Gif => format!(
+                "[{input}]fps={fps},split[palette_input_{output}][frames_{output}];\
+                 [palette_input_{output}]palettegen=stats_mode=diff[palette_{output}];\
+                 [frames_{output}][palette_{output}]\
+                 paletteuse=dither=bayer:bayer_scale=5:diff_mode=rectangle[{output}]",
+                input = input_label,
+                fps = fps,
+                output = output_label
+            ),
+            Self::Webp | Self::Apng => {
+                format!("[{}]fps={}[{}]", input_label, fps, output_label)
+            }
+        }
+    }
+
+    /// Add the encoder and loop arguments of the format
+    ///
+    /// Audio, subtitles and chapters are dropped, since animated formats can't hold them.
+    pub fn apply_ffmpeg_args(&self, encoder: &str, loop_count: u32, cmd: &mut FfmpegCommand) {
+        cmd.args(["-map_chapters", "-1", "-c:v", encoder]);
+
+        match self {
+            // The GIF muxer counts the repeats after the first play, where -1 plays once
+            Self::Gif => {
+                let repeat_count = match loop_count {
+                    0 => 0,
+                    1 => -1,
+                    _ => loop_count as i64 - 1,
+                };
+                cmd.args(["-loop", &repeat_count.to_string()]);
+            }
+            Self::Webp => {
+                cmd.args(["-loop", &loop_count.to_string()]);
+            }
+            Self::Apng => {
+                cmd.args(["-plays", &loop_count.to_string()]);
+            }
+        }
+    }
+}
+
+/// Get the frame rate of the outputs, `None` keeps the frame rate of each video
+pub fn output_fps(video_settings: &VideoSettings) -> Option<f64> {
+    video_settings.target_fps.or_else(|| {
+        AnimatedFormat::from_extension(&video_settings.format).map(|_| DEFAULT_ANIMATION_FPS)
+    })
+}
@@ -0,0 +1,190 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use ts_rs::TS;
+
+use crate::shared::external_command::ffmpeg_command;
+use crate::shared::ffmpeg_capabilities::FFMPEG_CAPABILITIES;
+use crate::video::video_codecs::VIDEO_CODEC_REGISTRY;
+
+/// Render device which VAAPI encoders upload the frames to
+pub const VAAPI_DEVICE: &str = "/dev/dri/renderD128";
+
+/// Hardware encoder family which encodes the videos
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum HardwareAcceleration {
+    /// Use the first hardware encoder which works on this machine
+    Auto,
+    /// NVIDIA GPUs
+    Nvenc,
+    /// Intel Quick Sync Video
+    Qsv,
+    /// AMD GPUs
+    Amf,
+    /// Video Acceleration API on Linux
+    Vaapi,
+    /// Encode with the configured encoder
+    Off,
+}
+
+/// Hardware encoder families in the order `Auto` tries them
+const HARDWARE_ACCELERATIONS: [HardwareAcceleration; 4] = [
+    HardwareAcceleration::Nvenc,
+    HardwareAcceleration::Qsv,
+    HardwareAcceleration::Amf,
+    HardwareAcceleration::Vaapi,
+];
+
+impl HardwareAcceleration {
+    /// Suffix of the FFmpeg encoders of the family, e.g. `h264_nvenc`
+    fn encoder_suffix(&self) -> Option<&'static str> {
+        match self {
+            Self::Nvenc => Some("_nvenc"),
+            Self::Qsv => Some("_qsv"),
+            Self::Amf => Some("_amf"),
+            Self::Vaapi => Some("_vaapi"),
+            Self::Auto | Self::Off => None,
+        }
+    }
+
+    /// Get the encoder of the family for a codec or encoder, without checking if it works
+    fn encoder_for_codec(&self, codec_or_encoder: &str) -> Option<&'static str> {
+        let suffix = self.encoder_suffix()?;
+        let codec = VIDEO_CODEC_REGISTRY
+            .get_codec_by_encoder(codec_or_encoder)
+            .or_else(|| VIDEO_CODEC_REGISTRY.get_codec_by_name(codec_or_encoder))?;
+
+        codec
+            .encoders
+            .iter()
+            .find(|encoder| encoder.ends_with(suffix))
+            .copied()
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref HARDWARE_ENCODER_MANAGER: Mutex<HardwareEncoderManager> =
+        Mutex::new(HardwareEncoderManager::new());
+}
+
+/// Detects which hardware encoders work on this machine
+///
+/// FFmpeg lists every encoder it was built with, also when the hardware or its driver is
+/// missing, so each listed hardware encoder is checked with a trial encode. The results are kept
+/// for the lifetime of the app.
+pub struct HardwareEncoderManager {
+    trial_results: HashMap<&'static str, bool>,
+}
+
+impl HardwareEncoderManager {
+    fn new() -> Self {
+        Self {
+            trial_results: HashMap::new(),
+        }
+    }
+
+    /// Select the hardware encoder for a codec or encoder, `None` when no matching hardware
+    /// encoder works on this machine
+    pub fn select_encoder(
+        codec_or_encoder: &str,
+        hardware_acceleration: HardwareAcceleration,
+    ) -> Option<&'static str> {
+        let accelerations: &[HardwareAcceleration] = match hardware_acceleration {
+            HardwareAcceleration::Off => &[],
+            HardwareAcceleration::Auto => &HARDWARE_ACCELERATIONS,
+            _ => std::slice::from_ref(&hardware_acceleration),
+        };
+
+        accelerations
+            .iter()
+            .filter_map(|acceleration| acceleration.encoder_for_codec(codec_or_encoder))
+            .find(|encoder| Self::is_encoder_available(encoder))
+    }
+
+    /// Get the hardware encoder families which work on this machine for a codec
+    pub fn available_accelerations(codec_or_encoder: &str) -> Vec<HardwareAcceleration> {
+        HARDWARE_ACCELERATIONS
+            .into_iter()
+            .filter(|acceleration| {
+                acceleration
+                    .encoder_for_codec(codec_or_encoder)
+                    .is_some_and(Self::is_encoder_available)
+            })
+            .collect()
+    }
+
+    /// Check if an encoder is included in FFmpeg and passes a trial encode
+    fn is_encoder_available(encoder: &'static str) -> bool {
+        if !FFMPEG_CAPABILITIES.has_encoder(encoder) {
+            return false;
+        }
+
+        if let Some(&is_available) = HARDWARE_ENCODER_MANAGER
+            .lock()
+            .unwrap()
+            .trial_results
+            .get(encoder)
+        {
+            return is_available;
+        }
+
+        // The lock isn't held during the trial, so trials of other encoders can run meanwhile
+        let is_available = run_trial_encode(encoder);
+        HARDWARE_ENCODER_MANAGER
+            .lock()
+            .unwrap()
+            .trial_results
+            .insert(encoder, is_available);
+        is_available
+    }
+}
+
+/// Encode a few frames of a generated video with the encoder, discarding the output
+fn run_trial_encode(encoder: &str) -> bool {
+    let mut cmd = match ffmpeg_command() {
+        Ok(cmd) => cmd,
+        Err(e) => {
+            warn!("Failed to run the trial encode of {}: {}", encoder, e);
+            return false;
+        }
+    };
+
+    if is_vaapi_encoder(encoder) {
+        cmd.args(["-vaapi_device", VAAPI_DEVICE]);
+    }
+    cmd.args([
+        "-v",
+        "error",
+        "-f",
+        "lavfi",
+        "-i",
+        "color=black:s=256x256:r=25",
+    ]);
+    if is_vaapi_encoder(encoder) {
+        cmd.args(["-vf", "format=nv12,hwupload"]);
+    }
+    cmd.args(["-frames:v", "5", "-c:v", encoder, "-f", "null", "-"]);
+
+    let is_available = cmd
+        .as_inner_mut()
+        .output()
+        .is_ok_and(|output| output.status.success());
+    info!(
+        "Hardware encoder {} is {}",
+        encoder,
+        if is_available {
+            "available"
+        } else {
+            "not available"
+        }
+    );
+    is_available
+}
+
+/// VAAPI encoders need the frames uploaded to the render device
+pub fn is_vaapi_encoder(encoder: &str) -> bool {
+    encoder.ends_with("_vaapi")
+}
@@ -1,5 +1,11 @@
+pub mod animated_export;
+pub mod hardware_encoders;
+pub mod poster_frame;
 pub mod video_codecs;
+pub mod video_compatibility;
+pub mod video_failure_artifacts;
 pub mod video_formats;
 pub mod video_handler;
+pub mod video_streams;
 pub mod video_structs;
 pub mod video_validator;
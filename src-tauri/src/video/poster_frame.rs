@@ -0,0 +1,70 @@
+use log::warn;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use crate::shared::external_command::ffmpeg_command;
+use crate::shared::run_report::RunReportManager;
+use crate::video::video_structs::Video;
+use crate::VideoSettings;
+
+/// Margin before the end of an output video, since seeking to its very end yields no frame
+const POSTER_END_MARGIN_SECONDS: f64 = 0.1;
+
+/// Get the path of the poster of an output video, e.g. `clip_poster.jpg` for `clip.mp4`
+pub fn poster_path(output_path: &Path) -> PathBuf {
+    let file_stem = output_path
+        .file_stem()
+        .map(|file_stem| file_stem.to_string_lossy())
+        .unwrap_or_default();
+    output_path.with_file_name(format!("{}_poster.jpg", file_stem))
+}
+
+/// Write a JPEG poster of the frame at the poster timestamp next to an output video
+///
+/// The frame is taken from the output, so it has the logo and the other settings applied. A
+/// failed poster only adds a warning, since the video itself was written.
+pub fn write_poster_frame(video: &Video, output_path: &Path, video_settings: &VideoSettings) {
+    let Some(poster_timestamp) = video_settings.poster_timestamp else {
+        return;
+    };
+
+    // Timestamps after the end of the output, e.g. of short or trimmed videos, use its last frame
+    let (trim_start, trim_end) = video_settings.trim_range(video.duration);
+    let output_duration = trim_end - trim_start;
+    let timestamp =
+        poster_timestamp.clamp(0.0, (output_duration - POSTER_END_MARGIN_SECONDS).max(0.0));
+
+    if let Err(e) = run_poster_command(output_path, timestamp) {
+        let message = format!(
+            "Failed to write the poster of {}: {}",
+            output_path.display(),
+            e
+        );
+        warn!("{}", message);
+        RunReportManager::add_warning(message);
+    }
+}
+
+fn run_poster_command(
+    output_path: &Path,
+    timestamp: f64,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut cmd = ffmpeg_command()?;
+    cmd.args(["-v", "error", "-y", "-ss", &timestamp.to_string()]);
+    cmd.input(output_path.to_str().ok_or("Invalid output file path")?);
+    cmd.args(["-frames:v", "1", "-q:v", "2"]);
+    cmd.output(
+        poster_path(output_path)
+            .to_str()
+            .ok_or("Invalid poster file path")?,
+    );
+
+    let output = cmd.as_inner_mut().output()?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr)
+            .trim()
+            .to_string()
+            .into());
+    }
+    Ok(())
+}
@@ -1,9 +1,178 @@
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use ts_rs::TS;
 
 use crate::shared::ffmpeg_structs::{Codec, CodecSupport, CodecType};
 
 pub type VideoCodec = Codec;
 
+/// Encoder independent speed setting, translated to the preset options of each encoder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum EncoderSpeed {
+    Fastest,
+    Fast,
+    Medium,
+    Slow,
+    Slowest,
+}
+
+/// How the encoder decides the bitrate of the video
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum VideoQualityMode {
+    /// Keep the rate control of the encoder
+    EncoderDefault,
+    /// Encode every scene at the CRF quality level, the bitrate follows the content
+    ConstantQuality,
+    /// Encode at the average bitrate
+    TargetBitrate,
+    /// Encode at the CRF quality level, without exceeding the bitrate
+    ConstrainedQuality,
+}
+
+/// How an encoder limits the bitrate when encoding at a quality level
+#[derive(Clone, Copy)]
+enum BitrateCap {
+    /// `-maxrate`, with a buffer of two seconds
+    MaxRate,
+    /// `-b:v`, which is the maximum bitrate in the constant quality mode of the encoder and
+    /// must be 0 for an unconstrained quality
+    Bitrate,
+}
+
+/// Encoders and the arguments which set their quality level, `{}` is replaced by the CRF value
+type EncoderQualityArgs = (&'static [&'static str], &'static [&'static str], BitrateCap);
+
+const ENCODER_QUALITY_ARGS: &[EncoderQualityArgs] = &[
+    (
+        &["libx264", "libx264rgb", "libx265", "libsvtav1"],
+        &["-crf", "{}"],
+        BitrateCap::MaxRate,
+    ),
+    (
+        &["libaom-av1", "libvpx", "libvpx-vp9"],
+        &["-crf", "{}"],
+        BitrateCap::Bitrate,
+    ),
+    (
+        &["h264_nvenc", "hevc_nvenc", "av1_nvenc"],
+        &["-rc", "vbr", "-cq", "{}", "-b:v", "0"],
+        BitrateCap::MaxRate,
+    ),
+    (
+        &["h264_qsv", "hevc_qsv", "av1_qsv", "vp9_qsv", "mpeg2_qsv"],
+        &["-global_quality", "{}"],
+        BitrateCap::MaxRate,
+    ),
+    (
+        &["h264_amf", "hevc_amf", "av1_amf"],
+        &["-rc", "cqp", "-qp_i", "{}", "-qp_p", "{}"],
+        BitrateCap::MaxRate,
+    ),
+];
+
+/// Encoders and their arguments for every speed, ordered from `Fastest` to `Slowest`
+type EncoderSpeedPresets = (&'static [&'static str], [&'static [&'static str]; 5]);
+
+const ENCODER_SPEED_PRESETS: &[EncoderSpeedPresets] = &[
+    (
+        &["libx264", "libx264rgb", "libx265"],
+        [
+            &["-preset", "ultrafast"],
+            &["-preset", "veryfast"],
+            &["-preset", "medium"],
+            &["-preset", "slow"],
+            &["-preset", "veryslow"],
+        ],
+    ),
+    (
+        &["h264_nvenc", "hevc_nvenc", "av1_nvenc"],
+        [
+            &["-preset", "p1"],
+            &["-preset", "p3"],
+            &["-preset", "p4"],
+            &["-preset", "p6"],
+            &["-preset", "p7"],
+        ],
+    ),
+    (
+        &["h264_qsv", "hevc_qsv", "av1_qsv", "vp9_qsv", "mpeg2_qsv"],
+        [
+            &["-preset", "veryfast"],
+            &["-preset", "faster"],
+            &["-preset", "medium"],
+            &["-preset", "slower"],
+            &["-preset", "veryslow"],
+        ],
+    ),
+    (
+        &["h264_amf", "hevc_amf", "av1_amf"],
+        [
+            &["-quality", "speed"],
+            &["-quality", "speed"],
+            &["-quality", "balanced"],
+            &["-quality", "quality"],
+            &["-quality", "quality"],
+        ],
+    ),
+    (
+        &["libsvtav1"],
+        [
+            &["-preset", "12"],
+            &["-preset", "10"],
+            &["-preset", "8"],
+            &["-preset", "5"],
+            &["-preset", "2"],
+        ],
+    ),
+    (
+        &["libaom-av1"],
+        [
+            &["-cpu-used", "8"],
+            &["-cpu-used", "6"],
+            &["-cpu-used", "4"],
+            &["-cpu-used", "2"],
+            &["-cpu-used", "0"],
+        ],
+    ),
+    (
+        &["libvpx", "libvpx-vp9"],
+        [
+            &["-deadline", "realtime", "-cpu-used", "8"],
+            &["-deadline", "good", "-cpu-used", "4"],
+            &["-deadline", "good", "-cpu-used", "2"],
+            &["-deadline", "good", "-cpu-used", "1"],
+            &["-deadline", "best", "-cpu-used", "0"],
+        ],
+    ),
+];
+
+/// Encoders which write 10-bit video with HDR colors, and the pixel format they encode it in
+const HDR_ENCODER_PIXEL_FORMATS: &[(&str, &str)] = &[
+    ("libx265", "yuv420p10le"),
+    ("libsvtav1", "yuv420p10le"),
+    ("libaom-av1", "yuv420p10le"),
+    ("libvpx-vp9", "yuv420p10le"),
+    ("hevc_nvenc", "p010le"),
+    ("av1_nvenc", "p010le"),
+    ("hevc_qsv", "p010le"),
+    ("av1_qsv", "p010le"),
+];
+
+/// Codecs which encode with full chroma resolution, every other codec is expected to subsample
+/// chroma (e.g. `yuv420p`) by default
+const FULL_CHROMA_CODECS: &[&VideoCodec] = &[
+    &video_codec::APNG,
+    &video_codec::GIF,
+    &video_codec::QTRLE,
+    &video_codec::V308,
+    &video_codec::V408,
+    &video_codec::V410,
+];
+
 pub mod video_codec {
     use super::{CodecSupport, CodecType, VideoCodec};
 
@@ -50,6 +219,7 @@ pub mod video_codec {
         &["libaom-av1", "av1", "av1_cuvid", "av1_qsv", "av1_amf"],
         &[
             "libaom-av1",
+            "libsvtav1",
             "av1_nvenc",
             "av1_qsv",
             "av1_amf",
@@ -829,6 +999,139 @@ impl VideoCodecRegistry {
         }
     }
 
+    /// Get the encoders to try in order for a codec or encoder setting
+    ///
+    /// An encoder setting falls back to the encoders listed after it, ending at the software
+    /// encoder, which is always the first encoder of a codec. A codec setting uses FFmpeg's
+    /// default encoder for the codec before falling back to the software encoder.
+    pub fn get_encoder_fallback_chain(&self, codec_or_encoder: &str) -> Vec<&'static str> {
+        let mut encoder_chain = Vec::new();
+
+        if let Some(codec) = self.get_codec_by_encoder(codec_or_encoder) {
+            let encoder_index = codec
+                .encoders
+                .iter()
+                .position(|encoder| encoder.eq_ignore_ascii_case(codec_or_encoder))
+                .unwrap_or(0);
+            encoder_chain.extend_from_slice(&codec.encoders[encoder_index..]);
+        } else if let Some(codec) = self.get_codec_by_name(codec_or_encoder) {
+            encoder_chain.push(codec.name);
+        }
+
+        if let Some(software_encoder) = self
+            .get_codec_by_encoder(codec_or_encoder)
+            .or_else(|| self.get_codec_by_name(codec_or_encoder))
+            .and_then(|codec| codec.encoders.first())
+        {
+            if !encoder_chain.contains(software_encoder) {
+                encoder_chain.push(software_encoder);
+            }
+        }
+
+        encoder_chain
+    }
+
+    /// Get the FFmpeg arguments which configure the speed of an encoder
+    ///
+    /// A codec name resolves to its software encoder, which FFmpeg uses by default. `None` when
+    /// the encoder doesn't have a speed preset.
+    pub fn get_encoder_speed_args(
+        &self,
+        codec_or_encoder: &str,
+        speed: EncoderSpeed,
+    ) -> Option<&'static [&'static str]> {
+        let encoder = self.resolve_encoder(codec_or_encoder)?;
+
+        ENCODER_SPEED_PRESETS
+            .iter()
+            .find(|(encoders, _)| {
+                encoders
+                    .iter()
+                    .any(|preset_encoder| preset_encoder.eq_ignore_ascii_case(encoder))
+            })
+            .map(|(_, speed_args)| speed_args[speed as usize])
+    }
+
+    /// Get the FFmpeg arguments which configure the rate control of an encoder
+    ///
+    /// The CRF value is passed on as is, so it uses the quality scale of the encoder, e.g. 0-51
+    /// for libx264 and 0-63 for AV1 and VP9. `None` when the encoder doesn't support the mode.
+    pub fn get_encoder_quality_args(
+        &self,
+        codec_or_encoder: &str,
+        quality_mode: VideoQualityMode,
+        crf: u32,
+        bitrate_kbps: u32,
+    ) -> Option<Vec<String>> {
+        let bitrate = format!("{}k", bitrate_kbps);
+        if quality_mode == VideoQualityMode::EncoderDefault {
+            return Some(Vec::new());
+        }
+        if quality_mode == VideoQualityMode::TargetBitrate {
+            return Some(vec!["-b:v".to_string(), bitrate]);
+        }
+
+        let encoder = self.resolve_encoder(codec_or_encoder)?;
+        let (_, quality_args, bitrate_cap) =
+            ENCODER_QUALITY_ARGS.iter().find(|(encoders, ..)| {
+                encoders
+                    .iter()
+                    .any(|quality_encoder| quality_encoder.eq_ignore_ascii_case(encoder))
+            })?;
+
+        let mut args: Vec<String> = quality_args
+            .iter()
+            .map(|arg| arg.replace("{}", &crf.to_string()))
+            .collect();
+        match (quality_mode, bitrate_cap) {
+            (VideoQualityMode::ConstrainedQuality, BitrateCap::MaxRate) => {
+                let buffer_size = format!("{}k", bitrate_kbps.saturating_mul(2));
+                args.extend(["-maxrate".to_string(), bitrate, "-bufsize".to_string()]);
+                args.push(buffer_size);
+            }
+            (VideoQualityMode::ConstrainedQuality, BitrateCap::Bitrate) => {
+                args.extend(["-b:v".to_string(), bitrate]);
+            }
+            (_, BitrateCap::Bitrate) => args.extend(["-b:v".to_string(), "0".to_string()]),
+            (_, BitrateCap::MaxRate) => {}
+        }
+
+        Some(args)
+    }
+
+    /// Get the 10-bit pixel format an encoder writes HDR video in, `None` when the encoder can't
+    /// write HDR video
+    pub fn get_hdr_pixel_format(&self, codec_or_encoder: &str) -> Option<&'static str> {
+        let encoder = self.resolve_encoder(codec_or_encoder)?;
+        HDR_ENCODER_PIXEL_FORMATS
+            .iter()
+            .find(|(hdr_encoder, _)| hdr_encoder.eq_ignore_ascii_case(encoder))
+            .map(|(_, pixel_format)| *pixel_format)
+    }
+
+    /// Resolve a codec name to its software encoder, which FFmpeg uses by default
+    pub fn resolve_encoder<'a>(&self, codec_or_encoder: &'a str) -> Option<&'a str> {
+        match self.get_codec_by_name(codec_or_encoder) {
+            Some(codec) if self.get_codec_by_encoder(codec_or_encoder).is_none() => {
+                codec.encoders.first().copied()
+            }
+            _ => Some(codec_or_encoder),
+        }
+    }
+
+    /// Check if a codec or encoder subsamples chroma, which requires even overlay dimensions
+    pub fn subsamples_chroma(&self, codec_or_encoder: &str) -> bool {
+        let codec = self
+            .get_codec_by_name(codec_or_encoder)
+            .or_else(|| self.get_codec_by_encoder(codec_or_encoder));
+
+        codec.is_none_or(|codec| {
+            !FULL_CHROMA_CODECS
+                .iter()
+                .any(|full_chroma_codec| full_chroma_codec.name == codec.name)
+        })
+    }
+
     pub fn get_available_decoders(&self, codec_name: &str) -> Vec<&'static str> {
         if let Some(codec) = self.get_codec_by_name(codec_name) {
             codec.decoders.to_vec()
@@ -0,0 +1,177 @@
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use ts_rs::TS;
+
+use crate::shared::ffmpeg_capabilities::FFMPEG_CAPABILITIES;
+use crate::shared::ffprobe::pixel_format_bit_depth;
+use crate::shared::run_report::RunReportManager;
+use crate::video::video_codecs::VIDEO_CODEC_REGISTRY;
+use crate::video::video_formats::VIDEO_FORMAT_REGISTRY;
+use crate::VideoSettings;
+
+/// A combination of video settings which fails when the videos are processed
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct VideoSettingsConflict {
+    pub message: String,
+    /// Codec which the format holds, which replaces the configured codec when processing
+    pub suggested_codec: Option<String>,
+}
+
+/// Pixel formats which the outputs are encoded in when the pixel format setting is automatic, in
+/// order of preference. 4:2:0 is the format which every player decodes.
+const AUTO_PIXEL_FORMATS: [&str; 2] = ["yuv420p", "nv12"];
+
+/// Pixel formats which sources with more than 8 bits per color channel are encoded in, in order
+/// of preference, so they aren't reduced to 8 bits when the encoder can write more
+const AUTO_HIGH_BIT_DEPTH_PIXEL_FORMATS: [&str; 2] = ["yuv420p10le", "p010le"];
+
+/// Find the combinations of the format, codec and pixel format settings which FFmpeg can't write
+pub fn find_video_settings_conflicts(video_settings: &VideoSettings) -> Vec<VideoSettingsConflict> {
+    let format = &video_settings.format;
+    if !VIDEO_FORMAT_REGISTRY.is_supported_for_writing(format) {
+        return vec![VideoSettingsConflict {
+            message: format!("Videos can't be written in the {} format", format),
+            suggested_codec: None,
+        }];
+    }
+
+    let mut conflicts = Vec::new();
+
+    // Unknown codecs are left to FFmpeg, which knows more codecs than the registry
+    let codec = VIDEO_CODEC_REGISTRY
+        .get_codec_by_encoder(&video_settings.codec)
+        .or_else(|| VIDEO_CODEC_REGISTRY.get_codec_by_name(&video_settings.codec));
+    if codec
+        .is_some_and(|codec| !VIDEO_FORMAT_REGISTRY.is_video_codec_compatible(format, codec.name))
+    {
+        conflicts.push(VideoSettingsConflict {
+            message: format!(
+                "The {} format can't hold {} video",
+                format, video_settings.codec
+            ),
+            suggested_codec: suggest_codec(video_settings),
+        });
+    }
+
+    if let Some(pixel_format) = &video_settings.pixel_format {
+        let encoder = VIDEO_CODEC_REGISTRY
+            .resolve_encoder(&video_settings.codec)
+            .unwrap_or(&video_settings.codec);
+        if select_pixel_format(encoder, None, Some(pixel_format)).is_none() {
+            conflicts.push(VideoSettingsConflict {
+                message: format!(
+                    "The {} encoder can't write the {} pixel format",
+                    encoder, pixel_format
+                ),
+                suggested_codec: None,
+            });
+        }
+    }
+
+    conflicts
+}
+
+/// Select the pixel format which an encoder writes, `None` leaves the choice to FFmpeg
+///
+/// A requested pixel format is only used when the encoder accepts it. Without one, sources with
+/// more than 8 bits per color channel keep their bit depth and other sources are encoded in
+/// 4:2:0, so unusual source formats don't make outputs which browsers can't play.
+pub fn select_pixel_format(
+    encoder: &str,
+    source_pixel_format: Option<&str>,
+    requested_pixel_format: Option<&str>,
+) -> Option<String> {
+    let encoder_pixel_formats = FFMPEG_CAPABILITIES.get_encoder_pixel_formats(encoder);
+    let is_supported = |pixel_format: &str| {
+        encoder_pixel_formats
+            .iter()
+            .any(|encoder_pixel_format| encoder_pixel_format == pixel_format)
+    };
+
+    if let Some(requested_pixel_format) = requested_pixel_format {
+        // Encoders which don't list their pixel formats are left to check the format themselves
+        return (encoder_pixel_formats.is_empty() || is_supported(requested_pixel_format))
+            .then(|| requested_pixel_format.to_string());
+    }
+
+    let is_high_bit_depth =
+        source_pixel_format.is_some_and(|pixel_format| pixel_format_bit_depth(pixel_format) > 8);
+    AUTO_HIGH_BIT_DEPTH_PIXEL_FORMATS
+        .iter()
+        .filter(|_| is_high_bit_depth)
+        .chain(AUTO_PIXEL_FORMATS.iter())
+        .find(|pixel_format| is_supported(pixel_format))
+        .map(|pixel_format| pixel_format.to_string())
+}
+
+/// Select the pixel format which an encoder of the encoder fallback chain writes
+///
+/// Fallback encoders don't always accept the requested pixel format, which is then replaced with
+/// the automatic pixel format of its bit depth instead of leaving the choice to FFmpeg.
+pub fn select_encoder_pixel_format(
+    encoder: &str,
+    source_pixel_format: Option<&str>,
+    requested_pixel_format: Option<&str>,
+) -> Option<String> {
+    select_pixel_format(encoder, source_pixel_format, requested_pixel_format).or_else(|| {
+        requested_pixel_format.and_then(|requested_pixel_format| {
+            select_pixel_format(encoder, Some(requested_pixel_format), None)
+        })
+    })
+}
+
+/// Replace a codec which the format can't hold with the suggested codec, warning about the change
+///
+/// Fails when the conflict can't be corrected, e.g. when the format can't be written.
+pub fn correct_video_settings_conflicts(
+    video_settings: &VideoSettings,
+) -> Result<VideoSettings, Box<dyn Error + Send + Sync>> {
+    let mut corrected_settings = video_settings.clone();
+
+    for conflict in find_video_settings_conflicts(video_settings) {
+        let Some(suggested_codec) = conflict.suggested_codec else {
+            return Err(conflict.message.into());
+        };
+
+        let message = format!(
+            "{}, encoding with {} instead",
+            conflict.message, suggested_codec
+        );
+        warn!("{}", message);
+        RunReportManager::add_warning(message);
+        corrected_settings.codec = suggested_codec;
+    }
+
+    Ok(corrected_settings)
+}
+
+/// Suggest a codec the format holds, preferring the favorite codecs and codecs FFmpeg can encode
+fn suggest_codec(video_settings: &VideoSettings) -> Option<String> {
+    let compatible_codecs =
+        VIDEO_FORMAT_REGISTRY.get_compatible_video_codecs(&video_settings.format)?;
+    let encodable_codecs: Vec<&str> = compatible_codecs
+        .iter()
+        .copied()
+        .filter(|codec_name| {
+            VIDEO_CODEC_REGISTRY
+                .get_available_encoders(codec_name)
+                .iter()
+                .any(|encoder| FFMPEG_CAPABILITIES.has_encoder(encoder))
+        })
+        .collect();
+
+    encodable_codecs
+        .iter()
+        .find(|codec_name| {
+            video_settings
+                .codec_favorite_list
+                .iter()
+                .any(|favorite| favorite.eq_ignore_ascii_case(codec_name))
+        })
+        .or_else(|| encodable_codecs.first())
+        .or_else(|| compatible_codecs.first())
+        .map(|codec_name| codec_name.to_string())
+}
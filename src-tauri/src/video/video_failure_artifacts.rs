@@ -0,0 +1,97 @@
+use log::warn;
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    shared::{
+        external_command::ffmpeg_command,
+        ffmpeg_logger::{ffmpeg_logger, FfmpegProcessError},
+        ffprobe::run_ffprobe,
+    },
+    video::video_structs::Video,
+};
+
+/// Folder in the output directory which collects the diagnostics of failed videos
+const FAILURE_ARTIFACTS_FOLDER: &str = "_failed";
+
+/// Write the diagnostics of a video which failed to encode into a folder per file
+///
+/// The folder contains the ffprobe output of the input, the failed command with the FFmpeg
+/// errors and, when the failing timestamp is known, the frame at that timestamp. Returns the
+/// folder, which is referenced from the run report.
+pub fn capture_failure_artifacts(
+    video: &Video,
+    output_directory: &Path,
+    command_line: &str,
+    error: &(dyn Error + Send + Sync + 'static),
+) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+    let file_stem = video
+        .file_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or("Invalid file name")?;
+    let artifact_directory = output_directory
+        .join(FAILURE_ARTIFACTS_FOLDER)
+        .join(file_stem);
+    fs::create_dir_all(&artifact_directory)?;
+
+    let probe_result = run_ffprobe(&video.file_path, &[])?;
+    fs::write(
+        artifact_directory.join("probe.json"),
+        serde_json::to_string_pretty(&probe_result)?,
+    )?;
+
+    let process_error = error.downcast_ref::<FfmpegProcessError>();
+    let mut command_report = format!("{}\n\nError: {}\n", command_line, error);
+    if let Some(process_error) = process_error {
+        if let Some(last_timestamp) = process_error.last_timestamp {
+            command_report.push_str(&format!("Failed at: {:.3}s\n", last_timestamp));
+        }
+        if !process_error.error_messages.is_empty() {
+            command_report.push_str("\nFFmpeg errors:\n");
+            command_report.push_str(&process_error.error_messages.join("\n"));
+            command_report.push('\n');
+        }
+    }
+    fs::write(artifact_directory.join("command.txt"), command_report)?;
+
+    if let Some(last_timestamp) = process_error.and_then(|error| error.last_timestamp) {
+        let frame_path = artifact_directory.join("frame.png");
+        if let Err(e) = extract_frame(&video.file_path, last_timestamp, &frame_path) {
+            warn!(
+                "Failed to extract the failing frame of {}: {}",
+                video.file_path.display(),
+                e
+            );
+        }
+    }
+
+    Ok(artifact_directory)
+}
+
+/// Extract the frame of a video at a timestamp in seconds as an image
+fn extract_frame(
+    video_path: &Path,
+    timestamp: f64,
+    output_path: &Path,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut ffmpeg_command = ffmpeg_command()?;
+    ffmpeg_command.args([
+        "-y",
+        "-ss",
+        &format!("{:.3}", timestamp),
+        "-i",
+        video_path.to_str().ok_or("Invalid video file path")?,
+        "-frames:v",
+        "1",
+    ]);
+
+    let ffmpeg_child = ffmpeg_command
+        .output(output_path.to_str().ok_or("Invalid output path")?)
+        .spawn()?;
+
+    ffmpeg_logger(ffmpeg_child, None, None)
+}
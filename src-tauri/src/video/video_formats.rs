@@ -331,6 +331,83 @@ pub mod video_format {
     ];
 }
 
+/// Containers which only hold some video codecs, ordered by preference, other containers hold
+/// every codec FFmpeg can mux
+const CONTAINER_VIDEO_CODECS: &[(&str, &[&str])] = &[
+    (
+        "mp4",
+        &[
+            "h264",
+            "hevc",
+            "av1",
+            "vp9",
+            "vvc",
+            "mpeg4",
+            "mpeg2video",
+            "mpeg1video",
+            "mjpeg",
+        ],
+    ),
+    ("webm", &["vp9", "av1", "vp8"]),
+    ("ogv", &["theora", "vp8"]),
+    ("flv", &["h264", "flv1", "vp6", "flashsv", "flashsv2"]),
+    ("f4v", &["h264"]),
+    ("3gp", &["h264", "hevc", "mpeg4", "h263"]),
+    ("3g2", &["h264", "hevc", "mpeg4", "h263"]),
+    ("ivf", &["vp9", "av1", "vp8"]),
+    ("mpeg", &["mpeg2video", "mpeg1video"]),
+    ("vob", &["mpeg2video", "mpeg1video"]),
+    ("dvd", &["mpeg2video", "mpeg1video"]),
+    ("svcd", &["mpeg2video"]),
+    ("vcd", &["mpeg1video"]),
+    (
+        "mpegts",
+        &[
+            "h264",
+            "hevc",
+            "av1",
+            "vvc",
+            "mpeg2video",
+            "mpeg1video",
+            "mpeg4",
+            "vc1",
+            "dirac",
+        ],
+    ),
+    (
+        "mxf",
+        &[
+            "h264",
+            "mpeg2video",
+            "dnxhd",
+            "prores",
+            "dvvideo",
+            "vc1",
+            "ffv1",
+        ],
+    ),
+    ("gxf", &["mpeg2video", "mjpeg", "dvvideo"]),
+    ("dv", &["dvvideo"]),
+    ("y4m", &["rawvideo", "wrapped_avframe"]),
+    ("h264", &["h264"]),
+    ("hevc", &["hevc"]),
+    ("vvc", &["vvc"]),
+    ("mpeg1video", &["mpeg1video"]),
+    ("mpeg2video", &["mpeg2video"]),
+    ("mpeg4", &["mpeg4"]),
+    ("mjpeg", &["mjpeg"]),
+    ("smjpeg", &["mjpeg"]),
+    ("vc1", &["vc1"]),
+    ("dnxhd", &["dnxhd"]),
+    ("dirac", &["dirac"]),
+    ("rawvideo", &["rawvideo"]),
+    ("swf", &["flv1", "mjpeg", "flashsv"]),
+    ("gif", &["gif"]),
+    ("apng", &["apng"]),
+    ("webp", &["webp"]),
+    ("avif", &["av1"]),
+];
+
 pub struct VideoFormatRegistry {
     formats_by_name: HashMap<String, &'static VideoFormat>,
     formats_by_extension: HashMap<String, &'static VideoFormat>,
@@ -377,6 +454,23 @@ impl VideoFormatRegistry {
             .unwrap_or(false)
     }
 
+    /// Get the video codecs a container holds for an extension, ordered by preference
+    ///
+    /// `None` when the container holds every codec or the extension is unknown.
+    pub fn get_compatible_video_codecs(&self, extension: &str) -> Option<&'static [&'static str]> {
+        let format = self.get_format_by_extension(extension)?;
+        CONTAINER_VIDEO_CODECS
+            .iter()
+            .find(|(format_name, _)| *format_name == format.name)
+            .map(|(_, codec_names)| *codec_names)
+    }
+
+    /// Check if the container of an extension holds a video codec
+    pub fn is_video_codec_compatible(&self, extension: &str, codec_name: &str) -> bool {
+        self.get_compatible_video_codecs(extension)
+            .is_none_or(|codec_names| codec_names.contains(&codec_name))
+    }
+
     pub fn get_writable_formats(&self) -> Vec<&'static VideoFormat> {
         video_format::ALL
             .iter()
@@ -1,26 +1,111 @@
 use ffmpeg_sidecar::command::FfmpegCommand;
-use log::info;
+use log::{info, warn};
 use rayon::prelude::*;
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::{error::Error, fs::read_dir, path::Path};
 
-use crate::shared::ffmpeg_processor::spawn_ffmpeg_process;
+use crate::shared::background_mode::run_in_background_mode;
+use crate::shared::brand_kit::resolve_video_brand_kit;
+use crate::shared::disk_readers::DiskReaderManager;
+use crate::shared::disk_space::{check_free_space, estimate_video_output_size};
+use crate::shared::external_command::ffmpeg_command;
+use crate::shared::ffmpeg_command_history::FfmpegCommandHistoryManager;
+use crate::shared::ffmpeg_file_log::FfmpegLogDirectory;
+use crate::shared::ffmpeg_logger::{ffmpeg_logger, FfmpegProcessError};
+use crate::shared::ffmpeg_processor::{
+    finish_partial_outputs, format_command_line, spawn_ffmpeg_process,
+};
+use crate::shared::ffmpeg_script::{script_logo_directory, write_ffmpeg_script};
 use crate::shared::ffmpeg_structs::FfmpegBatchCommand;
-use crate::shared::file_utils::{clear_and_create_folder, get_relative_path};
-use crate::shared::logo_handler::handle_logos;
+use crate::shared::ffprobe::pixel_format_bit_depth;
+use crate::shared::file_utils::{
+    clear_and_create_folder, find_missing_output_files, get_relative_path, partial_output_path,
+    trash_and_create_folder, validate_directory_overlap,
+};
+use crate::shared::filename_template::FilenameTemplate;
+use crate::shared::filter_builder::{build_media_filter, collect_overlay_layers, ResizeFilter};
+use crate::shared::hdr::{apply_hdr_color_args, tone_map_filter};
+use crate::shared::job_manifest::{skip_finished_inputs, JobManifestManager};
+use crate::shared::job_queue::{try_lock_run, Job};
+use crate::shared::logo_handler::{find_logo, handle_logos, temp_logo_directory};
+use crate::shared::logo_overrides::{LogoOverride, LogoOverrides};
 use crate::shared::logo_structs::Logo;
-use crate::shared::media_structs::{Media, Resolution};
+use crate::shared::media_structs::{validate_export_sizes, Media, Resolution};
 use crate::shared::media_validator::{
-    create_media_from_paths_parallel, filter_valid_media_paths, read_media_paths_recursive,
-    sort_by_file_size,
+    create_media_from_paths_parallel, filter_valid_media_paths, handle_cloud_placeholders,
+    handle_tiny_files, partition_growing_files, read_media_paths_recursive,
+    skip_media_outside_input_limits, sort_by_file_size, wait_for_growing_files,
 };
+use crate::shared::output_collision::{CollisionStrategy, OutputPathClaims};
+use crate::shared::output_mapping::OutputMapping;
+use crate::shared::process_limiter::ProcessLimiter;
 use crate::shared::process_manager::{check_process_cancelled, ProcessManager};
-use crate::shared::progress_handler::{ProgressManager, ProgressMode};
+use crate::shared::processing_marker::{
+    read_container_comment, skip_marked_files, ProcessingMarker,
+};
+use crate::shared::progress_handler::{
+    ProgressMode, ProgressSink, ProgressStage, SilentProgressSink,
+};
+use crate::shared::project_config::resolve_video_project_config;
+use crate::shared::review_queue::review_flagged_media;
+use crate::shared::run_history::RunHistoryManager;
+use crate::shared::run_log::{is_run_log_path, RunLogManager};
+use crate::shared::run_plan::{PlannedFile, RunPlan};
+use crate::shared::run_report::{
+    FailedFile, FileReport, RunId, RunPhase, RunReport, RunReportManager,
+};
+use crate::shared::source_disposition::dispose_source_file;
+use crate::video::animated_export::{output_fps, AnimatedFormat, DEFAULT_ANIMATION_FPS};
+use crate::video::hardware_encoders::{
+    is_vaapi_encoder, HardwareAcceleration, HardwareEncoderManager, VAAPI_DEVICE,
+};
+use crate::video::poster_frame::{poster_path, write_poster_frame};
+use crate::video::video_codecs::{EncoderSpeed, VideoQualityMode, VIDEO_CODEC_REGISTRY};
+use crate::video::video_compatibility::{
+    correct_video_settings_conflicts, select_encoder_pixel_format,
+};
+use crate::video::video_failure_artifacts::capture_failure_artifacts;
 use crate::video::video_structs::Video;
 use crate::video::video_validator::VideoSettingsValidator;
 use crate::VideoSettings;
 
-pub fn handle_videos(video_settings: &VideoSettings) -> Result<(), Box<dyn Error + Send + Sync>> {
+pub fn handle_videos(
+    video_settings: &VideoSettings,
+    progress: &dyn ProgressSink,
+) -> Result<RunReport, Box<dyn Error + Send + Sync>> {
+    let video_settings = &resolve_video_project_config(video_settings)?;
+    let run_id = RunId::next();
+    let started_at = chrono::Local::now();
+    let result = run_in_background_mode(video_settings.background_mode, || {
+        run_video_processing(video_settings, run_id, progress)
+    });
+    RunHistoryManager::record_run(
+        Job::Videos(video_settings.clone()),
+        run_id,
+        started_at,
+        &result,
+    );
+
+    result
+}
+
+fn run_video_processing(
+    video_settings: &VideoSettings,
+    run_id: RunId,
+    progress: &dyn ProgressSink,
+) -> Result<RunReport, Box<dyn Error + Send + Sync>> {
+    let video_settings = &resolve_video_brand_kit(video_settings)?;
+    validate_export_sizes(&video_settings.export_sizes)?;
+    video_settings.layers.validate()?;
+    video_settings.input_filter()?;
+    video_settings.output_mapping()?;
+    validate_directory_overlap(
+        &video_settings.input_directory,
+        &video_settings.output_directory,
+        video_settings.clear_files_output_directory,
+    )?;
+
     info!(
         "Starting video processing with settings: {:?}",
         video_settings,
@@ -32,134 +117,328 @@ pub fn handle_videos(video_settings: &VideoSettings) -> Result<(), Box<dyn Error
     let input_directory = &video_settings.input_directory;
     let output_directory = &video_settings.output_directory;
 
-    let mut video_list;
+    DiskReaderManager::configure(input_directory, video_settings.max_disk_readers);
+    ProcessLimiter::configure(video_settings.max_concurrent_processes);
 
     let start_time = std::time::Instant::now();
 
-    ProgressManager::start_progress_with_terminal(
-        "Clearing and creating output folder... (Step 1/6)".to_string(),
+    RunReportManager::start_report(run_id, "videos");
+
+    // Warnings about corrected settings belong to the report, so it must be started first
+    let video_settings = &correct_video_settings_conflicts(video_settings)?;
+
+    progress.start(
+        "Clearing and creating output folder...".to_string(),
         None,
         Some("frames".to_string()),
         None,
         Some("videos".to_string()),
     );
+    progress.set_stage(ProgressStage::Scanning);
 
     check_process_cancelled()?;
 
+    if video_settings.encoder_speed != EncoderSpeed::Medium
+        && VIDEO_CODEC_REGISTRY
+            .get_encoder_speed_args(&video_settings.codec, video_settings.encoder_speed)
+            .is_none()
+    {
+        let message = format!(
+            "The encoder speed setting is not supported by {} and is ignored",
+            video_settings.codec
+        );
+        warn!("{}", message);
+        RunReportManager::add_warning(message);
+    }
+
+    if video_settings
+        .target_fps
+        .is_some_and(|target_fps| !target_fps.is_finite() || target_fps <= 0.0)
+    {
+        return Err("The target frame rate must be greater than 0".into());
+    }
+    video_settings.validate_trim()?;
+
+    if matches!(
+        video_settings.quality_mode,
+        VideoQualityMode::TargetBitrate | VideoQualityMode::ConstrainedQuality
+    ) && video_settings.bitrate_kbps == 0
+    {
+        return Err("The bitrate of the video quality mode must be greater than 0".into());
+    }
+    if VIDEO_CODEC_REGISTRY
+        .get_encoder_quality_args(
+            &video_settings.codec,
+            video_settings.quality_mode,
+            video_settings.crf,
+            video_settings.bitrate_kbps,
+        )
+        .is_none()
+    {
+        let message = format!(
+            "The video quality mode is not supported by {} and is ignored",
+            video_settings.codec
+        );
+        warn!("{}", message);
+        RunReportManager::add_warning(message);
+    }
+
+    if video_settings.hardware_acceleration != HardwareAcceleration::Off
+        && HardwareEncoderManager::select_encoder(
+            &video_settings.codec,
+            video_settings.hardware_acceleration,
+        )
+        .is_none()
+    {
+        let message = format!(
+            "No hardware encoder for {} works on this machine, encoding with the configured encoder",
+            video_settings.codec
+        );
+        warn!("{}", message);
+        RunReportManager::add_warning(message);
+    }
+
     if video_settings.clear_files_output_directory || !output_directory.exists() {
         let clear_folder_time = std::time::Instant::now();
-        clear_and_create_folder(output_directory).unwrap();
+        trash_and_create_folder(output_directory, is_run_log_path)?;
         info!(
             "Clearing and creating output directory took: {:?}",
             clear_folder_time.elapsed()
         );
     }
 
-    ProgressManager::set_status(
-        "Reading video paths from input directory... (Step 2/6)".to_string(),
+    JobManifestManager::start(
+        output_directory,
+        &ProcessingMarker::for_settings(video_settings)?,
+        true,
     );
+    let _run_log_guard = RunLogManager::start(output_directory, video_settings.write_run_log);
+    FfmpegCommandHistoryManager::start(video_settings.record_ffmpeg_commands);
+
+    progress.set_status("Reading video paths from input directory...".to_string());
     check_process_cancelled()?;
 
     let read_paths_time = std::time::Instant::now();
-    let valid_video_paths =
-        read_video_paths_from_input_directory(video_settings, input_directory, output_directory)?;
+    let valid_video_paths = discover_video_paths(
+        video_settings,
+        &JobManifestManager::finished_inputs(),
+        false,
+    )?;
     info!("Reading video paths took: {:?}", read_paths_time.elapsed());
+    RunReportManager::record_phase(RunPhase::Discovery, read_paths_time.elapsed());
 
     if valid_video_paths.is_empty() {
-        ProgressManager::set_status("No videos found in the input directory".to_string());
+        progress.set_status("No videos found in the input directory".to_string());
         info!("No videos found in the input directory, returning early.");
         info!("Total time: {:?}", start_time.elapsed());
-        return Ok(());
+        JobManifestManager::finish();
+        return RunReportManager::finish_completed_report();
     }
 
     check_process_cancelled()?;
 
-    ProgressManager::set_status("Creating video structs... (Step 3/6)".to_string());
-    let video_creation_time = std::time::Instant::now();
-    video_list = create_media_from_paths_parallel(&valid_video_paths, Video::new)?;
-    info!(
-        "Creating video structs took: {:?}",
-        video_creation_time.elapsed()
-    );
+    let (mut valid_video_paths, mut growing_video_paths) =
+        partition_growing_files(valid_video_paths)?;
 
-    if video_list.is_empty() {
-        ProgressManager::set_status("No valid videos could be loaded".to_string());
-        info!("No valid videos could be loaded, returning early.");
-        info!("Total time: {:?}", start_time.elapsed());
-        return Ok(());
+    // A run which stops on a collision waits for the files which are still being written first,
+    // so every collision is found before anything is processed
+    if video_settings.collision_strategy == CollisionStrategy::Error
+        && !growing_video_paths.is_empty()
+    {
+        progress.set_status(format!(
+            "Waiting for {} videos which are still being written...",
+            growing_video_paths.len()
+        ));
+        valid_video_paths.extend(wait_for_growing_files(std::mem::take(
+            &mut growing_video_paths,
+        ))?);
     }
 
-    check_process_cancelled()?;
+    let mut output_path_claims = OutputPathClaims::new();
+    let mut output_paths = if valid_video_paths.is_empty() {
+        Vec::new()
+    } else {
+        process_video_paths(
+            video_settings,
+            &valid_video_paths,
+            &mut output_path_claims,
+            progress,
+        )?
+    };
 
-    ProgressManager::set_status("Sorting videos by file size... (Step 4/6)".to_string());
-    let sort_start = std::time::Instant::now();
-    sort_by_file_size(&mut video_list);
+    // Files which were still being written are processed last, once they're complete. The
+    // deferred pass runs the stages from scanning again, see `ProgressStage`.
+    if !growing_video_paths.is_empty() {
+        progress.set_stage(ProgressStage::Scanning);
+        progress.set_status(format!(
+            "Waiting for {} videos which are still being written...",
+            growing_video_paths.len()
+        ));
+        let deferred_video_paths = wait_for_growing_files(growing_video_paths)?;
+        if !deferred_video_paths.is_empty() {
+            output_paths.extend(process_video_paths(
+                video_settings,
+                &deferred_video_paths,
+                &mut output_path_claims,
+                progress,
+            )?);
+        }
+    }
+
+    progress.set_stage(ProgressStage::Verifying);
+    let verification_start = std::time::Instant::now();
+    let missing_output_paths = find_missing_output_files(&output_paths);
+    for missing_output_path in &missing_output_paths {
+        log::warn!("Output video is missing: {}", missing_output_path.display());
+    }
     info!(
-        "Sorting videos by file size took: {:?}",
-        sort_start.elapsed()
+        "Verifying {} output videos took: {:?}",
+        output_paths.len(),
+        verification_start.elapsed()
+    );
+    RunReportManager::record_phase(RunPhase::Verification, verification_start.elapsed());
+    progress.finish();
+    RunReportManager::record_output_paths(
+        output_paths
+            .into_iter()
+            .filter(|output_path| !missing_output_paths.contains(output_path))
+            .collect(),
     );
 
-    check_process_cancelled()?;
+    info!("Total time: {:?}", start_time.elapsed());
+    JobManifestManager::finish();
+    RunReportManager::finish_completed_report()
+}
 
-    ProgressManager::set_status("Applying video settings... (Step 5/6)".to_string());
-    let apply_settings_start = std::time::Instant::now();
-    apply_video_settings_per_video(video_settings, &mut video_list)?;
-    info!(
-        "Applying video settings took: {:?}",
-        apply_settings_start.elapsed()
-    );
+/// Create the video structs for the paths and process them, returning the output paths
+fn process_video_paths(
+    video_settings: &VideoSettings,
+    video_paths: &[PathBuf],
+    output_path_claims: &mut OutputPathClaims,
+    progress: &dyn ProgressSink,
+) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+    let input_directory = &video_settings.input_directory;
+    let output_directory = &video_settings.output_directory;
+
+    let video_list = prepare_video_list(video_settings, video_paths, false, progress)?;
+    if video_list.is_empty() {
+        progress.set_status("No valid videos could be loaded".to_string());
+        info!("No valid videos could be loaded, skipping processing.");
+        return Ok(Vec::new());
+    }
 
-    ProgressManager::set_status("Processing logos... (Step 6/6)".to_string());
+    let bitrate_kbps = matches!(
+        video_settings.quality_mode,
+        VideoQualityMode::TargetBitrate | VideoQualityMode::ConstrainedQuality
+    )
+    .then_some(video_settings.bitrate_kbps);
+    check_free_space(
+        output_directory,
+        video_list
+            .iter()
+            .flat_map(|video| {
+                let (trim_start, trim_end) = video_settings.trim_range(video.duration);
+                let codec_type = VIDEO_CODEC_REGISTRY
+                    .get_codec_by_name(&video.codec)
+                    .or_else(|| VIDEO_CODEC_REGISTRY.get_codec_by_encoder(&video.codec))
+                    .map(|codec| codec.codec_type);
+                video
+                    .output_resolutions()
+                    .into_iter()
+                    .map(move |resolution| {
+                        estimate_video_output_size(
+                            &resolution,
+                            video.frame_count,
+                            trim_end - trim_start,
+                            bitrate_kbps,
+                            codec_type,
+                        )
+                    })
+            })
+            .sum(),
+        progress,
+    )?;
+
+    progress.set_stage(ProgressStage::LogoPrep);
+    progress.set_status("Processing logos...".to_string());
     let logo_processing_start = std::time::Instant::now();
-    let logo_list = process_logos_for_video_resolutions(video_settings, &video_list)?;
+    let logo_list = process_logos_for_video_resolutions(
+        video_settings,
+        &video_list,
+        &temp_logo_directory()?,
+        progress,
+    )?;
     info!(
         "Processing logos took: {:?}",
         logo_processing_start.elapsed()
     );
+    RunReportManager::record_phase(RunPhase::LogoPreparation, logo_processing_start.elapsed());
 
     check_process_cancelled()?;
 
     let total_frame_count: usize = video_list.iter().map(|video| video.frame_count).sum();
 
-    ProgressManager::set_status("Processing videos... (Step 7/7)".to_string());
-    ProgressManager::set_total(total_frame_count);
-    ProgressManager::set_alternative_total(video_list.len());
+    progress.set_stage(ProgressStage::Encoding);
+    progress.set_status("Processing videos...".to_string());
+    progress.add_total(total_frame_count);
+    progress.add_alternative_total(video_list.len());
     let video_processing_start = std::time::Instant::now();
 
-    process_videos_from_video_list(
+    let output_paths = process_videos_from_video_list(
         output_directory,
         video_list,
         logo_list,
         video_settings,
         input_directory,
+        output_path_claims,
+        progress,
     )?;
 
-    ProgressManager::finish_progress();
-
     info!(
         "Processing videos took: {:?}",
         video_processing_start.elapsed()
     );
+    RunReportManager::record_phase(RunPhase::Processing, video_processing_start.elapsed());
 
-    info!("Total time: {:?}", start_time.elapsed());
-
-    Ok(())
+    Ok(output_paths)
 }
 
 /// Apply the video settings per video in parallel
 fn apply_video_settings_per_video(
     video_settings: &VideoSettings,
+    logo_overrides: &LogoOverrides,
     video_list: &mut [Video],
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     check_process_cancelled()?;
 
+    let resize_target = video_settings.resize_target();
+    let export_resize_targets = video_settings.export_resize_targets();
+
     video_list.par_iter_mut().try_for_each(
         |video| -> Result<(), Box<dyn Error + Send + Sync>> {
             check_process_cancelled()?;
 
-            video.resize_dimensions(&video_settings.min_pixel_count);
+            video.export_resolutions = export_resize_targets
+                .iter()
+                .map(|export_resize_target| {
+                    export_resize_target.calculate_resolution(&video.resolution)
+                })
+                .collect();
+            video.resize_to_target(&resize_target);
             video.file_type = video_settings.format.clone();
             video.codec = video_settings.codec.clone();
+            video.logo_override = logo_overrides.for_media(&video.file_path);
+            warn_hdr_fallback(video, video_settings);
+
+            // The progress counts output frames, which change with the trim and frame rate
+            let (trim_start, trim_end) = video_settings.trim_range(video.duration);
+            let section_duration = trim_end - trim_start;
+            if let Some(output_fps) = output_fps(video_settings) {
+                video.frame_count = (section_duration * output_fps).round() as usize;
+            } else if video.duration > 0.0 {
+                video.frame_count =
+                    (video.frame_count as f64 * section_duration / video.duration).round() as usize;
+            }
             Ok(())
         },
     )?;
@@ -167,125 +446,1109 @@ fn apply_video_settings_per_video(
     Ok(())
 }
 
+/// Warn when an HDR video is converted to SDR against the settings, or without tone mapping
+fn warn_hdr_fallback(video: &Video, video_settings: &VideoSettings) {
+    if video.hdr_transfer.is_none() {
+        return;
+    }
+
+    let is_8_bit_requested = video_settings
+        .pixel_format
+        .as_deref()
+        .is_some_and(|pixel_format| pixel_format_bit_depth(pixel_format) <= 8);
+    let is_hdr_lost = video_settings.preserve_hdr
+        && (is_8_bit_requested
+            || VIDEO_CODEC_REGISTRY
+                .get_hdr_pixel_format(&video.codec)
+                .is_none());
+    let can_tone_map = tone_map_filter().is_some();
+    if !is_hdr_lost && can_tone_map {
+        return;
+    }
+
+    let message = format!(
+        "Converted the HDR video {} to SDR{}{}",
+        video.file_path.display(),
+        if can_tone_map {
+            ""
+        } else {
+            " without tone mapping"
+        },
+        if is_hdr_lost && is_8_bit_requested {
+            ", since the pixel format has 8 bits per color channel".to_string()
+        } else if is_hdr_lost {
+            format!(", since {} can't write HDR video", video.codec)
+        } else {
+            String::new()
+        }
+    );
+    warn!("{}", message);
+    RunReportManager::add_warning(message);
+}
+
+/// A video with everything needed to build its FFmpeg command, so the command can be rebuilt
+/// with another encoder
+struct VideoWorkUnit<'a> {
+    video: Video,
+    /// A logo and an output path per output resolution, in the same order as the resolutions
+    logos: Vec<Option<&'a Logo>>,
+    output_paths: Vec<PathBuf>,
+}
+
+/// Plan a run with the settings, returning the videos it would process and the outputs it would
+/// write without running FFmpeg
+///
+/// Only the steps which don't change any file are taken, so cloud placeholders aren't downloaded,
+/// tiny files aren't quarantined and flagged videos aren't reviewed.
+pub fn plan_videos(
+    video_settings: &VideoSettings,
+) -> Result<RunPlan, Box<dyn Error + Send + Sync>> {
+    let video_settings = &resolve_video_brand_kit(&resolve_video_project_config(video_settings)?)?;
+    validate_export_sizes(&video_settings.export_sizes)?;
+    video_settings.layers.validate()?;
+    video_settings.input_filter()?;
+    video_settings.output_mapping()?;
+    validate_directory_overlap(
+        &video_settings.input_directory,
+        &video_settings.output_directory,
+        video_settings.clear_files_output_directory,
+    )?;
+    video_settings.validate_trim()?;
+
+    // Holding the run lock keeps a run from starting while the plan reads the input directory
+    let _run_guard = try_lock_run()?;
+    ProcessManager::reset_cancel();
+
+    let (planned_videos, report) =
+        RunReportManager::collect_report("videos", || plan_video_run(video_settings))?;
+    let planned_files: Vec<PlannedFile> = planned_videos?
+        .videos
+        .into_iter()
+        .map(|(video, output_paths)| PlannedFile {
+            input_path: video.file_path,
+            output_paths,
+        })
+        .collect();
+
+    Ok(RunPlan {
+        media_type: report.media_type,
+        // Every video is processed by its own FFmpeg command
+        estimated_batch_count: planned_files.len(),
+        planned_files,
+        skipped_files: report.skipped_files,
+        warnings: report.warnings,
+    })
+}
+
+/// Write the FFmpeg commands a run with the settings would execute to a `.sh` or `.bat` script,
+/// so the videos can be processed on another machine, returning the number of commands
+///
+/// The logos are prepared into a folder next to the script. Every video is encoded with the
+/// software encoder of its codec, and the posters aren't part of the script.
+pub fn export_videos_script(
+    video_settings: &VideoSettings,
+    script_path: &Path,
+) -> Result<usize, Box<dyn Error + Send + Sync>> {
+    let video_settings = &resolve_video_brand_kit(&resolve_video_project_config(video_settings)?)?;
+    validate_export_sizes(&video_settings.export_sizes)?;
+    video_settings.layers.validate()?;
+    video_settings.input_filter()?;
+    video_settings.output_mapping()?;
+    validate_directory_overlap(
+        &video_settings.input_directory,
+        &video_settings.output_directory,
+        video_settings.clear_files_output_directory,
+    )?;
+    video_settings.validate_trim()?;
+
+    // Holding the run lock keeps a run from starting while the export reads the input directory
+    let _run_guard = try_lock_run()?;
+    ProcessManager::reset_cancel();
+
+    let (ffmpeg_batch_commands, _) = RunReportManager::collect_report("videos", || {
+        create_video_script_commands(video_settings, script_path)
+    })?;
+    let ffmpeg_batch_commands = ffmpeg_batch_commands?;
+
+    write_ffmpeg_script(
+        script_path,
+        &video_settings.input_directory,
+        &video_settings.output_directory,
+        &ffmpeg_batch_commands,
+    )?;
+    info!(
+        "Exported {} FFmpeg commands to {}",
+        ffmpeg_batch_commands.len(),
+        script_path.display()
+    );
+
+    Ok(ffmpeg_batch_commands.len())
+}
+
+/// Create the FFmpeg commands of a planned run, with the logos prepared next to the script
+fn create_video_script_commands(
+    video_settings: &VideoSettings,
+    script_path: &Path,
+) -> Result<Vec<FfmpegBatchCommand>, Box<dyn Error + Send + Sync>> {
+    let PlannedVideoRun {
+        video_settings,
+        videos,
+    } = plan_video_run(video_settings)?;
+
+    let video_list: Vec<Video> = videos.iter().map(|(video, _)| video.clone()).collect();
+    let logo_list = process_logos_for_video_resolutions(
+        &video_settings,
+        &video_list,
+        &script_logo_directory(script_path),
+        &SilentProgressSink,
+    )?;
+
+    videos
+        .iter()
+        .map(|(video, output_paths)| {
+            let logos: Vec<Option<&Logo>> = video
+                .output_resolutions()
+                .iter()
+                .map(|resolution| {
+                    logo_list.as_ref().and_then(|logo_list| {
+                        find_logo(logo_list, resolution, &video.logo_override)
+                    })
+                })
+                .collect();
+            // The machine which runs the script may not have the hardware encoder, the software
+            // encoder ends every fallback chain
+            let encoder_chain = get_encoder_chain(&video.codec, HardwareAcceleration::Off);
+            create_video_ffmpeg_command(
+                video,
+                &logos,
+                encoder_chain.last().copied().unwrap_or(&video.codec),
+                &video_settings,
+                output_paths,
+                None,
+            )
+        })
+        .collect()
+}
+
+/// The videos a run would process, with an output path per output resolution of each video
+struct PlannedVideoRun {
+    /// The settings with the conflicts corrected which the run would correct
+    video_settings: VideoSettings,
+    videos: Vec<(Video, Vec<PathBuf>)>,
+}
+
+fn plan_video_run(
+    video_settings: &VideoSettings,
+) -> Result<PlannedVideoRun, Box<dyn Error + Send + Sync>> {
+    let input_directory = &video_settings.input_directory;
+    let output_directory = &video_settings.output_directory;
+
+    DiskReaderManager::configure(input_directory, video_settings.max_disk_readers);
+
+    // A run which clears the output directory removes the manifest of an interrupted run as well
+    let finished_inputs = if video_settings.clear_files_output_directory {
+        HashSet::new()
+    } else {
+        JobManifestManager::read_finished_inputs(
+            output_directory,
+            &ProcessingMarker::for_settings(video_settings)?,
+            true,
+        )
+    };
+
+    // The corrections are reported as warnings of the plan
+    let mut video_settings = correct_video_settings_conflicts(video_settings)?;
+
+    // The outputs in an output directory which the run clears don't prevent processing a video
+    if video_settings.clear_files_output_directory {
+        video_settings.collision_strategy = CollisionStrategy::Overwrite;
+    }
+    let video_settings = &video_settings;
+
+    let video_paths = discover_video_paths(video_settings, &finished_inputs, true)?;
+    let (video_paths, growing_video_paths) = partition_growing_files(video_paths)?;
+
+    let mut video_list =
+        prepare_video_list(video_settings, &video_paths, true, &SilentProgressSink)?;
+
+    // A run processes the files which are still being written last, once they're complete
+    if !growing_video_paths.is_empty() {
+        RunReportManager::add_warning(format!(
+            "{} videos are still being written, a run processes them last",
+            growing_video_paths.len()
+        ));
+        video_list.extend(prepare_video_list(
+            video_settings,
+            &growing_video_paths,
+            true,
+            &SilentProgressSink,
+        )?);
+    }
+
+    let filename_template = FilenameTemplate::new(&video_settings.filename_template)?;
+    let output_mapping = video_settings.output_mapping()?;
+    let mut output_path_claims = OutputPathClaims::new();
+    let videos = video_list
+        .into_iter()
+        .map(|video| {
+            let output_paths = plan_video_output_paths(
+                &video,
+                video_settings,
+                &filename_template,
+                &output_mapping,
+                input_directory,
+                output_directory,
+                &mut output_path_claims,
+            )?;
+            Ok(output_paths.map(|output_paths| (video, output_paths)))
+        })
+        .filter_map(Result::transpose)
+        .collect::<Result<_, Box<dyn Error + Send + Sync>>>()?;
+
+    Ok(PlannedVideoRun {
+        video_settings: video_settings.clone(),
+        videos,
+    })
+}
+
+/// Read the video paths from the input directory and skip the files a run doesn't process
+///
+/// A dry run, e.g. of a plan, doesn't download cloud placeholders or quarantine tiny files.
+fn discover_video_paths(
+    video_settings: &VideoSettings,
+    finished_inputs: &HashSet<PathBuf>,
+    dry_run: bool,
+) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+    let input_directory = &video_settings.input_directory;
+    let output_directory = &video_settings.output_directory;
+
+    let video_paths =
+        read_video_paths_from_input_directory(video_settings, input_directory, output_directory)?;
+    let video_paths = skip_finished_inputs(video_paths, finished_inputs);
+    let video_paths = handle_cloud_placeholders(
+        video_paths,
+        video_settings.hydrate_cloud_placeholders && !dry_run,
+    )?;
+    let video_paths = handle_tiny_files(
+        video_paths,
+        video_settings.min_file_size,
+        video_settings.quarantine_tiny_files && !dry_run,
+        input_directory,
+        output_directory,
+    )?;
+    let video_paths = if video_settings.skip_marked_files {
+        let processing_marker = ProcessingMarker::for_settings(video_settings)?;
+        skip_marked_files(video_paths, &processing_marker, read_container_comment)?
+    } else {
+        video_paths
+    };
+
+    Ok(video_paths)
+}
+
+/// Create the video structs for the paths and apply the settings to them, sorted in the order
+/// they're processed
+///
+/// A dry run, e.g. of a plan, doesn't hold flagged videos for review.
+fn prepare_video_list(
+    video_settings: &VideoSettings,
+    video_paths: &[PathBuf],
+    dry_run: bool,
+    progress: &dyn ProgressSink,
+) -> Result<Vec<Video>, Box<dyn Error + Send + Sync>> {
+    progress.set_stage(ProgressStage::Probing);
+    progress.set_status("Creating video structs...".to_string());
+    let video_creation_time = std::time::Instant::now();
+    let mut video_list = create_media_from_paths_parallel(video_paths, Video::new)?;
+    info!(
+        "Creating video structs took: {:?}",
+        video_creation_time.elapsed()
+    );
+    RunReportManager::record_phase(RunPhase::StructCreation, video_creation_time.elapsed());
+
+    if video_list.is_empty() {
+        return Ok(video_list);
+    }
+
+    check_process_cancelled()?;
+
+    video_list =
+        skip_media_outside_input_limits(video_list, &VideoSettingsValidator::new(video_settings));
+
+    if video_settings.review_flagged_files && !dry_run {
+        video_list = review_flagged_media(video_list, video_settings.min_pixel_count, progress)?;
+    }
+
+    check_process_cancelled()?;
+
+    progress.set_status("Sorting videos by file size...".to_string());
+    let sort_start = std::time::Instant::now();
+    sort_by_file_size(&mut video_list);
+    info!(
+        "Sorting videos by file size took: {:?}",
+        sort_start.elapsed()
+    );
+
+    check_process_cancelled()?;
+
+    progress.set_status("Applying video settings...".to_string());
+    let apply_settings_start = std::time::Instant::now();
+    let logo_overrides = if video_settings.add_logo && video_settings.search_child_folders {
+        LogoOverrides::load(
+            &video_settings.input_directory,
+            video_list.iter().map(|video| video.file_path.as_path()),
+        )?
+    } else {
+        LogoOverrides::default()
+    };
+    apply_video_settings_per_video(video_settings, &logo_overrides, &mut video_list)?;
+    info!(
+        "Applying video settings took: {:?}",
+        apply_settings_start.elapsed()
+    );
+
+    Ok(video_list)
+}
+
+/// Process the first seconds of a single video with the settings, returning the path of the
+/// preview clip
+pub fn preview_video(
+    video_settings: &VideoSettings,
+    video_path: &Path,
+    duration_seconds: f64,
+) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+    if !duration_seconds.is_finite() || duration_seconds <= 0.0 {
+        return Err("Preview duration must be greater than 0 seconds".into());
+    }
+    let video_settings = &resolve_video_brand_kit(video_settings)?;
+    let video_settings = &correct_video_settings_conflicts(video_settings)?;
+    video_settings.validate_trim()?;
+    validate_export_sizes(&video_settings.export_sizes)?;
+    video_settings.layers.validate()?;
+
+    // A cancelled run keeps the cancel flag set until the next run, which would cancel the preview
+    if !RunReportManager::is_run_active() {
+        ProcessManager::clear();
+    }
+
+    let preview_directory = temp_preview_video_directory()?;
+    clear_and_create_folder(&preview_directory)?;
+
+    let mut video_list = vec![Video::new(video_path.to_path_buf())?];
+    apply_video_settings_per_video(video_settings, &LogoOverrides::default(), &mut video_list)?;
+    // Only the first export size is previewed
+    video_list[0].export_resolutions.truncate(1);
+
+    let logo_list = process_logos_for_video_resolutions(
+        video_settings,
+        &video_list,
+        &preview_directory.join("logos"),
+        &SilentProgressSink,
+    )?;
+    let video = video_list.remove(0);
+    let logos: Vec<Option<&Logo>> = video
+        .output_resolutions()
+        .iter()
+        .map(|resolution| {
+            logo_list
+                .as_ref()
+                .and_then(|logo_list| find_logo(logo_list, resolution, &video.logo_override))
+        })
+        .collect();
+
+    // Every preview gets its own name, so the UI doesn't play a cached earlier preview
+    let preview_path = preview_directory.join(format!(
+        "preview_{}.{}",
+        chrono::Local::now().format("%H%M%S%3f"),
+        video.file_type
+    ));
+
+    let encoder_chain = get_encoder_chain(&video.codec, video_settings.hardware_acceleration);
+
+    let mut last_error: Option<Box<dyn Error + Send + Sync>> = None;
+    for encoder in encoder_chain {
+        check_process_cancelled()?;
+
+        let mut ffmpeg_batch_command = create_video_ffmpeg_command(
+            &video,
+            &logos,
+            encoder,
+            video_settings,
+            std::slice::from_ref(&preview_path),
+            Some(duration_seconds),
+        )?;
+        match ffmpeg_logger(ffmpeg_batch_command.command.spawn()?, None, None) {
+            Ok(()) => {
+                finish_partial_outputs(&ffmpeg_batch_command.output_paths)?;
+                return Ok(preview_path);
+            }
+            Err(e) => {
+                check_process_cancelled()?;
+
+                warn!("Encoding the preview with {} failed: {}", encoder, e);
+                let _ = std::fs::remove_file(partial_output_path(&preview_path));
+                last_error = Some(e);
+            }
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| "No encoder available for the video".into()))
+}
+
+/// Folder of the preview clips, in the application root
+pub fn temp_preview_video_directory() -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+    let app_root = std::env::current_exe()?
+        .parent()
+        .ok_or("Failed to get application directory")?
+        .to_path_buf();
+
+    Ok(app_root.join("temp_preview_videos"))
+}
+
+/// Process the videos from the video list in parallel
+///
+/// Returns the paths of all output files that were written
 fn process_videos_from_video_list(
     output_directory: &Path,
     video_list: Vec<Video>,
     logo_list: Option<Vec<Logo>>,
     video_settings: &VideoSettings,
     input_directory: &Path,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
+    output_path_claims: &mut OutputPathClaims,
+    progress: &dyn ProgressSink,
+) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
     check_process_cancelled()?;
 
-    let mut ffmpeg_command_list: Vec<FfmpegBatchCommand> = Vec::new();
+    let filename_template = FilenameTemplate::new(&video_settings.filename_template)?;
+    let output_mapping = video_settings.output_mapping()?;
+    let ffmpeg_log_directory =
+        FfmpegLogDirectory::new(output_directory, video_settings.ffmpeg_log_level)?;
+    let mut work_units: Vec<VideoWorkUnit> = Vec::new();
 
     for video in video_list {
         check_process_cancelled()?;
 
-        let logo: Option<&Logo> = if let Some(ref logo_list) = logo_list {
-            logo_list
-                .iter()
-                .find(|logo| logo.compatible_image_resolution == video.resolution)
-        } else {
-            None
+        let output_resolutions = video.output_resolutions();
+        let logos: Vec<Option<&Logo>> = output_resolutions
+            .iter()
+            .map(|resolution| {
+                logo_list
+                    .as_ref()
+                    .and_then(|logo_list| find_logo(logo_list, resolution, &video.logo_override))
+            })
+            .collect();
+
+        let Some(output_paths) = plan_video_output_paths(
+            &video,
+            video_settings,
+            &filename_template,
+            &output_mapping,
+            input_directory,
+            output_directory,
+            output_path_claims,
+        )?
+        else {
+            continue;
         };
 
-        let final_output_directory =
-            if video_settings.keep_child_folders_structure_in_output_directory {
-                let relative_video_path = get_relative_path(input_directory, &video.file_path)
-                    .unwrap_or_else(|_| PathBuf::from(""));
-                let relative_dir_path = relative_video_path.parent().unwrap_or(Path::new(""));
-                output_directory.join(relative_dir_path)
-            } else {
-                output_directory.to_path_buf()
-            };
+        work_units.push(VideoWorkUnit {
+            video,
+            logos,
+            output_paths,
+        });
+    }
 
-        let batch_command = create_video_ffmpeg_command(&video, logo, &final_output_directory)?;
-        ffmpeg_command_list.push(batch_command);
+    // Create the output directories once every collision is resolved, templates can place files
+    // in subfolders
+    for output_path in work_units
+        .iter()
+        .flat_map(|work_unit| &work_unit.output_paths)
+    {
+        if let Some(output_directory) = output_path.parent() {
+            std::fs::create_dir_all(output_directory)?;
+        }
     }
 
+    let mut output_paths: Vec<PathBuf> = work_units
+        .iter()
+        .flat_map(|work_unit| work_unit.output_paths.iter().cloned())
+        .collect();
+    if video_settings.poster_timestamp.is_some() {
+        let poster_paths: Vec<PathBuf> = output_paths
+            .iter()
+            .map(|output_path| poster_path(output_path))
+            .collect();
+        output_paths.extend(poster_paths);
+    }
+
+    RunReportManager::record_planned_files(work_units.len());
+    JobManifestManager::record_planned_inputs(work_units.iter().map(|work_unit| {
+        (
+            work_unit.video.file_path.as_path(),
+            work_unit.output_paths.as_slice(),
+        )
+    }));
+
     // Execute FFmpeg commands in parallel
-    ffmpeg_command_list.into_iter().par_bridge().try_for_each(
-        |mut ffmpeg_batch_command| -> Result<(), Box<dyn Error + Send + Sync>> {
-            spawn_ffmpeg_process(&mut ffmpeg_batch_command, ProgressMode::PerFrame)?;
-            Ok(())
-        },
-    )?;
+    let failed_work_units: Vec<&VideoWorkUnit> = work_units
+        .par_iter()
+        .map(
+            |work_unit| -> Result<Option<&VideoWorkUnit>, Box<dyn Error + Send + Sync>> {
+                if let Err(e) = process_video_work_unit(
+                    work_unit,
+                    video_settings,
+                    ffmpeg_log_directory.as_ref(),
+                    progress,
+                    false,
+                ) {
+                    // A cancelled process fails as well, which must not be retried
+                    check_process_cancelled()?;
+
+                    warn!(
+                        "Processing {} failed, retrying it once the other videos are done: {}",
+                        work_unit.video.file_path.display(),
+                        e
+                    );
+                    return Ok(Some(work_unit));
+                }
+                Ok(None)
+            },
+        )
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+
+    // Failed videos are retried one at a time, since some only fail next to the other processes,
+    // e.g. when the memory of the GPU runs out
+    let mut failed_output_paths: Vec<&PathBuf> = Vec::new();
+    for work_unit in failed_work_units {
+        check_process_cancelled()?;
+        if let Err(e) = process_video_work_unit(
+            work_unit,
+            video_settings,
+            ffmpeg_log_directory.as_ref(),
+            progress,
+            true,
+        ) {
+            check_process_cancelled()?;
+
+            warn!(
+                "Skipping {}, it failed to process again: {}",
+                work_unit.video.file_path.display(),
+                e
+            );
+            failed_output_paths.extend(&work_unit.output_paths);
+        }
+    }
+
+    Ok(output_paths
+        .into_iter()
+        .filter(|output_path| {
+            !failed_output_paths.iter().any(|failed_output_path| {
+                *failed_output_path == output_path
+                    || poster_path(failed_output_path) == *output_path
+            })
+        })
+        .collect())
+}
+
+/// Get the path of every output of a video, in the same order as its output resolutions
+///
+/// Returns `None` when the video is skipped, since its outputs already exist.
+fn plan_video_output_paths(
+    video: &Video,
+    video_settings: &VideoSettings,
+    filename_template: &FilenameTemplate,
+    output_mapping: &OutputMapping,
+    input_directory: &Path,
+    output_directory: &Path,
+    output_path_claims: &mut OutputPathClaims,
+) -> Result<Option<Vec<PathBuf>>, Box<dyn Error + Send + Sync>> {
+    let mapped_output = output_mapping.find(&video.file_path, input_directory);
+    let final_output_directory = if let Some(mapped_output) = mapped_output {
+        output_directory.join(&mapped_output.subfolder)
+    } else if video_settings.keep_child_folders_structure_in_output_directory {
+        let relative_video_path = get_relative_path(input_directory, &video.file_path)
+            .unwrap_or_else(|_| PathBuf::from(""));
+        let relative_dir_path = relative_video_path.parent().unwrap_or(Path::new(""));
+        output_directory.join(relative_dir_path)
+    } else {
+        output_directory.to_path_buf()
+    };
+
+    let output_paths = video
+        .output_resolutions()
+        .iter()
+        .enumerate()
+        .map(|(size_index, resolution)| {
+            let relative_output_path =
+                match mapped_output.and_then(|output| output.file_stem.as_ref()) {
+                    Some(file_stem) => filename_template.render_with_stem(
+                        file_stem,
+                        Some(resolution),
+                        &video.file_type,
+                    )?,
+                    None => filename_template.render(
+                        &video.file_path,
+                        Some(resolution),
+                        &video.file_type,
+                    )?,
+                };
+            // Every export size is written to its own subfolder
+            let output_path = match video_settings.export_sizes.get(size_index) {
+                Some(export_size) => final_output_directory
+                    .join(&export_size.name)
+                    .join(relative_output_path),
+                None => final_output_directory.join(relative_output_path),
+            };
+            Ok(output_path)
+        })
+        .collect::<Result<_, Box<dyn Error + Send + Sync>>>()?;
 
+    let output_paths = output_path_claims
+        .resolve_output_collisions(output_paths, video_settings.collision_strategy)?;
+    if output_paths.is_none() {
+        RunReportManager::record_skipped_file(
+            video.file_path.clone(),
+            "The outputs already exist".to_string(),
+        );
+    }
+    Ok(output_paths)
+}
+
+/// Process a video and write the posters of its outputs
+///
+/// The failure is only reported on the final attempt, so a video which succeeds when retried
+/// isn't listed as failed.
+fn process_video_work_unit(
+    work_unit: &VideoWorkUnit,
+    video_settings: &VideoSettings,
+    ffmpeg_log_directory: Option<&FfmpegLogDirectory>,
+    progress: &dyn ProgressSink,
+    is_final_attempt: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    process_video_with_encoder_fallback(
+        work_unit,
+        video_settings,
+        ffmpeg_log_directory,
+        progress,
+        is_final_attempt,
+    )?;
+    for output_path in &work_unit.output_paths {
+        write_poster_frame(&work_unit.video, output_path, video_settings);
+    }
+    JobManifestManager::record_finished_inputs([work_unit.video.file_path.as_path()]);
+    dispose_source_file(
+        &work_unit.video.file_path,
+        &work_unit.output_paths,
+        video_settings.source_disposition,
+        &video_settings.input_directory,
+    );
     Ok(())
 }
 
+/// Process a video with the configured encoder, retrying with the next encoder of the codec
+/// when it can't be set up on this machine (e.g. an unsupported hardware encoder)
+///
+/// The failure diagnostics are only captured and reported when `record_failure` is set.
+fn process_video_with_encoder_fallback(
+    work_unit: &VideoWorkUnit,
+    video_settings: &VideoSettings,
+    ffmpeg_log_directory: Option<&FfmpegLogDirectory>,
+    progress: &dyn ProgressSink,
+    record_failure: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let encoder_chain =
+        get_encoder_chain(&work_unit.video.codec, video_settings.hardware_acceleration);
+
+    let mut last_error: Option<Box<dyn Error + Send + Sync>> = None;
+    let mut last_command_line = String::new();
+    for encoder in encoder_chain {
+        check_process_cancelled()?;
+
+        let mut ffmpeg_batch_command = create_video_ffmpeg_command(
+            &work_unit.video,
+            &work_unit.logos,
+            encoder,
+            video_settings,
+            &work_unit.output_paths,
+            None,
+        )?;
+
+        match spawn_ffmpeg_process(
+            &mut ffmpeg_batch_command,
+            ProgressMode::PerFrame,
+            progress,
+            ffmpeg_log_directory,
+        ) {
+            Ok(()) => {
+                if let Some(failed_error) = &last_error {
+                    let message = format!(
+                        "Encoded {} with fallback encoder {} after the configured encoder failed: {}",
+                        work_unit.video.file_path.display(),
+                        encoder,
+                        failed_error
+                    );
+                    warn!("{}", message);
+                    RunReportManager::add_warning(message);
+                }
+                for output_path in &work_unit.output_paths {
+                    RunReportManager::record_file(FileReport {
+                        output_path: output_path.clone(),
+                        encoder: Some(encoder.to_string()),
+                    });
+                }
+                return Ok(());
+            }
+            Err(e) => {
+                // A cancelled process must not be retried
+                check_process_cancelled()?;
+
+                warn!(
+                    "Encoding {} with {} failed: {}",
+                    work_unit.video.file_path.display(),
+                    encoder,
+                    e
+                );
+                last_command_line = format_command_line(&ffmpeg_batch_command.command);
+
+                // Only another encoder is tried when this one couldn't be set up, other errors
+                // fail with every encoder
+                let is_encoder_init_error = e
+                    .downcast_ref::<FfmpegProcessError>()
+                    .is_some_and(FfmpegProcessError::is_encoder_init_error);
+                last_error = Some(e);
+                if !is_encoder_init_error {
+                    break;
+                }
+            }
+        }
+    }
+
+    let error = last_error.unwrap_or_else(|| "No encoder available for the video".into());
+    if !record_failure {
+        return Err(error);
+    }
+
+    let artifact_directory = match capture_failure_artifacts(
+        &work_unit.video,
+        &video_settings.output_directory,
+        &last_command_line,
+        error.as_ref(),
+    ) {
+        Ok(artifact_directory) => Some(artifact_directory),
+        Err(e) => {
+            warn!(
+                "Failed to capture the failure diagnostics of {}: {}",
+                work_unit.video.file_path.display(),
+                e
+            );
+            None
+        }
+    };
+    RunReportManager::record_failed_file(FailedFile {
+        path: work_unit.video.file_path.clone(),
+        error: error.to_string(),
+        artifact_directory,
+    });
+
+    Err(error)
+}
+
+/// Get the encoders to try in order for a codec, starting with the hardware encoder of the
+/// hardware acceleration setting when one works on this machine
+fn get_encoder_chain(codec: &str, hardware_acceleration: HardwareAcceleration) -> Vec<&str> {
+    let mut encoder_chain: Vec<&str> = VIDEO_CODEC_REGISTRY.get_encoder_fallback_chain(codec);
+    if encoder_chain.is_empty() {
+        encoder_chain.push(codec);
+    }
+
+    if let Some(hardware_encoder) =
+        HardwareEncoderManager::select_encoder(codec, hardware_acceleration)
+    {
+        encoder_chain.retain(|encoder| *encoder != hardware_encoder);
+        encoder_chain.insert(0, hardware_encoder);
+    }
+
+    encoder_chain
+}
+
 fn create_video_ffmpeg_command(
     video: &Video,
-    logo: Option<&Logo>,
-    output_directory: &Path,
+    logos: &[Option<&Logo>],
+    encoder: &str,
+    video_settings: &VideoSettings,
+    output_paths: &[PathBuf],
+    duration_seconds: Option<f64>,
 ) -> Result<FfmpegBatchCommand, Box<dyn Error + Send + Sync>> {
     check_process_cancelled()?;
 
-    // Create output directory
-    std::fs::create_dir_all(output_directory)?;
-
-    let mut cmd = FfmpegCommand::new();
+    let mut cmd = ffmpeg_command()?;
 
     #[cfg(target_os = "windows")]
     cmd.hide_banner();
 
+    // The partial outputs of an interrupted earlier run are overwritten
+    cmd.arg("-y");
+
+    if is_vaapi_encoder(encoder) {
+        cmd.args(["-vaapi_device", VAAPI_DEVICE]);
+    }
+
+    // Seek before the input, so FFmpeg skips the part before the trim start instead of decoding it
+    if let Some(trim_start) = video_settings.trim_start {
+        cmd.args(["-ss", &trim_start.to_string()]);
+    }
+    if let Some(trim_end) = video_settings.trim_end {
+        let trim_duration = trim_end - video_settings.trim_start.unwrap_or(0.0);
+        cmd.args(["-t", &trim_duration.to_string()]);
+    }
     cmd.input(video.file_path.to_str().ok_or("Invalid video file path")?);
 
-    if let Some(logo) = logo {
-        cmd.input(logo.file_path.to_str().ok_or("Invalid logo file path")?);
+    // The logos follow the video as inputs, in the order of the outputs
+    let mut logo_inputs: Vec<Option<(&Logo, usize)>> = Vec::with_capacity(logos.len());
+    for logo in logos {
+        logo_inputs.push(match logo {
+            Some(logo) => {
+                cmd.input(logo.file_path.to_str().ok_or("Invalid logo file path")?);
+                Some((*logo, logo_inputs.iter().flatten().count() + 1))
+            }
+            None => None,
+        });
     }
 
-    if let Some(logo) = logo {
-        let filter_complex = format!(
-            "[0:v]scale={}:{}[resized];[resized][1:v]overlay={}:{}[final]",
-            video.resolution.width, video.resolution.height, logo.position.x, logo.position.y
-        );
-        cmd.args(["-filter_complex", &filter_complex]);
-        cmd.args(["-map", "[final]"]);
+    let output_extension = output_paths
+        .first()
+        .and_then(|output_path| output_path.extension())
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    let animated_format = AnimatedFormat::from_extension(output_extension);
+
+    let resize_filter = ResizeFilter::new(
+        video_settings.resize_mode,
+        None,
+        &video_settings.resize_pad_color,
+    )?
+    .with_color_adjustments(&video_settings.color_adjustments);
+
+    // HDR videos keep their colors when the encoder can write them, otherwise they're tone mapped.
+    // A requested pixel format with 8 bits per color channel can't hold HDR colors.
+    let requested_pixel_format = video_settings.pixel_format.as_deref();
+    let hdr_pixel_format = video
+        .hdr_transfer
+        .as_ref()
+        .filter(|_| video_settings.preserve_hdr)
+        .and_then(|_| VIDEO_CODEC_REGISTRY.get_hdr_pixel_format(encoder))
+        .and_then(|hdr_pixel_format| match requested_pixel_format {
+            Some(pixel_format) if pixel_format_bit_depth(pixel_format) > 8 => {
+                select_encoder_pixel_format(encoder, None, Some(pixel_format))
+                    .filter(|pixel_format| pixel_format_bit_depth(pixel_format) > 8)
+            }
+            Some(_) => None,
+            None => Some(hdr_pixel_format.to_string()),
+        });
+    let tone_map_filter = video
+        .hdr_transfer
+        .as_ref()
+        .filter(|_| hdr_pixel_format.is_none())
+        .and_then(|_| tone_map_filter());
+    let input_filter: Option<String> = [
+        tone_map_filter.map(str::to_string),
+        video
+            .adjustment
+            .as_ref()
+            .and_then(|adjustment| adjustment.to_filter()),
+    ]
+    .into_iter()
+    .flatten()
+    .reduce(|filters, filter| format!("{},{}", filters, filter));
+
+    let output_resolutions = video.output_resolutions();
+    let mut filter_parts = Vec::new();
+
+    // The video is decoded once and split into a stream per rendition
+    let (input_labels, input_filter) = if output_resolutions.len() == 1 {
+        (vec!["0:v".to_string()], input_filter)
     } else {
-        let filter_complex = format!(
-            "[0:v]scale={}:{}[final]",
-            video.resolution.width, video.resolution.height
+        let input_labels: Vec<String> = (0..output_resolutions.len())
+            .map(|size_index| format!("split{}", size_index))
+            .collect();
+        let split_filter = format!(
+            "split={}{}",
+            input_labels.len(),
+            input_labels
+                .iter()
+                .map(|label| format!("[{}]", label))
+                .collect::<String>()
         );
-        cmd.args(["-filter_complex", &filter_complex]);
-        cmd.args(["-map", "[final]"]);
+        filter_parts.push(match input_filter {
+            Some(input_filter) => format!("[0:v]{},{}", input_filter, split_filter),
+            None => format!("[0:v]{}", split_filter),
+        });
+        (input_labels, None)
+    };
+
+    let mut output_labels = Vec::with_capacity(output_resolutions.len());
+    for (size_index, ((input_label, output_resolution), logo_input)) in input_labels
+        .iter()
+        .zip(&output_resolutions)
+        .zip(&logo_inputs)
+        .enumerate()
+    {
+        let overlay_layers = collect_overlay_layers(&video_settings.layers, *logo_input);
+        let final_label = format!("final{}", size_index);
+        filter_parts.push(build_media_filter(
+            input_label,
+            input_filter.as_deref(),
+            output_resolution,
+            &resize_filter,
+            &overlay_layers,
+            &final_label,
+        ));
+
+        let output_label = if is_vaapi_encoder(encoder) {
+            let output_label = format!("hwframes{}", size_index);
+            filter_parts.push(format!(
+                "[{}]format=nv12,hwupload[{}]",
+                final_label, output_label
+            ));
+            output_label
+        } else if let Some(animated_format) = animated_format {
+            let output_label = format!("animated{}", size_index);
+            let fps = output_fps(video_settings).unwrap_or(DEFAULT_ANIMATION_FPS);
+            filter_parts.push(animated_format.build_filter(&final_label, fps, &output_label));
+            output_label
+        } else {
+            final_label
+        };
+        output_labels.push(output_label);
     }
+    cmd.args(["-filter_complex", &filter_parts.join(";")]);
 
-    cmd.args(["-map", "0:a?"]);
+    let processing_marker = ProcessingMarker::for_settings(video_settings)?;
+    for (output_label, output_path) in output_labels.iter().zip(output_paths) {
+        cmd.args(["-map", &format!("[{}]", output_label)]);
 
-    cmd.args(["-c:v", &video.codec]);
+        match animated_format {
+            Some(animated_format) => animated_format.apply_ffmpeg_args(
+                encoder,
+                video_settings.animation_loop_count,
+                &mut cmd,
+            ),
+            None => {
+                video_settings.stream_mapping.apply_ffmpeg_args(
+                    &video.streams,
+                    output_extension,
+                    &mut cmd,
+                );
+                apply_encoder_args(
+                    video,
+                    encoder,
+                    video_settings,
+                    hdr_pixel_format.as_deref(),
+                    &mut cmd,
+                );
+            }
+        }
 
-    let file_stem = video
-        .file_path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .ok_or("Invalid file name")?;
+        // Mark the output as processed with the current settings
+        cmd.args([
+            "-metadata",
+            &format!("comment={}", processing_marker.as_str()),
+        ]);
 
-    let new_filename = format!("{}.{}", file_stem, video.file_type);
-    let output_file = output_directory.join(new_filename);
+        // Preview clips only encode the start of the video
+        if let Some(duration_seconds) = duration_seconds {
+            cmd.duration(duration_seconds.to_string());
+        }
 
-    cmd.output(output_file.to_str().ok_or("Invalid output file path")?);
+        cmd.output(
+            partial_output_path(output_path)
+                .to_str()
+                .ok_or("Invalid output file path")?,
+        );
+    }
 
     Ok(FfmpegBatchCommand {
         command: cmd,
         batch_size: 1,
+        input_size: video.file_size,
+        input_paths: vec![video.file_path.clone()],
+        output_paths: output_paths.to_vec(),
     })
 }
 
+/// Add the arguments of the encoder of a video output, e.g. its pixel format and quality
+fn apply_encoder_args(
+    video: &Video,
+    encoder: &str,
+    video_settings: &VideoSettings,
+    hdr_pixel_format: Option<&str>,
+    cmd: &mut FfmpegCommand,
+) {
+    cmd.args(["-c:v", encoder]);
+
+    if let (Some(hdr_transfer), Some(hdr_pixel_format)) = (&video.hdr_transfer, hdr_pixel_format) {
+        cmd.args(["-pix_fmt", hdr_pixel_format]);
+        apply_hdr_color_args(hdr_transfer, cmd);
+        if let Some(hdr_metadata) = &video.hdr_metadata {
+            hdr_metadata.apply_encoder_args(encoder, cmd);
+        }
+    } else if !is_vaapi_encoder(encoder) {
+        // VAAPI encoders get their pixel format from the uploaded frames
+        if let Some(pixel_format) = select_encoder_pixel_format(
+            encoder,
+            video.pixel_format.as_deref(),
+            video_settings.pixel_format.as_deref(),
+        ) {
+            cmd.args(["-pix_fmt", &pixel_format]);
+        }
+    }
+
+    if let Some(target_fps) = video_settings.target_fps {
+        cmd.args(["-r", &target_fps.to_string()]);
+    }
+
+    if let Some(speed_args) =
+        VIDEO_CODEC_REGISTRY.get_encoder_speed_args(encoder, video_settings.encoder_speed)
+    {
+        cmd.args(speed_args);
+    }
+
+    if let Some(threads) = video_settings.threads_per_encoder() {
+        cmd.args(["-threads", &threads.to_string()]);
+    }
+
+    if let Some(quality_args) = VIDEO_CODEC_REGISTRY.get_encoder_quality_args(
+        encoder,
+        video_settings.quality_mode,
+        video_settings.crf,
+        video_settings.bitrate_kbps,
+    ) {
+        cmd.args(quality_args);
+    }
+}
+
 fn process_logos_for_video_resolutions(
     video_settings: &VideoSettings,
     video_list: &Vec<Video>,
+    logo_directory: &Path,
+    progress: &dyn ProgressSink,
 ) -> Result<Option<Vec<Logo>>, Box<dyn Error + Send + Sync>> {
     let logo_list: Option<Vec<Logo>> = if video_settings.add_logo {
-        // Make a hashset of all the unique resolutions of the Videos
-        let mut unique_resolutions = std::collections::HashSet::new();
+        // Make a hashset of all the unique resolutions and logo overrides of the Videos
+        let mut logo_targets = std::collections::HashSet::new();
         for video in video_list {
-            unique_resolutions.insert(video.resolution.clone());
+            for resolution in video.output_resolutions() {
+                logo_targets.insert((resolution, video.logo_override.clone()));
+            }
         }
-        let unique_resolutions: Vec<Resolution> = unique_resolutions.into_iter().collect();
+        let logo_targets: Vec<(Resolution, Option<LogoOverride>)> =
+            logo_targets.into_iter().collect();
 
-        // Create a vector to store Logo structs for each unique resolution
-        let logos = handle_logos(video_settings, unique_resolutions)?;
+        // Create a vector to store Logo structs for each unique resolution and override
+        let logos = handle_logos(video_settings, logo_targets, logo_directory, progress)?;
         Some(logos)
     } else {
         None
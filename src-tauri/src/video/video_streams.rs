@@ -0,0 +1,197 @@
+use ffmpeg_sidecar::command::FfmpegCommand;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{error::Error, path::Path};
+use ts_rs::TS;
+
+use crate::shared::ffprobe::run_ffprobe;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum StreamKind {
+    Video,
+    Audio,
+    Subtitle,
+    /// Data, attachment and unknown streams
+    Other,
+}
+
+/// A stream of a media file as reported by ffprobe
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct StreamInfo {
+    /// Index of the stream in the input file, as used by `-map 0:<index>`
+    pub index: u32,
+    pub kind: StreamKind,
+    pub codec: Option<String>,
+    /// ISO 639-2 language tag, e.g. `eng`
+    pub language: Option<String>,
+    pub title: Option<String>,
+    /// Number of audio channels, only set for audio streams
+    pub channels: Option<u32>,
+    pub is_default: bool,
+}
+
+impl StreamInfo {
+    fn from_probe_stream(stream: &serde_json::Value) -> Option<Self> {
+        let kind = match stream["codec_type"].as_str() {
+            Some("video") => StreamKind::Video,
+            Some("audio") => StreamKind::Audio,
+            Some("subtitle") => StreamKind::Subtitle,
+            _ => StreamKind::Other,
+        };
+        let tag = |name: &str| stream["tags"][name].as_str().map(str::to_string);
+
+        Some(Self {
+            index: stream["index"].as_u64()? as u32,
+            kind,
+            codec: stream["codec_name"].as_str().map(str::to_string),
+            language: tag("language"),
+            title: tag("title"),
+            channels: stream["channels"].as_u64().map(|channels| channels as u32),
+            is_default: stream["disposition"]["default"].as_u64() == Some(1),
+        })
+    }
+}
+
+/// Read all streams of an ffprobe result
+pub fn read_streams(probe_result: &serde_json::Value) -> Vec<StreamInfo> {
+    probe_result["streams"]
+        .as_array()
+        .map(|streams| {
+            streams
+                .iter()
+                .filter_map(StreamInfo::from_probe_stream)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Probe the streams of a media file
+pub fn probe_streams(path: &Path) -> Result<Vec<StreamInfo>, Box<dyn Error + Send + Sync>> {
+    let probe_result = run_ffprobe(path, &[])?;
+    Ok(read_streams(&probe_result))
+}
+
+/// Which streams of a kind are kept in the output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub enum StreamSelection {
+    All,
+    None,
+    /// Keep the streams tagged with one of the languages, e.g. `eng`
+    Languages(Vec<String>),
+}
+
+impl StreamSelection {
+    fn includes(&self, stream: &StreamInfo) -> bool {
+        match self {
+            StreamSelection::All => true,
+            StreamSelection::None => false,
+            StreamSelection::Languages(languages) => {
+                stream.language.as_ref().is_some_and(|language| {
+                    languages
+                        .iter()
+                        .any(|selected| selected.eq_ignore_ascii_case(language))
+                })
+            }
+        }
+    }
+}
+
+/// Subtitle codecs which store text, which containers with their own text format convert
+const TEXT_SUBTITLE_CODECS: &[&str] = &[
+    "subrip",
+    "srt",
+    "ass",
+    "ssa",
+    "mov_text",
+    "webvtt",
+    "text",
+    "microdvd",
+    "subviewer",
+];
+
+/// The audio and subtitle streams which are kept when processing videos
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/", rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct StreamMapping {
+    pub audio: StreamSelection,
+    /// Keep the chapters of the source video
+    pub chapters: bool,
+    /// Keep the global metadata of the source video, e.g. its title and creation time
+    pub metadata: bool,
+    pub subtitles: StreamSelection,
+}
+
+impl Default for StreamMapping {
+    fn default() -> Self {
+        Self {
+            audio: StreamSelection::All,
+            chapters: true,
+            metadata: true,
+            subtitles: StreamSelection::None,
+        }
+    }
+}
+
+impl StreamMapping {
+    /// Map the selected audio and subtitle streams of the first input to the output
+    pub fn apply_ffmpeg_args(
+        &self,
+        streams: &[StreamInfo],
+        output_extension: &str,
+        cmd: &mut FfmpegCommand,
+    ) {
+        let mut subtitle_output_index = 0;
+        for stream in streams {
+            match stream.kind {
+                StreamKind::Audio if self.audio.includes(stream) => {
+                    cmd.args(["-map", &format!("0:{}", stream.index)]);
+                }
+                StreamKind::Subtitle if self.subtitles.includes(stream) => {
+                    let source_codec = stream.codec.as_deref().unwrap_or("unknown");
+                    let Some(subtitle_codec) =
+                        subtitle_codec_for_extension(output_extension, source_codec)
+                    else {
+                        warn!(
+                            "Skipping subtitle stream {} ({}), the {} container can't hold it",
+                            stream.index, source_codec, output_extension
+                        );
+                        continue;
+                    };
+                    cmd.args(["-map", &format!("0:{}", stream.index)]);
+                    cmd.args([&format!("-c:s:{}", subtitle_output_index), subtitle_codec]);
+                    subtitle_output_index += 1;
+                }
+                _ => {}
+            }
+        }
+
+        // -1 drops the chapters and metadata, which FFmpeg otherwise copies from the first input
+        cmd.args(["-map_chapters", if self.chapters { "0" } else { "-1" }]);
+        cmd.args(["-map_metadata", if self.metadata { "0" } else { "-1" }]);
+    }
+}
+
+/// Get the codec a subtitle stream is written with in an output container, `None` when the
+/// container can't hold the subtitles
+///
+/// Containers with their own text format convert text subtitles, but can't hold bitmap subtitles
+/// like PGS.
+fn subtitle_codec_for_extension(extension: &str, source_codec: &str) -> Option<&'static str> {
+    let is_text_subtitle = TEXT_SUBTITLE_CODECS.contains(&source_codec);
+    match extension.to_lowercase().as_str() {
+        "mp4" | "m4v" | "mov" => is_text_subtitle.then_some("mov_text"),
+        "webm" => is_text_subtitle.then_some("webvtt"),
+        // Matroska has no mov_text, so it's converted to SubRip
+        "mkv" if source_codec == "mov_text" => Some("srt"),
+        "mkv" | "nut" => Some("copy"),
+        "ts" | "m2ts" | "mts" => (source_codec == "dvb_subtitle").then_some("copy"),
+        _ => None,
+    }
+}
@@ -1,12 +1,21 @@
 use serde::{Deserialize, Serialize};
-use std::{error::Error, path::PathBuf};
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
 
 use crate::{
     shared::{
-        file_utils::{read_file_size, read_file_type},
-        media_structs::{Media, Resolution},
+        ffprobe::{find_video_stream, read_hdr_metadata, run_ffprobe, stream_hdr_transfer},
+        file_utils::{read_file_type, FileInfo},
+        hdr::HdrMetadata,
+        logo_overrides::LogoOverride,
+        media_structs::{Media, MediaAdjustment, Resolution},
+    },
+    video::{
+        video_formats::VIDEO_FORMAT_REGISTRY,
+        video_streams::{read_streams, StreamInfo},
     },
-    video::video_formats::VIDEO_FORMAT_REGISTRY,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,40 +27,31 @@ pub struct Video {
     pub duration: f64,
     pub codec: String,
     pub frame_count: usize,
+    /// Resolution of every export size, empty when the video has a single output
+    pub export_resolutions: Vec<Resolution>,
+    /// Transfer characteristics of a video with HDR colors, e.g. `smpte2084` (PQ)
+    pub hdr_transfer: Option<String>,
+    /// Mastering display and content light metadata of a video with HDR colors
+    pub hdr_metadata: Option<HdrMetadata>,
+    pub logo_override: Option<LogoOverride>,
+    pub adjustment: Option<MediaAdjustment>,
+    /// Pixel format of the video stream, e.g. `yuv420p10le`
+    pub pixel_format: Option<String>,
+    pub streams: Vec<StreamInfo>,
 }
 
 impl Video {
     pub fn new(path: PathBuf) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let file_size = read_file_size(&path)?;
+        let file_size = FileInfo::read(&path)?.scheduling_size();
 
         // Get file type from extension and validate it's supported by FFmpeg
         let file_type = read_video_file_type(&path)?;
 
         // Use ffprobe to get video information
-        let output = std::process::Command::new("ffprobe")
-            .args([
-                "-v",
-                "quiet",
-                "-print_format",
-                "json",
-                "-show_format",
-                "-show_streams",
-                "-count_packets",
-                path.to_str().unwrap(),
-            ])
-            .output()?;
-
-        let probe_result: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+        let probe_result = run_ffprobe(&path, &["-count_packets"])?;
 
         // Extract video stream information
-        let video_stream = probe_result["streams"]
-            .as_array()
-            .and_then(|streams| {
-                streams
-                    .iter()
-                    .find(|stream| stream["codec_type"].as_str() == Some("video"))
-            })
-            .ok_or("No video stream found")?;
+        let video_stream = find_video_stream(&probe_result).ok_or("No video stream found")?;
 
         let width = video_stream["width"].as_u64().unwrap_or(0) as u32;
         let height = video_stream["height"].as_u64().unwrap_or(0) as u32;
@@ -72,6 +72,11 @@ impl Video {
             .and_then(|fc| fc.parse::<u64>().ok())
             .unwrap_or(0) as usize;
 
+        let hdr_transfer = stream_hdr_transfer(video_stream);
+        let hdr_metadata = hdr_transfer
+            .as_ref()
+            .and_then(|_| read_hdr_metadata(&path, video_stream));
+
         Ok(Self {
             file_path: path,
             resolution,
@@ -80,9 +85,25 @@ impl Video {
             duration,
             codec,
             frame_count,
+            export_resolutions: Vec::new(),
+            hdr_transfer,
+            hdr_metadata,
+            logo_override: None,
+            adjustment: None,
+            pixel_format: video_stream["pix_fmt"].as_str().map(str::to_string),
+            streams: read_streams(&probe_result),
         })
     }
 
+    /// Get the resolution of every output of the video
+    pub fn output_resolutions(&self) -> Vec<Resolution> {
+        if self.export_resolutions.is_empty() {
+            vec![self.resolution.clone()]
+        } else {
+            self.export_resolutions.clone()
+        }
+    }
+
     pub fn get_duration(&self) -> f64 {
         self.duration
     }
@@ -99,6 +120,10 @@ impl Video {
 impl Media for Video {
     type FileType = String;
 
+    fn get_file_path(&self) -> &Path {
+        &self.file_path
+    }
+
     fn get_resolution(&self) -> &Resolution {
         &self.resolution
     }
@@ -114,6 +139,10 @@ impl Media for Video {
     fn set_resolution(&mut self, resolution: Resolution) {
         self.resolution = resolution;
     }
+
+    fn set_adjustment(&mut self, adjustment: MediaAdjustment) {
+        self.adjustment = Some(adjustment);
+    }
 }
 
 /// Read the video file type and validate it's supported by FFmpeg
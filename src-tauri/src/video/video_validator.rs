@@ -1,15 +1,34 @@
+use crate::shared::filename_template::FilenameTemplate;
+use crate::shared::input_filter::InputFilter;
+use crate::shared::media_structs::Resolution;
 use crate::shared::media_validator::MediaValidator;
+use crate::shared::output_collision::CollisionStrategy;
+use crate::shared::output_mapping::OutputMapping;
 use crate::video::video_formats::VIDEO_FORMAT_REGISTRY;
 use crate::VideoSettings;
 use std::path::Path;
 
 pub struct VideoSettingsValidator<'a> {
     settings: &'a VideoSettings,
+    filename_template: FilenameTemplate,
+    input_filter: InputFilter,
+    output_mapping: OutputMapping,
 }
 
 impl<'a> VideoSettingsValidator<'a> {
     pub fn new(settings: &'a VideoSettings) -> Self {
-        Self { settings }
+        // Invalid templates, filters and mappings are rejected before processing, so the
+        // defaults are only a fallback
+        let filename_template =
+            FilenameTemplate::new(&settings.filename_template).unwrap_or_default();
+        let input_filter = settings.input_filter().unwrap_or_default();
+        let output_mapping = settings.output_mapping().unwrap_or_default();
+        Self {
+            settings,
+            filename_template,
+            input_filter,
+            output_mapping,
+        }
     }
 }
 
@@ -26,12 +45,47 @@ impl<'a> MediaValidator for VideoSettingsValidator<'a> {
         &self.settings.format
     }
 
-    fn should_overwrite_existing(&self) -> bool {
-        self.settings.overwrite_existing_files_output_directory
+    fn get_filename_template(&self) -> &FilenameTemplate {
+        &self.filename_template
+    }
+
+    fn get_input_filter(&self) -> &InputFilter {
+        &self.input_filter
+    }
+
+    fn get_output_mapping(&self) -> &OutputMapping {
+        &self.output_mapping
+    }
+
+    fn should_number_outputs(&self) -> bool {
+        false
+    }
+
+    fn get_output_subfolders(&self) -> Vec<&str> {
+        self.settings
+            .export_sizes
+            .iter()
+            .map(|export_size| export_size.name.as_str())
+            .collect()
+    }
+
+    fn get_collision_strategy(&self) -> CollisionStrategy {
+        self.settings.collision_strategy
     }
 
     fn should_keep_folder_structure(&self) -> bool {
         self.settings
             .keep_child_folders_structure_in_output_directory
     }
+
+    fn get_min_input_resolution(&self) -> Resolution {
+        Resolution {
+            width: self.settings.min_input_width,
+            height: self.settings.min_input_height,
+        }
+    }
+
+    fn get_max_file_size(&self) -> u64 {
+        self.settings.max_file_size_mb as u64 * 1024 * 1024
+    }
 }
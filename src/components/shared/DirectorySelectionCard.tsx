@@ -3,8 +3,24 @@ import { useFormContext } from "react-hook-form";
 import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card";
 import { Label } from "@/components/ui/label";
 import { FileInput } from "@/components/ui/path-input";
+import { RadioGroup, RadioGroupItem } from "@/components/ui/radio-group";
 import { Switch } from "@/components/ui/switch";
+import type { CollisionStrategy } from "@/types/CollisionStrategy";
 import type { ImageSettings } from "@/types/ImageSettings";
+import type { SourceDisposition } from "@/types/SourceDisposition";
+
+const SOURCE_DISPOSITION_OPTIONS: { value: SourceDisposition; label: string }[] = [
+	{ value: "keep", label: "Keep" },
+	{ value: "trash", label: "Trash" },
+	{ value: "archive", label: "Archive" },
+];
+
+const COLLISION_STRATEGY_OPTIONS: { value: CollisionStrategy; label: string }[] = [
+	{ value: "overwrite", label: "Overwrite" },
+	{ value: "skip", label: "Skip" },
+	{ value: "renameWithSuffix", label: "Rename" },
+	{ value: "error", label: "Stop" },
+];
 
 export function DirectorySelectionCard() {
 	const { setValue, watch } = useFormContext<ImageSettings>();
@@ -13,11 +29,11 @@ export function DirectorySelectionCard() {
 	const inputDirectory = watch("inputDirectory");
 	const outputDirectory = watch("outputDirectory");
 	const searchChildFolders = watch("searchChildFolders");
-	const clearFilesInputDirectory = watch("clearFilesInputDirectory");
+	const sourceDisposition = watch("sourceDisposition");
 	const keepChildFoldersStructureInOutputDirectory = watch(
 		"keepChildFoldersStructureInOutputDirectory",
 	);
-	const overwriteExistingFilesOutputDirectory = watch("overwriteExistingFilesOutputDirectory");
+	const collisionStrategy = watch("collisionStrategy");
 	const clearFilesOutputDirectory = watch("clearFilesOutputDirectory");
 
 	return (
@@ -40,15 +56,31 @@ export function DirectorySelectionCard() {
 									label='Search child folders'
 								/>
 							</div>
-							<div className='flex items-center space-x-2'>
-								<Switch
-									id={`${baseId}-clearFilesInputDirectory`}
-									checked={clearFilesInputDirectory}
-									onCheckedChange={(checked) =>
-										setValue("clearFilesInputDirectory", checked)
+							<div className='space-y-2'>
+								<Label className='text-sm font-medium'>Processed source files</Label>
+								<RadioGroup
+									value={sourceDisposition}
+									onValueChange={(value) =>
+										setValue("sourceDisposition", value as SourceDisposition)
 									}
-									label='Clear input directory'
-								/>
+									className='flex gap-4'
+								>
+									{SOURCE_DISPOSITION_OPTIONS.map(({ value, label }) => (
+										<div key={value} className='flex items-center space-x-2'>
+											<RadioGroupItem
+												value={value}
+												id={`${baseId}-sourceDisposition-${value}`}
+												className='flex-shrink-0'
+											/>
+											<Label
+												htmlFor={`${baseId}-sourceDisposition-${value}`}
+												className='text-sm cursor-pointer'
+											>
+												{label}
+											</Label>
+										</div>
+									))}
+								</RadioGroup>
 							</div>
 						</div>
 						<div className='flex-1'>
@@ -87,15 +119,31 @@ export function DirectorySelectionCard() {
 									label='Keep folder structure'
 								/>
 							</div>
-							<div className='flex items-center space-x-2'>
-								<Switch
-									id={`${baseId}-overwriteExistingFilesOutputDirectory`}
-									checked={overwriteExistingFilesOutputDirectory}
-									onCheckedChange={(checked) =>
-										setValue("overwriteExistingFilesOutputDirectory", checked)
+							<div className='space-y-2'>
+								<Label className='text-sm font-medium'>Existing output files</Label>
+								<RadioGroup
+									value={collisionStrategy}
+									onValueChange={(value) =>
+										setValue("collisionStrategy", value as CollisionStrategy)
 									}
-									label='Overwrite existing files'
-								/>
+									className='flex gap-4'
+								>
+									{COLLISION_STRATEGY_OPTIONS.map(({ value, label }) => (
+										<div key={value} className='flex items-center space-x-2'>
+											<RadioGroupItem
+												value={value}
+												id={`${baseId}-collisionStrategy-${value}`}
+												className='flex-shrink-0'
+											/>
+											<Label
+												htmlFor={`${baseId}-collisionStrategy-${value}`}
+												className='text-sm cursor-pointer'
+											>
+												{label}
+											</Label>
+										</div>
+									))}
+								</RadioGroup>
 							</div>
 							<div className='flex items-center space-x-2'>
 								<Switch
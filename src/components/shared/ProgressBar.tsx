@@ -1,8 +1,18 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { useEffect, useRef, useState } from "react";
 import type { ProgressInfo } from "../../types/ProgressInfo";
+import type { ProgressStage } from "../../types/ProgressStage";
 import { Progress } from "../ui/progress";
 
+const PROGRESS_STAGES: { stage: ProgressStage; label: string }[] = [
+	{ stage: "scanning", label: "Scanning" },
+	{ stage: "probing", label: "Probing" },
+	{ stage: "logoPrep", label: "Preparing logos" },
+	{ stage: "encoding", label: "Encoding" },
+	{ stage: "verifying", label: "Verifying" },
+];
+
 interface ProgressBarProps {
 	isProcessing: boolean;
 }
@@ -14,64 +24,63 @@ export default function ProgressBar({ isProcessing }: ProgressBarProps) {
 	const hideTimeoutRef = useRef<NodeJS.Timeout | null>(null);
 
 	useEffect(() => {
-		let intervalId: NodeJS.Timeout;
-
-		const fetchProgress = async () => {
-			try {
-				const result = await invoke<ProgressInfo | null>("get_progress_info");
-				if (result) {
-					setProgressInfo(result);
-					setIsVisible(true);
-
-					// Check if progress reaches 100%
-					if (result.percentage >= 100 && !isCompleted) {
-						setIsCompleted(true);
-						if (intervalId) {
-							clearInterval(intervalId);
-						}
-
-						// Clear any existing timeout
-						if (hideTimeoutRef.current) {
-							clearTimeout(hideTimeoutRef.current);
-						}
-
-						// Set timeout to hide after 2 seconds
-						hideTimeoutRef.current = setTimeout(() => {
-							setIsVisible(false);
-							setProgressInfo(null);
-							setIsCompleted(false);
-						}, 5000);
-					}
-				} else {
-					// No progress info available, stop polling
-					if (intervalId) {
-						clearInterval(intervalId);
+		let isListening = true;
+
+		const updateProgress = (result: ProgressInfo | null) => {
+			if (!isListening) {
+				return;
+			}
+			if (result) {
+				setProgressInfo(result);
+				setIsVisible(true);
+
+				// Check if progress reaches 100%
+				if (result.percentage >= 100 && !isCompleted) {
+					setIsCompleted(true);
+					isListening = false;
+
+					// Clear any existing timeout
+					if (hideTimeoutRef.current) {
+						clearTimeout(hideTimeoutRef.current);
 					}
-					setIsVisible(false);
-					setProgressInfo(null);
-					setIsCompleted(false);
+
+					// Set timeout to hide after 5 seconds
+					hideTimeoutRef.current = setTimeout(() => {
+						setIsVisible(false);
+						setProgressInfo(null);
+						setIsCompleted(false);
+					}, 5000);
 				}
-			} catch (error) {
-				console.error("Failed to fetch progress info:", error);
+			} else {
+				// No progress info available, stop listening
+				isListening = false;
+				setIsVisible(false);
+				setProgressInfo(null);
+				setIsCompleted(false);
 			}
 		};
 
 		if (isProcessing || (isVisible && !isCompleted)) {
-			// Start polling when processing or still visible and not completed
-			intervalId = setInterval(fetchProgress, 1000 / 60);
-			// Initial fetch
-			fetchProgress();
-		} else if (!(isProcessing || isVisible)) {
+			// Listen to the progress updates when processing or still visible and not completed
+			const unlistenPromise = listen<ProgressInfo | null>("progress://update", (event) =>
+				updateProgress(event.payload),
+			);
+			// Show the progress which was emitted before listening, a run which didn't start yet
+			// has no progress
+			invoke<ProgressInfo | null>("get_progress_info")
+				.then((result) => result && updateProgress(result))
+				.catch((error) => console.error("Failed to fetch progress info:", error));
+
+			return () => {
+				isListening = false;
+				unlistenPromise.then((unlistenFn) => unlistenFn());
+			};
+		}
+		if (!(isProcessing || isVisible)) {
 			// Reset state when not processing and not visible
 			setProgressInfo(null);
 			setIsCompleted(false);
 		}
-
-		return () => {
-			if (intervalId) {
-				clearInterval(intervalId);
-			}
-		};
 	}, [isProcessing, isVisible, isCompleted]);
 
 	// Cleanup timeout on unmount
@@ -104,15 +113,20 @@ export default function ProgressBar({ isProcessing }: ProgressBarProps) {
 		return parts.join(" ");
 	};
 
+	const formatAmount = (amount: number) =>
+		progressInfo.unit === "bytes"
+			? `${(amount / (1024 * 1024)).toFixed(1)} MB`
+			: `${amount} ${progressInfo.unit}`;
+
 	return (
 		<div className='mt-1'>
 			<div className='flex justify-around items-center text-sm'>
 				<span className='font-medium'>{progressInfo.status}</span>
 				<span>
-					{progressInfo.itemsPerSecond.toFixed(1)} {progressInfo.unit}/sec
+					{progressInfo.megabytesPerSecond.toFixed(1)} MB/sec
 				</span>
 				<span className='text-muted-foreground'>
-					{progressInfo.current} / {progressInfo.total} {progressInfo.unit}
+					{formatAmount(progressInfo.current)} / {formatAmount(progressInfo.total)}
 				</span>
 				{progressInfo.alternativeTotal > 0 && (
 					<span>
@@ -128,6 +142,23 @@ export default function ProgressBar({ isProcessing }: ProgressBarProps) {
 				</div>
 			</div>
 
+			{progressInfo.stage && (
+				<div className='flex justify-center gap-4 text-xs text-muted-foreground'>
+					{PROGRESS_STAGES.map(({ stage, label }, index) => {
+						const timing = progressInfo.stageTimings.find((timing) => timing.stage === stage);
+						return (
+							<span
+								key={stage}
+								className={stage === progressInfo.stage ? "font-medium text-foreground" : undefined}
+							>
+								{index + 1}. {label}
+								{timing && ` (${formatTime(timing.durationSeconds)})`}
+							</span>
+						);
+					})}
+				</div>
+			)}
+
 			<Progress value={Math.round(progressInfo.percentage * 100) / 100} className='w-full' />
 		</div>
 	);
@@ -31,9 +31,9 @@ export const imageFormSchema = z
 		logoCorner: z.enum(logoCorners),
 		shouldConvertFormat: z.boolean(),
 		format: z.string({ error: "Selecting a format is required." }),
-		clearFilesInputDirectory: z.boolean(),
+		sourceDisposition: z.enum(["keep", "archive", "trash"]),
 		clearFilesOutputDirectory: z.boolean(),
-		overwriteExistingFilesOutputDirectory: z.boolean(),
+		collisionStrategy: z.enum(["overwrite", "skip", "renameWithSuffix", "error"]),
 	})
 	.refine(
 		(data) => {
@@ -37,9 +37,9 @@ export const videoFormSchema = z
 		codec: z.string({
 			error: "Selecting a codec is required.",
 		}),
-		clearFilesInputDirectory: z.boolean(),
+		sourceDisposition: z.enum(["keep", "archive", "trash"]),
 		clearFilesOutputDirectory: z.boolean(),
-		overwriteExistingFilesOutputDirectory: z.boolean(),
+		collisionStrategy: z.enum(["overwrite", "skip", "renameWithSuffix", "error"]),
 	})
 	.refine(
 		(data) => {
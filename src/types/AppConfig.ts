@@ -1,5 +1,17 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BrandKit } from "./BrandKit";
+import type { ImagePreset } from "./ImagePreset";
 import type { ImageSettings } from "./ImageSettings";
+import type { VideoPreset } from "./VideoPreset";
 import type { VideoSettings } from "./VideoSettings";
 
-export type AppConfig = { imageSettings: ImageSettings, videoSettings: VideoSettings, };
+export type AppConfig = { brandKits: Array<BrandKit>, 
+/**
+ * Check for a new release of the app on startup, off until the user enables it since the
+ * check sends a request to GitHub
+ */
+checkForUpdatesOnStartup: boolean, 
+/**
+ * Version of the config structure, which decides the migrations an older config needs
+ */
+configVersion: number, imagePresets: Array<ImagePreset>, imageSettings: ImageSettings, videoPresets: Array<VideoPreset>, videoSettings: VideoSettings, };
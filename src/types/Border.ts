@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A frame drawn around each output, in pixels of the output
+ */
+export type Border = { color: string, 
+/**
+ * Space between the media and the border
+ */
+padding: number, paddingColor: string, width: number, };
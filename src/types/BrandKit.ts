@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ColorAdjustments } from "./ColorAdjustments";
+import type { Corner } from "./Corner";
+import type { MetadataMode } from "./MetadataMode";
+
+/**
+ * A named bundle of branding settings, e.g. per client, applied on top of the run's settings
+ *
+ * Unset fields keep the value of the regular settings.
+ */
+export type BrandKit = { name: string, colorAdjustments: ColorAdjustments | null, logoCorner: Corner | null, logoKeyColor: string | null, logoKeyTolerance: number | null, logoPath: string | null, logoScale: number | null, metadataMode: MetadataMode | null, padColor: string | null, };
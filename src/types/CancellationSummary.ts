@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How far a run got before it was cancelled
+ */
+export type CancellationSummary = { reason: string, 
+/**
+ * Files which were processed before the cancellation
+ */
+completedFiles: number, 
+/**
+ * Files which were being processed, their FFmpeg processes were killed
+ */
+killedFiles: number, 
+/**
+ * Files which were planned but never started
+ */
+notStartedFiles: number, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What a run does with an input whose output file already exists
+ */
+export type CollisionStrategy = /**
+ * Replace the existing file
+ */
+"overwrite" | /**
+ * Keep the existing file and skip the input
+ */
+"skip" | /**
+ * Write the output next to the existing file with a numbered suffix, e.g. `photo_1.jpg`
+ */
+"renameWithSuffix" | /**
+ * Stop the run before anything is processed
+ */
+"error";
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ColorTone } from "./ColorTone";
+
+/**
+ * Global color adjustments, so all exports of a brand get a consistent look
+ */
+export type ColorAdjustments = { 
+/**
+ * Brightness from -1 to 1, 0 keeps the brightness
+ */
+brightness: number, 
+/**
+ * Contrast from 0 to 2, 1 keeps the contrast
+ */
+contrast: number, 
+/**
+ * Saturation from 0 (gray) to 3, 1 keeps the saturation
+ */
+saturation: number, tone: ColorTone, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Area of the original media in pixels
+ */
+export type CropArea = { x: number, y: number, width: number, height: number, };
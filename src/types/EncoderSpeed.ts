@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Encoder independent speed setting, translated to the preset options of each encoder
+ */
+export type EncoderSpeed = "fastest" | "fast" | "medium" | "slow" | "slowest";
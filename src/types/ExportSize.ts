@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * An additional output size of each image or video, written to a subfolder of the output directory
+ */
+export type ExportSize = { 
+/**
+ * Name of the subfolder the outputs of this size are written to, e.g. `web`
+ */
+name: string, minPixelCount: number, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A media file which failed to process
+ */
+export type FailedFile = { path: string, error: string, 
+/**
+ * Folder with the diagnostics of the failure, e.g. the FFmpeg command and failing frame
+ */
+artifactDirectory: string | null, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The FFmpeg command of a work unit as it was run
+ */
+export type FfmpegCommandRecord = { inputPaths: Array<string>, outputPaths: Array<string>, 
+/**
+ * Arguments of the command, without the FFmpeg executable
+ */
+args: Array<string>, 
+/**
+ * The command as a command line which can be copied into a terminal
+ */
+commandLine: string, };
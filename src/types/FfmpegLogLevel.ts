@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The FFmpeg messages which are written to the log of each file
+ */
+export type FfmpegLogLevel = /**
+ * Don't write log files
+ */
+"off" | "error" | "warning" | "info" | /**
+ * Run FFmpeg with debug output, which is slower and produces large logs
+ */
+"debug";
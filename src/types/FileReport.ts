@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Details of a single output file which aren't known before processing
+ */
+export type FileReport = { outputPath: string, encoder: string | null, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type FileTimingStats = { sampleCount: number, meanSeconds: number, minSeconds: number, p50Seconds: number, p90Seconds: number, p95Seconds: number, p99Seconds: number, maxSeconds: number, };
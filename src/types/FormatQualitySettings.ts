@@ -0,0 +1,47 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Quality and compression of the output formats which support them
+ */
+export type FormatQualitySettings = { 
+/**
+ * Bits per channel of AVIF outputs: 8, 10 or 12
+ */
+avifBitDepth: number, 
+/**
+ * AVIF encoding effort from 0 (fastest) to 8 (smallest file)
+ */
+avifEffort: number, 
+/**
+ * AVIF quality from 0 (smallest file) to 100 (best quality)
+ */
+avifQuality: number, 
+/**
+ * JPEG quality from 1 (smallest file) to 100 (best quality)
+ */
+jpegQuality: number, 
+/**
+ * Bits per channel of JPEG XL outputs: 8 or 16
+ */
+jxlBitDepth: number, 
+/**
+ * JPEG XL encoding effort from 1 (fastest) to 9 (smallest file)
+ */
+jxlEffort: number, 
+/**
+ * JPEG XL quality from 1 (smallest file) to 100 (lossless)
+ */
+jxlQuality: number, 
+/**
+ * Keep the transparency of the source in AVIF and JPEG XL outputs, off by default so the
+ * outputs of existing settings don't change
+ */
+keepAlpha: boolean, 
+/**
+ * PNG compression level from 0 (fastest) to 9 (smallest file)
+ */
+pngCompressionLevel: number, 
+/**
+ * WebP quality from 0 (smallest file) to 100 (best quality)
+ */
+webpQuality: number, };
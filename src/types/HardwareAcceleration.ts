@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Hardware encoder family which encodes the videos
+ */
+export type HardwareAcceleration = /**
+ * Use the first hardware encoder which works on this machine
+ */
+"auto" | /**
+ * NVIDIA GPUs
+ */
+"nvenc" | /**
+ * Intel Quick Sync Video
+ */
+"qsv" | /**
+ * AMD GPUs
+ */
+"amf" | /**
+ * Video Acceleration API on Linux
+ */
+"vaapi" | /**
+ * Encode with the configured encoder
+ */
+"off";
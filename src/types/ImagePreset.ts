@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ImageSettings } from "./ImageSettings";
+
+/**
+ * Image settings saved under a name, e.g. "Instagram" or "Client proofs"
+ */
+export type ImagePreset = { name: string, settings: ImageSettings, };
@@ -1,4 +1,132 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Border } from "./Border";
+import type { CollisionStrategy } from "./CollisionStrategy";
+import type { ColorAdjustments } from "./ColorAdjustments";
 import type { Corner } from "./Corner";
+import type { ExportSize } from "./ExportSize";
+import type { FfmpegLogLevel } from "./FfmpegLogLevel";
+import type { FormatQualitySettings } from "./FormatQualitySettings";
+import type { LayersSettings } from "./LayersSettings";
+import type { MetadataMode } from "./MetadataMode";
+import type { ResizeMode } from "./ResizeMode";
+import type { Resolution } from "./Resolution";
+import type { ScaleAlgorithm } from "./ScaleAlgorithm";
+import type { SequenceNaming } from "./SequenceNaming";
+import type { SourceDisposition } from "./SourceDisposition";
 
-export type ImageSettings = { addLogo: boolean, clearFilesInputDirectory: boolean, clearFilesOutputDirectory: boolean, formatFavoriteList: Array<string>, format: string, inputDirectory: string, keepChildFoldersStructureInOutputDirectory: boolean, logoCorner: Corner, logoPath: string | null, logoScale: number, logoXOffsetScale: number, logoYOffsetScale: number, minPixelCount: number, outputDirectory: string, overwriteExistingFilesOutputDirectory: boolean, searchChildFolders: boolean, shouldConvertFormat: boolean, };
+export type ImageSettings = { addLogo: boolean, 
+/**
+ * Fill color behind transparent areas in output formats without transparency, e.g. JPEG
+ */
+backgroundColor: string, 
+/**
+ * Run on half of the CPU cores with a lower FFmpeg priority, so the machine stays usable
+ */
+backgroundMode: boolean, 
+/**
+ * Frame around each output image, drawn below the overlay layers
+ */
+border: Border | null, brandKit: string | null, clearFilesOutputDirectory: boolean, 
+/**
+ * What happens to inputs whose output file already exists
+ */
+collisionStrategy: CollisionStrategy, colorAdjustments: ColorAdjustments, 
+/**
+ * Write a contact sheet of the output images per folder
+ */
+createContactSheets: boolean, 
+/**
+ * Number of hash bits in which images may differ to count as duplicates, 0 only matches
+ * images which look identical
+ */
+duplicateMaxDistance: number, 
+/**
+ * Embed a small EXIF thumbnail in JPEG outputs, which file browsers use for fast previews
+ */
+embedExifThumbnail: boolean, 
+/**
+ * Glob patterns of input files to skip, e.g. `*_edited.jpg`
+ */
+excludePatterns: Array<string>, 
+/**
+ * Sizes which each image is exported in, replacing the single output when set
+ */
+exportSizes: Array<ExportSize>, ffmpegLogLevel: FfmpegLogLevel, filenameTemplate: string, formatFavoriteList: Array<string>, format: string, formatQuality: FormatQualitySettings, hydrateCloudPlaceholders: boolean, 
+/**
+ * Glob patterns of input files to process, e.g. `*_final.jpg`, empty processes all files
+ */
+includePatterns: Array<string>, inputDirectory: string, keepChildFoldersStructureInOutputDirectory: boolean, layers: LayersSettings, logoCorner: Corner, logoKeyColor: string | null, logoKeyTolerance: number, logoPath: string | null, logoScale: number, logoXOffsetScale: number, logoYOffsetScale: number, 
+/**
+ * Maximum number of FFmpeg processes running at the same time, 0 disables the limit
+ */
+maxConcurrentProcesses: number, 
+/**
+ * Maximum number of FFmpeg processes reading an input directory on an HDD at the same time,
+ * 0 disables the limit
+ */
+maxDiskReaders: number, 
+/**
+ * Inputs larger than this number of megabytes are skipped, 0 disables the check
+ */
+maxFileSizeMb: number, metadataMode: MetadataMode, 
+/**
+ * Files smaller than this number of bytes are skipped, 0 disables the check
+ */
+minFileSize: number, 
+/**
+ * Inputs with a lower height are skipped, 0 disables the check
+ */
+minInputHeight: number, 
+/**
+ * Inputs with a lower width are skipped, 0 disables the check
+ */
+minInputWidth: number, minPixelCount: number, 
+/**
+ * Skip inputs last modified before this date, in the `YYYY-MM-DD` format
+ */
+modifiedAfter: string | null, 
+/**
+ * Skip inputs last modified after this date, in the `YYYY-MM-DD` format
+ */
+modifiedBefore: string | null, neverUpscale: boolean, outputDirectory: string, 
+/**
+ * CSV file which maps inputs to their own output folder or name, e.g. per product
+ */
+outputMappingPath: string | null, 
+/**
+ * Keep the HDR colors of HDR sources in AVIF outputs, other outputs are tone mapped to SDR
+ */
+preserveHdr: boolean, 
+/**
+ * Move skipped empty and near-empty files to the quarantine folder
+ */
+quarantineTinyFiles: boolean, 
+/**
+ * Keep the FFmpeg command of every work unit of a run, so it can be reproduced manually
+ */
+recordFfmpegCommands: boolean, 
+/**
+ * Stop the run when a logo is enlarged so much that it looks blurry, instead of warning
+ */
+rejectUpscaledLogos: boolean, resizeAspectRatio: Resolution, resizeMode: ResizeMode, resizeOutputResolution: Resolution | null, resizePadColor: string, reviewFlaggedFiles: boolean, scaleAlgorithm: ScaleAlgorithm, searchChildFolders: boolean, sequenceNaming: SequenceNaming | null, 
+/**
+ * Strength of the sharpening applied after resizing, from 0 to 1.5
+ */
+sharpenAmount: number | null, shouldConvertFormat: boolean, 
+/**
+ * Process only one copy of images which look the same
+ */
+skipDuplicateImages: boolean, 
+/**
+ * Skip inputs which were already processed with the same settings
+ */
+skipMarkedFiles: boolean, 
+/**
+ * What happens to an input file once all its outputs are written
+ */
+sourceDisposition: SourceDisposition, 
+/**
+ * Write a log with a JSON record per processed file to the output directory, for auditing
+ * the outputs of a run
+ */
+writeRunLog: boolean, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AppConfig } from "./AppConfig";
+
+/**
+ * Settings imported from a file, with the files they refer to which don't exist on this machine
+ */
+export type ImportedSettings = { config: AppConfig, 
+/**
+ * The missing logos and output mappings, e.g. "Logo of brand kit Studio: /logos/studio.png"
+ */
+missingFiles: Array<string>, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ImageSettings } from "./ImageSettings";
+import type { VideoSettings } from "./VideoSettings";
+
+/**
+ * A processing run with the settings it was queued with
+ */
+export type Job = { "kind": "images", "settings": ImageSettings } | { "kind": "videos", "settings": VideoSettings };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { QueuedJob } from "./QueuedJob";
+
+/**
+ * The running job and the jobs waiting after it, in the order they run
+ */
+export type JobQueueState = { runningJob: QueuedJob | null, pendingJobs: Array<QueuedJob>, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BlendMode } from "./BlendMode";
+import type { LayerKind } from "./LayerKind";
+
+export type LayerSettings = { kind: LayerKind, blendMode: BlendMode, };
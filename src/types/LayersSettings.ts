@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LayerSettings } from "./LayerSettings";
+
+/**
+ * Overlay layers in the order they're drawn on top of the media, from bottom to top
+ */
+export type LayersSettings = { layers: Array<LayerSettings>, };
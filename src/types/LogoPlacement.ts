@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Position } from "./Position";
+import type { Resolution } from "./Resolution";
+
+/**
+ * Size and position of the logo on media of a resolution
+ */
+export type LogoPlacement = { mediaResolution: Resolution, resolution: Resolution, position: Position, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CropArea } from "./CropArea";
+
+/**
+ * Manual crop and clockwise rotation which are applied to media before it's resized
+ */
+export type MediaAdjustment = { crop: CropArea | null, rotationDegrees: number, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How metadata like the capture date, GPS location and orientation is handled for output images
+ */
+export type MetadataMode = /**
+ * Copy the metadata and EXIF data of the source image to the output image
+ */
+"preserve" | /**
+ * Remove all metadata from the output image, e.g. for privacy-focused exports
+ */
+"strip";
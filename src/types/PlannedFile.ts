@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * An input of a plan with the outputs a run would write for it
+ */
+export type PlannedFile = { inputPath: string, outputPaths: Array<string>, };
@@ -1,3 +1,26 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ProgressStage } from "./ProgressStage";
+import type { StageTiming } from "./StageTiming";
 
-export type ProgressInfo = { current: number, total: number, percentage: number, unit: string, elapsedTime: number, estimatedRemaining: number | null, itemsPerSecond: number, status: string, alternativeCurrent: number, alternativeTotal: number, alternativeUnit: string, };
+export type ProgressInfo = { current: number, total: number, percentage: number, unit: string, elapsedTime: number, estimatedRemaining: number | null, 
+/**
+ * Clock time at which the progress is estimated to finish, e.g. `14:32`
+ */
+estimatedFinishTime: string | null, 
+/**
+ * Smoothed rate at which the input files are read
+ */
+megabytesPerSecond: number, status: string, 
+/**
+ * Stage of the run, `None` before the run entered its first stage
+ */
+stage: ProgressStage | null, 
+/**
+ * Time spent in the stages which started, in the order they started, where the time of a
+ * stage which ran more than once is accumulated
+ */
+stageTimings: Array<StageTiming>, alternativeCurrent: number, alternativeTotal: number, alternativeUnit: string, 
+/**
+ * Input files of the batches which are being processed
+ */
+activeFiles: Array<string>, runningBatchCount: number, finishedBatchCount: number, };
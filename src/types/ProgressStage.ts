@@ -0,0 +1,25 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Stage of a processing run, in the order the stages run
+ *
+ * Files which were still being written are processed in a deferred pass after the encoding,
+ * which runs the stages from scanning to encoding again. The stage number goes back for that
+ * pass, and the time of the repeated stages is added to their earlier time.
+ */
+export type ProgressStage = /**
+ * Reading the input paths and filtering the files which are skipped
+ */
+"scanning" | /**
+ * Reading the files and preparing their settings
+ */
+"probing" | /**
+ * Creating the logos for the output resolutions
+ */
+"logoPrep" | /**
+ * Running the FFmpeg processes which write the outputs
+ */
+"encoding" | /**
+ * Checking that every output was written
+ */
+"verifying";
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How media is resized to its target resolution
+ */
+export type ResizeMode = /**
+ * Scale the shortest side to the minimum pixel count, keeping the aspect ratio
+ */
+"fit" | /**
+ * Scale to cover the target aspect ratio and crop the overflow from the center
+ */
+"fill" | /**
+ * Scale to the target aspect ratio, distorting the media
+ */
+"stretch" | /**
+ * Scale to fit inside the target aspect ratio and pad the remaining area with a color
+ */
+"padToAspect";
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MediaAdjustment } from "./MediaAdjustment";
+import type { ReviewAction } from "./ReviewAction";
+
+export type ReviewDecision = { action: ReviewAction, adjustment: MediaAdjustment | null, };
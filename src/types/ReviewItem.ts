@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Resolution } from "./Resolution";
+import type { ReviewDecision } from "./ReviewDecision";
+import type { ReviewFlag } from "./ReviewFlag";
+
+/**
+ * A flagged media file which waits for a decision of the user
+ */
+export type ReviewItem = { path: string, resolution: Resolution, flags: Array<ReviewFlag>, decision: ReviewDecision | null, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Job } from "./Job";
+import type { RunOutcome } from "./RunOutcome";
+
+/**
+ * A finished run with the settings it was started with
+ */
+export type RunHistoryEntry = { 
+/**
+ * Start time in RFC 3339 format
+ */
+startedAt: string, job: Job, outcome: RunOutcome, 
+/**
+ * The error a failed run stopped with
+ */
+error: string | null, totalDurationSeconds: number, outputCount: number, skippedFileCount: number, failedFileCount: number, };
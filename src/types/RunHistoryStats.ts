@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Totals over the runs of the history
+ */
+export type RunHistoryStats = { runCount: number, completedRunCount: number, cancelledRunCount: number, failedRunCount: number, totalDurationSeconds: number, outputCount: number, skippedFileCount: number, failedFileCount: number, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PlannedFile } from "./PlannedFile";
+import type { SkippedFile } from "./SkippedFile";
+
+/**
+ * What a run with the settings would do, found without writing or moving any file
+ */
+export type RunPlan = { mediaType: string, plannedFiles: Array<PlannedFile>, 
+/**
+ * Number of FFmpeg commands the files would be processed with
+ */
+estimatedBatchCount: number, skippedFiles: Array<SkippedFile>, warnings: Array<string>, };
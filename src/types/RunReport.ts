@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CancellationSummary } from "./CancellationSummary";
+import type { FailedFile } from "./FailedFile";
+import type { FileReport } from "./FileReport";
+import type { FileTimingStats } from "./FileTimingStats";
+import type { PhaseTiming } from "./PhaseTiming";
+import type { SkippedFile } from "./SkippedFile";
+
+export type RunReport = { mediaType: string, totalDurationSeconds: number, phaseTimings: Array<PhaseTiming>, fileTimingStats: FileTimingStats | null, warnings: Array<string>, outputPaths: Array<string>, fileReports: Array<FileReport>, skippedFiles: Array<SkippedFile>, failedFiles: Array<FailedFile>, 
+/**
+ * Set when the run was cancelled, the report then only covers the files processed so far
+ */
+cancellation: CancellationSummary | null, };
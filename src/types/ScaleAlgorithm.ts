@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The scaling algorithm FFmpeg uses to resize media
+ */
+export type ScaleAlgorithm = /**
+ * Fastest scaling, downscaled media looks soft
+ */
+"fastBilinear" | "bilinear" | /**
+ * Sharper than bilinear at a small cost in speed
+ */
+"bicubic" | /**
+ * Sharpest scaling, best suited for downscaling photos
+ */
+"lanczos";
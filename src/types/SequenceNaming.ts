@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Names outputs as a numbered sequence instead of after their source files, e.g. `wedding_0001`
+ */
+export type SequenceNaming = { prefix: string, startIndex: number, 
+/**
+ * Minimum number of digits, shorter numbers are padded with zeros
+ */
+zeroPadding: number, };
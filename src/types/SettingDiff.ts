@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A setting whose stored value, of a preset or a brand kit, differs from the current settings
+ */
+export type SettingDiff = { 
+/**
+ * Name of the setting, e.g. `logoScale`
+ */
+field: string, currentValue: unknown, 
+/**
+ * Value of the preset, or of the settings after applying the brand kit
+ */
+storedValue: unknown, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A field of a settings payload which doesn't deserialize into the config types
+ */
+export type SettingsPayloadError = { 
+/**
+ * Path of the field, e.g. `imageSettings.formatQuality.jpegQuality`
+ */
+path: string, message: string, };
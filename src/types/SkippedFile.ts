@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A media file which was found but not processed
+ */
+export type SkippedFile = { path: string, reason: string, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What happens to an input file once all its outputs are written
+ */
+export type SourceDisposition = /**
+ * Leave the input file where it is
+ */
+"keep" | /**
+ * Move the input file to the `_processed` folder beside the input directory, e.g.
+ * `photos_processed` for `photos`
+ */
+"archive" | /**
+ * Move the input file to the trash or recycle bin
+ */
+"trash";
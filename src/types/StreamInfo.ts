@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StreamKind } from "./StreamKind";
+
+/**
+ * A stream of a media file as reported by ffprobe
+ */
+export type StreamInfo = { 
+/**
+ * Index of the stream in the input file, as used by `-map 0:<index>`
+ */
+index: number, kind: StreamKind, codec: string | null, 
+/**
+ * ISO 639-2 language tag, e.g. `eng`
+ */
+language: string | null, title: string | null, 
+/**
+ * Number of audio channels, only set for audio streams
+ */
+channels: number | null, isDefault: boolean, };
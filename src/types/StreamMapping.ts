@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StreamSelection } from "./StreamSelection";
+
+/**
+ * The audio and subtitle streams which are kept when processing videos
+ */
+export type StreamMapping = { audio: StreamSelection, 
+/**
+ * Keep the chapters of the source video
+ */
+chapters: boolean, 
+/**
+ * Keep the global metadata of the source video, e.g. its title and creation time
+ */
+metadata: boolean, subtitles: StreamSelection, };
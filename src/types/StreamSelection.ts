@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Which streams of a kind are kept in the output
+ */
+export type StreamSelection = "all" | "none" | { 
+/**
+ * Keep the streams tagged with one of the languages, e.g. `eng`
+ */
+"languages": Array<string> };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A release of the app compared to the running version
+ */
+export type UpdateInfo = { currentVersion: string, latestVersion: string, isUpdateAvailable: boolean, 
+/**
+ * Release notes in Markdown
+ */
+releaseNotes: string, 
+/**
+ * Page of the release which lists its downloads
+ */
+downloadUrl: string, publishedAt: string | null, };
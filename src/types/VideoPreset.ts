@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { VideoSettings } from "./VideoSettings";
+
+/**
+ * Video settings saved under a name, e.g. "Archive HEVC"
+ */
+export type VideoPreset = { name: string, settings: VideoSettings, };
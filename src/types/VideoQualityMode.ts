@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How the encoder decides the bitrate of the video
+ */
+export type VideoQualityMode = /**
+ * Keep the rate control of the encoder
+ */
+"encoderDefault" | /**
+ * Encode every scene at the CRF quality level, the bitrate follows the content
+ */
+"constantQuality" | /**
+ * Encode at the average bitrate
+ */
+"targetBitrate" | /**
+ * Encode at the CRF quality level, without exceeding the bitrate
+ */
+"constrainedQuality";
@@ -1,4 +1,146 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CollisionStrategy } from "./CollisionStrategy";
+import type { ColorAdjustments } from "./ColorAdjustments";
 import type { Corner } from "./Corner";
+import type { EncoderSpeed } from "./EncoderSpeed";
+import type { ExportSize } from "./ExportSize";
+import type { FfmpegLogLevel } from "./FfmpegLogLevel";
+import type { HardwareAcceleration } from "./HardwareAcceleration";
+import type { LayersSettings } from "./LayersSettings";
+import type { ResizeMode } from "./ResizeMode";
+import type { Resolution } from "./Resolution";
+import type { SourceDisposition } from "./SourceDisposition";
+import type { StreamMapping } from "./StreamMapping";
+import type { VideoQualityMode } from "./VideoQualityMode";
 
-export type VideoSettings = { addLogo: boolean, clearFilesInputDirectory: boolean, clearFilesOutputDirectory: boolean, codecFavoriteList: Array<string>, codec: string, formatFavoriteList: Array<string>, format: string, inputDirectory: string, keepChildFoldersStructureInOutputDirectory: boolean, logoCorner: Corner, logoPath: string | null, logoScale: number, logoXOffsetScale: number, logoYOffsetScale: number, minPixelCount: number, outputDirectory: string, overwriteExistingFilesOutputDirectory: boolean, searchChildFolders: boolean, shouldConvertCodec: boolean, shouldConvertFormat: boolean, };
+export type VideoSettings = { addLogo: boolean, 
+/**
+ * Number of times GIF, WebP and APNG outputs play, 0 loops them forever
+ */
+animationLoopCount: number, 
+/**
+ * Run on half of the CPU cores with a lower FFmpeg priority, so the machine stays usable
+ */
+backgroundMode: boolean, 
+/**
+ * Bitrate in kilobits per second of the target bitrate and constrained quality modes
+ */
+bitrateKbps: number, brandKit: string | null, clearFilesOutputDirectory: boolean, codecFavoriteList: Array<string>, codec: string, 
+/**
+ * What happens to inputs whose output file already exists
+ */
+collisionStrategy: CollisionStrategy, colorAdjustments: ColorAdjustments, 
+/**
+ * Quality level of the constant and constrained quality modes, in the scale of the encoder
+ */
+crf: number, encoderSpeed: EncoderSpeed, 
+/**
+ * Threads of each video encoder, 0 divides the CPU cores over the concurrent processes
+ */
+encoderThreads: number, 
+/**
+ * Glob patterns of input files to skip, e.g. `*_edited.jpg`
+ */
+excludePatterns: Array<string>, 
+/**
+ * Renditions which each video is exported in, replacing the single output when set
+ */
+exportSizes: Array<ExportSize>, ffmpegLogLevel: FfmpegLogLevel, filenameTemplate: string, formatFavoriteList: Array<string>, format: string, 
+/**
+ * Hardware encoder family to encode with, falling back to the configured encoder
+ */
+hardwareAcceleration: HardwareAcceleration, hydrateCloudPlaceholders: boolean, 
+/**
+ * Glob patterns of input files to process, e.g. `*_final.jpg`, empty processes all files
+ */
+includePatterns: Array<string>, inputDirectory: string, keepChildFoldersStructureInOutputDirectory: boolean, layers: LayersSettings, logoCorner: Corner, logoKeyColor: string | null, logoKeyTolerance: number, logoPath: string | null, logoScale: number, logoXOffsetScale: number, logoYOffsetScale: number, 
+/**
+ * Maximum number of FFmpeg processes running at the same time, 0 disables the limit
+ */
+maxConcurrentProcesses: number, 
+/**
+ * Maximum number of FFmpeg processes reading an input directory on an HDD at the same time,
+ * 0 disables the limit
+ */
+maxDiskReaders: number, 
+/**
+ * Inputs larger than this number of megabytes are skipped, 0 disables the check
+ */
+maxFileSizeMb: number, 
+/**
+ * Files smaller than this number of bytes are skipped, 0 disables the check
+ */
+minFileSize: number, 
+/**
+ * Inputs with a lower height are skipped, 0 disables the check
+ */
+minInputHeight: number, 
+/**
+ * Inputs with a lower width are skipped, 0 disables the check
+ */
+minInputWidth: number, minPixelCount: number, 
+/**
+ * Skip inputs last modified before this date, in the `YYYY-MM-DD` format
+ */
+modifiedAfter: string | null, 
+/**
+ * Skip inputs last modified after this date, in the `YYYY-MM-DD` format
+ */
+modifiedBefore: string | null, neverUpscale: boolean, outputDirectory: string, 
+/**
+ * CSV file which maps inputs to their own output folder or name, e.g. per product
+ */
+outputMappingPath: string | null, 
+/**
+ * Pixel format of the outputs, e.g. `yuv420p10le`, `None` picks one from the source and the
+ * encoder
+ */
+pixelFormat: string | null, 
+/**
+ * Second of each output video which is also written as a JPEG poster next to it, `None`
+ * writes no posters
+ */
+posterTimestamp: number | null, 
+/**
+ * Keep the HDR colors of HDR sources when the encoder can write them, other outputs are
+ * tone mapped to SDR
+ */
+preserveHdr: boolean, qualityMode: VideoQualityMode, 
+/**
+ * Move skipped empty and near-empty files to the quarantine folder
+ */
+quarantineTinyFiles: boolean, 
+/**
+ * Keep the FFmpeg command of every work unit of a run, so it can be reproduced manually
+ */
+recordFfmpegCommands: boolean, 
+/**
+ * Stop the run when a logo is enlarged so much that it looks blurry, instead of warning
+ */
+rejectUpscaledLogos: boolean, resizeAspectRatio: Resolution, resizeMode: ResizeMode, resizeOutputResolution: Resolution | null, resizePadColor: string, reviewFlaggedFiles: boolean, searchChildFolders: boolean, shouldConvertCodec: boolean, shouldConvertFormat: boolean, 
+/**
+ * Skip inputs which were already processed with the same settings
+ */
+skipMarkedFiles: boolean, 
+/**
+ * What happens to an input file once all its outputs are written
+ */
+sourceDisposition: SourceDisposition, streamMapping: StreamMapping, 
+/**
+ * Frame rate the videos are converted to, `None` keeps the frame rate of each video, or uses
+ * 15 frames per second for GIF, WebP and APNG outputs
+ */
+targetFps: number | null, 
+/**
+ * Second of each video where the processed section ends, `None` processes until the end
+ */
+trimEnd: number | null, 
+/**
+ * Second of each video where the processed section starts, `None` starts at the beginning
+ */
+trimStart: number | null, 
+/**
+ * Write a log with a JSON record per processed file to the output directory, for auditing
+ * the outputs of a run
+ */
+writeRunLog: boolean, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A combination of video settings which fails when the videos are processed
+ */
+export type VideoSettingsConflict = { message: string, 
+/**
+ * Codec which the format holds, which replaces the configured codec when processing
+ */
+suggestedCodec: string | null, };